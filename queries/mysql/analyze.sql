@@ -0,0 +1 @@
+ANALYZE TABLE devices, settings, measurements, notifications, firmwares, firmware_stats, alert_states, quarantine, calibrations, rollups_hourly, rollups_daily;
\ No newline at end of file
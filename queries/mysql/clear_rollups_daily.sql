@@ -0,0 +1,21 @@
+-- Only buckets that still have measurements, the pruned ones are kept
+DELETE FROM rollups_daily
+WHERE
+    (
+        ? IS NULL
+        OR node = ?
+    )
+    AND (
+        ? IS NULL
+        OR bucket >= DATE_FORMAT(FROM_UNIXTIME(?), '%Y-%m-%d 00:00:00')
+    )
+    AND EXISTS (
+        SELECT
+            1
+        FROM
+            measurements m
+        WHERE
+            m.node = rollups_daily.node
+            AND m.`when` >= rollups_daily.bucket
+            AND m.`when` < rollups_daily.bucket + INTERVAL 1 DAY
+    );
\ No newline at end of file
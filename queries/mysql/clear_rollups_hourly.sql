@@ -0,0 +1,21 @@
+-- Only buckets that still have measurements, the pruned ones are kept
+DELETE FROM rollups_hourly
+WHERE
+    (
+        ? IS NULL
+        OR node = ?
+    )
+    AND (
+        ? IS NULL
+        OR bucket >= DATE_FORMAT(FROM_UNIXTIME(?), '%Y-%m-%d %H:00:00')
+    )
+    AND EXISTS (
+        SELECT
+            1
+        FROM
+            measurements m
+        WHERE
+            m.node = rollups_hourly.node
+            AND m.`when` >= rollups_hourly.bucket
+            AND m.`when` < rollups_hourly.bucket + INTERVAL 1 HOUR
+    );
\ No newline at end of file
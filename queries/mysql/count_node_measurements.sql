@@ -0,0 +1,10 @@
+SELECT
+    COUNT(*)
+FROM
+    measurements
+WHERE
+    node = ?
+    AND (
+        ? IS NULL
+        OR `when` >= NOW() - INTERVAL ? SECOND
+    );
\ No newline at end of file
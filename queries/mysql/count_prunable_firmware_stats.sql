@@ -0,0 +1,6 @@
+SELECT
+    COUNT(*)
+FROM
+    firmware_stats
+WHERE
+    `when` < NOW() - INTERVAL ? SECOND;
\ No newline at end of file
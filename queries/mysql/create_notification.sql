@@ -0,0 +1,4 @@
+INSERT INTO
+    notifications (node, content, severity, kind, title)
+VALUES
+    (?, ?, ?, ?, ?);
\ No newline at end of file
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS
+    schema_migrations (
+        version INT PRIMARY KEY,
+        name VARCHAR(64) NOT NULL,
+        applied DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+    ) DEFAULT CHARSET = utf8mb4;
+
+-- Statements of a migration that were already executed, since they can't be rolled back if a later one fails
+CREATE TABLE IF NOT EXISTS
+    schema_migration_progress (
+        version INT PRIMARY KEY,
+        statements INT NOT NULL
+    ) DEFAULT CHARSET = utf8mb4;
\ No newline at end of file
@@ -0,0 +1,3 @@
+DELETE FROM calibrations
+WHERE
+    node = ?;
\ No newline at end of file
@@ -0,0 +1,3 @@
+DELETE FROM schema_migration_progress
+WHERE
+    version = ?;
\ No newline at end of file
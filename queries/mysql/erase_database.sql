@@ -0,0 +1,25 @@
+DROP TABLE IF EXISTS alert_states;
+
+DROP TABLE IF EXISTS calibrations;
+
+DROP TABLE IF EXISTS quarantine;
+
+DROP TABLE IF EXISTS rollups_hourly;
+
+DROP TABLE IF EXISTS rollups_daily;
+
+DROP TABLE IF EXISTS settings;
+
+DROP TABLE IF EXISTS notifications;
+
+DROP TABLE IF EXISTS measurements;
+
+DROP TABLE IF EXISTS firmware_stats;
+
+DROP TABLE IF EXISTS firmwares;
+
+DROP TABLE IF EXISTS devices;
+
+DROP TABLE IF EXISTS schema_migrations;
+
+DROP TABLE IF EXISTS schema_migration_progress;
\ No newline at end of file
@@ -0,0 +1,35 @@
+DELETE FROM alert_states;
+
+DELETE FROM calibrations;
+
+DELETE FROM quarantine;
+
+DELETE FROM rollups_hourly;
+
+DELETE FROM rollups_daily;
+
+DELETE FROM measurements;
+
+DELETE FROM settings;
+
+DELETE FROM notifications;
+
+DELETE FROM firmware_stats;
+
+DELETE FROM firmwares;
+
+DELETE FROM devices;
+
+ALTER TABLE quarantine AUTO_INCREMENT = 1;
+
+ALTER TABLE measurements AUTO_INCREMENT = 1;
+
+ALTER TABLE settings AUTO_INCREMENT = 1;
+
+ALTER TABLE notifications AUTO_INCREMENT = 1;
+
+ALTER TABLE firmware_stats AUTO_INCREMENT = 1;
+
+ALTER TABLE firmwares AUTO_INCREMENT = 1;
+
+ALTER TABLE devices AUTO_INCREMENT = 1;
\ No newline at end of file
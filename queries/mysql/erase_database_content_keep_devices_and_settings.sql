@@ -0,0 +1,25 @@
+DELETE FROM alert_states;
+
+DELETE FROM quarantine;
+
+DELETE FROM rollups_hourly;
+
+DELETE FROM rollups_daily;
+
+DELETE FROM measurements;
+
+DELETE FROM notifications;
+
+DELETE FROM firmware_stats;
+
+DELETE FROM firmwares;
+
+ALTER TABLE quarantine AUTO_INCREMENT = 1;
+
+ALTER TABLE measurements AUTO_INCREMENT = 1;
+
+ALTER TABLE notifications AUTO_INCREMENT = 1;
+
+ALTER TABLE firmware_stats AUTO_INCREMENT = 1;
+
+ALTER TABLE firmwares AUTO_INCREMENT = 1;
\ No newline at end of file
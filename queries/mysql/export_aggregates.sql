@@ -0,0 +1,41 @@
+SELECT
+    node,
+    CAST(UNIX_TIMESTAMP(`when`) DIV ? * ? AS SIGNED) AS bucket,
+    COUNT(*),
+    -- Adding a DOUBLE makes the summaries DOUBLEs
+    MIN(temperature) + 0E0,
+    MAX(temperature) + 0E0,
+    AVG(temperature) + 0E0,
+    MIN(humidity) + 0E0,
+    MAX(humidity) + 0E0,
+    AVG(humidity) + 0E0,
+    MIN(air_pressure) + 0E0,
+    MAX(air_pressure) + 0E0,
+    AVG(air_pressure) + 0E0,
+    MIN(battery) + 0E0,
+    MAX(battery) + 0E0,
+    AVG(battery) + 0E0,
+    MIN(wifi_rssi) + 0E0,
+    MAX(wifi_rssi) + 0E0,
+    AVG(wifi_rssi) + 0E0
+FROM
+    measurements
+WHERE
+    (
+        ? IS NULL
+        OR node = ?
+    )
+    AND (
+        ? IS NULL
+        OR `when` >= FROM_UNIXTIME(?)
+    )
+    AND (
+        ? IS NULL
+        OR `when` < FROM_UNIXTIME(?)
+    )
+GROUP BY
+    node,
+    bucket
+ORDER BY
+    bucket,
+    node;
\ No newline at end of file
@@ -0,0 +1,33 @@
+SELECT
+    CAST(UNIX_TIMESTAMP(`when`) AS SIGNED),
+    temperature,
+    humidity,
+    air_pressure,
+    cpu_temp,
+    battery,
+    wifi_ssid,
+    wifi_rssi,
+    flags,
+    COALESCE(raw_temperature, temperature),
+    COALESCE(raw_humidity, humidity),
+    COALESCE(raw_air_pressure, air_pressure),
+    COALESCE(raw_battery, battery),
+    node
+FROM
+    measurements
+WHERE
+    (
+        ? IS NULL
+        OR node = ?
+    )
+    AND (
+        ? IS NULL
+        OR `when` >= FROM_UNIXTIME(?)
+    )
+    AND (
+        ? IS NULL
+        OR `when` < FROM_UNIXTIME(?)
+    )
+ORDER BY
+    `when`,
+    node;
\ No newline at end of file
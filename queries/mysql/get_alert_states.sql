@@ -0,0 +1,8 @@
+SELECT
+    rule,
+    active,
+    streak
+FROM
+    alert_states
+WHERE
+    node = ?;
\ No newline at end of file
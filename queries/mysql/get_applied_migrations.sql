@@ -0,0 +1,8 @@
+SELECT
+    version,
+    name,
+    CAST(UNIX_TIMESTAMP(applied) AS SIGNED)
+FROM
+    schema_migrations
+ORDER BY
+    version;
\ No newline at end of file
@@ -0,0 +1,21 @@
+-- The latest samples in the window, oldest first
+SELECT
+    epoch,
+    battery
+FROM
+    (
+        SELECT
+            CAST(UNIX_TIMESTAMP(`when`) AS SIGNED) AS epoch,
+            battery
+        FROM
+            measurements
+        WHERE
+            node = ?
+            AND `when` >= NOW() - INTERVAL ? SECOND
+        ORDER BY
+            `when` DESC
+        LIMIT
+            ?
+    ) AS recent
+ORDER BY
+    epoch;
\ No newline at end of file
@@ -0,0 +1,6 @@
+SELECT
+    id
+FROM
+    devices
+WHERE
+    mac_address = ?;
\ No newline at end of file
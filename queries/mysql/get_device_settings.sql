@@ -0,0 +1,12 @@
+-- Expired timed mutes are resolved by the caller, since the settings are cached
+SELECT
+    battery_ignore,
+    ota,
+    sleep_time,
+    sbop,
+    mute_notifications,
+    CAST(UNIX_TIMESTAMP(muted_until) AS SIGNED)
+FROM
+    settings
+WHERE
+    node = ?;
\ No newline at end of file
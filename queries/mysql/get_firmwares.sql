@@ -0,0 +1,9 @@
+SELECT
+    id,
+    CONCAT(version_major, '.', version_middle, '.', version_minor) AS version,
+    CAST(LENGTH(firmware) AS SIGNED),
+    firmware,
+    DATE_FORMAT(added_date, '%d.%m.%Y %H:%i:%s') AS added_date,
+    restrict_nodes
+FROM
+    firmwares
\ No newline at end of file
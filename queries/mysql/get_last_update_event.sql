@@ -0,0 +1,11 @@
+SELECT
+  id
+FROM
+  firmware_stats
+WHERE
+  success IS NULL
+  AND node = ?
+ORDER BY
+  `when` DESC
+LIMIT
+  1;
\ No newline at end of file
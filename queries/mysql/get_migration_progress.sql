@@ -0,0 +1,6 @@
+SELECT
+    statements
+FROM
+    schema_migration_progress
+WHERE
+    version = ?;
\ No newline at end of file
@@ -0,0 +1,17 @@
+SELECT
+    id,
+    node,
+    CAST(UNIX_TIMESTAMP(`when`) AS SIGNED),
+    content,
+    `read`,
+    severity,
+    kind,
+    title
+FROM
+    notifications
+WHERE
+    (NOT ? OR NOT `read`)
+    AND (? IS NULL OR node = ?)
+    AND (? IS NULL OR `when` >= NOW() - INTERVAL ? SECOND)
+ORDER BY
+    `when` DESC;
\ No newline at end of file
@@ -0,0 +1,30 @@
+SELECT
+  f.version_major,
+  f.version_middle,
+  f.version_minor,
+  f.firmware
+FROM
+  firmwares f
+WHERE
+  (
+    f.restrict_nodes IS NULL
+    OR JSON_CONTAINS(f.restrict_nodes, JSON_ARRAY(?))
+  )
+  AND (f.version_major, f.version_middle, f.version_minor) > (?, ?, ?)
+  AND NOT EXISTS (
+    SELECT
+      1
+    FROM
+      firmware_stats fs
+    WHERE
+      fs.node = ?
+      AND fs.to_version_major = f.version_major
+      AND fs.to_version_middle = f.version_middle
+      AND fs.to_version_minor = f.version_minor
+  )
+ORDER BY
+  f.version_major DESC,
+  f.version_middle DESC,
+  f.version_minor DESC
+LIMIT
+  1;
\ No newline at end of file
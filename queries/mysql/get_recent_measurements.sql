@@ -0,0 +1,22 @@
+SELECT
+    CAST(UNIX_TIMESTAMP(`when`) AS SIGNED),
+    temperature,
+    humidity,
+    air_pressure,
+    cpu_temp,
+    battery,
+    wifi_ssid,
+    wifi_rssi,
+    flags,
+    COALESCE(raw_temperature, temperature),
+    COALESCE(raw_humidity, humidity),
+    COALESCE(raw_air_pressure, air_pressure),
+    COALESCE(raw_battery, battery)
+FROM
+    measurements
+WHERE
+    node = ?
+ORDER BY
+    `when` DESC
+LIMIT
+    ?;
\ No newline at end of file
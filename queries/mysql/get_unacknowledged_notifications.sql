@@ -0,0 +1,23 @@
+SELECT
+    id,
+    node,
+    CAST(UNIX_TIMESTAMP(`when`) AS SIGNED),
+    content,
+    `read`,
+    severity,
+    kind,
+    title
+FROM
+    notifications
+WHERE
+    NOT `read`
+    AND escalations < ?
+    AND COALESCE(escalated, `when`) <= NOW() - INTERVAL ? SECOND
+    -- Ranks as in `Severity::level()`
+    AND CASE severity
+        WHEN 'critical' THEN 2
+        WHEN 'warning' THEN 1
+        ELSE 0
+    END >= ?
+ORDER BY
+    `when`;
\ No newline at end of file
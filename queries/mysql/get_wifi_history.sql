@@ -0,0 +1,11 @@
+SELECT
+    CAST(UNIX_TIMESTAMP(`when`) AS SIGNED),
+    wifi_ssid,
+    wifi_rssi
+FROM
+    measurements
+WHERE
+    node = ?
+    AND `when` >= NOW() - INTERVAL ? SECOND
+ORDER BY
+    `when`;
\ No newline at end of file
@@ -0,0 +1,25 @@
+INSERT INTO
+    measurements (node, `when`, temperature, humidity, air_pressure, cpu_temp, battery, wifi_ssid, wifi_rssi)
+SELECT
+    ?,
+    FROM_UNIXTIME(?),
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
+    ?
+FROM
+    DUAL
+WHERE
+    NOT ?
+    OR NOT EXISTS (
+        SELECT
+            1
+        FROM
+            measurements
+        WHERE
+            node = ?
+            AND `when` = FROM_UNIXTIME(?)
+    );
\ No newline at end of file
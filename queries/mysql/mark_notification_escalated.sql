@@ -0,0 +1,6 @@
+UPDATE notifications
+SET
+  escalations = escalations + 1,
+  escalated = NOW()
+WHERE
+  id = ?;
\ No newline at end of file
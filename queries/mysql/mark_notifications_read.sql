@@ -0,0 +1,6 @@
+UPDATE notifications
+SET
+  `read` = TRUE
+WHERE
+  NOT `read`
+  AND (? IS NULL OR id = ?);
\ No newline at end of file
@@ -0,0 +1,106 @@
+CREATE TABLE
+    devices (
+        id INT AUTO_INCREMENT PRIMARY KEY,
+        mac_address VARCHAR(17) UNIQUE NOT NULL CHECK (mac_address REGEXP '^([0-9A-F]{2}:){5}[0-9A-F]{2}$'),
+        location VARCHAR(64) DEFAULT NULL,
+        note VARCHAR(1024) DEFAULT NULL
+    ) DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE
+    measurements (
+        id INT AUTO_INCREMENT PRIMARY KEY,
+        node INT NOT NULL,
+        `when` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        temperature FLOAT NOT NULL CHECK (
+            temperature > -100.00
+            AND temperature < 100.00
+        ),
+        humidity SMALLINT NOT NULL CHECK (
+            humidity >= 0
+            AND humidity <= 100
+        ),
+        air_pressure SMALLINT DEFAULT NULL,
+        -- Column checks can't refer to other columns, the other backends check the temperature again here
+        cpu_temp FLOAT NOT NULL,
+        battery FLOAT NOT NULL CHECK (
+            battery > 0
+            AND battery < 5.00
+        ),
+        wifi_ssid VARCHAR(32) NOT NULL,
+        wifi_rssi SMALLINT NOT NULL,
+        FOREIGN KEY (node) REFERENCES devices (id)
+    ) DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE
+    settings (
+        id INT AUTO_INCREMENT PRIMARY KEY,
+        node INT UNIQUE NOT NULL,
+        battery_ignore BOOLEAN NOT NULL DEFAULT FALSE,
+        ota BOOLEAN NOT NULL DEFAULT FALSE,
+        sleep_time SMALLINT NOT NULL DEFAULT 60 CHECK (sleep_time > 0),
+        sbop BOOLEAN NOT NULL DEFAULT TRUE,
+        mute_notifications BOOLEAN NOT NULL DEFAULT FALSE,
+        -- TEXT columns can't have a default and JSON ones can't be read as strings
+        device_specific VARCHAR(4096) NOT NULL DEFAULT '{}' CHECK (JSON_VALID(device_specific)),
+        FOREIGN KEY (node) REFERENCES devices (id)
+    ) DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE
+    notifications (
+        id INT AUTO_INCREMENT PRIMARY KEY,
+        node INT NOT NULL,
+        `when` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        content VARCHAR(1024) NOT NULL,
+        `read` BOOLEAN NOT NULL DEFAULT FALSE,
+        FOREIGN KEY (node) REFERENCES devices (id)
+    ) DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE
+    firmwares (
+        id INT AUTO_INCREMENT PRIMARY KEY,
+        version_major SMALLINT NOT NULL CHECK (version_major >= 0),
+        version_middle SMALLINT NOT NULL CHECK (version_middle >= 0),
+        version_minor SMALLINT NOT NULL CHECK (version_minor >= 0),
+        firmware LONGBLOB NOT NULL CHECK (LENGTH(firmware) > 0),
+        added_date DATETIME UNIQUE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        -- JSON array of node IDs
+        restrict_nodes TEXT DEFAULT NULL CHECK (JSON_VALID(restrict_nodes))
+    ) DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE
+    firmware_stats (
+        id INT AUTO_INCREMENT PRIMARY KEY,
+        node INT NOT NULL,
+        from_version_major SMALLINT NOT NULL CHECK (from_version_major >= 0),
+        from_version_middle SMALLINT NOT NULL CHECK (from_version_middle >= 0),
+        from_version_minor SMALLINT NOT NULL CHECK (from_version_minor >= 0),
+        to_version_major SMALLINT NOT NULL CHECK (to_version_major >= 0),
+        to_version_middle SMALLINT NOT NULL CHECK (to_version_middle >= 0),
+        to_version_minor SMALLINT NOT NULL CHECK (to_version_minor >= 0),
+        `when` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        success BOOLEAN DEFAULT NULL,
+        FOREIGN KEY (node) REFERENCES devices (id)
+    ) DEFAULT CHARSET = utf8mb4;
+
+--
+-- INDEXES
+--
+-- measurements per device / time-series access
+CREATE INDEX idx_measurements_node_when ON measurements (node, `when` DESC);
+
+-- notifications per node
+CREATE INDEX idx_notifications_node_when ON notifications (node, `when` DESC);
+
+-- update-report lookup: get latest pending update for node (partial indexes are not supported)
+CREATE INDEX idx_firmware_stats_pending_node_when ON firmware_stats (node, success, `when` DESC);
+
+-- check whether a node already tried a target firmware
+CREATE INDEX idx_firmware_stats_node_target_version ON firmware_stats (
+    node,
+    to_version_major,
+    to_version_middle,
+    to_version_minor
+);
+
+-- firmware version comparisons
+CREATE INDEX idx_firmwares_version ON firmwares (version_major, version_middle, version_minor);
\ No newline at end of file
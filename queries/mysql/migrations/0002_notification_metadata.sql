@@ -0,0 +1,6 @@
+ALTER TABLE notifications
+ADD COLUMN severity VARCHAR(8) NOT NULL DEFAULT 'info',
+ADD COLUMN kind VARCHAR(32) NOT NULL DEFAULT 'custom',
+ADD COLUMN title VARCHAR(256) NOT NULL DEFAULT '',
+ADD COLUMN escalations INT NOT NULL DEFAULT 0,
+ADD COLUMN escalated DATETIME DEFAULT NULL;
\ No newline at end of file
@@ -0,0 +1,10 @@
+CREATE TABLE
+    alert_states (
+        node INT NOT NULL,
+        rule VARCHAR(64) NOT NULL,
+        active BOOLEAN NOT NULL DEFAULT FALSE,
+        streak INT NOT NULL DEFAULT 0,
+        changed DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (node, rule),
+        FOREIGN KEY (node) REFERENCES devices (id)
+    ) DEFAULT CHARSET = utf8mb4;
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE measurements ADD COLUMN flags INT NOT NULL DEFAULT 0;
\ No newline at end of file
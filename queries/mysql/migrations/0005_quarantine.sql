@@ -0,0 +1,15 @@
+CREATE TABLE
+    quarantine (
+        id INT AUTO_INCREMENT PRIMARY KEY,
+        node INT NOT NULL,
+        `when` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        temperature FLOAT NOT NULL,
+        humidity SMALLINT NOT NULL,
+        air_pressure INT DEFAULT NULL,
+        cpu_temp FLOAT NOT NULL,
+        battery FLOAT NOT NULL,
+        wifi_ssid VARCHAR(32) NOT NULL,
+        wifi_rssi SMALLINT NOT NULL,
+        reason VARCHAR(1024) NOT NULL,
+        FOREIGN KEY (node) REFERENCES devices (id)
+    ) DEFAULT CHARSET = utf8mb4;
\ No newline at end of file
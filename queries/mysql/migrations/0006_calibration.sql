@@ -0,0 +1,16 @@
+ALTER TABLE measurements
+ADD COLUMN raw_temperature FLOAT DEFAULT NULL,
+ADD COLUMN raw_humidity SMALLINT DEFAULT NULL,
+ADD COLUMN raw_air_pressure SMALLINT DEFAULT NULL,
+ADD COLUMN raw_battery FLOAT DEFAULT NULL;
+
+CREATE TABLE
+    calibrations (
+        node INT PRIMARY KEY,
+        temperature_offset FLOAT NOT NULL DEFAULT 0,
+        temperature_scale FLOAT NOT NULL DEFAULT 1,
+        humidity_offset FLOAT NOT NULL DEFAULT 0,
+        air_pressure_offset FLOAT NOT NULL DEFAULT 0,
+        battery_scale FLOAT NOT NULL DEFAULT 1,
+        FOREIGN KEY (node) REFERENCES devices (id)
+    ) DEFAULT CHARSET = utf8mb4;
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE devices ADD COLUMN altitude FLOAT DEFAULT NULL;
\ No newline at end of file
@@ -0,0 +1,48 @@
+-- Buckets are in UTC and identified by their start
+CREATE TABLE
+    rollups_hourly (
+        node INT NOT NULL,
+        bucket DATETIME NOT NULL,
+        samples INT NOT NULL,
+        temperature_min FLOAT NOT NULL,
+        temperature_max FLOAT NOT NULL,
+        temperature_avg FLOAT NOT NULL,
+        humidity_min SMALLINT NOT NULL,
+        humidity_max SMALLINT NOT NULL,
+        humidity_avg FLOAT NOT NULL,
+        air_pressure_min SMALLINT DEFAULT NULL,
+        air_pressure_max SMALLINT DEFAULT NULL,
+        air_pressure_avg FLOAT DEFAULT NULL,
+        battery_min FLOAT NOT NULL,
+        battery_max FLOAT NOT NULL,
+        battery_avg FLOAT NOT NULL,
+        rssi_min SMALLINT NOT NULL,
+        rssi_max SMALLINT NOT NULL,
+        rssi_avg FLOAT NOT NULL,
+        PRIMARY KEY (node, bucket),
+        FOREIGN KEY (node) REFERENCES devices (id)
+    ) DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE
+    rollups_daily (
+        node INT NOT NULL,
+        bucket DATETIME NOT NULL,
+        samples INT NOT NULL,
+        temperature_min FLOAT NOT NULL,
+        temperature_max FLOAT NOT NULL,
+        temperature_avg FLOAT NOT NULL,
+        humidity_min SMALLINT NOT NULL,
+        humidity_max SMALLINT NOT NULL,
+        humidity_avg FLOAT NOT NULL,
+        air_pressure_min SMALLINT DEFAULT NULL,
+        air_pressure_max SMALLINT DEFAULT NULL,
+        air_pressure_avg FLOAT DEFAULT NULL,
+        battery_min FLOAT NOT NULL,
+        battery_max FLOAT NOT NULL,
+        battery_avg FLOAT NOT NULL,
+        rssi_min SMALLINT NOT NULL,
+        rssi_max SMALLINT NOT NULL,
+        rssi_avg FLOAT NOT NULL,
+        PRIMARY KEY (node, bucket),
+        FOREIGN KEY (node) REFERENCES devices (id)
+    ) DEFAULT CHARSET = utf8mb4;
\ No newline at end of file
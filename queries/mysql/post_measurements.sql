@@ -0,0 +1,4 @@
+INSERT INTO
+    measurements (node, temperature, humidity, air_pressure, cpu_temp, battery, wifi_ssid, wifi_rssi, flags, raw_temperature, raw_humidity, raw_air_pressure, raw_battery)
+VALUES
+    (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
\ No newline at end of file
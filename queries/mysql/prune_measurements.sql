@@ -0,0 +1,7 @@
+DELETE FROM measurements
+WHERE
+    `when` < NOW() - INTERVAL ? SECOND
+ORDER BY
+    id
+LIMIT
+    ?;
\ No newline at end of file
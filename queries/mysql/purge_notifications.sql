@@ -0,0 +1,3 @@
+DELETE FROM notifications
+WHERE
+    `when` < NOW() - INTERVAL ? SECOND;
\ No newline at end of file
@@ -0,0 +1,2 @@
+INSERT INTO firmwares (version_major, version_middle, version_minor, firmware, restrict_nodes)
+VALUES (?, ?, ?, ?, ?)
\ No newline at end of file
@@ -0,0 +1,4 @@
+INSERT INTO
+    quarantine (node, temperature, humidity, air_pressure, cpu_temp, battery, wifi_ssid, wifi_rssi, reason)
+VALUES
+    (?, ?, ?, ?, ?, ?, ?, ?, ?);
\ No newline at end of file
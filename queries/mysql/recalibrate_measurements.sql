@@ -0,0 +1,24 @@
+UPDATE measurements
+SET
+    raw_temperature = COALESCE(raw_temperature, temperature),
+    raw_humidity = COALESCE(raw_humidity, humidity),
+    raw_air_pressure = COALESCE(raw_air_pressure, air_pressure),
+    raw_battery = COALESCE(raw_battery, battery),
+    temperature = COALESCE(raw_temperature, temperature) * ? + ?,
+    humidity = LEAST(GREATEST(ROUND(COALESCE(raw_humidity, humidity) + ?), 0), 100),
+    air_pressure = ROUND(COALESCE(raw_air_pressure, air_pressure) + ?),
+    battery = COALESCE(raw_battery, battery) * ?
+WHERE
+    node = ?
+    AND (
+        ? IS NULL
+        OR `when` >= NOW() - INTERVAL ? SECOND
+    )
+    -- Measurements with implausible recalibrated readings are skipped
+    AND COALESCE(raw_temperature, temperature) * ? + ? BETWEEN ? AND ?
+    AND LEAST(GREATEST(ROUND(COALESCE(raw_humidity, humidity) + ?), 0), 100) BETWEEN ? AND ?
+    AND (
+        COALESCE(raw_air_pressure, air_pressure) IS NULL
+        OR ROUND(COALESCE(raw_air_pressure, air_pressure) + ?) BETWEEN ? AND ?
+    )
+    AND COALESCE(raw_battery, battery) * ? BETWEEN ? AND ?;
\ No newline at end of file
@@ -0,0 +1,55 @@
+INSERT INTO
+    rollups_daily (
+        node,
+        bucket,
+        samples,
+        temperature_min,
+        temperature_max,
+        temperature_avg,
+        humidity_min,
+        humidity_max,
+        humidity_avg,
+        air_pressure_min,
+        air_pressure_max,
+        air_pressure_avg,
+        battery_min,
+        battery_max,
+        battery_avg,
+        rssi_min,
+        rssi_max,
+        rssi_avg
+    )
+SELECT
+    node,
+    DATE_FORMAT(`when`, '%Y-%m-%d 00:00:00') AS bucket,
+    COUNT(*),
+    MIN(temperature),
+    MAX(temperature),
+    AVG(temperature),
+    MIN(humidity),
+    MAX(humidity),
+    AVG(humidity),
+    MIN(air_pressure),
+    MAX(air_pressure),
+    AVG(air_pressure),
+    MIN(battery),
+    MAX(battery),
+    AVG(battery),
+    MIN(wifi_rssi),
+    MAX(wifi_rssi),
+    AVG(wifi_rssi)
+FROM
+    measurements
+WHERE
+    (
+        ? IS NULL
+        OR node = ?
+    )
+    -- Whole buckets are recomputed
+    AND (
+        ? IS NULL
+        OR `when` >= DATE_FORMAT(FROM_UNIXTIME(?), '%Y-%m-%d 00:00:00')
+    )
+GROUP BY
+    node,
+    bucket;
\ No newline at end of file
@@ -0,0 +1,12 @@
+INSERT INTO
+    firmware_stats (
+        node,
+        from_version_major,
+        from_version_middle,
+        from_version_minor,
+        to_version_major,
+        to_version_middle,
+        to_version_minor
+    )
+VALUES
+    (?, ?, ?, ?, ?, ?, ?);
\ No newline at end of file
@@ -0,0 +1,9 @@
+-- Assignments are applied from left to right, so the change has to be detected before active is updated
+INSERT INTO
+    alert_states (node, rule, active, streak)
+VALUES
+    (?, ?, ?, ?) ON DUPLICATE KEY
+UPDATE
+    changed = IF(active <> VALUES(active), NOW(), changed),
+    active = VALUES(active),
+    streak = VALUES(streak);
\ No newline at end of file
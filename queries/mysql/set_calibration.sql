@@ -0,0 +1,10 @@
+INSERT INTO
+    calibrations (node, temperature_offset, temperature_scale, humidity_offset, air_pressure_offset, battery_scale)
+VALUES
+    (?, ?, ?, ?, ?, ?) ON DUPLICATE KEY
+UPDATE
+    temperature_offset = VALUES(temperature_offset),
+    temperature_scale = VALUES(temperature_scale),
+    humidity_offset = VALUES(humidity_offset),
+    air_pressure_offset = VALUES(air_pressure_offset),
+    battery_scale = VALUES(battery_scale);
\ No newline at end of file
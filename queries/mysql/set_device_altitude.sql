@@ -0,0 +1,5 @@
+UPDATE devices
+SET
+  altitude = ?
+WHERE
+  id = ?;
\ No newline at end of file
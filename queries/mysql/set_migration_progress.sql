@@ -0,0 +1,4 @@
+REPLACE INTO
+    schema_migration_progress (version, statements)
+VALUES
+    (?, ?);
\ No newline at end of file
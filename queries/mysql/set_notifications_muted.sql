@@ -0,0 +1,6 @@
+UPDATE settings
+SET
+  mute_notifications = ?,
+  muted_until = FROM_UNIXTIME(?)
+WHERE
+  node = ?;
\ No newline at end of file
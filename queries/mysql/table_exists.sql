@@ -0,0 +1,10 @@
+SELECT
+    EXISTS (
+        SELECT
+            1
+        FROM
+            information_schema.tables
+        WHERE
+            table_schema = DATABASE()
+            AND table_name = ?
+    );
\ No newline at end of file
@@ -0,0 +1,14 @@
+SELECT
+    node,
+    rule,
+    active,
+    streak,
+    CAST(UNIX_TIMESTAMP(changed) AS SIGNED)
+FROM
+    alert_states
+ORDER BY
+    node,
+    rule
+-- Offset first, to bind the parameters in the same order as for the other tables
+LIMIT
+    ?, ?;
\ No newline at end of file
@@ -0,0 +1,14 @@
+SELECT
+    node,
+    temperature_offset,
+    temperature_scale,
+    humidity_offset,
+    air_pressure_offset,
+    battery_scale
+FROM
+    calibrations
+ORDER BY
+    node
+-- Offset first, to bind the parameters in the same order as for the other tables
+LIMIT
+    ?, ?;
\ No newline at end of file
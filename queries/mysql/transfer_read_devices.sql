@@ -0,0 +1,14 @@
+SELECT
+    id,
+    mac_address,
+    location,
+    note,
+    altitude
+FROM
+    devices
+WHERE
+    id > ?
+ORDER BY
+    id
+LIMIT
+    ?;
\ No newline at end of file
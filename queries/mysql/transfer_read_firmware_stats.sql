@@ -0,0 +1,19 @@
+SELECT
+    id,
+    node,
+    from_version_major,
+    from_version_middle,
+    from_version_minor,
+    to_version_major,
+    to_version_middle,
+    to_version_minor,
+    CAST(UNIX_TIMESTAMP(`when`) AS SIGNED),
+    success
+FROM
+    firmware_stats
+WHERE
+    id > ?
+ORDER BY
+    id
+LIMIT
+    ?;
\ No newline at end of file
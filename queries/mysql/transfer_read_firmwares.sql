@@ -0,0 +1,16 @@
+SELECT
+    id,
+    version_major,
+    version_middle,
+    version_minor,
+    firmware,
+    CAST(UNIX_TIMESTAMP(added_date) AS SIGNED),
+    restrict_nodes
+FROM
+    firmwares
+WHERE
+    id > ?
+ORDER BY
+    id
+LIMIT
+    ?;
\ No newline at end of file
@@ -0,0 +1,24 @@
+SELECT
+    id,
+    node,
+    CAST(UNIX_TIMESTAMP(`when`) AS SIGNED),
+    temperature,
+    humidity,
+    air_pressure,
+    cpu_temp,
+    battery,
+    wifi_ssid,
+    wifi_rssi,
+    flags,
+    raw_temperature,
+    raw_humidity,
+    raw_air_pressure,
+    raw_battery
+FROM
+    measurements
+WHERE
+    id > ?
+ORDER BY
+    id
+LIMIT
+    ?;
\ No newline at end of file
@@ -0,0 +1,19 @@
+SELECT
+    id,
+    node,
+    CAST(UNIX_TIMESTAMP(`when`) AS SIGNED),
+    content,
+    `read`,
+    severity,
+    kind,
+    title,
+    escalations,
+    CAST(UNIX_TIMESTAMP(escalated) AS SIGNED)
+FROM
+    notifications
+WHERE
+    id > ?
+ORDER BY
+    id
+LIMIT
+    ?;
\ No newline at end of file
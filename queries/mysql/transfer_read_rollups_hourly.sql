@@ -0,0 +1,27 @@
+SELECT
+    node,
+    CAST(UNIX_TIMESTAMP(bucket) AS SIGNED),
+    samples,
+    temperature_min,
+    temperature_max,
+    temperature_avg,
+    humidity_min,
+    humidity_max,
+    humidity_avg,
+    air_pressure_min,
+    air_pressure_max,
+    air_pressure_avg,
+    battery_min,
+    battery_max,
+    battery_avg,
+    rssi_min,
+    rssi_max,
+    rssi_avg
+FROM
+    rollups_hourly
+ORDER BY
+    node,
+    bucket
+-- Offset first, to bind the parameters in the same order as for the other tables
+LIMIT
+    ?, ?;
\ No newline at end of file
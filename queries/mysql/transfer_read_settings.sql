@@ -0,0 +1,18 @@
+SELECT
+    id,
+    node,
+    battery_ignore,
+    ota,
+    sleep_time,
+    sbop,
+    mute_notifications,
+    device_specific,
+    CAST(UNIX_TIMESTAMP(muted_until) AS SIGNED)
+FROM
+    settings
+WHERE
+    id > ?
+ORDER BY
+    id
+LIMIT
+    ?;
\ No newline at end of file
@@ -0,0 +1,5 @@
+SELECT
+    COUNT(*),
+    NULL
+FROM
+    alert_states;
\ No newline at end of file
@@ -0,0 +1,5 @@
+SELECT
+    COUNT(*),
+    MAX(id)
+FROM
+    devices;
\ No newline at end of file
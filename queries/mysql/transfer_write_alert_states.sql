@@ -0,0 +1,16 @@
+REPLACE INTO
+    alert_states (
+        node,
+        rule,
+        active,
+        streak,
+        changed
+    )
+VALUES
+    (
+        ?,
+        ?,
+        ?,
+        ?,
+        FROM_UNIXTIME(?)
+    );
\ No newline at end of file
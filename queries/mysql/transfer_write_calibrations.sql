@@ -0,0 +1,18 @@
+REPLACE INTO
+    calibrations (
+        node,
+        temperature_offset,
+        temperature_scale,
+        humidity_offset,
+        air_pressure_offset,
+        battery_scale
+    )
+VALUES
+    (
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?
+    );
\ No newline at end of file
@@ -0,0 +1,4 @@
+INSERT INTO
+    devices (id, mac_address, location, note, altitude)
+VALUES
+    (?, ?, ?, ?, ?);
\ No newline at end of file
@@ -0,0 +1,26 @@
+INSERT INTO
+    firmware_stats (
+        id,
+        node,
+        from_version_major,
+        from_version_middle,
+        from_version_minor,
+        to_version_major,
+        to_version_middle,
+        to_version_minor,
+        `when`,
+        success
+    )
+VALUES
+    (
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        FROM_UNIXTIME(?),
+        ?
+    );
\ No newline at end of file
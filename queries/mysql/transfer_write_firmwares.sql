@@ -0,0 +1,12 @@
+INSERT INTO
+    firmwares (
+        id,
+        version_major,
+        version_middle,
+        version_minor,
+        firmware,
+        added_date,
+        restrict_nodes
+    )
+VALUES
+    (?, ?, ?, ?, ?, FROM_UNIXTIME(?), ?);
\ No newline at end of file
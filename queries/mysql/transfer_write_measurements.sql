@@ -0,0 +1,36 @@
+INSERT INTO
+    measurements (
+        id,
+        node,
+        `when`,
+        temperature,
+        humidity,
+        air_pressure,
+        cpu_temp,
+        battery,
+        wifi_ssid,
+        wifi_rssi,
+        flags,
+        raw_temperature,
+        raw_humidity,
+        raw_air_pressure,
+        raw_battery
+    )
+VALUES
+    (
+        ?,
+        ?,
+        FROM_UNIXTIME(?),
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?
+    );
\ No newline at end of file
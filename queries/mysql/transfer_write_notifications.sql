@@ -0,0 +1,26 @@
+INSERT INTO
+    notifications (
+        id,
+        node,
+        `when`,
+        content,
+        `read`,
+        severity,
+        kind,
+        title,
+        escalations,
+        escalated
+    )
+VALUES
+    (
+        ?,
+        ?,
+        FROM_UNIXTIME(?),
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        FROM_UNIXTIME(?)
+    );
\ No newline at end of file
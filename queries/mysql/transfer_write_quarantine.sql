@@ -0,0 +1,28 @@
+INSERT INTO
+    quarantine (
+        id,
+        node,
+        `when`,
+        temperature,
+        humidity,
+        air_pressure,
+        cpu_temp,
+        battery,
+        wifi_ssid,
+        wifi_rssi,
+        reason
+    )
+VALUES
+    (
+        ?,
+        ?,
+        FROM_UNIXTIME(?),
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?
+    );
\ No newline at end of file
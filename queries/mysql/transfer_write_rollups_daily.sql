@@ -0,0 +1,42 @@
+REPLACE INTO
+    rollups_daily (
+        node,
+        bucket,
+        samples,
+        temperature_min,
+        temperature_max,
+        temperature_avg,
+        humidity_min,
+        humidity_max,
+        humidity_avg,
+        air_pressure_min,
+        air_pressure_max,
+        air_pressure_avg,
+        battery_min,
+        battery_max,
+        battery_avg,
+        rssi_min,
+        rssi_max,
+        rssi_avg
+    )
+VALUES
+    (
+        ?,
+        FROM_UNIXTIME(?),
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?,
+        ?
+    );
\ No newline at end of file
@@ -0,0 +1,14 @@
+INSERT INTO
+    settings (
+        id,
+        node,
+        battery_ignore,
+        ota,
+        sleep_time,
+        sbop,
+        mute_notifications,
+        device_specific,
+        muted_until
+    )
+VALUES
+    (?, ?, ?, ?, ?, ?, ?, ?, FROM_UNIXTIME(?));
\ No newline at end of file
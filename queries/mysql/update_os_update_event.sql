@@ -0,0 +1,5 @@
+UPDATE firmware_stats
+SET
+  success = ?
+WHERE
+  id = ?;
\ No newline at end of file
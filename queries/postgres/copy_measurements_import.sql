@@ -0,0 +1,12 @@
+COPY measurements_import (
+    "when",
+    temperature,
+    humidity,
+    air_pressure,
+    cpu_temp,
+    battery,
+    wifi_ssid,
+    wifi_rssi
+)
+FROM
+    STDIN;
\ No newline at end of file
@@ -0,0 +1,10 @@
+SELECT
+    COUNT(*)
+FROM
+    measurements
+WHERE
+    node = $1
+    AND (
+        $2::INT8 IS NULL
+        OR "when" >= NOW() - make_interval(secs => $2)
+    );
\ No newline at end of file
@@ -0,0 +1,6 @@
+SELECT
+    COUNT(*)
+FROM
+    quarantine
+WHERE
+    "when" < NOW() - make_interval(secs => $1);
\ No newline at end of file
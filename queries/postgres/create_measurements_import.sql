@@ -0,0 +1,11 @@
+CREATE TEMPORARY TABLE
+    measurements_import (
+        "when" INT8 NOT NULL,
+        temperature REAL NOT NULL,
+        humidity SMALLINT NOT NULL,
+        air_pressure SMALLINT DEFAULT NULL,
+        cpu_temp REAL NOT NULL,
+        battery REAL NOT NULL,
+        wifi_ssid VARCHAR(32) NOT NULL,
+        wifi_rssi INT2 NOT NULL
+    ) ON COMMIT DROP;
\ No newline at end of file
@@ -1,2 +1,2 @@
-INSERT INTO notifications(node, content)
-VALUES ($1, $2);
\ No newline at end of file
+INSERT INTO notifications(node, content, severity, kind, title)
+VALUES ($1, $2, $3, $4, $5);
\ No newline at end of file
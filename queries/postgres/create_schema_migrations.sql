@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS
+    schema_migrations (
+        version INT4 PRIMARY KEY,
+        name VARCHAR(64) NOT NULL,
+        applied TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW ()
+    );
\ No newline at end of file
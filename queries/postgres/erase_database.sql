@@ -1,8 +1,14 @@
-DROP TABLE IF EXISTS statistics,
+DROP TABLE IF EXISTS alert_states,
+calibrations,
+quarantine,
+rollups_hourly,
+rollups_daily,
+statistics,
 settings,
 notifications,
 measurements,
 firmware_stats,
 firmwares,
 devices,
+schema_migrations,
 _sqlx_migrations CASCADE;
\ No newline at end of file
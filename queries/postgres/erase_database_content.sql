@@ -1,4 +1,9 @@
-TRUNCATE statistics,
+TRUNCATE alert_states,
+calibrations,
+quarantine,
+rollups_hourly,
+rollups_daily,
+statistics,
 measurements,
 settings,
 notifications,
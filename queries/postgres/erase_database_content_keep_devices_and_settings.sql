@@ -1,4 +1,8 @@
-TRUNCATE statistics,
+TRUNCATE alert_states,
+quarantine,
+rollups_hourly,
+rollups_daily,
+statistics,
 measurements,
 notifications,
 firmware_stats,
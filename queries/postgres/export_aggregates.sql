@@ -0,0 +1,31 @@
+SELECT
+    node,
+    (FLOOR(EXTRACT(EPOCH FROM "when") / $4) * $4)::INT8 AS bucket,
+    COUNT(*),
+    MIN(temperature)::FLOAT8,
+    MAX(temperature)::FLOAT8,
+    AVG(temperature)::FLOAT8,
+    MIN(humidity)::FLOAT8,
+    MAX(humidity)::FLOAT8,
+    AVG(humidity)::FLOAT8,
+    MIN(air_pressure)::FLOAT8,
+    MAX(air_pressure)::FLOAT8,
+    AVG(air_pressure)::FLOAT8,
+    MIN(battery)::FLOAT8,
+    MAX(battery)::FLOAT8,
+    AVG(battery)::FLOAT8,
+    MIN(wifi_rssi)::FLOAT8,
+    MAX(wifi_rssi)::FLOAT8,
+    AVG(wifi_rssi)::FLOAT8
+FROM
+    measurements
+WHERE
+    ($1::INT4 IS NULL OR node = $1)
+    AND ($2::INT8 IS NULL OR "when" >= to_timestamp($2))
+    AND ($3::INT8 IS NULL OR "when" < to_timestamp($3))
+GROUP BY
+    node,
+    bucket
+ORDER BY
+    bucket,
+    node;
\ No newline at end of file
@@ -0,0 +1,24 @@
+SELECT
+    EXTRACT(EPOCH FROM "when")::INT8,
+    temperature,
+    humidity,
+    air_pressure,
+    cpu_temp,
+    battery,
+    wifi_ssid,
+    wifi_rssi,
+    flags,
+    COALESCE(raw_temperature, temperature),
+    COALESCE(raw_humidity, humidity),
+    COALESCE(raw_air_pressure, air_pressure),
+    COALESCE(raw_battery, battery),
+    node
+FROM
+    measurements
+WHERE
+    ($1::INT4 IS NULL OR node = $1)
+    AND ($2::INT8 IS NULL OR "when" >= to_timestamp($2))
+    AND ($3::INT8 IS NULL OR "when" < to_timestamp($3))
+ORDER BY
+    "when",
+    node;
\ No newline at end of file
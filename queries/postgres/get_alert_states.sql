@@ -0,0 +1,3 @@
+SELECT rule, active, streak
+FROM alert_states
+WHERE node = $1;
\ No newline at end of file
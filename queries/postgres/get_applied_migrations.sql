@@ -0,0 +1,8 @@
+SELECT
+    version,
+    name,
+    EXTRACT(EPOCH FROM applied)::INT8
+FROM
+    schema_migrations
+ORDER BY
+    version;
\ No newline at end of file
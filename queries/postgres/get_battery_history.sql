@@ -0,0 +1,21 @@
+-- The latest samples in the window, oldest first
+SELECT
+    epoch,
+    battery
+FROM
+    (
+        SELECT
+            EXTRACT(EPOCH FROM "when")::INT8 AS epoch,
+            battery
+        FROM
+            measurements
+        WHERE
+            node = $1
+            AND "when" >= NOW() - make_interval(secs => $2)
+        ORDER BY
+            "when" DESC
+        LIMIT
+            $3
+    ) AS recent
+ORDER BY
+    epoch;
\ No newline at end of file
@@ -0,0 +1,10 @@
+SELECT
+    temperature_offset,
+    temperature_scale,
+    humidity_offset,
+    air_pressure_offset,
+    battery_scale
+FROM
+    calibrations
+WHERE
+    node = $1;
\ No newline at end of file
@@ -0,0 +1,11 @@
+SELECT
+    node,
+    temperature_offset,
+    temperature_scale,
+    humidity_offset,
+    air_pressure_offset,
+    battery_scale
+FROM
+    calibrations
+ORDER BY
+    node;
\ No newline at end of file
@@ -1,3 +1,4 @@
-SELECT battery_ignore, ota, sleep_time, sbop, mute_notifications
+-- Expired timed mutes are resolved by the caller, since the settings are cached
+SELECT battery_ignore, ota, sleep_time, sbop, mute_notifications, EXTRACT(EPOCH FROM muted_until)::INT8
 FROM settings
 WHERE node = $1;
\ No newline at end of file
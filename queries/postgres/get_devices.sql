@@ -0,0 +1,3 @@
+SELECT id, mac_address, note, altitude
+FROM devices
+ORDER BY id;
\ No newline at end of file
@@ -3,7 +3,7 @@ SELECT
     concat_ws ('.', version_major, version_middle, version_minor) AS version,
     length (firmware),
     firmware,
-    to_char (added_date, 'DD.MM.YYYY HH24:MI:SS') AS added_date,
+    to_char (added_date AT TIME ZONE 'UTC', 'DD.MM.YYYY HH24:MI:SS') AS added_date,
     restrict_nodes
 FROM
     firmwares
\ No newline at end of file
@@ -0,0 +1,23 @@
+SELECT DISTINCT ON (node)
+    EXTRACT(EPOCH FROM "when")::INT8,
+    temperature,
+    humidity,
+    air_pressure,
+    cpu_temp,
+    battery,
+    wifi_ssid,
+    wifi_rssi,
+    flags,
+    COALESCE(raw_temperature, temperature),
+    COALESCE(raw_humidity, humidity),
+    COALESCE(raw_air_pressure, air_pressure),
+    COALESCE(raw_battery, battery),
+    node
+FROM
+    measurements
+WHERE
+    "when" >= NOW() - make_interval(secs => $1)
+ORDER BY
+    node,
+    "when" DESC,
+    id DESC;
\ No newline at end of file
@@ -0,0 +1,17 @@
+SELECT
+    id,
+    node,
+    EXTRACT(EPOCH FROM "when")::INT8,
+    content,
+    read,
+    severity,
+    kind,
+    title
+FROM
+    notifications
+WHERE
+    (NOT $1 OR NOT read)
+    AND ($2::INT4 IS NULL OR node = $2)
+    AND ($3::INT8 IS NULL OR "when" >= NOW() - make_interval(secs => $3))
+ORDER BY
+    "when" DESC;
\ No newline at end of file
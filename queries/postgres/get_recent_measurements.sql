@@ -0,0 +1,22 @@
+SELECT
+    EXTRACT(EPOCH FROM "when")::INT8,
+    temperature,
+    humidity,
+    air_pressure,
+    cpu_temp,
+    battery,
+    wifi_ssid,
+    wifi_rssi,
+    flags,
+    COALESCE(raw_temperature, temperature),
+    COALESCE(raw_humidity, humidity),
+    COALESCE(raw_air_pressure, air_pressure),
+    COALESCE(raw_battery, battery)
+FROM
+    measurements
+WHERE
+    node = $1
+ORDER BY
+    "when" DESC
+LIMIT
+    $2;
\ No newline at end of file
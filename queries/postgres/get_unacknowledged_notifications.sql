@@ -0,0 +1,23 @@
+SELECT
+    id,
+    node,
+    EXTRACT(EPOCH FROM "when")::INT8,
+    content,
+    read,
+    severity,
+    kind,
+    title
+FROM
+    notifications
+WHERE
+    NOT read
+    AND escalations < $2
+    AND COALESCE(escalated, "when") <= NOW() - make_interval(secs => $1)
+    -- Ranks as in `Severity::level()`
+    AND CASE severity
+        WHEN 'critical' THEN 2
+        WHEN 'warning' THEN 1
+        ELSE 0
+    END >= $3
+ORDER BY
+    "when";
\ No newline at end of file
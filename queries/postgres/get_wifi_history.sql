@@ -0,0 +1,11 @@
+SELECT
+    EXTRACT(EPOCH FROM "when")::INT8,
+    wifi_ssid,
+    wifi_rssi
+FROM
+    measurements
+WHERE
+    node = $1
+    AND "when" >= NOW() - make_interval(secs => $2)
+ORDER BY
+    "when";
\ No newline at end of file
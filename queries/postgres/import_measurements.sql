@@ -0,0 +1,36 @@
+INSERT INTO
+    measurements (
+        "node",
+        "when",
+        "temperature",
+        "humidity",
+        "air_pressure",
+        "cpu_temp",
+        "battery",
+        "wifi_ssid",
+        "wifi_rssi"
+    )
+SELECT
+    $1,
+    to_timestamp(i."when"),
+    i.temperature,
+    i.humidity,
+    i.air_pressure,
+    i.cpu_temp,
+    i.battery,
+    i.wifi_ssid,
+    i.wifi_rssi
+FROM
+    measurements_import i
+WHERE
+    NOT $2
+    OR NOT EXISTS (
+        SELECT
+            1
+        FROM
+            measurements m
+        WHERE
+            m.node = $1
+            AND m."when" > to_timestamp(i."when" - 1)
+            AND m."when" < to_timestamp(i."when" + 1)
+    );
\ No newline at end of file
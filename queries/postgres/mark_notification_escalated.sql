@@ -0,0 +1,7 @@
+UPDATE
+  notifications
+SET
+  escalations = escalations + 1,
+  escalated = NOW()
+WHERE
+  id = $1;
\ No newline at end of file
@@ -0,0 +1,7 @@
+UPDATE
+  notifications
+SET
+  read = TRUE
+WHERE
+  NOT read
+  AND ($1::INT4 IS NULL OR id = $1);
\ No newline at end of file
@@ -0,0 +1,14 @@
+-- Timestamps of the initial schema were stored without a time zone, the server always wrote them in UTC
+ALTER TABLE notifications
+ALTER COLUMN "when" TYPE TIMESTAMP WITH TIME ZONE USING "when" AT TIME ZONE 'UTC',
+ADD COLUMN severity VARCHAR(8) NOT NULL DEFAULT 'info',
+ADD COLUMN kind VARCHAR(32) NOT NULL DEFAULT 'custom',
+ADD COLUMN title VARCHAR(256) NOT NULL DEFAULT '',
+ADD COLUMN escalations INT4 NOT NULL DEFAULT 0,
+ADD COLUMN escalated TIMESTAMP WITH TIME ZONE;
+
+ALTER TABLE firmwares
+ALTER COLUMN added_date TYPE TIMESTAMP WITH TIME ZONE USING added_date AT TIME ZONE 'UTC';
+
+ALTER TABLE firmware_stats
+ALTER COLUMN "when" TYPE TIMESTAMP WITH TIME ZONE USING "when" AT TIME ZONE 'UTC';
\ No newline at end of file
@@ -0,0 +1,9 @@
+CREATE TABLE
+    alert_states (
+        node INT4 NOT NULL REFERENCES devices (id),
+        rule VARCHAR(64) NOT NULL,
+        active BOOLEAN NOT NULL DEFAULT FALSE,
+        streak INT4 NOT NULL DEFAULT 0,
+        changed TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+        PRIMARY KEY (node, rule)
+    );
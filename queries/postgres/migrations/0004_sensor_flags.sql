@@ -0,0 +1,2 @@
+ALTER TABLE measurements
+ADD COLUMN flags INT4 NOT NULL DEFAULT 0;
@@ -0,0 +1,14 @@
+CREATE TABLE
+    quarantine (
+        id SERIAL PRIMARY KEY,
+        node INT4 NOT NULL REFERENCES devices (id),
+        "when" TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+        temperature REAL NOT NULL,
+        humidity INT2 NOT NULL,
+        air_pressure INT4 DEFAULT NULL,
+        cpu_temp REAL NOT NULL,
+        battery REAL NOT NULL,
+        wifi_ssid TEXT NOT NULL,
+        wifi_rssi INT2 NOT NULL,
+        reason TEXT NOT NULL
+    );
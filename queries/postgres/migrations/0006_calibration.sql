@@ -0,0 +1,15 @@
+ALTER TABLE measurements
+ADD COLUMN raw_temperature REAL DEFAULT NULL,
+ADD COLUMN raw_humidity SMALLINT DEFAULT NULL,
+ADD COLUMN raw_air_pressure SMALLINT DEFAULT NULL,
+ADD COLUMN raw_battery REAL DEFAULT NULL;
+
+CREATE TABLE
+    calibrations (
+        node INT4 PRIMARY KEY REFERENCES devices (id),
+        temperature_offset REAL NOT NULL DEFAULT 0,
+        temperature_scale REAL NOT NULL DEFAULT 1,
+        humidity_offset REAL NOT NULL DEFAULT 0,
+        air_pressure_offset REAL NOT NULL DEFAULT 0,
+        battery_scale REAL NOT NULL DEFAULT 1
+    );
@@ -0,0 +1,2 @@
+ALTER TABLE devices
+ADD COLUMN altitude REAL DEFAULT NULL;
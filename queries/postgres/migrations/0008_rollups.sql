@@ -0,0 +1,46 @@
+-- Buckets are in UTC and identified by their start
+CREATE TABLE
+    rollups_hourly (
+        node INT4 NOT NULL REFERENCES devices (id),
+        bucket TIMESTAMP WITH TIME ZONE NOT NULL,
+        samples INT4 NOT NULL,
+        temperature_min REAL NOT NULL,
+        temperature_max REAL NOT NULL,
+        temperature_avg REAL NOT NULL,
+        humidity_min SMALLINT NOT NULL,
+        humidity_max SMALLINT NOT NULL,
+        humidity_avg REAL NOT NULL,
+        air_pressure_min SMALLINT DEFAULT NULL,
+        air_pressure_max SMALLINT DEFAULT NULL,
+        air_pressure_avg REAL DEFAULT NULL,
+        battery_min REAL NOT NULL,
+        battery_max REAL NOT NULL,
+        battery_avg REAL NOT NULL,
+        rssi_min INT2 NOT NULL,
+        rssi_max INT2 NOT NULL,
+        rssi_avg REAL NOT NULL,
+        PRIMARY KEY (node, bucket)
+    );
+
+CREATE TABLE
+    rollups_daily (
+        node INT4 NOT NULL REFERENCES devices (id),
+        bucket TIMESTAMP WITH TIME ZONE NOT NULL,
+        samples INT4 NOT NULL,
+        temperature_min REAL NOT NULL,
+        temperature_max REAL NOT NULL,
+        temperature_avg REAL NOT NULL,
+        humidity_min SMALLINT NOT NULL,
+        humidity_max SMALLINT NOT NULL,
+        humidity_avg REAL NOT NULL,
+        air_pressure_min SMALLINT DEFAULT NULL,
+        air_pressure_max SMALLINT DEFAULT NULL,
+        air_pressure_avg REAL DEFAULT NULL,
+        battery_min REAL NOT NULL,
+        battery_max REAL NOT NULL,
+        battery_avg REAL NOT NULL,
+        rssi_min INT2 NOT NULL,
+        rssi_max INT2 NOT NULL,
+        rssi_avg REAL NOT NULL,
+        PRIMARY KEY (node, bucket)
+    );
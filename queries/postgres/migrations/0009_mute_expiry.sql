@@ -0,0 +1,2 @@
+-- Expiry of a timed mute, NULL if the node is muted indefinitely
+ALTER TABLE settings ADD COLUMN muted_until TIMESTAMP WITH TIME ZONE DEFAULT NULL;
\ No newline at end of file
@@ -6,6 +6,11 @@ INSERT INTO measurements(
         "cpu_temp",
         "battery",
         "wifi_ssid",
-        "wifi_rssi"
+        "wifi_rssi",
+        "flags",
+        "raw_temperature",
+        "raw_humidity",
+        "raw_air_pressure",
+        "raw_battery"
     )
-VALUES ($1, $2, $3, $4, $5, $6, $7, $8);
\ No newline at end of file
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13);
\ No newline at end of file
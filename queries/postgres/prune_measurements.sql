@@ -0,0 +1,12 @@
+DELETE FROM measurements
+WHERE
+    id IN (
+        SELECT
+            id
+        FROM
+            measurements
+        WHERE
+            "when" < NOW() - make_interval(secs => $1)
+        LIMIT
+            $2
+    );
\ No newline at end of file
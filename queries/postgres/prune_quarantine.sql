@@ -0,0 +1,12 @@
+DELETE FROM quarantine
+WHERE
+    id IN (
+        SELECT
+            id
+        FROM
+            quarantine
+        WHERE
+            "when" < NOW() - make_interval(secs => $1)
+        LIMIT
+            $2
+    );
\ No newline at end of file
@@ -0,0 +1,67 @@
+INSERT INTO
+    rollups_hourly (
+        node,
+        bucket,
+        samples,
+        temperature_min,
+        temperature_max,
+        temperature_avg,
+        humidity_min,
+        humidity_max,
+        humidity_avg,
+        air_pressure_min,
+        air_pressure_max,
+        air_pressure_avg,
+        battery_min,
+        battery_max,
+        battery_avg,
+        rssi_min,
+        rssi_max,
+        rssi_avg
+    )
+SELECT
+    node,
+    date_trunc('hour', "when", 'UTC') AS bucket,
+    COUNT(*),
+    MIN(temperature),
+    MAX(temperature),
+    AVG(temperature),
+    MIN(humidity),
+    MAX(humidity),
+    AVG(humidity),
+    MIN(air_pressure),
+    MAX(air_pressure),
+    AVG(air_pressure),
+    MIN(battery),
+    MAX(battery),
+    AVG(battery),
+    MIN(wifi_rssi),
+    MAX(wifi_rssi),
+    AVG(wifi_rssi)
+FROM
+    measurements
+WHERE
+    ($1::INT4 IS NULL OR node = $1)
+    -- Whole buckets are recomputed
+    AND ($2::INT8 IS NULL OR "when" >= date_trunc('hour', to_timestamp($2), 'UTC'))
+GROUP BY
+    node,
+    bucket
+ON CONFLICT (node, bucket) DO UPDATE
+SET
+    samples = EXCLUDED.samples,
+    temperature_min = EXCLUDED.temperature_min,
+    temperature_max = EXCLUDED.temperature_max,
+    temperature_avg = EXCLUDED.temperature_avg,
+    humidity_min = EXCLUDED.humidity_min,
+    humidity_max = EXCLUDED.humidity_max,
+    humidity_avg = EXCLUDED.humidity_avg,
+    air_pressure_min = EXCLUDED.air_pressure_min,
+    air_pressure_max = EXCLUDED.air_pressure_max,
+    air_pressure_avg = EXCLUDED.air_pressure_avg,
+    battery_min = EXCLUDED.battery_min,
+    battery_max = EXCLUDED.battery_max,
+    battery_avg = EXCLUDED.battery_avg,
+    rssi_min = EXCLUDED.rssi_min,
+    rssi_max = EXCLUDED.rssi_max,
+    rssi_avg = EXCLUDED.rssi_avg;
\ No newline at end of file
@@ -0,0 +1,7 @@
+INSERT INTO alert_states(node, rule, active, streak)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT (node, rule) DO UPDATE
+SET
+  changed = CASE WHEN alert_states.active <> EXCLUDED.active THEN NOW() ELSE alert_states.changed END,
+  active = EXCLUDED.active,
+  streak = EXCLUDED.streak;
\ No newline at end of file
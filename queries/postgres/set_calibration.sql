@@ -0,0 +1,9 @@
+INSERT INTO calibrations(node, temperature_offset, temperature_scale, humidity_offset, air_pressure_offset, battery_scale)
+VALUES ($1, $2, $3, $4, $5, $6)
+ON CONFLICT (node) DO UPDATE
+SET
+  temperature_offset = EXCLUDED.temperature_offset,
+  temperature_scale = EXCLUDED.temperature_scale,
+  humidity_offset = EXCLUDED.humidity_offset,
+  air_pressure_offset = EXCLUDED.air_pressure_offset,
+  battery_scale = EXCLUDED.battery_scale;
\ No newline at end of file
@@ -0,0 +1,6 @@
+UPDATE
+  devices
+SET
+  altitude = $2
+WHERE
+  id = $1;
\ No newline at end of file
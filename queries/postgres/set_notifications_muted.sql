@@ -0,0 +1,7 @@
+UPDATE
+  settings
+SET
+  mute_notifications = $2,
+  muted_until = to_timestamp($3)
+WHERE
+  node = $1;
\ No newline at end of file
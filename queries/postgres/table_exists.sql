@@ -0,0 +1 @@
+SELECT to_regclass($1) IS NOT NULL;
\ No newline at end of file
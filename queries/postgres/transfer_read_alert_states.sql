@@ -0,0 +1,15 @@
+SELECT
+    node,
+    rule,
+    active,
+    streak,
+    EXTRACT(EPOCH FROM changed)::INT8
+FROM
+    alert_states
+ORDER BY
+    node,
+    rule
+LIMIT
+    $2
+OFFSET
+    $1;
\ No newline at end of file
@@ -0,0 +1,14 @@
+SELECT
+    id,
+    mac_address,
+    location::TEXT,
+    note,
+    altitude
+FROM
+    devices
+WHERE
+    id > $1
+ORDER BY
+    id
+LIMIT
+    $2;
\ No newline at end of file
@@ -0,0 +1,19 @@
+SELECT
+    id,
+    node,
+    from_version_major,
+    from_version_middle,
+    from_version_minor,
+    to_version_major,
+    to_version_middle,
+    to_version_minor,
+    EXTRACT(EPOCH FROM "when")::INT8,
+    success
+FROM
+    firmware_stats
+WHERE
+    id > $1
+ORDER BY
+    id
+LIMIT
+    $2;
\ No newline at end of file
@@ -0,0 +1,24 @@
+SELECT
+    id,
+    node,
+    EXTRACT(EPOCH FROM "when")::INT8,
+    temperature,
+    humidity,
+    air_pressure,
+    cpu_temp,
+    battery,
+    wifi_ssid,
+    wifi_rssi,
+    flags,
+    raw_temperature,
+    raw_humidity,
+    raw_air_pressure,
+    raw_battery
+FROM
+    measurements
+WHERE
+    id > $1
+ORDER BY
+    id
+LIMIT
+    $2;
\ No newline at end of file
@@ -0,0 +1,19 @@
+SELECT
+    id,
+    node,
+    EXTRACT(EPOCH FROM "when")::INT8,
+    content,
+    read,
+    severity,
+    kind,
+    title,
+    escalations,
+    EXTRACT(EPOCH FROM escalated)::INT8
+FROM
+    notifications
+WHERE
+    id > $1
+ORDER BY
+    id
+LIMIT
+    $2;
\ No newline at end of file
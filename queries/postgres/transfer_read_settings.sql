@@ -0,0 +1,18 @@
+SELECT
+    id,
+    node,
+    battery_ignore,
+    ota,
+    sleep_time,
+    sbop,
+    mute_notifications,
+    device_specific::TEXT,
+    EXTRACT(EPOCH FROM muted_until)::INT8
+FROM
+    settings
+WHERE
+    id > $1
+ORDER BY
+    id
+LIMIT
+    $2;
\ No newline at end of file
@@ -0,0 +1,2 @@
+SELECT
+    setval(pg_get_serial_sequence($1, 'id'), $2);
\ No newline at end of file
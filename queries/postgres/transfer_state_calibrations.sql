@@ -0,0 +1,5 @@
+SELECT
+    COUNT(*),
+    NULL::INT4
+FROM
+    calibrations;
\ No newline at end of file
@@ -0,0 +1,5 @@
+SELECT
+    COUNT(*),
+    MAX(id)
+FROM
+    firmware_stats;
\ No newline at end of file
@@ -0,0 +1,21 @@
+INSERT INTO
+    alert_states (
+        node,
+        rule,
+        active,
+        streak,
+        changed
+    )
+VALUES
+    (
+        $1,
+        $2,
+        $3,
+        $4,
+        to_timestamp($5)
+    )
+ON CONFLICT (node, rule) DO UPDATE
+SET
+    active = EXCLUDED.active,
+    streak = EXCLUDED.streak,
+    changed = EXCLUDED.changed;
\ No newline at end of file
@@ -0,0 +1,4 @@
+INSERT INTO
+    devices (id, mac_address, location, note, altitude)
+VALUES
+    ($1, $2, $3::POINT, $4, $5);
\ No newline at end of file
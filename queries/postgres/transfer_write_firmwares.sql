@@ -0,0 +1,12 @@
+INSERT INTO
+    firmwares (
+        id,
+        version_major,
+        version_middle,
+        version_minor,
+        firmware,
+        added_date,
+        restrict_nodes
+    )
+VALUES
+    ($1, $2, $3, $4, $5, to_timestamp($6), $7);
\ No newline at end of file
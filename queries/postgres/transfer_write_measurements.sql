@@ -0,0 +1,36 @@
+INSERT INTO
+    measurements (
+        id,
+        node,
+        "when",
+        temperature,
+        humidity,
+        air_pressure,
+        cpu_temp,
+        battery,
+        wifi_ssid,
+        wifi_rssi,
+        flags,
+        raw_temperature,
+        raw_humidity,
+        raw_air_pressure,
+        raw_battery
+    )
+VALUES
+    (
+        $1,
+        $2,
+        to_timestamp($3),
+        $4,
+        $5,
+        $6,
+        $7,
+        $8,
+        $9,
+        $10,
+        $11,
+        $12,
+        $13,
+        $14,
+        $15
+    );
\ No newline at end of file
@@ -0,0 +1,26 @@
+INSERT INTO
+    notifications (
+        id,
+        node,
+        "when",
+        content,
+        read,
+        severity,
+        kind,
+        title,
+        escalations,
+        escalated
+    )
+VALUES
+    (
+        $1,
+        $2,
+        to_timestamp($3),
+        $4,
+        $5,
+        $6,
+        $7,
+        $8,
+        $9,
+        to_timestamp($10)
+    );
\ No newline at end of file
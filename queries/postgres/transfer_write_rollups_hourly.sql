@@ -0,0 +1,60 @@
+INSERT INTO
+    rollups_hourly (
+        node,
+        bucket,
+        samples,
+        temperature_min,
+        temperature_max,
+        temperature_avg,
+        humidity_min,
+        humidity_max,
+        humidity_avg,
+        air_pressure_min,
+        air_pressure_max,
+        air_pressure_avg,
+        battery_min,
+        battery_max,
+        battery_avg,
+        rssi_min,
+        rssi_max,
+        rssi_avg
+    )
+VALUES
+    (
+        $1,
+        to_timestamp($2),
+        $3,
+        $4,
+        $5,
+        $6,
+        $7,
+        $8,
+        $9,
+        $10,
+        $11,
+        $12,
+        $13,
+        $14,
+        $15,
+        $16,
+        $17,
+        $18
+    )
+ON CONFLICT (node, bucket) DO UPDATE
+SET
+    samples = EXCLUDED.samples,
+    temperature_min = EXCLUDED.temperature_min,
+    temperature_max = EXCLUDED.temperature_max,
+    temperature_avg = EXCLUDED.temperature_avg,
+    humidity_min = EXCLUDED.humidity_min,
+    humidity_max = EXCLUDED.humidity_max,
+    humidity_avg = EXCLUDED.humidity_avg,
+    air_pressure_min = EXCLUDED.air_pressure_min,
+    air_pressure_max = EXCLUDED.air_pressure_max,
+    air_pressure_avg = EXCLUDED.air_pressure_avg,
+    battery_min = EXCLUDED.battery_min,
+    battery_max = EXCLUDED.battery_max,
+    battery_avg = EXCLUDED.battery_avg,
+    rssi_min = EXCLUDED.rssi_min,
+    rssi_max = EXCLUDED.rssi_max,
+    rssi_avg = EXCLUDED.rssi_avg;
\ No newline at end of file
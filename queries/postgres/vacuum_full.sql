@@ -0,0 +1 @@
+VACUUM (FULL, ANALYZE) measurements, notifications, firmware_stats, quarantine;
\ No newline at end of file
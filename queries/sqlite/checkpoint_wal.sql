@@ -0,0 +1 @@
+PRAGMA wal_checkpoint (TRUNCATE);
\ No newline at end of file
@@ -0,0 +1,10 @@
+SELECT
+    COUNT(*)
+FROM
+    measurements
+WHERE
+    node = ?1
+    AND (
+        ?2 IS NULL
+        OR "when" >= datetime ('now', '-' || ?2 || ' seconds')
+    );
\ No newline at end of file
@@ -0,0 +1,6 @@
+SELECT
+    COUNT(*)
+FROM
+    measurements
+WHERE
+    "when" < datetime ('now', '-' || ?1 || ' seconds');
\ No newline at end of file
@@ -0,0 +1,6 @@
+SELECT
+    COUNT(*)
+FROM
+    notifications
+WHERE
+    "when" < datetime ('now', '-' || ?1 || ' seconds');
\ No newline at end of file
@@ -1,4 +1,4 @@
 INSERT INTO
-    notifications (node, content)
+    notifications (node, content, severity, kind, title)
 VALUES
-    (?1, ?2);
\ No newline at end of file
+    (?1, ?2, ?3, ?4, ?5);
\ No newline at end of file
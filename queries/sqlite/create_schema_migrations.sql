@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS
+    schema_migrations (
+        version INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        applied TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    ) STRICT;
\ No newline at end of file
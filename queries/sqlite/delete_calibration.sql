@@ -0,0 +1,3 @@
+DELETE FROM calibrations
+WHERE
+    node = ?1;
\ No newline at end of file
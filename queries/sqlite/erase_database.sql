@@ -1,3 +1,13 @@
+DROP TABLE IF EXISTS alert_states;
+
+DROP TABLE IF EXISTS calibrations;
+
+DROP TABLE IF EXISTS quarantine;
+
+DROP TABLE IF EXISTS rollups_hourly;
+
+DROP TABLE IF EXISTS rollups_daily;
+
 DROP TABLE IF EXISTS statistics;
 
 DROP TABLE IF EXISTS settings;
@@ -12,4 +22,6 @@ DROP TABLE IF EXISTS firmwares;
 
 DROP TABLE IF EXISTS devices;
 
+DROP TABLE IF EXISTS schema_migrations;
+
 DROP TABLE IF EXISTS _sqlx_migrations;
\ No newline at end of file
@@ -1,3 +1,13 @@
+DELETE FROM alert_states;
+
+DELETE FROM calibrations;
+
+DELETE FROM quarantine;
+
+DELETE FROM rollups_hourly;
+
+DELETE FROM rollups_daily;
+
 DELETE FROM statistics;
 
 DELETE FROM measurements;
@@ -15,6 +25,7 @@ DELETE FROM devices;
 DELETE FROM sqlite_sequence
 WHERE
     name IN (
+        'quarantine',
         'statistics',
         'measurements',
         'settings',
@@ -1,3 +1,11 @@
+DELETE FROM alert_states;
+
+DELETE FROM quarantine;
+
+DELETE FROM rollups_hourly;
+
+DELETE FROM rollups_daily;
+
 DELETE FROM statistics;
 
 DELETE FROM measurements;
@@ -11,6 +19,7 @@ DELETE FROM firmwares;
 DELETE FROM sqlite_sequence
 WHERE
     name IN (
+        'quarantine',
         'statistics',
         'measurements',
         'notifications',
@@ -0,0 +1,40 @@
+SELECT
+    node,
+    (unixepoch ("when") / ?4) * ?4 AS bucket,
+    COUNT(*),
+    CAST(MIN(temperature) AS REAL),
+    CAST(MAX(temperature) AS REAL),
+    AVG(temperature),
+    CAST(MIN(humidity) AS REAL),
+    CAST(MAX(humidity) AS REAL),
+    AVG(humidity),
+    CAST(MIN(air_pressure) AS REAL),
+    CAST(MAX(air_pressure) AS REAL),
+    AVG(air_pressure),
+    CAST(MIN(battery) AS REAL),
+    CAST(MAX(battery) AS REAL),
+    AVG(battery),
+    CAST(MIN(wifi_rssi) AS REAL),
+    CAST(MAX(wifi_rssi) AS REAL),
+    AVG(wifi_rssi)
+FROM
+    measurements
+WHERE
+    (
+        ?1 IS NULL
+        OR node = ?1
+    )
+    AND (
+        ?2 IS NULL
+        OR "when" >= datetime (?2, 'unixepoch')
+    )
+    AND (
+        ?3 IS NULL
+        OR "when" < datetime (?3, 'unixepoch')
+    )
+GROUP BY
+    node,
+    bucket
+ORDER BY
+    bucket,
+    node;
\ No newline at end of file
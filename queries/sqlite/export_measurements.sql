@@ -0,0 +1,33 @@
+SELECT
+    unixepoch ("when"),
+    temperature,
+    humidity,
+    air_pressure,
+    cpu_temp,
+    battery,
+    wifi_ssid,
+    wifi_rssi,
+    flags,
+    COALESCE(raw_temperature, temperature),
+    COALESCE(raw_humidity, humidity),
+    COALESCE(raw_air_pressure, air_pressure),
+    COALESCE(raw_battery, battery),
+    node
+FROM
+    measurements
+WHERE
+    (
+        ?1 IS NULL
+        OR node = ?1
+    )
+    AND (
+        ?2 IS NULL
+        OR "when" >= datetime (?2, 'unixepoch')
+    )
+    AND (
+        ?3 IS NULL
+        OR "when" < datetime (?3, 'unixepoch')
+    )
+ORDER BY
+    "when",
+    node;
\ No newline at end of file
@@ -0,0 +1,8 @@
+SELECT
+    rule,
+    active,
+    streak
+FROM
+    alert_states
+WHERE
+    node = ?1;
\ No newline at end of file
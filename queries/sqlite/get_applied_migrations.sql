@@ -0,0 +1,8 @@
+SELECT
+    version,
+    name,
+    unixepoch (applied)
+FROM
+    schema_migrations
+ORDER BY
+    version;
\ No newline at end of file
@@ -0,0 +1,21 @@
+-- The latest samples in the window, oldest first
+SELECT
+    epoch,
+    battery
+FROM
+    (
+        SELECT
+            unixepoch ("when") AS epoch,
+            battery
+        FROM
+            measurements
+        WHERE
+            node = ?1
+            AND "when" >= datetime ('now', '-' || ?2 || ' seconds')
+        ORDER BY
+            "when" DESC
+        LIMIT
+            ?3
+    ) AS recent
+ORDER BY
+    epoch;
\ No newline at end of file
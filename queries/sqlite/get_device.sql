@@ -0,0 +1,9 @@
+SELECT
+    id,
+    mac_address,
+    note,
+    altitude
+FROM
+    devices
+WHERE
+    id = ?1;
\ No newline at end of file
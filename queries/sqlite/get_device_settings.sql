@@ -1,9 +1,11 @@
+-- Expired timed mutes are resolved by the caller, since the settings are cached
 SELECT
     battery_ignore,
     ota,
     sleep_time,
     sbop,
-    mute_notifications
+    mute_notifications,
+    unixepoch (muted_until)
 FROM
     settings
 WHERE
@@ -0,0 +1,35 @@
+SELECT
+    unixepoch ("when"),
+    temperature,
+    humidity,
+    air_pressure,
+    cpu_temp,
+    battery,
+    wifi_ssid,
+    wifi_rssi,
+    flags,
+    COALESCE(raw_temperature, temperature),
+    COALESCE(raw_humidity, humidity),
+    COALESCE(raw_air_pressure, air_pressure),
+    COALESCE(raw_battery, battery),
+    node
+FROM
+    (
+        SELECT
+            *,
+            ROW_NUMBER() OVER (
+                PARTITION BY
+                    node
+                ORDER BY
+                    "when" DESC,
+                    id DESC
+            ) AS position
+        FROM
+            measurements
+        WHERE
+            "when" >= datetime ('now', '-' || ?1 || ' seconds')
+    )
+WHERE
+    position = 1
+ORDER BY
+    node;
\ No newline at end of file
@@ -0,0 +1,17 @@
+SELECT
+    id,
+    node,
+    unixepoch ("when"),
+    content,
+    read,
+    severity,
+    kind,
+    title
+FROM
+    notifications
+WHERE
+    (NOT ?1 OR NOT read)
+    AND (?2 IS NULL OR node = ?2)
+    AND (?3 IS NULL OR "when" >= datetime ('now', '-' || ?3 || ' seconds'))
+ORDER BY
+    "when" DESC;
\ No newline at end of file
@@ -0,0 +1,23 @@
+SELECT
+    id,
+    node,
+    unixepoch ("when"),
+    content,
+    read,
+    severity,
+    kind,
+    title
+FROM
+    notifications
+WHERE
+    NOT read
+    AND escalations < ?2
+    AND COALESCE(escalated, "when") <= datetime ('now', '-' || ?1 || ' seconds')
+    -- Ranks as in `Severity::level()`
+    AND CASE severity
+        WHEN 'critical' THEN 2
+        WHEN 'warning' THEN 1
+        ELSE 0
+    END >= ?3
+ORDER BY
+    "when";
\ No newline at end of file
@@ -0,0 +1,11 @@
+SELECT
+    unixepoch ("when"),
+    wifi_ssid,
+    wifi_rssi
+FROM
+    measurements
+WHERE
+    node = ?1
+    AND "when" >= datetime ('now', '-' || ?2 || ' seconds')
+ORDER BY
+    "when";
\ No newline at end of file
@@ -0,0 +1,23 @@
+INSERT INTO
+    measurements ("node", "when", "temperature", "humidity", "air_pressure", "cpu_temp", "battery", "wifi_ssid", "wifi_rssi")
+SELECT
+    ?1,
+    datetime (?2, 'unixepoch'),
+    ?3,
+    ?4,
+    ?5,
+    ?6,
+    ?7,
+    ?8,
+    ?9
+WHERE
+    NOT ?10
+    OR NOT EXISTS (
+        SELECT
+            1
+        FROM
+            measurements
+        WHERE
+            node = ?1
+            AND "when" = datetime (?2, 'unixepoch')
+    );
\ No newline at end of file
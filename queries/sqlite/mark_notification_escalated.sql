@@ -0,0 +1,6 @@
+UPDATE notifications
+SET
+  escalations = escalations + 1,
+  escalated = CURRENT_TIMESTAMP
+WHERE
+  id = ?1;
\ No newline at end of file
@@ -0,0 +1,6 @@
+UPDATE notifications
+SET
+  read = 1
+WHERE
+  NOT read
+  AND (?1 IS NULL OR id = ?1);
\ No newline at end of file
@@ -0,0 +1,9 @@
+ALTER TABLE notifications ADD COLUMN severity TEXT NOT NULL DEFAULT 'info';
+
+ALTER TABLE notifications ADD COLUMN kind TEXT NOT NULL DEFAULT 'custom';
+
+ALTER TABLE notifications ADD COLUMN title TEXT NOT NULL DEFAULT '';
+
+ALTER TABLE notifications ADD COLUMN escalations INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE notifications ADD COLUMN escalated TEXT;
@@ -0,0 +1,9 @@
+CREATE TABLE
+    alert_states (
+        node INTEGER NOT NULL REFERENCES devices (id),
+        rule TEXT NOT NULL,
+        active INTEGER NOT NULL DEFAULT 0,
+        streak INTEGER NOT NULL DEFAULT 0,
+        changed TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (node, rule)
+    ) STRICT;
@@ -0,0 +1 @@
+ALTER TABLE measurements ADD COLUMN flags INTEGER NOT NULL DEFAULT 0;
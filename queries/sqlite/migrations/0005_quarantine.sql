@@ -0,0 +1,14 @@
+CREATE TABLE
+    quarantine (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        node INTEGER NOT NULL REFERENCES devices (id),
+        "when" TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        temperature REAL NOT NULL,
+        humidity INTEGER NOT NULL,
+        air_pressure INTEGER DEFAULT NULL,
+        cpu_temp REAL NOT NULL,
+        battery REAL NOT NULL,
+        wifi_ssid TEXT NOT NULL,
+        wifi_rssi INTEGER NOT NULL,
+        reason TEXT NOT NULL
+    ) STRICT;
@@ -0,0 +1,17 @@
+ALTER TABLE measurements ADD COLUMN raw_temperature REAL DEFAULT NULL;
+
+ALTER TABLE measurements ADD COLUMN raw_humidity INTEGER DEFAULT NULL;
+
+ALTER TABLE measurements ADD COLUMN raw_air_pressure INTEGER DEFAULT NULL;
+
+ALTER TABLE measurements ADD COLUMN raw_battery REAL DEFAULT NULL;
+
+CREATE TABLE
+    calibrations (
+        node INTEGER PRIMARY KEY REFERENCES devices (id),
+        temperature_offset REAL NOT NULL DEFAULT 0,
+        temperature_scale REAL NOT NULL DEFAULT 1,
+        humidity_offset REAL NOT NULL DEFAULT 0,
+        air_pressure_offset REAL NOT NULL DEFAULT 0,
+        battery_scale REAL NOT NULL DEFAULT 1
+    ) STRICT;
@@ -0,0 +1 @@
+ALTER TABLE devices ADD COLUMN altitude REAL DEFAULT NULL;
@@ -0,0 +1,46 @@
+-- Buckets are in UTC and identified by their start
+CREATE TABLE
+    rollups_hourly (
+        node INTEGER NOT NULL REFERENCES devices (id),
+        bucket TEXT NOT NULL,
+        samples INTEGER NOT NULL,
+        temperature_min REAL NOT NULL,
+        temperature_max REAL NOT NULL,
+        temperature_avg REAL NOT NULL,
+        humidity_min INTEGER NOT NULL,
+        humidity_max INTEGER NOT NULL,
+        humidity_avg REAL NOT NULL,
+        air_pressure_min INTEGER DEFAULT NULL,
+        air_pressure_max INTEGER DEFAULT NULL,
+        air_pressure_avg REAL DEFAULT NULL,
+        battery_min REAL NOT NULL,
+        battery_max REAL NOT NULL,
+        battery_avg REAL NOT NULL,
+        rssi_min INTEGER NOT NULL,
+        rssi_max INTEGER NOT NULL,
+        rssi_avg REAL NOT NULL,
+        PRIMARY KEY (node, bucket)
+    ) STRICT;
+
+CREATE TABLE
+    rollups_daily (
+        node INTEGER NOT NULL REFERENCES devices (id),
+        bucket TEXT NOT NULL,
+        samples INTEGER NOT NULL,
+        temperature_min REAL NOT NULL,
+        temperature_max REAL NOT NULL,
+        temperature_avg REAL NOT NULL,
+        humidity_min INTEGER NOT NULL,
+        humidity_max INTEGER NOT NULL,
+        humidity_avg REAL NOT NULL,
+        air_pressure_min INTEGER DEFAULT NULL,
+        air_pressure_max INTEGER DEFAULT NULL,
+        air_pressure_avg REAL DEFAULT NULL,
+        battery_min REAL NOT NULL,
+        battery_max REAL NOT NULL,
+        battery_avg REAL NOT NULL,
+        rssi_min INTEGER NOT NULL,
+        rssi_max INTEGER NOT NULL,
+        rssi_avg REAL NOT NULL,
+        PRIMARY KEY (node, bucket)
+    ) STRICT;
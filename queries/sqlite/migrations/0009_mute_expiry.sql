@@ -0,0 +1,2 @@
+-- Expiry of a timed mute, NULL if the node is muted indefinitely
+ALTER TABLE settings ADD COLUMN muted_until TEXT DEFAULT NULL;
\ No newline at end of file
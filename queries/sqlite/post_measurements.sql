@@ -1,4 +1,4 @@
 INSERT INTO
-    measurements ("node", "temperature", "humidity", "air_pressure", "cpu_temp", "battery", "wifi_ssid", "wifi_rssi")
+    measurements ("node", "temperature", "humidity", "air_pressure", "cpu_temp", "battery", "wifi_ssid", "wifi_rssi", "flags", "raw_temperature", "raw_humidity", "raw_air_pressure", "raw_battery")
 VALUES
-    (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8);
\ No newline at end of file
+    (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13);
\ No newline at end of file
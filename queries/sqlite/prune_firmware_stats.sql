@@ -0,0 +1,12 @@
+DELETE FROM firmware_stats
+WHERE
+    id IN (
+        SELECT
+            id
+        FROM
+            firmware_stats
+        WHERE
+            "when" < datetime ('now', '-' || ?1 || ' seconds')
+        LIMIT
+            ?2
+    );
\ No newline at end of file
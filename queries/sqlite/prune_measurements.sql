@@ -0,0 +1,12 @@
+DELETE FROM measurements
+WHERE
+    id IN (
+        SELECT
+            id
+        FROM
+            measurements
+        WHERE
+            "when" < datetime ('now', '-' || ?1 || ' seconds')
+        LIMIT
+            ?2
+    );
\ No newline at end of file
@@ -0,0 +1,12 @@
+DELETE FROM notifications
+WHERE
+    id IN (
+        SELECT
+            id
+        FROM
+            notifications
+        WHERE
+            "when" < datetime ('now', '-' || ?1 || ' seconds')
+        LIMIT
+            ?2
+    );
\ No newline at end of file
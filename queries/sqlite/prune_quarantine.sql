@@ -0,0 +1,12 @@
+DELETE FROM quarantine
+WHERE
+    id IN (
+        SELECT
+            id
+        FROM
+            quarantine
+        WHERE
+            "when" < datetime ('now', '-' || ?1 || ' seconds')
+        LIMIT
+            ?2
+    );
\ No newline at end of file
@@ -0,0 +1,3 @@
+DELETE FROM notifications
+WHERE
+    "when" < datetime ('now', '-' || ?1 || ' seconds');
\ No newline at end of file
@@ -0,0 +1,4 @@
+INSERT INTO
+    quarantine ("node", "temperature", "humidity", "air_pressure", "cpu_temp", "battery", "wifi_ssid", "wifi_rssi", "reason")
+VALUES
+    (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9);
\ No newline at end of file
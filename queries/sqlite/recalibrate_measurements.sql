@@ -0,0 +1,24 @@
+UPDATE measurements
+SET
+    raw_temperature = COALESCE(raw_temperature, temperature),
+    raw_humidity = COALESCE(raw_humidity, humidity),
+    raw_air_pressure = COALESCE(raw_air_pressure, air_pressure),
+    raw_battery = COALESCE(raw_battery, battery),
+    temperature = COALESCE(raw_temperature, temperature) * ?3 + ?2,
+    humidity = CAST(MIN(MAX(ROUND(COALESCE(raw_humidity, humidity) + ?4), 0), 100) AS INTEGER),
+    air_pressure = CAST(ROUND(COALESCE(raw_air_pressure, air_pressure) + ?5) AS INTEGER),
+    battery = COALESCE(raw_battery, battery) * ?6
+WHERE
+    node = ?1
+    AND (
+        ?7 IS NULL
+        OR "when" >= datetime ('now', '-' || ?7 || ' seconds')
+    )
+    -- Measurements with implausible recalibrated readings are skipped
+    AND COALESCE(raw_temperature, temperature) * ?3 + ?2 BETWEEN ?8 AND ?9
+    AND MIN(MAX(ROUND(COALESCE(raw_humidity, humidity) + ?4), 0), 100) BETWEEN ?10 AND ?11
+    AND (
+        COALESCE(raw_air_pressure, air_pressure) IS NULL
+        OR ROUND(COALESCE(raw_air_pressure, air_pressure) + ?5) BETWEEN ?12 AND ?13
+    )
+    AND COALESCE(raw_battery, battery) * ?6 BETWEEN ?14 AND ?15;
\ No newline at end of file
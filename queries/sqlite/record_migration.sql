@@ -0,0 +1,4 @@
+INSERT INTO
+    schema_migrations (version, name)
+VALUES
+    (?1, ?2);
\ No newline at end of file
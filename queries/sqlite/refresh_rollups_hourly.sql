@@ -0,0 +1,73 @@
+INSERT INTO
+    rollups_hourly (
+        node,
+        bucket,
+        samples,
+        temperature_min,
+        temperature_max,
+        temperature_avg,
+        humidity_min,
+        humidity_max,
+        humidity_avg,
+        air_pressure_min,
+        air_pressure_max,
+        air_pressure_avg,
+        battery_min,
+        battery_max,
+        battery_avg,
+        rssi_min,
+        rssi_max,
+        rssi_avg
+    )
+SELECT
+    node,
+    strftime ('%Y-%m-%d %H:00:00', "when") AS bucket,
+    COUNT(*),
+    MIN(temperature),
+    MAX(temperature),
+    AVG(temperature),
+    MIN(humidity),
+    MAX(humidity),
+    AVG(humidity),
+    MIN(air_pressure),
+    MAX(air_pressure),
+    AVG(air_pressure),
+    MIN(battery),
+    MAX(battery),
+    AVG(battery),
+    MIN(wifi_rssi),
+    MAX(wifi_rssi),
+    AVG(wifi_rssi)
+FROM
+    measurements
+WHERE
+    (
+        ?1 IS NULL
+        OR node = ?1
+    )
+    -- Whole buckets are recomputed
+    AND (
+        ?2 IS NULL
+        OR "when" >= strftime ('%Y-%m-%d %H:00:00', ?2, 'unixepoch')
+    )
+GROUP BY
+    node,
+    bucket
+ON CONFLICT (node, bucket) DO UPDATE
+SET
+    samples = excluded.samples,
+    temperature_min = excluded.temperature_min,
+    temperature_max = excluded.temperature_max,
+    temperature_avg = excluded.temperature_avg,
+    humidity_min = excluded.humidity_min,
+    humidity_max = excluded.humidity_max,
+    humidity_avg = excluded.humidity_avg,
+    air_pressure_min = excluded.air_pressure_min,
+    air_pressure_max = excluded.air_pressure_max,
+    air_pressure_avg = excluded.air_pressure_avg,
+    battery_min = excluded.battery_min,
+    battery_max = excluded.battery_max,
+    battery_avg = excluded.battery_avg,
+    rssi_min = excluded.rssi_min,
+    rssi_max = excluded.rssi_max,
+    rssi_avg = excluded.rssi_avg;
\ No newline at end of file
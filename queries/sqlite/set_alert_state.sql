@@ -0,0 +1,12 @@
+INSERT INTO
+    alert_states (node, rule, active, streak)
+VALUES
+    (?1, ?2, ?3, ?4) ON CONFLICT (node, rule) DO
+UPDATE
+SET
+    changed = CASE
+        WHEN alert_states.active <> excluded.active THEN CURRENT_TIMESTAMP
+        ELSE alert_states.changed
+    END,
+    active = excluded.active,
+    streak = excluded.streak;
\ No newline at end of file
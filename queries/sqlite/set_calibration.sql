@@ -0,0 +1,11 @@
+INSERT INTO
+    calibrations (node, temperature_offset, temperature_scale, humidity_offset, air_pressure_offset, battery_scale)
+VALUES
+    (?1, ?2, ?3, ?4, ?5, ?6) ON CONFLICT (node) DO
+UPDATE
+SET
+    temperature_offset = excluded.temperature_offset,
+    temperature_scale = excluded.temperature_scale,
+    humidity_offset = excluded.humidity_offset,
+    air_pressure_offset = excluded.air_pressure_offset,
+    battery_scale = excluded.battery_scale;
\ No newline at end of file
@@ -0,0 +1,6 @@
+UPDATE settings
+SET
+  mute_notifications = ?2,
+  muted_until = datetime (?3, 'unixepoch')
+WHERE
+  node = ?1;
\ No newline at end of file
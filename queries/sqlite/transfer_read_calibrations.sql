@@ -0,0 +1,15 @@
+SELECT
+    node,
+    temperature_offset,
+    temperature_scale,
+    humidity_offset,
+    air_pressure_offset,
+    battery_scale
+FROM
+    calibrations
+ORDER BY
+    node
+LIMIT
+    ?2
+OFFSET
+    ?1;
\ No newline at end of file
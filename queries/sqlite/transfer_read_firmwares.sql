@@ -0,0 +1,16 @@
+SELECT
+    id,
+    version_major,
+    version_middle,
+    version_minor,
+    firmware,
+    unixepoch (added_date),
+    restrict_nodes
+FROM
+    firmwares
+WHERE
+    id > ?1
+ORDER BY
+    id
+LIMIT
+    ?2;
\ No newline at end of file
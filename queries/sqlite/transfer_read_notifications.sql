@@ -0,0 +1,19 @@
+SELECT
+    id,
+    node,
+    unixepoch ("when"),
+    content,
+    read,
+    severity,
+    kind,
+    title,
+    escalations,
+    unixepoch (escalated)
+FROM
+    notifications
+WHERE
+    id > ?1
+ORDER BY
+    id
+LIMIT
+    ?2;
\ No newline at end of file
@@ -0,0 +1,28 @@
+SELECT
+    node,
+    unixepoch (bucket),
+    samples,
+    temperature_min,
+    temperature_max,
+    temperature_avg,
+    humidity_min,
+    humidity_max,
+    humidity_avg,
+    air_pressure_min,
+    air_pressure_max,
+    air_pressure_avg,
+    battery_min,
+    battery_max,
+    battery_avg,
+    rssi_min,
+    rssi_max,
+    rssi_avg
+FROM
+    rollups_hourly
+ORDER BY
+    node,
+    bucket
+LIMIT
+    ?2
+OFFSET
+    ?1;
\ No newline at end of file
@@ -0,0 +1,18 @@
+SELECT
+    id,
+    node,
+    battery_ignore,
+    ota,
+    sleep_time,
+    sbop,
+    mute_notifications,
+    device_specific,
+    unixepoch (muted_until)
+FROM
+    settings
+WHERE
+    id > ?1
+ORDER BY
+    id
+LIMIT
+    ?2;
\ No newline at end of file
@@ -0,0 +1,5 @@
+SELECT
+    COUNT(*),
+    NULL
+FROM
+    calibrations;
\ No newline at end of file
@@ -0,0 +1,5 @@
+SELECT
+    COUNT(*),
+    NULL
+FROM
+    rollups_hourly;
\ No newline at end of file
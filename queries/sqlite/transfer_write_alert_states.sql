@@ -0,0 +1,16 @@
+INSERT OR REPLACE INTO
+    alert_states (
+        node,
+        rule,
+        active,
+        streak,
+        changed
+    )
+VALUES
+    (
+        ?1,
+        ?2,
+        ?3,
+        ?4,
+        datetime (?5, 'unixepoch')
+    );
\ No newline at end of file
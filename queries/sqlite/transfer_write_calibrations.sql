@@ -0,0 +1,18 @@
+INSERT OR REPLACE INTO
+    calibrations (
+        node,
+        temperature_offset,
+        temperature_scale,
+        humidity_offset,
+        air_pressure_offset,
+        battery_scale
+    )
+VALUES
+    (
+        ?1,
+        ?2,
+        ?3,
+        ?4,
+        ?5,
+        ?6
+    );
\ No newline at end of file
@@ -0,0 +1,26 @@
+INSERT INTO
+    firmware_stats (
+        id,
+        node,
+        from_version_major,
+        from_version_middle,
+        from_version_minor,
+        to_version_major,
+        to_version_middle,
+        to_version_minor,
+        "when",
+        success
+    )
+VALUES
+    (
+        ?1,
+        ?2,
+        ?3,
+        ?4,
+        ?5,
+        ?6,
+        ?7,
+        ?8,
+        datetime (?9, 'unixepoch'),
+        ?10
+    );
\ No newline at end of file
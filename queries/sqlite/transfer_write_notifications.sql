@@ -0,0 +1,26 @@
+INSERT INTO
+    notifications (
+        id,
+        node,
+        "when",
+        content,
+        read,
+        severity,
+        kind,
+        title,
+        escalations,
+        escalated
+    )
+VALUES
+    (
+        ?1,
+        ?2,
+        datetime (?3, 'unixepoch'),
+        ?4,
+        ?5,
+        ?6,
+        ?7,
+        ?8,
+        ?9,
+        datetime (?10, 'unixepoch')
+    );
\ No newline at end of file
@@ -0,0 +1,28 @@
+INSERT INTO
+    quarantine (
+        id,
+        node,
+        "when",
+        temperature,
+        humidity,
+        air_pressure,
+        cpu_temp,
+        battery,
+        wifi_ssid,
+        wifi_rssi,
+        reason
+    )
+VALUES
+    (
+        ?1,
+        ?2,
+        datetime (?3, 'unixepoch'),
+        ?4,
+        ?5,
+        ?6,
+        ?7,
+        ?8,
+        ?9,
+        ?10,
+        ?11
+    );
\ No newline at end of file
@@ -0,0 +1,14 @@
+INSERT INTO
+    settings (
+        id,
+        node,
+        battery_ignore,
+        ota,
+        sleep_time,
+        sbop,
+        mute_notifications,
+        device_specific,
+        muted_until
+    )
+VALUES
+    (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime (?9, 'unixepoch'));
\ No newline at end of file
@@ -0,0 +1,93 @@
+use crate::{
+    cli::{self, CalibrationCommand},
+    error::Error,
+    server::{
+        calibration::Calibration,
+        config::Config,
+        db::{DatabaseBackend, DatabaseClient},
+    },
+};
+use tracing::{info, warn};
+
+pub async fn run(command: CalibrationCommand, config: &Config) -> Result<(), Error> {
+    let client = DatabaseClient::new(config).await?;
+
+    match command {
+        CalibrationCommand::List => {
+            let entries = client.get_calibrations().await?;
+
+            for entry in &entries {
+                let calibration = entry.calibration;
+                println!(
+                    "Node #{}: temperature * {} {:+}°C, humidity {:+}%, air pressure {:+}hPa, battery * {}",
+                    entry.node,
+                    calibration.temperature_scale,
+                    calibration.temperature_offset,
+                    calibration.humidity_offset,
+                    calibration.air_pressure_offset,
+                    calibration.battery_scale
+                );
+            }
+
+            println!("Total: {} calibrated node(s)", entries.len());
+        }
+        CalibrationCommand::Set {
+            node,
+            temperature_offset,
+            temperature_scale,
+            humidity_offset,
+            air_pressure_offset,
+            battery_scale,
+        } => {
+            let current = client.get_calibration(node).await?.unwrap_or_default();
+            let calibration = Calibration {
+                temperature_offset: temperature_offset.unwrap_or(current.temperature_offset),
+                temperature_scale: temperature_scale.unwrap_or(current.temperature_scale),
+                humidity_offset: humidity_offset.unwrap_or(current.humidity_offset),
+                air_pressure_offset: air_pressure_offset.unwrap_or(current.air_pressure_offset),
+                battery_scale: battery_scale.unwrap_or(current.battery_scale),
+            };
+
+            client.set_calibration(node, &calibration).await?;
+            info!(
+                "Updated calibration of node #{node}, apply it to stored measurements with `calibration apply {node}`"
+            );
+        }
+        CalibrationCommand::Reset { node } => {
+            if client.delete_calibration(node).await? {
+                info!("Removed calibration of node #{node}");
+            } else {
+                warn!("Node #{node} is not calibrated");
+            }
+        }
+        CalibrationCommand::Apply { node, since } => {
+            let from = since.map(cli::ago).transpose()?;
+
+            // Without a calibration, the raw readings are restored
+            let calibration = client.get_calibration(node).await?.unwrap_or_default();
+            let (updated, skipped) = client
+                .recalibrate_measurements(node, &calibration, &config.validation, since)
+                .await?;
+
+            info!("Recalibrated {updated} measurement(s) of node #{node}");
+            if skipped > 0 {
+                warn!(
+                    "Skipped {skipped} measurement(s) of node #{node}, their recalibrated readings are outside of the plausible ranges"
+                );
+            }
+
+            client.refresh_rollups(Some(node), from).await?;
+        }
+        CalibrationCommand::Altitude { node, meters } => {
+            if !client.set_device_altitude(node, meters).await? {
+                warn!("Node #{node} does not exist");
+            } else if let Some(meters) = meters {
+                info!("Set altitude of node #{node} to {meters}m");
+            } else {
+                info!("Removed altitude of node #{node}");
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,6 +1,10 @@
-use crate::server::db::NodeId;
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use crate::{
+    error::Error,
+    server::db::{NodeId, NotificationId},
+};
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::{num::ParseIntError, path::PathBuf, str::FromStr, time::Duration};
 
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)]
@@ -36,6 +40,30 @@ pub enum Command {
         command: OtaCommand,
     },
 
+    /// Notification inbox management
+    Notification {
+        #[command(subcommand)]
+        command: NotificationCommand,
+    },
+
+    /// Per-node sensor calibration
+    Calibration {
+        #[command(subcommand)]
+        command: CalibrationCommand,
+    },
+
+    /// Node health reports
+    Report {
+        #[command(subcommand)]
+        command: ReportCommand,
+    },
+
+    /// Measurement data
+    Measurements {
+        #[command(subcommand)]
+        command: MeasurementsCommand,
+    },
+
     /// Test connection to a PWMP server
     Test {
         /// Host to connect to
@@ -79,9 +107,71 @@ pub enum DatabaseCommand {
     /// Test connection to the database
     Test,
 
-    /// Initialize the database
+    /// Initialize the database, same as `migrate`
     Init,
 
+    /// Apply pending schema migrations
+    Migrate {
+        /// Only list the pending migrations
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show the schema version and migrations of the database
+    Status,
+
+    /// Compute the hourly and daily rollups of existing measurements
+    Backfill {
+        /// Only compute the rollups of the specified node
+        #[arg(long)]
+        node: Option<NodeId>,
+
+        /// Only compute the rollups of measurements younger than the specified age (eg. 30d)
+        #[arg(long, value_parser = humantime::parse_duration)]
+        since: Option<Duration>,
+    },
+
+    /// Delete rows older than their configured retention
+    Prune {
+        /// Only count the rows that would be deleted
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Reclaim the disk space of deleted rows
+    Vacuum {
+        /// Rewrite the tables, which needs about as much free disk space as they take
+        #[arg(long)]
+        full: bool,
+    },
+
+    /// Copy all data from one database to another, possibly of a different backend
+    Copy {
+        /// Configuration file of the source database
+        #[arg(long, value_name = "PATH")]
+        from: PathBuf,
+
+        /// Configuration file of the target database, which is initialized if empty
+        #[arg(long, value_name = "PATH")]
+        to: PathBuf,
+
+        /// Number of rows to copy per transaction
+        #[arg(long, default_value_t = 1000)]
+        batch_size: u32,
+    },
+
+    /// Back up all data into a backend-independent archive, while the server is stopped
+    Backup {
+        /// Path to the archive (eg. backup.tar.gz)
+        file: PathBuf,
+    },
+
+    /// Restore a backup into an empty database, which is initialized if needed
+    Restore {
+        /// Path to the archive
+        file: PathBuf,
+    },
+
     /// Completely ERASE ALL DATA from the database (*UNRECOVERABLE*)
     Erase {
         /// Only remove rows, not tables
@@ -91,6 +181,10 @@ pub enum DatabaseCommand {
         /// Keep configured devices and their settings
         #[arg(long)]
         keep_devices: bool,
+
+        /// Don't back up the database before erasing it
+        #[arg(long)]
+        no_backup: bool,
     },
 }
 
@@ -115,3 +209,329 @@ pub enum OtaCommand {
         restrict: Option<Vec<NodeId>>,
     },
 }
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum NotificationCommand {
+    /// List notifications, newest first
+    List {
+        /// Only show unread notifications
+        #[arg(long)]
+        unread: bool,
+
+        /// Only show notifications of the specified node
+        #[arg(long)]
+        node: Option<NodeId>,
+
+        /// Only show notifications younger than the specified age (eg. 12h, 7d)
+        #[arg(long, value_parser = humantime::parse_duration)]
+        since: Option<Duration>,
+    },
+
+    /// Mark notifications as read, which also acknowledges them for escalation
+    #[command(visible_alias = "ack")]
+    Read {
+        /// Notification ID, or `all`
+        target: NotificationTarget,
+    },
+
+    /// Delete old notifications
+    Purge {
+        /// Delete notifications older than the specified age (eg. 30d)
+        #[arg(long, value_parser = humantime::parse_duration)]
+        older_than: Duration,
+    },
+
+    /// Create a notification for a node and push it through the configured backend
+    Send { node: NodeId, message: String },
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum CalibrationCommand {
+    /// List the calibrations of all nodes
+    List,
+
+    /// Set the calibration of a node, which applies to new measurements. Unspecified values are kept.
+    Set {
+        node: NodeId,
+
+        /// Added to the temperature after scaling, in °C
+        #[arg(long, allow_hyphen_values = true)]
+        temperature_offset: Option<f32>,
+
+        /// Multiplier of the raw temperature
+        #[arg(long)]
+        temperature_scale: Option<f32>,
+
+        /// Added to the humidity, in %
+        #[arg(long, allow_hyphen_values = true)]
+        humidity_offset: Option<f32>,
+
+        /// Added to the air pressure, in hPa
+        #[arg(long, allow_hyphen_values = true)]
+        air_pressure_offset: Option<f32>,
+
+        /// Multiplier of the raw battery voltage, corrects the voltage divider ratio
+        #[arg(long)]
+        battery_scale: Option<f32>,
+    },
+
+    /// Remove the calibration of a node
+    Reset { node: NodeId },
+
+    /// Recompute stored measurements of a node from their raw readings, using its current calibration
+    Apply {
+        node: NodeId,
+
+        /// Only recompute measurements younger than the specified age (eg. 30d)
+        #[arg(long, value_parser = humantime::parse_duration)]
+        since: Option<Duration>,
+    },
+
+    /// Set the altitude of a node, used to compute its sea-level pressure. Omit the altitude to unset it.
+    Altitude {
+        node: NodeId,
+
+        /// Altitude above sea level, in meters
+        #[arg(allow_hyphen_values = true)]
+        meters: Option<f32>,
+    },
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum ReportCommand {
+    /// Show the battery voltage, discharge rate and predicted remaining life of every node
+    Battery,
+
+    /// Show the signal strength statistics and network changes of every node
+    Wifi {
+        /// Only show the specified node
+        #[arg(long)]
+        node: Option<NodeId>,
+
+        /// List every network change, not just their count
+        #[arg(long)]
+        roams: bool,
+    },
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum MeasurementsCommand {
+    /// Export measurements for offline analysis
+    Export {
+        /// Only export measurements of the specified node
+        #[arg(long)]
+        node: Option<NodeId>,
+
+        /// Only export measurements taken at or after this time (eg. 2024-01-31, 2024-01-31T12:00:00Z, or an age like 7d)
+        #[arg(long, value_parser = parse_time)]
+        from: Option<DateTime<Utc>>,
+
+        /// Only export measurements taken before this time, same format as `--from`
+        #[arg(long, value_parser = parse_time)]
+        to: Option<DateTime<Utc>>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+
+        /// Export the minimum, maximum and average of every period (eg. 1h, 1d) instead of individual measurements
+        #[arg(long, value_parser = parse_period)]
+        aggregate: Option<Duration>,
+
+        /// Include the derived metrics (dew point, heat index, etc.) of every measurement
+        #[arg(long, conflicts_with = "aggregate")]
+        derived: bool,
+
+        /// Output file, standard output if not specified
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Import historical measurements of a node, validated like posted measurements
+    Import {
+        /// Input file, `-` for standard input
+        file: PathBuf,
+
+        /// Node the measurements belong to
+        #[arg(long)]
+        node: NodeId,
+
+        /// Input format
+        #[arg(long, value_enum, default_value_t = ImportFormat::Csv)]
+        format: ImportFormat,
+
+        /// Read a field from a differently named column (eg. `temperature=temp_c`), fields are read from the columns named like
+        /// them by default
+        #[arg(long = "map", value_name = "FIELD=COLUMN", value_parser = parse_field_pair)]
+        mappings: Vec<(ImportField, String)>,
+
+        /// Value of a field whose column is missing or empty (eg. `wifi_ssid=legacy`)
+        #[arg(long = "default", value_name = "FIELD=VALUE", value_parser = parse_field_pair)]
+        defaults: Vec<(ImportField, String)>,
+
+        /// strftime-like format of the timestamps (eg. "%d.%m.%Y %H:%M"), times without an offset are in UTC.
+        /// RFC 3339 timestamps, "YYYY-MM-DD HH:MM:SS" and Unix timestamps are accepted by default
+        #[arg(long)]
+        time_format: Option<String>,
+
+        /// Number of measurements inserted per transaction
+        #[arg(long, default_value_t = 5000)]
+        batch_size: usize,
+
+        /// Skip measurements taken within a second of an existing measurement of the node, or of a previous row
+        #[arg(long)]
+        skip_duplicates: bool,
+
+        /// Validate and insert the measurements, but roll back instead of storing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row
+    Csv,
+
+    /// One JSON object per line
+    Jsonl,
+
+    /// Apache Parquet
+    Parquet,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ImportFormat {
+    /// Comma-separated values with a header row
+    Csv,
+
+    /// One JSON object per line
+    Jsonl,
+}
+
+/// Field of an imported measurement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportField {
+    When,
+    Temperature,
+    Humidity,
+    AirPressure,
+    CpuTemperature,
+    Battery,
+    WifiSsid,
+    WifiRssi,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum NotificationTarget {
+    All,
+    Id(NotificationId),
+}
+
+impl ImportField {
+    pub const ALL: [Self; 8] = [
+        Self::When,
+        Self::Temperature,
+        Self::Humidity,
+        Self::AirPressure,
+        Self::CpuTemperature,
+        Self::Battery,
+        Self::WifiSsid,
+        Self::WifiRssi,
+    ];
+
+    /// Name of the field, which is also the name of its column in exports.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::When => "when",
+            Self::Temperature => "temperature",
+            Self::Humidity => "humidity",
+            Self::AirPressure => "air_pressure",
+            Self::CpuTemperature => "cpu_temperature",
+            Self::Battery => "battery",
+            Self::WifiSsid => "wifi_ssid",
+            Self::WifiRssi => "wifi_rssi",
+        }
+    }
+}
+
+impl FromStr for ImportField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|field| field.as_str() == s)
+            .ok_or_else(|| format!("unknown field `{s}`"))
+    }
+}
+
+impl FromStr for NotificationTarget {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("all") {
+            return Ok(Self::All);
+        }
+
+        s.parse().map(Self::Id)
+    }
+}
+
+/// Time the given age ago.
+pub fn ago(age: Duration) -> Result<DateTime<Utc>, Error> {
+    TimeDelta::from_std(age)
+        .ok()
+        .and_then(|age| Utc::now().checked_sub_signed(age))
+        .ok_or(Error::AgeOutOfRange)
+}
+
+/// Parse a date (midnight UTC), an RFC 3339 timestamp, or an age relative to now.
+fn parse_time(s: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(date.and_time(chrono::NaiveTime::MIN).and_utc());
+    }
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Ok(time.to_utc());
+    }
+
+    let age = humantime::parse_duration(s)
+        .map_err(|_| "expected a date, an RFC 3339 timestamp or an age".to_string())?;
+
+    ago(age).map_err(|why| why.to_string())
+}
+
+/// Parse a `FIELD=VALUE` pair.
+fn parse_field_pair(s: &str) -> Result<(ImportField, String), String> {
+    let (field, value) = s
+        .split_once('=')
+        .ok_or_else(|| "expected FIELD=VALUE".to_string())?;
+
+    Ok((field.parse()?, value.to_string()))
+}
+
+/// Parse an aggregation period, which must be a whole number of seconds.
+fn parse_period(s: &str) -> Result<Duration, String> {
+    let period = humantime::parse_duration(s).map_err(|why| why.to_string())?;
+
+    if period.as_secs() == 0 || period.subsec_nanos() != 0 {
+        return Err("the period must be a whole number of seconds".to_string());
+    }
+
+    Ok(period)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ago_rejects_too_long_ages() {
+        assert!(ago(Duration::from_hours(1)).is_ok());
+        assert!(matches!(ago(Duration::MAX), Err(Error::AgeOutOfRange)));
+
+        let age = humantime::parse_duration("300000y").unwrap();
+        assert!(matches!(ago(age), Err(Error::AgeOutOfRange)));
+    }
+}
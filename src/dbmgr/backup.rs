@@ -0,0 +1,589 @@
+//! Backend-independent backups.
+//!
+//! A backup is a gzipped tar archive. The rows of every table are stored as JSON Lines in parts of up to [`PART_ROWS`] rows,
+//! in the order of [`TransferTable::ALL`], and every firmware blob is stored as a separate entry before the part with its row.
+//! The manifest is the last entry, since it has the checksums of all the other entries.
+//!
+//! Tables are read in batches without a common snapshot, so a backup fails if the row count of any table changed while it was taken.
+//! A restore that fails erases the content it wrote, so the database is empty again for the next attempt.
+
+use crate::{
+    error::Error,
+    server::db::{
+        DatabaseBackend, DatabaseClient, EraseOptions,
+        migrations::{SCHEMA_VERSION, SchemaVersion},
+        transfer::{self, FirmwareRow, TransferRow, TransferTable},
+    },
+};
+use chrono::{DateTime, Utc};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read},
+    path::{Path, PathBuf},
+};
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+/// Version of the archive layout.
+const FORMAT: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+
+/// Maximum number of rows per part.
+const PART_ROWS: u32 = 10_000;
+
+type ArchiveWriter = tar::Builder<GzEncoder<BufWriter<File>>>;
+type ArchiveReader = tar::Archive<GzDecoder<BufReader<File>>>;
+
+/// Content and data of an archive entry.
+type EntryData = Result<(Content, Vec<u8>), Error>;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    pub schema_version: SchemaVersion,
+    pub server_version: String,
+    pub created: DateTime<Utc>,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    pub sha256: String,
+    #[serde(flatten)]
+    pub content: Content,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "content", rename_all = "snake_case")]
+pub enum Content {
+    /// JSON Lines with rows of a table.
+    Rows { table: TransferTable, rows: u64 },
+    /// Blob of the firmware with the ID.
+    Firmware { id: i32 },
+}
+
+impl Manifest {
+    /// Number of backed up rows of all tables.
+    pub fn total_rows(&self) -> u64 {
+        TransferTable::ALL
+            .into_iter()
+            .map(|table| self.rows(table))
+            .sum()
+    }
+
+    /// Number of backed up rows of `table`.
+    pub fn rows(&self, table: TransferTable) -> u64 {
+        self.entries
+            .iter()
+            .map(|entry| match entry.content {
+                Content::Rows { table: other, rows } if other == table => rows,
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+/// Back up every table into an archive at `path`. It's written under a temporary name until it's complete.
+pub async fn backup(client: &DatabaseClient, path: &Path) -> Result<Manifest, Error> {
+    let schema_version = client.get_schema_version().await?;
+
+    if schema_version != SCHEMA_VERSION {
+        return Err(Error::SchemaVersionMismatch(schema_version));
+    }
+
+    let created = Utc::now();
+    let partial = partial_path(path);
+    let mut archive = tar::Builder::new(GzEncoder::new(
+        BufWriter::new(File::create(&partial)?),
+        Compression::default(),
+    ));
+    let mut entries = Vec::new();
+    let mut backed_up = Vec::new();
+
+    for table in TransferTable::ALL {
+        let mut after = 0;
+        let mut parts = 0;
+        let mut rows = 0;
+
+        loop {
+            let batch = client
+                .read_rows(table, after, table.batch_limit(PART_ROWS))
+                .await?;
+            if batch.is_empty() {
+                break;
+            }
+            after = transfer::next_after(after, &batch)?;
+
+            let mut data = Vec::new();
+
+            for row in &batch {
+                if let TransferRow::Firmware(firmware) = row {
+                    let name = format!("firmwares/{}.bin", firmware.id);
+                    let sha256 = append(&mut archive, &name, &firmware.firmware, created)?;
+
+                    entries.push(Entry {
+                        name,
+                        sha256,
+                        content: Content::Firmware { id: firmware.id },
+                    });
+                }
+
+                serde_json::to_writer(&mut data, row)?;
+                data.push(b'\n');
+            }
+
+            parts += 1;
+            rows += batch.len() as u64;
+
+            let name = format!("{}/{parts:06}.jsonl", table.as_str());
+            let sha256 = append(&mut archive, &name, &data, created)?;
+
+            entries.push(Entry {
+                name,
+                sha256,
+                content: Content::Rows {
+                    table,
+                    rows: batch.len() as u64,
+                },
+            });
+        }
+
+        info!("Backed up {rows} row(s) of {}", table.as_str());
+        backed_up.push((table, rows));
+    }
+
+    // Rows written to tables that were already backed up would be missing from the backup
+    for (table, rows) in backed_up {
+        if client.get_table_state(table).await?.rows != rows {
+            drop(archive);
+            fs::remove_file(&partial)?;
+            return Err(Error::DatabaseChanged(table.as_str()));
+        }
+    }
+
+    let manifest = Manifest {
+        format: FORMAT,
+        schema_version,
+        server_version: env!("CARGO_PKG_VERSION").to_string(),
+        created,
+        entries,
+    };
+    append(
+        &mut archive,
+        MANIFEST,
+        &serde_json::to_vec_pretty(&manifest)?,
+        created,
+    )?;
+
+    archive
+        .into_inner()?
+        .finish()?
+        .into_inner()
+        .map_err(io::IntoInnerError::into_error)?
+        .sync_all()?;
+    fs::rename(&partial, path)?;
+
+    Ok(manifest)
+}
+
+/// Check the checksums and schema version of the archive at `path`, and return its manifest.
+pub fn verify(path: &Path) -> Result<Manifest, Error> {
+    let mut archive = open(path)?;
+    let mut checksums = HashMap::new();
+    let mut manifest = None;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry_name(&entry)?;
+
+        if name == MANIFEST {
+            manifest = Some(serde_json::from_reader::<_, Manifest>(&mut entry)?);
+            continue;
+        }
+
+        let mut hasher = Sha256::new();
+        io::copy(&mut entry, &mut hasher)?;
+        checksums.insert(name, format!("{:x}", hasher.finalize()));
+    }
+
+    let manifest = manifest.ok_or_else(|| invalid("no manifest"))?;
+
+    if manifest.format != FORMAT {
+        return Err(invalid(format!("unsupported format {}", manifest.format)));
+    }
+
+    if manifest.schema_version != SCHEMA_VERSION {
+        return Err(Error::SchemaVersionMismatch(manifest.schema_version));
+    }
+
+    for entry in &manifest.entries {
+        match checksums.remove(&entry.name) {
+            Some(sha256) if sha256 == entry.sha256 => (),
+            Some(_) => {
+                return Err(invalid(format!(
+                    "checksum of `{}` doesn't match",
+                    entry.name
+                )));
+            }
+            None => return Err(invalid(format!("`{}` is missing", entry.name))),
+        }
+    }
+
+    if let Some(name) = checksums.into_keys().next() {
+        return Err(invalid(format!("`{name}` is not in the manifest")));
+    }
+
+    Ok(manifest)
+}
+
+/// Restore the archive at `path`, which has been [verified](verify), into an empty database.
+/// If the restore fails, the content of the database is erased again.
+pub async fn restore(
+    client: &DatabaseClient,
+    path: &Path,
+    manifest: &Manifest,
+) -> Result<(), Error> {
+    for table in TransferTable::ALL {
+        if client.get_table_state(table).await?.rows > 0 {
+            return Err(Error::DatabaseNotEmpty);
+        }
+    }
+
+    let result = restore_entries(client, path, manifest).await;
+
+    if result.is_err() {
+        info!("Erasing the partially restored content");
+        if let Err(why) = client
+            .erase(EraseOptions::ContentOnly {
+                keep_devices: false,
+            })
+            .await
+        {
+            error!(
+                "Failed to erase the partially restored content, erase the database before trying again: {why}"
+            );
+        }
+    }
+
+    result
+}
+
+async fn restore_entries(
+    client: &DatabaseClient,
+    path: &Path,
+    manifest: &Manifest,
+) -> Result<(), Error> {
+    let contents = manifest
+        .entries
+        .iter()
+        .map(|entry| (entry.name.clone(), entry.content))
+        .collect();
+    let mut entries = read_entries(path.to_path_buf(), contents);
+    let mut restored: HashMap<TransferTable, u64> = HashMap::new();
+    let mut blobs = HashMap::new();
+
+    while let Some(entry) = entries.recv().await {
+        let (content, data) = entry?;
+
+        match content {
+            Content::Firmware { id } => {
+                blobs.insert(id, data);
+            }
+            Content::Rows { table, .. } => {
+                let rows = parse_rows(table, &data, &mut blobs)?;
+                client.write_rows(&rows).await?;
+
+                let total = restored.entry(table).or_default();
+                *total += rows.len() as u64;
+                info!(
+                    "Restored {total} row(s) of {} ({} total)",
+                    table.as_str(),
+                    manifest.rows(table)
+                );
+            }
+        }
+    }
+
+    for table in TransferTable::ALL {
+        let rows = client.get_table_state(table).await?.rows;
+        let expected = manifest.rows(table);
+
+        if rows != expected {
+            return Err(invalid(format!(
+                "restored {rows} row(s) of {} instead of {expected}",
+                table.as_str()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Read the entries listed in `contents` on a blocking thread, since the archive reader can't be held across awaits.
+fn read_entries(path: PathBuf, contents: HashMap<String, Content>) -> mpsc::Receiver<EntryData> {
+    let (sender, receiver) = mpsc::channel(1);
+
+    tokio::task::spawn_blocking(move || {
+        if let Err(why) = send_entries(&path, &contents, &sender) {
+            let _ = sender.blocking_send(Err(why));
+        }
+    });
+
+    receiver
+}
+
+fn send_entries(
+    path: &Path,
+    contents: &HashMap<String, Content>,
+    sender: &mpsc::Sender<EntryData>,
+) -> Result<(), Error> {
+    let mut archive = open(path)?;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        // Only the manifest is not in it
+        let Some(content) = contents.get(&entry_name(&entry)?) else {
+            continue;
+        };
+
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+
+        if sender.blocking_send(Ok((*content, data))).is_err() {
+            // The restore failed
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_rows(
+    table: TransferTable,
+    data: &[u8],
+    blobs: &mut HashMap<i32, Vec<u8>>,
+) -> Result<Vec<TransferRow>, Error> {
+    data.split(|byte| *byte == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            Ok(match table {
+                TransferTable::Devices => TransferRow::Device(serde_json::from_slice(line)?),
+                TransferTable::Settings => TransferRow::Settings(serde_json::from_slice(line)?),
+                TransferTable::Measurements => {
+                    TransferRow::Measurement(serde_json::from_slice(line)?)
+                }
+                TransferTable::Notifications => {
+                    TransferRow::Notification(serde_json::from_slice(line)?)
+                }
+                TransferTable::Firmwares => {
+                    let mut row: FirmwareRow = serde_json::from_slice(line)?;
+                    row.firmware = blobs.remove(&row.id).ok_or_else(|| {
+                        invalid(format!("blob of firmware #{} is missing", row.id))
+                    })?;
+
+                    TransferRow::Firmware(row)
+                }
+                TransferTable::FirmwareStats => {
+                    TransferRow::FirmwareStat(serde_json::from_slice(line)?)
+                }
+                TransferTable::Quarantine => TransferRow::Quarantine(serde_json::from_slice(line)?),
+                TransferTable::Calibrations => {
+                    TransferRow::Calibration(serde_json::from_slice(line)?)
+                }
+                TransferTable::AlertStates => {
+                    TransferRow::AlertState(serde_json::from_slice(line)?)
+                }
+                TransferTable::RollupsHourly => {
+                    TransferRow::RollupHourly(serde_json::from_slice(line)?)
+                }
+                TransferTable::RollupsDaily => {
+                    TransferRow::RollupDaily(serde_json::from_slice(line)?)
+                }
+            })
+        })
+        .collect()
+}
+
+/// Append an entry and return its checksum.
+fn append(
+    archive: &mut ArchiveWriter,
+    name: &str,
+    data: &[u8],
+    created: DateTime<Utc>,
+) -> Result<String, Error> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(created.timestamp().try_into()?);
+    archive.append_data(&mut header, name, data)?;
+
+    Ok(format!("{:x}", Sha256::digest(data)))
+}
+
+fn open(path: &Path) -> Result<ArchiveReader, Error> {
+    Ok(tar::Archive::new(GzDecoder::new(BufReader::new(
+        File::open(path)?,
+    ))))
+}
+
+fn entry_name<R: Read>(entry: &tar::Entry<R>) -> Result<String, Error> {
+    Ok(entry.path()?.to_string_lossy().into_owned())
+}
+
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".partial");
+    name.into()
+}
+
+fn invalid(reason: impl Into<String>) -> Error {
+    Error::InvalidBackup(reason.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{
+        calibration::Calibration,
+        config::{Config, DatabaseConfig},
+        db::{MeasurementEntry, RawReadings},
+        diagnostics::SensorFlags,
+        notification_client::{Notification, NotificationKind, Severity},
+    };
+    use pwmp_client::pwmp_msg::version::Version;
+    use std::env;
+
+    async fn memory(devices: &[&str]) -> DatabaseClient {
+        let config = Config {
+            database: DatabaseConfig::Memory {
+                devices: devices.iter().map(|&mac| mac.into()).collect(),
+            },
+            ..Default::default()
+        };
+        DatabaseClient::new(&config).await.unwrap()
+    }
+
+    async fn source() -> DatabaseClient {
+        let client = memory(&["AA:BB:CC:DD:EE:01", "AA:BB:CC:DD:EE:02"]).await;
+        let measurement = MeasurementEntry {
+            when: Utc::now(),
+            temperature: 21.5,
+            humidity: 50,
+            air_pressure: Some(1000),
+            cpu_temp: 30.0,
+            battery: 3.9,
+            wifi_ssid: "Home".to_string(),
+            wifi_rssi: -60,
+            flags: SensorFlags::default(),
+            raw: RawReadings {
+                temperature: 21.5,
+                humidity: 50,
+                air_pressure: Some(1000),
+                battery: 3.9,
+            },
+        };
+
+        client.post_measurements(1, &measurement).await.unwrap();
+        client
+            .quarantine_measurements(2, &measurement, "Too humid")
+            .await
+            .unwrap();
+        client
+            .set_calibration(1, &Calibration::default())
+            .await
+            .unwrap();
+        client.set_alert_state(1, "Frost", true, 2).await.unwrap();
+        client.refresh_rollups(None, None).await.unwrap();
+        client
+            .upload_firmware(vec![0xE9; 16], Version::new(2, 0, 0), None)
+            .await
+            .unwrap();
+        client
+            .send_os_update_stat(1, Version::new(1, 0, 0), Version::new(2, 0, 0))
+            .await
+            .unwrap();
+        client
+            .create_notification(&Notification {
+                node: 2,
+                kind: NotificationKind::Custom,
+                severity: Severity::Warning,
+                title: "Door open".into(),
+                body: "Front door".into(),
+                message: "Front door".into(),
+                url: None,
+            })
+            .await
+            .unwrap();
+        client
+    }
+
+    fn archive_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("pwmp-{name}-{}.tar.gz", std::process::id()))
+    }
+
+    async fn row_counts(client: &DatabaseClient) -> Vec<u64> {
+        let mut counts = Vec::new();
+        for table in TransferTable::ALL {
+            counts.push(client.get_table_state(table).await.unwrap().rows);
+        }
+        counts
+    }
+
+    #[tokio::test]
+    async fn restores_every_table() {
+        let source = source().await;
+        let path = archive_path("round-trip");
+
+        let manifest = backup(&source, &path).await.unwrap();
+        for table in TransferTable::ALL {
+            assert_ne!(manifest.rows(table), 0, "{} is empty", table.as_str());
+        }
+
+        let manifest = verify(&path).unwrap();
+        let target = memory(&[]).await;
+        restore(&target, &path, &manifest).await.unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(row_counts(&target).await, row_counts(&source).await);
+        assert_eq!(
+            target.get_firmwares().await.unwrap().len(),
+            source.get_firmwares().await.unwrap().len()
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_restore_leaves_database_empty() {
+        let source = source().await;
+        let path = archive_path("failed");
+        backup(&source, &path).await.unwrap();
+
+        // The final row count check fails after every row was written
+        let mut manifest = verify(&path).unwrap();
+        let entry = manifest
+            .entries
+            .iter_mut()
+            .find(|entry| matches!(entry.content, Content::Rows { .. }))
+            .unwrap();
+        if let Content::Rows { rows, .. } = &mut entry.content {
+            *rows += 1;
+        }
+
+        let target = memory(&[]).await;
+        assert!(restore(&target, &path, &manifest).await.is_err());
+        assert!(row_counts(&target).await.iter().all(|&rows| rows == 0));
+
+        // So it can be retried
+        let manifest = verify(&path).unwrap();
+        restore(&target, &path, &manifest).await.unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(row_counts(&target).await, row_counts(&source).await);
+    }
+}
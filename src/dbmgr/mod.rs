@@ -1,15 +1,21 @@
 use crate::{
-    cli::DatabaseCommand,
+    cli::{self, DatabaseCommand},
     server::{
+        self,
         config::Config,
-        db::{DatabaseBackend, DatabaseClient, EraseOptions},
+        db::{DatabaseBackend, DatabaseClient, EraseOptions, migrations::SCHEMA_VERSION, transfer},
+        retention,
     },
 };
+use chrono::Utc;
 use std::{
     io::{Write, stdin, stdout},
+    path::{Path, PathBuf},
     process::exit,
 };
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+
+mod backup;
 
 #[allow(clippy::cognitive_complexity)]
 pub async fn main(cmd: DatabaseCommand, config: &Config) {
@@ -18,25 +24,56 @@ pub async fn main(cmd: DatabaseCommand, config: &Config) {
             Ok(_) => info!("Connection successful"),
             Err(why) => error!("Failed to connect: {why}"),
         },
-        DatabaseCommand::Init => {
-            debug!("Initializing database pool");
-            let client = match DatabaseClient::new(config).await {
-                Ok(conn) => conn,
+        DatabaseCommand::Init => migrate(config, false).await,
+        DatabaseCommand::Migrate { dry_run } => migrate(config, dry_run).await,
+        DatabaseCommand::Status => status(config).await,
+        DatabaseCommand::Backfill { node, since } => {
+            let from = match since.map(cli::ago).transpose() {
+                Ok(from) => from,
                 Err(why) => {
-                    error!("Failed to connect: {why}");
+                    error!("Invalid `--since`: {why}");
                     exit(1);
                 }
             };
+            let client = connect(config).await;
+
+            info!("Computing rollups, this may take a while");
+            match client.refresh_rollups(node, from).await {
+                Ok(updated) => info!("Updated {updated} rollup bucket(s)"),
+                Err(why) => error!("Failed to compute rollups: {why}"),
+            }
+        }
+        DatabaseCommand::Prune { dry_run } => prune(config, dry_run).await,
+        DatabaseCommand::Vacuum { full } => {
+            let client = connect(config).await;
 
-            info!("Executing migrations");
-            match client.run_migrations().await {
-                Ok(()) => info!("Migrations executed successfully"),
-                Err(why) => error!("Failed to execute migrations: {why}"),
+            match client.compact(full).await {
+                Ok(()) => info!("Reclaimed disk space"),
+                Err(why) => error!("Failed to reclaim disk space: {why}"),
             }
         }
+        DatabaseCommand::Copy {
+            from,
+            to,
+            batch_size,
+        } => copy(&from, &to, batch_size).await,
+        DatabaseCommand::Backup { file } => {
+            let client = connect(config).await;
+
+            match backup::backup(&client, &file).await {
+                Ok(manifest) => info!(
+                    "Backed up {} row(s) into {}",
+                    manifest.total_rows(),
+                    file.display()
+                ),
+                Err(why) => error!("Failed to back up the database: {why}"),
+            }
+        }
+        DatabaseCommand::Restore { file } => restore(config, &file).await,
         DatabaseCommand::Erase {
             content_only,
             keep_devices,
+            no_backup,
         } => {
             let client = match DatabaseClient::new(config).await {
                 Ok(conn) => conn,
@@ -49,6 +86,24 @@ pub async fn main(cmd: DatabaseCommand, config: &Config) {
             info!("Connected to the database");
             confirm_erase(&config.database.name(), &config.database.host());
 
+            if !no_backup {
+                let file = PathBuf::from(format!(
+                    "pwmp-backup-{}.tar.gz",
+                    Utc::now().format("%Y%m%d-%H%M%S")
+                ));
+
+                info!("Backing up the database before erasing it");
+                match backup::backup(&client, &file).await {
+                    Ok(_) => info!("Backed up the database into {}", file.display()),
+                    Err(why) => {
+                        error!(
+                            "Failed to back up the database, nothing was erased (use `--no-backup` to erase it anyway): {why}"
+                        );
+                        exit(1);
+                    }
+                }
+            }
+
             let opts = EraseOptions::new(content_only, keep_devices);
             match client.erase(opts).await {
                 Ok(()) => info!("Success!"),
@@ -58,6 +113,253 @@ pub async fn main(cmd: DatabaseCommand, config: &Config) {
     }
 }
 
+async fn connect(config: &Config) -> DatabaseClient {
+    debug!("Initializing database pool");
+    match DatabaseClient::new(config).await {
+        Ok(conn) => conn,
+        Err(why) => {
+            error!("Failed to connect: {why}");
+            exit(1);
+        }
+    }
+}
+
+async fn migrate(config: &Config, dry_run: bool) {
+    let client = connect(config).await;
+
+    if dry_run {
+        match client.get_pending_migrations().await {
+            Ok(pending) if pending.is_empty() => info!("Database is up to date"),
+            Ok(pending) => {
+                for migration in pending {
+                    info!(
+                        "Would apply migration {:04} ({})",
+                        migration.version, migration.name
+                    );
+                }
+            }
+            Err(why) => error!("Failed to get pending migrations: {why}"),
+        }
+
+        return;
+    }
+
+    info!("Executing migrations");
+    match client.run_migrations().await {
+        Ok(applied) if applied.is_empty() => info!("Database is up to date"),
+        Ok(applied) => {
+            for migration in applied {
+                info!(
+                    "Applied migration {:04} ({})",
+                    migration.version, migration.name
+                );
+            }
+
+            info!("Migrations executed successfully");
+        }
+        Err(why) => error!("Failed to execute migrations: {why}"),
+    }
+}
+
+async fn status(config: &Config) {
+    let client = connect(config).await;
+
+    let applied = match client.get_applied_migrations().await {
+        Ok(applied) => applied,
+        Err(why) => {
+            error!("Failed to get applied migrations: {why}");
+            exit(1);
+        }
+    };
+    let version = applied
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or_default();
+
+    println!("Schema version: {version} (this server requires {SCHEMA_VERSION})");
+
+    for migration in client.migrations() {
+        let status = match applied
+            .iter()
+            .find(|applied| applied.version == migration.version)
+            .and_then(|applied| applied.applied)
+        {
+            Some(when) => format!("applied {when}"),
+            // Migrations of databases created before they were versioned are not recorded
+            None if migration.version <= version => {
+                "applied before migrations were versioned".to_string()
+            }
+            None => "pending".to_string(),
+        };
+
+        println!("{:04} {}: {status}", migration.version, migration.name);
+    }
+
+    for migration in applied
+        .iter()
+        .filter(|migration| migration.version > SCHEMA_VERSION)
+    {
+        println!(
+            "{:04} {}: applied by a newer server",
+            migration.version, migration.name
+        );
+    }
+}
+
+async fn prune(config: &Config, dry_run: bool) {
+    if !config.retention.is_enabled() {
+        warn!("No retention is configured, nothing to prune");
+        return;
+    }
+
+    let client = connect(config).await;
+
+    if dry_run {
+        match retention::count(&config.retention, &client).await {
+            Ok(counts) => {
+                for (table, rows) in counts {
+                    info!("Would prune {rows} row(s) from {}", table.as_str());
+                }
+            }
+            Err(why) => error!("Failed to count prunable rows: {why}"),
+        }
+
+        return;
+    }
+
+    match retention::prune(&config.retention, &client).await {
+        Ok(pruned) if pruned.is_empty() => info!("Nothing to prune"),
+        Ok(pruned) => {
+            for (table, rows) in pruned {
+                info!("Pruned {rows} row(s) from {}", table.as_str());
+            }
+        }
+        Err(why) => error!("Failed to prune: {why}"),
+    }
+}
+
+async fn copy(from: &Path, to: &Path, batch_size: u32) {
+    let from = load_config(from);
+    let to = load_config(to);
+
+    if from.database.host() == to.database.host() && from.database.name() == to.database.name() {
+        error!("Source and target are the same database");
+        exit(1);
+    }
+
+    let source = connect(&from).await;
+    let target = connect(&to).await;
+
+    server::check_schema_version(&source).await;
+
+    match target.run_migrations().await {
+        Ok(applied) if !applied.is_empty() => info!("Initialized the target database"),
+        Ok(_) => (),
+        Err(why) => {
+            error!("Failed to execute migrations on the target database: {why}");
+            exit(1);
+        }
+    }
+
+    info!("Copying {} to {}", from.database.name(), to.database.name());
+
+    match transfer::copy(&source, &target, batch_size.max(1)).await {
+        Ok(copied) => info!("Copied {copied} row(s)"),
+        Err(why) => {
+            error!("Failed to copy, run the command again to resume: {why}");
+            exit(1);
+        }
+    }
+
+    match transfer::verify(&source, &target).await {
+        Ok(mismatches) if mismatches.is_empty() => info!("Row counts of all tables match"),
+        Ok(mismatches) => {
+            for (table, expected, actual) in mismatches {
+                error!(
+                    "Row count of {} differs: {expected} in the source, {actual} in the target",
+                    table.as_str()
+                );
+            }
+
+            warn!(
+                "Rows deleted from the source after they were copied remain in the target, erase it and copy again"
+            );
+            exit(1);
+        }
+        Err(why) => {
+            error!("Failed to verify row counts: {why}");
+            exit(1);
+        }
+    }
+
+    // Copied rollups are kept for buckets whose measurements were pruned, the others are recomputed from the measurements
+    info!("Computing rollups, this may take a while");
+    match target.refresh_rollups(None, None).await {
+        Ok(updated) => info!("Updated {updated} rollup bucket(s)"),
+        Err(why) => error!("Failed to compute rollups: {why}"),
+    }
+}
+
+async fn restore(config: &Config, file: &Path) {
+    let manifest = match backup::verify(file) {
+        Ok(manifest) => manifest,
+        Err(why) => {
+            error!("Failed to read the backup: {why}");
+            exit(1);
+        }
+    };
+
+    info!(
+        "Restoring {} row(s) backed up by server v{} at {}",
+        manifest.total_rows(),
+        manifest.server_version,
+        manifest.created.format("%Y-%m-%d %H:%M:%S")
+    );
+
+    let client = connect(config).await;
+
+    match client.run_migrations().await {
+        Ok(applied) if !applied.is_empty() => info!("Initialized the database"),
+        Ok(_) => (),
+        Err(why) => {
+            error!("Failed to execute migrations: {why}");
+            exit(1);
+        }
+    }
+
+    match backup::restore(&client, file, &manifest).await {
+        Ok(()) => info!("Restored the backup"),
+        Err(why) => {
+            error!("Failed to restore the backup: {why}");
+            exit(1);
+        }
+    }
+
+    // Restored rollups are kept for buckets whose measurements were pruned, the others are recomputed from the measurements
+    info!("Computing rollups, this may take a while");
+    match client.refresh_rollups(None, None).await {
+        Ok(updated) => info!("Updated {updated} rollup bucket(s)"),
+        Err(why) => error!("Failed to compute rollups: {why}"),
+    }
+}
+
+/// Load an existing configuration file.
+fn load_config(path: &Path) -> Config {
+    if !path.exists() {
+        error!("Configuration file {} does not exist", path.display());
+        exit(1);
+    }
+
+    match server::config::setup(&path.to_path_buf()) {
+        Ok((config, _)) => config,
+        Err(why) => {
+            error!("Failed to load {}: {why}", path.display());
+            exit(1);
+        }
+    }
+}
+
 fn confirm_erase(database_name: &str, host: &str) {
     const KEY: &str = "yes, do it!";
 
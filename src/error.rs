@@ -62,6 +62,10 @@ pub enum Error {
     #[error("A process has returned a non-zero exit code")]
     SubprocessExit,
 
+    /// A subprocess did not exit in time.
+    #[error("A process did not exit in time")]
+    SubprocessTimeout,
+
     /// Failed to parse a UTF-8 string.
     #[error("Failed to parse a UTF-8 string")]
     StringFromUtf8Bytes(#[from] FromUtf8Error),
@@ -74,6 +78,10 @@ pub enum Error {
     #[error("confy: {0}")]
     Config(#[from] confy::ConfyError),
 
+    /// The configuration is invalid.
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
     /// The log file path is not absolute.
     #[error("Path to the log file must be absolute")]
     IllegalLogfilePath,
@@ -95,6 +103,65 @@ pub enum Error {
     #[error("Failed to perform HTTP request: {0}")]
     Reqwest(#[from] reqwest::Error),
 
+    /// Failed to parse an email address.
+    #[error("Failed to parse an email address: {0}")]
+    MailAddress(#[from] lettre::address::AddressError),
+
+    /// Failed to build an email message.
+    #[error("Failed to build an email: {0}")]
+    MailBuild(#[from] lettre::error::Error),
+
+    /// SMTP transport error.
+    #[error("SMTP: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+
+    /// Failed to compile or render a notification template.
+    #[error("Template: {0}")]
+    Template(#[from] minijinja::Error),
+
+    /// Failed to write a CSV export.
+    #[error("CSV: {0}")]
+    Csv(#[from] csv::Error),
+
+    /// Failed to write a JSON export.
+    #[error("JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Failed to write a Parquet export.
+    #[error("Parquet: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    /// A mapped column is not in the imported file.
+    #[error("Column `{0}` is not in the input")]
+    ImportColumnMissing(String),
+
+    /// Schema version of a database or backup is not the one this server requires.
+    #[error(
+        "Schema version {0} doesn't match the one this server requires ({required})",
+        required = crate::server::db::migrations::SCHEMA_VERSION
+    )]
+    SchemaVersionMismatch(crate::server::db::migrations::SchemaVersion),
+
+    /// A backup archive is damaged or not a backup.
+    #[error("Invalid backup: {0}")]
+    InvalidBackup(String),
+
+    /// An age passed on the command line reaches before the earliest representable time.
+    #[error("Age is too long")]
+    AgeOutOfRange,
+
+    /// Backups can only be restored into empty databases.
+    #[error("The database is not empty")]
+    DatabaseNotEmpty,
+
+    /// Rows were added or removed while a backup was taken, so it may be inconsistent.
+    #[error("Row count of {0} changed during the backup")]
+    DatabaseChanged(&'static str),
+
+    /// A write to the in-memory database violates a constraint of the schema of the other backends.
+    #[error("Constraint violated: {0}")]
+    Constraint(String),
+
     /// Failed to send message to [`notify_loop()`](crate::server::handle::notify_loop)'s MPSC buffer.
     #[error("Timeout while sending notification to notify loop")]
     MpscTrySend,
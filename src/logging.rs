@@ -1,10 +1,15 @@
 use crate::{error::Error, server::config::Config};
-use std::{fs, time::SystemTime};
+use std::{
+    fs,
+    io::{stderr, stdout},
+    time::SystemTime,
+};
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::{
     fmt::{
         format::{FmtSpan, Writer},
         time::FormatTime,
+        writer::BoxMakeWriter,
     },
     layer::SubscriberExt,
     util::SubscriberInitExt,
@@ -20,7 +25,7 @@ impl FormatTime for DateTimeFormatter {
     }
 }
 
-pub fn setup(force_debug: bool, config: &Config) -> Result<(), Error> {
+pub fn setup(force_debug: bool, to_stderr: bool, config: &Config) -> Result<(), Error> {
     let level = if cfg!(debug_assertions) | force_debug {
         LevelFilter::DEBUG
     } else {
@@ -52,14 +57,21 @@ pub fn setup(force_debug: bool, config: &Config) -> Result<(), Error> {
         None
     };
 
-    let stdout_layer = tracing_subscriber::fmt::layer()
+    let console = if to_stderr {
+        BoxMakeWriter::new(stderr)
+    } else {
+        BoxMakeWriter::new(stdout)
+    };
+
+    let console_layer = tracing_subscriber::fmt::layer()
+        .with_writer(console)
         .compact()
         .with_timer(DateTimeFormatter)
         .with_target(false)
         .with_span_events(FmtSpan::CLOSE);
 
     tracing_subscriber::registry()
-        .with(stdout_layer)
+        .with(console_layer)
         .with(file_layer)
         .with(level)
         .init();
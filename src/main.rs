@@ -4,11 +4,15 @@ use server::config::Config;
 use std::env;
 use tracing::{debug, info, warn};
 
+mod calibutil;
 mod cli;
 mod dbmgr;
 mod error;
 mod logging;
+mod measureutil;
+mod notifutil;
 mod otautil;
+mod reportutil;
 mod server;
 mod svcmgr;
 mod tester;
@@ -21,7 +25,10 @@ async fn main() -> Result<(), error::Error> {
     let force_debug =
         args.debug || env::var("PWMP_DEBUG").is_ok_and(|value| value.to_lowercase() == "true");
 
-    if let Err(why) = logging::setup(force_debug, &config) {
+    // Exports may be written to standard output, so keep the logs out of it
+    let log_to_stderr = matches!(args.command, Some(Command::Measurements { .. }));
+
+    if let Err(why) = logging::setup(force_debug, log_to_stderr, &config) {
         eprintln!("Failed to set up logging: {why}");
         return Err(why);
     }
@@ -40,6 +47,10 @@ async fn main() -> Result<(), error::Error> {
         Some(Command::Database { command }) => dbmgr::main(command, &config).await,
         Some(Command::Test { host, mac, port }) => tester::test(host, port, mac),
         Some(Command::Ota { command }) => otautil::run(command, &config).await?,
+        Some(Command::Notification { command }) => notifutil::run(command, &config).await?,
+        Some(Command::Calibration { command }) => calibutil::run(command, &config).await?,
+        Some(Command::Report { command }) => reportutil::run(command, &config).await?,
+        Some(Command::Measurements { command }) => measureutil::run(command, &config).await?,
         None => server::main(config).await,
     }
 
@@ -0,0 +1,416 @@
+//! Import of historical measurements.
+
+use crate::{
+    cli::{ImportField, ImportFormat},
+    error::Error,
+    server::{
+        config::{Config, ValidationConfig, ValidationPolicy},
+        db::{DatabaseBackend, DatabaseClient, MeasurementEntry, NodeId, RawReadings},
+        diagnostics::SensorFlags,
+        validation,
+    },
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, BufRead, BufReader, Read, stdin},
+    path::PathBuf,
+    str::FromStr,
+};
+use tracing::{info, warn};
+
+type Input = Box<dyn Read + Send>;
+
+/// Values of a row, in the order of [`ImportField::ALL`].
+type Values = Vec<Option<String>>;
+
+/// Line number and values of a row, or the reason why they can't be read.
+type Row = (u64, Result<Values, String>);
+
+pub struct ImportOptions {
+    pub file: PathBuf,
+    pub node: NodeId,
+    pub format: ImportFormat,
+    pub mappings: Vec<(ImportField, String)>,
+    pub defaults: Vec<(ImportField, String)>,
+    pub time_format: Option<String>,
+    pub batch_size: usize,
+    pub skip_duplicates: bool,
+    pub dry_run: bool,
+}
+
+/// Where the value of a field comes from.
+struct Source {
+    column: String,
+    /// Whether the column was mapped explicitly, in which case it must exist.
+    mapped: bool,
+    /// Used if the column is missing or empty.
+    default: Option<String>,
+}
+
+enum Reader {
+    Csv {
+        records: csv::StringRecordsIntoIter<Input>,
+        /// Index of the column of every field.
+        columns: Vec<Option<usize>>,
+    },
+    Jsonl {
+        lines: io::Lines<BufReader<Input>>,
+        line: u64,
+    },
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    imported: u64,
+    invalid: u64,
+    duplicates: u64,
+}
+
+pub async fn run(
+    client: &DatabaseClient,
+    config: &Config,
+    options: ImportOptions,
+) -> Result<(), Error> {
+    let node = options.node;
+
+    if client.get_device(node).await?.is_none() {
+        warn!("Node #{node} does not exist");
+        return Ok(());
+    }
+
+    if options
+        .defaults
+        .iter()
+        .any(|(field, _)| *field == ImportField::When)
+    {
+        warn!("Every measurement must have an explicit timestamp, `when` can't have a default");
+        return Ok(());
+    }
+
+    let sources = sources(&options.mappings, &options.defaults);
+    let input: Input = if options.file.as_os_str() == "-" {
+        Box::new(stdin())
+    } else {
+        Box::new(File::open(&options.file)?)
+    };
+    let mut reader = Reader::new(options.format, input, &sources)?;
+
+    // A batch size of zero would never insert anything
+    let batch_size = options.batch_size.max(1);
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut seen = HashSet::new();
+    let mut earliest: Option<DateTime<Utc>> = None;
+    let mut stats = Stats::default();
+
+    while let Some((line, values)) = reader.next_row(&sources)? {
+        let measurement = values
+            .and_then(|values| parse_measurement(&values, options.time_format.as_deref()))
+            .and_then(|measurement| validate(&config.validation, measurement));
+        let measurement = match measurement {
+            Ok(measurement) => measurement,
+            Err(reason) => {
+                warn!("Skipping line {line}: {reason}");
+                stats.invalid += 1;
+                continue;
+            }
+        };
+
+        if options.skip_duplicates && !seen.insert(measurement.when.timestamp()) {
+            stats.duplicates += 1;
+            continue;
+        }
+
+        earliest = Some(earliest.map_or(measurement.when, |when| when.min(measurement.when)));
+        batch.push(measurement);
+
+        if batch.len() >= batch_size {
+            insert(client, node, &mut batch, &options, &mut stats).await?;
+        }
+    }
+
+    if !batch.is_empty() {
+        insert(client, node, &mut batch, &options, &mut stats).await?;
+    }
+
+    let Stats {
+        imported,
+        invalid,
+        duplicates,
+    } = stats;
+
+    if options.dry_run {
+        info!(
+            "Would import {imported} measurement(s) of node #{node}, skipped {invalid} invalid and {duplicates} duplicate(s)"
+        );
+        return Ok(());
+    }
+
+    info!(
+        "Imported {imported} measurement(s) of node #{node}, skipped {invalid} invalid and {duplicates} duplicate(s)"
+    );
+
+    if imported > 0 {
+        client.refresh_rollups(Some(node), earliest).await?;
+    }
+
+    Ok(())
+}
+
+/// Insert a batch of measurements and clear it.
+async fn insert(
+    client: &DatabaseClient,
+    node: NodeId,
+    batch: &mut Vec<MeasurementEntry>,
+    options: &ImportOptions,
+    stats: &mut Stats,
+) -> Result<(), Error> {
+    let inserted = client
+        .import_measurements(node, batch, options.skip_duplicates, options.dry_run)
+        .await?;
+
+    stats.imported += inserted;
+    stats.duplicates += batch.len() as u64 - inserted;
+    batch.clear();
+
+    info!(
+        "Processed {} row(s) so far",
+        stats.imported + stats.invalid + stats.duplicates
+    );
+    Ok(())
+}
+
+fn sources(mappings: &[(ImportField, String)], defaults: &[(ImportField, String)]) -> Vec<Source> {
+    let find = |pairs: &[(ImportField, String)], field| {
+        pairs
+            .iter()
+            .rev()
+            .find(|(other, _)| *other == field)
+            .map(|(_, value)| value.clone())
+    };
+
+    ImportField::ALL
+        .into_iter()
+        .map(|field| {
+            let mapped = find(mappings, field);
+
+            Source {
+                mapped: mapped.is_some(),
+                column: mapped.unwrap_or_else(|| field.as_str().to_string()),
+                default: find(defaults, field),
+            }
+        })
+        .collect()
+}
+
+impl Reader {
+    fn new(format: ImportFormat, input: Input, sources: &[Source]) -> Result<Self, Error> {
+        match format {
+            ImportFormat::Csv => {
+                let mut reader = csv::Reader::from_reader(input);
+                let headers = reader.headers()?;
+                let mut columns = Vec::with_capacity(sources.len());
+
+                for source in sources {
+                    let index = headers.iter().position(|header| header == source.column);
+
+                    if index.is_none() && source.mapped {
+                        return Err(Error::ImportColumnMissing(source.column.clone()));
+                    }
+
+                    columns.push(index);
+                }
+
+                Ok(Self::Csv {
+                    records: reader.into_records(),
+                    columns,
+                })
+            }
+            ImportFormat::Jsonl => Ok(Self::Jsonl {
+                lines: BufReader::new(input).lines(),
+                line: 0,
+            }),
+        }
+    }
+
+    /// Read the next row, returns `None` at the end of the input.
+    fn next_row(&mut self, sources: &[Source]) -> Result<Option<Row>, Error> {
+        match self {
+            Self::Csv { records, columns } => {
+                let Some(record) = records.next().transpose()? else {
+                    return Ok(None);
+                };
+                let line = record.position().map_or(0, csv::Position::line);
+                let values = sources
+                    .iter()
+                    .zip(columns.iter())
+                    .map(|(source, column)| {
+                        column
+                            .and_then(|column| record.get(column))
+                            .filter(|value| !value.is_empty())
+                            .map(str::to_string)
+                            .or_else(|| source.default.clone())
+                    })
+                    .collect();
+
+                Ok(Some((line, Ok(values))))
+            }
+            Self::Jsonl { lines, line } => loop {
+                let Some(text) = lines.next().transpose()? else {
+                    return Ok(None);
+                };
+                *line += 1;
+
+                if text.trim().is_empty() {
+                    continue;
+                }
+
+                let values =
+                    serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&text)
+                        .map(|object| {
+                            sources
+                                .iter()
+                                .map(|source| {
+                                    match object.get(&source.column) {
+                                        Some(serde_json::Value::String(value))
+                                            if !value.is_empty() =>
+                                        {
+                                            Some(value.clone())
+                                        }
+                                        Some(
+                                            serde_json::Value::Null | serde_json::Value::String(_),
+                                        )
+                                        | None => None,
+                                        Some(value) => Some(value.to_string()),
+                                    }
+                                    .or_else(|| source.default.clone())
+                                })
+                                .collect()
+                        })
+                        .map_err(|why| format!("invalid JSON: {why}"));
+
+                return Ok(Some((*line, values)));
+            },
+        }
+    }
+}
+
+fn parse_measurement(
+    values: &[Option<String>],
+    time_format: Option<&str>,
+) -> Result<MeasurementEntry, String> {
+    let value = |field: ImportField| {
+        let index = ImportField::ALL
+            .iter()
+            .position(|other| *other == field)
+            .expect("every field should have a value");
+
+        values[index].as_deref()
+    };
+    let required = |field: ImportField| {
+        value(field).ok_or_else(|| format!("no value for `{}`", field.as_str()))
+    };
+
+    let when = parse_time(required(ImportField::When)?, time_format)?;
+    let temperature = parse(
+        ImportField::Temperature,
+        required(ImportField::Temperature)?,
+    )?;
+    let humidity = parse_integer(ImportField::Humidity, required(ImportField::Humidity)?)?;
+    let air_pressure = value(ImportField::AirPressure)
+        .map(|value| parse_integer(ImportField::AirPressure, value))
+        .transpose()?;
+    let cpu_temp = parse(
+        ImportField::CpuTemperature,
+        required(ImportField::CpuTemperature)?,
+    )?;
+    let battery = parse(ImportField::Battery, required(ImportField::Battery)?)?;
+    let wifi_ssid = required(ImportField::WifiSsid)?.to_string();
+    let wifi_rssi = parse_integer(ImportField::WifiRssi, required(ImportField::WifiRssi)?)?;
+
+    Ok(MeasurementEntry {
+        when,
+        temperature,
+        humidity,
+        air_pressure,
+        cpu_temp,
+        battery,
+        wifi_ssid,
+        wifi_rssi,
+        flags: SensorFlags::default(),
+        raw: RawReadings {
+            temperature,
+            humidity,
+            air_pressure,
+            battery,
+        },
+    })
+}
+
+/// Validate a measurement like a posted one. With the clamp policy the readings are clamped, otherwise invalid measurements
+/// are skipped, since quarantined measurements lose their timestamps.
+fn validate(
+    config: &ValidationConfig,
+    mut measurement: MeasurementEntry,
+) -> Result<MeasurementEntry, String> {
+    let reasons = validation::validate(config, &measurement);
+
+    if reasons.is_empty() {
+        return Ok(measurement);
+    }
+
+    if matches!(config.policy, ValidationPolicy::Clamp) {
+        validation::clamp(config, &mut measurement);
+
+        if validation::validate(config, &measurement).is_empty() {
+            return Ok(measurement);
+        }
+    }
+
+    Err(reasons.join(", "))
+}
+
+/// Parse a timestamp with the specified format, or as an RFC 3339, "YYYY-MM-DD HH:MM:SS" or Unix timestamp.
+/// Timestamps without an offset are in UTC.
+fn parse_time(value: &str, format: Option<&str>) -> Result<DateTime<Utc>, String> {
+    let invalid = || format!("invalid timestamp `{value}`");
+
+    if let Some(format) = format {
+        return DateTime::parse_from_str(value, format)
+            .map(|time| time.to_utc())
+            .or_else(|_| NaiveDateTime::parse_from_str(value, format).map(|time| time.and_utc()))
+            .map_err(|_| invalid());
+    }
+
+    if let Ok(secs) = value.parse::<i64>() {
+        return DateTime::from_timestamp(secs, 0).ok_or_else(invalid);
+    }
+
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.to_utc())
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").map(|time| time.and_utc())
+        })
+        .map_err(|_| invalid())
+}
+
+fn parse<T: FromStr>(field: ImportField, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid {} `{value}`", field.as_str()))
+}
+
+/// Parse an integer, rounding decimal values.
+#[allow(clippy::cast_possible_truncation)] // checked to be in range
+fn parse_integer<T: TryFrom<i64>>(field: ImportField, value: &str) -> Result<T, String> {
+    let invalid = || format!("invalid {} `{value}`", field.as_str());
+    let number = parse::<f64>(field, value)?.round();
+
+    if !(-1e15..=1e15).contains(&number) {
+        return Err(invalid());
+    }
+
+    T::try_from(number as i64).map_err(|_| invalid())
+}
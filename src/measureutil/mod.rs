@@ -0,0 +1,235 @@
+use crate::{
+    cli::MeasurementsCommand,
+    error::Error,
+    server::{
+        config::Config,
+        db::{DatabaseBackend, DatabaseClient, MeasurementRange, NodeId, Summary},
+        meteo::DerivedMetrics,
+    },
+};
+use futures_util::TryStreamExt;
+use import::ImportOptions;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, stdout},
+    time::Duration,
+};
+use tracing::info;
+use writer::{Column, ColumnType, Output, RowWriter, Value};
+
+mod import;
+mod writer;
+
+const MEASUREMENT_COLUMNS: &[Column] = &[
+    Column::new("node", ColumnType::Int),
+    Column::new("when", ColumnType::Timestamp),
+    Column::new("temperature", ColumnType::Real),
+    Column::new("humidity", ColumnType::Int),
+    Column::new("air_pressure", ColumnType::Int),
+    Column::new("cpu_temperature", ColumnType::Real),
+    Column::new("battery", ColumnType::Real),
+    Column::new("wifi_ssid", ColumnType::Text),
+    Column::new("wifi_rssi", ColumnType::Int),
+    Column::new("flags", ColumnType::Text),
+];
+
+const DERIVED_COLUMNS: &[Column] = &[
+    Column::new("dew_point", ColumnType::Float),
+    Column::new("dew_point_category", ColumnType::Text),
+    Column::new("heat_index", ColumnType::Float),
+    Column::new("humidex", ColumnType::Float),
+    Column::new("absolute_humidity", ColumnType::Float),
+    Column::new("sea_level_pressure", ColumnType::Float),
+];
+
+const AGGREGATE_COLUMNS: &[Column] = &[
+    Column::new("node", ColumnType::Int),
+    Column::new("bucket", ColumnType::Timestamp),
+    Column::new("samples", ColumnType::Int),
+    Column::new("temperature_min", ColumnType::Float),
+    Column::new("temperature_max", ColumnType::Float),
+    Column::new("temperature_avg", ColumnType::Float),
+    Column::new("humidity_min", ColumnType::Float),
+    Column::new("humidity_max", ColumnType::Float),
+    Column::new("humidity_avg", ColumnType::Float),
+    Column::new("air_pressure_min", ColumnType::Float),
+    Column::new("air_pressure_max", ColumnType::Float),
+    Column::new("air_pressure_avg", ColumnType::Float),
+    Column::new("battery_min", ColumnType::Float),
+    Column::new("battery_max", ColumnType::Float),
+    Column::new("battery_avg", ColumnType::Float),
+    Column::new("wifi_rssi_min", ColumnType::Float),
+    Column::new("wifi_rssi_max", ColumnType::Float),
+    Column::new("wifi_rssi_avg", ColumnType::Float),
+];
+
+pub async fn run(command: MeasurementsCommand, config: &Config) -> Result<(), Error> {
+    let client = DatabaseClient::new(config).await?;
+
+    match command {
+        MeasurementsCommand::Export {
+            node,
+            from,
+            to,
+            format,
+            aggregate,
+            derived,
+            output,
+        } => {
+            let range = MeasurementRange { node, from, to };
+            let output: Output = match &output {
+                Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+                None => Box::new(BufWriter::new(stdout())),
+            };
+
+            let rows = if let Some(period) = aggregate {
+                let writer = writer::new(format, AGGREGATE_COLUMNS.to_vec(), output)?;
+                export_aggregates(&client, range, period, writer).await?
+            } else {
+                let mut columns = MEASUREMENT_COLUMNS.to_vec();
+
+                if derived {
+                    columns.extend_from_slice(DERIVED_COLUMNS);
+                }
+
+                let writer = writer::new(format, columns, output)?;
+                export_measurements(&client, range, derived, writer).await?
+            };
+
+            info!("Exported {rows} row(s)");
+        }
+        MeasurementsCommand::Import {
+            file,
+            node,
+            format,
+            mappings,
+            defaults,
+            time_format,
+            batch_size,
+            skip_duplicates,
+            dry_run,
+        } => {
+            let options = ImportOptions {
+                file,
+                node,
+                format,
+                mappings,
+                defaults,
+                time_format,
+                batch_size,
+                skip_duplicates,
+                dry_run,
+            };
+
+            import::run(&client, config, options).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write every measurement in the range as a row. Returns the number of rows.
+async fn export_measurements(
+    client: &DatabaseClient,
+    range: MeasurementRange,
+    derived: bool,
+    mut writer: Box<dyn RowWriter>,
+) -> Result<u64, Error> {
+    // Needed for the sea-level pressure
+    let altitudes: HashMap<NodeId, Option<f32>> = if derived {
+        client
+            .get_devices()
+            .await?
+            .into_iter()
+            .map(|device| (device.id, device.altitude))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    let mut measurements = client.stream_measurements(range).await?;
+    let mut rows = 0;
+
+    while let Some((node, measurement)) = measurements.try_next().await? {
+        let mut row = vec![
+            i64::from(node).into(),
+            measurement.when.into(),
+            measurement.temperature.into(),
+            i64::from(measurement.humidity).into(),
+            measurement.air_pressure.map(i64::from).into(),
+            measurement.cpu_temp.into(),
+            measurement.battery.into(),
+            measurement.wifi_ssid.into(),
+            i64::from(measurement.wifi_rssi).into(),
+            measurement.flags.names().join(",").into(),
+        ];
+
+        if derived {
+            let metrics = DerivedMetrics::new(
+                measurement.temperature,
+                measurement.humidity,
+                measurement.air_pressure,
+                altitudes.get(&node).copied().flatten(),
+            );
+
+            row.extend([
+                metrics.dew_point.into(),
+                metrics.dew_point_category.map(str::to_string).into(),
+                metrics.heat_index.into(),
+                metrics.humidex.into(),
+                metrics.absolute_humidity.into(),
+                metrics.sea_level_pressure.into(),
+            ]);
+        }
+
+        writer.write(row)?;
+        rows += 1;
+    }
+
+    writer.finish()?;
+    Ok(rows)
+}
+
+/// Write a summary of every node and period in the range as a row. Returns the number of rows.
+async fn export_aggregates(
+    client: &DatabaseClient,
+    range: MeasurementRange,
+    period: Duration,
+    mut writer: Box<dyn RowWriter>,
+) -> Result<u64, Error> {
+    let mut aggregates = client.stream_aggregates(range, period).await?;
+    let mut rows = 0;
+
+    while let Some(aggregate) = aggregates.try_next().await? {
+        let mut row = vec![
+            i64::from(aggregate.node).into(),
+            aggregate.bucket.into(),
+            aggregate.samples.into(),
+        ];
+
+        for summary in [
+            Some(aggregate.temperature),
+            Some(aggregate.humidity),
+            aggregate.air_pressure,
+            Some(aggregate.battery),
+            Some(aggregate.wifi_rssi),
+        ] {
+            row.extend(summary_values(summary));
+        }
+
+        writer.write(row)?;
+        rows += 1;
+    }
+
+    writer.finish()?;
+    Ok(rows)
+}
+
+fn summary_values(summary: Option<Summary>) -> [Value; 3] {
+    [
+        summary.map(|summary| summary.min).into(),
+        summary.map(|summary| summary.max).into(),
+        summary.map(|summary| summary.avg).into(),
+    ]
+}
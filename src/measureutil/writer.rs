@@ -0,0 +1,362 @@
+//! Row writers of the export formats.
+
+use crate::{cli::ExportFormat, error::Error};
+use chrono::{DateTime, SecondsFormat, Utc};
+use parquet::{
+    basic::{LogicalType, Repetition, TimeUnit, Type as PhysicalType},
+    data_type::{ByteArray, ByteArrayType, DoubleType, FloatType, Int64Type},
+    file::{
+        properties::WriterProperties,
+        writer::{SerializedColumnWriter, SerializedFileWriter},
+    },
+    format::MilliSeconds,
+    schema::types::Type,
+};
+use serde::{Serialize, Serializer, ser::SerializeMap};
+use std::{fmt, io::Write, sync::Arc};
+
+pub type Output = Box<dyn Write + Send>;
+
+/// Number of rows buffered before they're written to a Parquet row group.
+const ROW_GROUP_SIZE: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ColumnType {
+    Int,
+    /// Single precision, the precision of the sensor readings.
+    Real,
+    Float,
+    Text,
+    Timestamp,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Column {
+    pub name: &'static str,
+    pub kind: ColumnType,
+}
+
+/// A value of a row, `Null` in any column means the value is not available.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Null,
+    Int(i64),
+    Real(f32),
+    Float(f64),
+    Text(String),
+    Timestamp(DateTime<Utc>),
+}
+
+pub trait RowWriter: Send {
+    /// Write a row with one value per column.
+    fn write(&mut self, row: Vec<Value>) -> Result<(), Error>;
+
+    /// Write out everything still buffered.
+    fn finish(self: Box<Self>) -> Result<(), Error>;
+}
+
+impl Column {
+    pub const fn new(name: &'static str, kind: ColumnType) -> Self {
+        Self { name, kind }
+    }
+
+    fn parquet_type(self) -> Result<Type, Error> {
+        let (physical, logical) = match self.kind {
+            ColumnType::Int => (PhysicalType::INT64, None),
+            ColumnType::Real => (PhysicalType::FLOAT, None),
+            ColumnType::Float => (PhysicalType::DOUBLE, None),
+            ColumnType::Text => (PhysicalType::BYTE_ARRAY, Some(LogicalType::String)),
+            ColumnType::Timestamp => (
+                PhysicalType::INT64,
+                Some(LogicalType::Timestamp {
+                    is_adjusted_to_u_t_c: true,
+                    unit: TimeUnit::MILLIS(MilliSeconds {}),
+                }),
+            ),
+        };
+
+        Ok(Type::primitive_type_builder(self.name, physical)
+            .with_repetition(Repetition::OPTIONAL)
+            .with_logical_type(logical)
+            .build()?)
+    }
+}
+
+impl<T: Into<Self>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Self::Real(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<DateTime<Utc>> for Value {
+    fn from(value: DateTime<Utc>) -> Self {
+        Self::Timestamp(value)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => Ok(()),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Real(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value}"),
+            Self::Text(value) => write!(f, "{value}"),
+            Self::Timestamp(value) => write!(f, "{}", rfc3339(value)),
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Null => serializer.serialize_none(),
+            Self::Int(value) => serializer.serialize_i64(*value),
+            Self::Real(value) => serializer.serialize_f32(*value),
+            Self::Float(value) => serializer.serialize_f64(*value),
+            Self::Text(value) => serializer.serialize_str(value),
+            Self::Timestamp(value) => serializer.serialize_str(&rfc3339(value)),
+        }
+    }
+}
+
+/// Create a writer of the specified format, writing the header (if the format has one) right away.
+pub fn new(
+    format: ExportFormat,
+    columns: Vec<Column>,
+    output: Output,
+) -> Result<Box<dyn RowWriter>, Error> {
+    Ok(match format {
+        ExportFormat::Csv => Box::new(CsvWriter::new(&columns, output)?),
+        ExportFormat::Jsonl => Box::new(JsonLinesWriter { columns, output }),
+        ExportFormat::Parquet => Box::new(ParquetWriter::new(columns, output)?),
+    })
+}
+
+fn rfc3339(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+struct CsvWriter(csv::Writer<Output>);
+
+impl CsvWriter {
+    fn new(columns: &[Column], output: Output) -> Result<Self, Error> {
+        let mut writer = csv::Writer::from_writer(output);
+        writer.write_record(columns.iter().map(|column| column.name))?;
+
+        Ok(Self(writer))
+    }
+}
+
+impl RowWriter for CsvWriter {
+    fn write(&mut self, row: Vec<Value>) -> Result<(), Error> {
+        self.0.write_record(row.iter().map(ToString::to_string))?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), Error> {
+        self.0.flush()?;
+        Ok(())
+    }
+}
+
+struct JsonLinesWriter {
+    columns: Vec<Column>,
+    output: Output,
+}
+
+/// A row serialized as an object keyed by the column names.
+struct JsonRow<'a>(&'a [Column], &'a [Value]);
+
+impl Serialize for JsonRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+
+        for (column, value) in self.0.iter().zip(self.1) {
+            map.serialize_entry(column.name, value)?;
+        }
+
+        map.end()
+    }
+}
+
+impl RowWriter for JsonLinesWriter {
+    fn write(&mut self, row: Vec<Value>) -> Result<(), Error> {
+        serde_json::to_writer(&mut self.output, &JsonRow(&self.columns, &row))?;
+        self.output.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), Error> {
+        self.output.flush()?;
+        Ok(())
+    }
+}
+
+struct ParquetWriter {
+    writer: SerializedFileWriter<Output>,
+    columns: Vec<Column>,
+    /// Buffered values of every column.
+    buffer: Vec<Vec<Value>>,
+    rows: usize,
+}
+
+impl ParquetWriter {
+    fn new(columns: Vec<Column>, output: Output) -> Result<Self, Error> {
+        let fields = columns
+            .iter()
+            .map(|column| column.parquet_type().map(Arc::new))
+            .collect::<Result<_, _>>()?;
+        let schema = Type::group_type_builder("measurements")
+            .with_fields(fields)
+            .build()?;
+        let writer = SerializedFileWriter::new(
+            output,
+            Arc::new(schema),
+            Arc::new(WriterProperties::builder().build()),
+        )?;
+
+        Ok(Self {
+            writer,
+            buffer: vec![Vec::with_capacity(ROW_GROUP_SIZE); columns.len()],
+            columns,
+            rows: 0,
+        })
+    }
+
+    /// Write the buffered rows as a row group.
+    fn flush(&mut self) -> Result<(), Error> {
+        let mut group = self.writer.next_row_group()?;
+
+        for (column, values) in self.columns.iter().zip(&mut self.buffer) {
+            let mut writer = group
+                .next_column()?
+                .expect("the schema should have a field for every column");
+            write_column(&mut writer, column.kind, values)?;
+            writer.close()?;
+            values.clear();
+        }
+
+        group.close()?;
+        self.rows = 0;
+
+        Ok(())
+    }
+}
+
+impl RowWriter for ParquetWriter {
+    fn write(&mut self, row: Vec<Value>) -> Result<(), Error> {
+        for (values, value) in self.buffer.iter_mut().zip(row) {
+            values.push(value);
+        }
+
+        self.rows += 1;
+
+        if self.rows >= ROW_GROUP_SIZE {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), Error> {
+        if self.rows > 0 {
+            self.flush()?;
+        }
+
+        let mut output = self.writer.into_inner()?;
+        output.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Write the values of a column, nulls are only recorded in the definition levels.
+fn write_column(
+    writer: &mut SerializedColumnWriter<'_>,
+    kind: ColumnType,
+    values: &[Value],
+) -> Result<(), Error> {
+    let levels: Vec<i16> = values
+        .iter()
+        .map(|value| i16::from(!matches!(value, Value::Null)))
+        .collect();
+
+    match kind {
+        ColumnType::Int | ColumnType::Timestamp => {
+            let values: Vec<i64> = values
+                .iter()
+                .filter_map(|value| match value {
+                    Value::Int(value) => Some(*value),
+                    Value::Timestamp(value) => Some(value.timestamp_millis()),
+                    _ => None,
+                })
+                .collect();
+            writer
+                .typed::<Int64Type>()
+                .write_batch(&values, Some(&levels), None)?;
+        }
+        ColumnType::Real => {
+            let values: Vec<f32> = values
+                .iter()
+                .filter_map(|value| match value {
+                    Value::Real(value) => Some(*value),
+                    _ => None,
+                })
+                .collect();
+            writer
+                .typed::<FloatType>()
+                .write_batch(&values, Some(&levels), None)?;
+        }
+        ColumnType::Float => {
+            let values: Vec<f64> = values
+                .iter()
+                .filter_map(|value| match value {
+                    Value::Float(value) => Some(*value),
+                    _ => None,
+                })
+                .collect();
+            writer
+                .typed::<DoubleType>()
+                .write_batch(&values, Some(&levels), None)?;
+        }
+        ColumnType::Text => {
+            let values: Vec<ByteArray> = values
+                .iter()
+                .filter_map(|value| match value {
+                    Value::Text(value) => Some(value.as_str().into()),
+                    _ => None,
+                })
+                .collect();
+            writer
+                .typed::<ByteArrayType>()
+                .write_batch(&values, Some(&levels), None)?;
+        }
+    }
+
+    Ok(())
+}
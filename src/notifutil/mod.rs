@@ -0,0 +1,88 @@
+use crate::{
+    cli::{NotificationCommand, NotificationTarget},
+    error::Error,
+    server::{
+        config::Config,
+        db::{DatabaseBackend, DatabaseClient},
+        notification_client::{NotificationClient, NotificationKind, NotificationTemplates},
+    },
+};
+use minijinja::context;
+use std::process::exit;
+use tracing::{error, info, warn};
+
+pub async fn run(command: NotificationCommand, config: &Config) -> Result<(), Error> {
+    let client = DatabaseClient::new(config).await?;
+
+    match command {
+        NotificationCommand::List {
+            unread,
+            node,
+            since,
+        } => {
+            let notifications = client.get_notifications(unread, node, since).await?;
+            let mut unread_count = 0;
+
+            for entry in &notifications {
+                print!(
+                    "#{}: {}, node #{}, {} {}, {}",
+                    entry.id, entry.when, entry.node, entry.severity, entry.kind, entry.title
+                );
+
+                if entry.read {
+                    println!(": {}", entry.content);
+                } else {
+                    println!(" (unread): {}", entry.content);
+                    unread_count += 1;
+                }
+            }
+
+            println!(
+                "Total: {} notification(s), {unread_count} unread",
+                notifications.len()
+            );
+        }
+        NotificationCommand::Read { target } => {
+            let id = match target {
+                NotificationTarget::All => None,
+                NotificationTarget::Id(id) => Some(id),
+            };
+
+            let changed = client.mark_notifications_read(id).await?;
+
+            match id {
+                Some(id) if changed == 0 => warn!("No unread notification with ID {id}"),
+                _ => info!("Marked {changed} notification(s) as read"),
+            }
+        }
+        NotificationCommand::Purge { older_than } => {
+            let deleted = client.purge_notifications(older_than).await?;
+            info!(
+                "Deleted {deleted} notification(s) older than {}",
+                humantime::format_duration(older_than)
+            );
+        }
+        NotificationCommand::Send { node, message } => {
+            let templates = NotificationTemplates::new(&config.notification.templates)?;
+            let notify = NotificationClient::new(config.notification.push_backend.as_ref())?;
+
+            let Some(device) = client.get_device(node).await? else {
+                error!("Node #{node} does not exist");
+                exit(1);
+            };
+
+            let notification =
+                templates.render(&device, NotificationKind::Custom, context! { message })?;
+            client.create_notification(&notification).await?;
+
+            // Manually sent notifications are pushed even if the node is muted
+            notify.send_notification(&notification).await?;
+            // Don't wait for the next digest
+            notify.flush().await?;
+
+            info!("Notification sent");
+        }
+    }
+
+    Ok(())
+}
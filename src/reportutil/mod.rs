@@ -0,0 +1,103 @@
+use crate::{
+    cli::ReportCommand,
+    error::Error,
+    server::{
+        battery,
+        config::Config,
+        db::{DatabaseBackend, DatabaseClient},
+        wifi,
+    },
+};
+
+pub async fn run(command: ReportCommand, config: &Config) -> Result<(), Error> {
+    let client = DatabaseClient::new(config).await?;
+
+    match command {
+        ReportCommand::Battery => {
+            for device in client.get_devices().await? {
+                print!("#{} ({}): ", device.id, device.mac);
+
+                if client
+                    .get_settings(device.id)
+                    .await?
+                    .is_some_and(|settings| settings.battery_ignore)
+                {
+                    println!("ignored");
+                    continue;
+                }
+
+                let samples = client
+                    .get_battery_history(
+                        device.id,
+                        config.battery.window,
+                        config.battery.max_samples,
+                    )
+                    .await?;
+                let Some(health) = battery::analyze(&samples, &config.battery) else {
+                    println!("no measurements");
+                    continue;
+                };
+
+                print!("{:.2}V", health.voltage);
+
+                match health.discharge_rate {
+                    Some(rate) if rate > 0.0 => print!(", -{rate:.3}V/day"),
+                    Some(rate) => print!(", +{:.3}V/day", -rate),
+                    None => print!(", trend unknown"),
+                }
+
+                if let Some(days) = health.days_remaining {
+                    print!(", ~{days:.1} day(s) left");
+                }
+
+                match health.last_charge {
+                    Some(when) => println!(", last charged {when}"),
+                    None => println!(),
+                }
+            }
+        }
+        ReportCommand::Wifi { node, roams } => {
+            let devices = client
+                .get_devices()
+                .await?
+                .into_iter()
+                .filter(|device| node.is_none_or(|node| device.id == node));
+
+            for device in devices {
+                print!("#{} ({}): ", device.id, device.mac);
+
+                let samples = client
+                    .get_wifi_history(device.id, config.wifi.window)
+                    .await?;
+                let Some(report) = wifi::analyze(&samples) else {
+                    println!("no measurements");
+                    continue;
+                };
+
+                print!(
+                    "{} at {}dBm, min/avg/max {}/{:.1}/{}dBm",
+                    report.ssid, report.rssi, report.rssi_min, report.rssi_avg, report.rssi_max
+                );
+
+                match report.rssi_trend {
+                    Some(trend) => print!(", {trend:+.2}dBm/day"),
+                    None => print!(", trend unknown"),
+                }
+
+                println!(
+                    ", {} network change(s) in {} measurement(s)",
+                    report.roams.len(),
+                    report.samples
+                );
+
+                if roams {
+                    for roam in &report.roams {
+                        println!("  {}: {} -> {}", roam.when, roam.from, roam.to);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
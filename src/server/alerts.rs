@@ -0,0 +1,120 @@
+use super::{
+    config::{AlertCondition, AlertRuleConfig, Metric},
+    db::{DatabaseBackend, DatabaseClient, MeasurementEntry, NodeId},
+};
+use crate::error::Error;
+use tracing::debug;
+
+/// A change of an alert rule's state that should be notified about.
+#[derive(Debug)]
+pub struct AlertEvent<'a> {
+    pub rule: &'a AlertRuleConfig,
+    pub value: f32,
+    /// Whether the alert has been resolved, as opposed to triggered.
+    pub resolved: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct AlertState {
+    active: bool,
+    streak: u32,
+}
+
+/// Evaluate the rules that apply to `node` against a new measurement, and persist their state.
+pub async fn evaluate<'a>(
+    rules: &'a [AlertRuleConfig],
+    node: NodeId,
+    measurement: &MeasurementEntry,
+    db: &DatabaseClient,
+) -> Result<Vec<AlertEvent<'a>>, Error> {
+    let rules: Vec<_> = rules
+        .iter()
+        .filter(|rule| rule.nodes.is_empty() || rule.nodes.contains(&node))
+        .collect();
+
+    if rules.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let states = db.get_alert_states(node).await?;
+    let mut events = Vec::new();
+
+    for rule in rules {
+        // Nodes without an air pressure sensor can't breach such rules
+        let Some(value) = metric_value(rule.metric, measurement) else {
+            continue;
+        };
+
+        let previous = states
+            .iter()
+            .find(|state| *state.rule == *rule.name)
+            .map_or_else(AlertState::default, |state| AlertState {
+                active: state.active,
+                streak: state.streak,
+            });
+        let next = step(rule, value, previous);
+
+        if next != previous {
+            debug!("Node #{node}: rule '{}' is now {next:?}", rule.name);
+            db.set_alert_state(node, &rule.name, next.active, next.streak)
+                .await?;
+        }
+
+        if next.active != previous.active {
+            events.push(AlertEvent {
+                rule,
+                value,
+                resolved: previous.active,
+            });
+        }
+    }
+
+    Ok(events)
+}
+
+fn step(rule: &AlertRuleConfig, value: f32, state: AlertState) -> AlertState {
+    let (breached, cleared) = match rule.condition {
+        AlertCondition::Below => (
+            value < rule.threshold,
+            value >= rule.threshold + rule.hysteresis,
+        ),
+        AlertCondition::Above => (
+            value > rule.threshold,
+            value <= rule.threshold - rule.hysteresis,
+        ),
+    };
+
+    if state.active {
+        AlertState {
+            active: !cleared,
+            streak: 0,
+        }
+    } else if breached {
+        let streak = state.streak + 1;
+
+        if streak >= rule.consecutive {
+            AlertState {
+                active: true,
+                streak: 0,
+            }
+        } else {
+            AlertState {
+                active: false,
+                streak,
+            }
+        }
+    } else {
+        AlertState::default()
+    }
+}
+
+fn metric_value(metric: Metric, measurement: &MeasurementEntry) -> Option<f32> {
+    match metric {
+        Metric::Temperature => Some(measurement.temperature),
+        Metric::Humidity => Some(f32::from(measurement.humidity)),
+        Metric::AirPressure => measurement.air_pressure.map(f32::from),
+        Metric::Battery => Some(measurement.battery),
+        Metric::CpuTemperature => Some(measurement.cpu_temp),
+        Metric::WifiRssi => Some(f32::from(measurement.wifi_rssi)),
+    }
+}
@@ -0,0 +1,281 @@
+use super::{
+    NotifySender,
+    config::BatteryConfig,
+    db::{BatterySample, DatabaseBackend, DatabaseClient, NodeId},
+    notification_client::{NotificationKind, NotificationTemplates},
+    notify::notify_send,
+    trend,
+};
+use crate::error::Error;
+use chrono::{DateTime, Utc};
+use minijinja::{Value, context};
+use pwmp_client::pwmp_msg::aliases::BatteryVoltage;
+use tracing::{debug, info};
+
+/// Battery alerts keep their state along with alert rules, under names that rules don't use.
+const BATTERY_RULE: &str = "@battery";
+const CHARGING_RULE: &str = "@battery_charging";
+
+#[derive(Debug, Clone, Copy)]
+pub struct BatteryHealth {
+    /// Latest voltage.
+    pub voltage: BatteryVoltage,
+    /// Estimated discharge rate in volts per day, positive while discharging.
+    pub discharge_rate: Option<f64>,
+    /// Estimated days until the voltage drops to the cutoff voltage.
+    pub days_remaining: Option<f64>,
+    /// Time of the last detected charge.
+    pub last_charge: Option<DateTime<Utc>>,
+    /// Whether the latest sample is a charge.
+    pub charging: bool,
+}
+
+/// Estimate the health of a battery from its voltage history, oldest sample first.
+///
+/// Only the samples since the last charge are used, since charging resets the discharge curve.
+pub fn analyze(samples: &[BatterySample], config: &BatteryConfig) -> Option<BatteryHealth> {
+    let latest = samples.last()?;
+
+    let charge = samples
+        .windows(2)
+        .rposition(|pair| pair[1].voltage - pair[0].voltage >= config.charge_threshold)
+        .map(|index| index + 1);
+    let discharging = &samples[charge.unwrap_or_default()..];
+
+    let voltages: Vec<_> = discharging
+        .iter()
+        .map(|sample| (sample.when, f64::from(sample.voltage)))
+        .collect();
+    let discharge_rate = trend::per_day(&voltages).map(|slope| -slope);
+    let days_remaining = discharge_rate
+        .filter(|rate| *rate > 0.0)
+        .map(|rate| (f64::from(latest.voltage - config.cutoff_voltage) / rate).max(0.0));
+
+    Some(BatteryHealth {
+        voltage: latest.voltage,
+        discharge_rate,
+        days_remaining,
+        last_charge: charge.map(|index| samples[index].when),
+        charging: charge == Some(samples.len() - 1),
+    })
+}
+
+/// Check the battery of a node after it posted measurements, and notify about low batteries and charges.
+pub async fn check(
+    config: &BatteryConfig,
+    notify: &NotifySender,
+    templates: &NotificationTemplates,
+    node_id: NodeId,
+    db: &DatabaseClient,
+) -> Result<(), Error> {
+    if config.alert_voltage.is_none() && config.alert_days.is_none() && !config.notify_charging {
+        return Ok(());
+    }
+
+    if db
+        .get_settings(node_id)
+        .await?
+        .is_some_and(|settings| settings.battery_ignore)
+    {
+        return Ok(());
+    }
+
+    let samples = db
+        .get_battery_history(node_id, config.window, config.max_samples)
+        .await?;
+    let Some(health) = analyze(&samples, config) else {
+        return Ok(());
+    };
+
+    let states = db.get_alert_states(node_id).await?;
+    let active = |rule| {
+        states
+            .iter()
+            .any(|state| state.rule == rule && state.active)
+    };
+    let was_low = active(BATTERY_RULE);
+    let was_charging = active(CHARGING_RULE);
+
+    if health.charging != was_charging {
+        db.set_alert_state(node_id, CHARGING_RULE, health.charging, 0)
+            .await?;
+    }
+
+    if health.charging {
+        debug!("Node #{node_id}: battery charged to {}V", health.voltage);
+
+        if was_low {
+            db.set_alert_state(node_id, BATTERY_RULE, false, 0).await?;
+        }
+
+        // The voltage keeps rising for several measurements, only the start of a charge is notified
+        if config.notify_charging && !was_charging {
+            notify_send(
+                notify,
+                templates,
+                node_id,
+                db,
+                NotificationKind::BatteryCharging,
+                values(&health, config),
+            )
+            .await?;
+        }
+
+        return Ok(());
+    }
+
+    let low = config
+        .alert_voltage
+        .is_some_and(|voltage| health.voltage < voltage)
+        || config.alert_days.is_some_and(|days| {
+            health
+                .days_remaining
+                .is_some_and(|remaining| remaining < f64::from(days))
+        });
+
+    if !low || was_low {
+        return Ok(());
+    }
+
+    info!("Node #{node_id}: battery is low ({}V)", health.voltage);
+    db.set_alert_state(node_id, BATTERY_RULE, true, 0).await?;
+
+    notify_send(
+        notify,
+        templates,
+        node_id,
+        db,
+        NotificationKind::BatteryLow,
+        values(&health, config),
+    )
+    .await
+}
+
+/// Values available to the `battery_low` and `battery_charging` templates.
+fn values(health: &BatteryHealth, config: &BatteryConfig) -> Value {
+    context! {
+        voltage => health.voltage,
+        discharge_rate => health.discharge_rate,
+        days_remaining => health.days_remaining,
+        cutoff_voltage => config.cutoff_voltage,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{
+        config::{Config, DatabaseConfig, NotificationTemplatesConfig},
+        db::{MeasurementEntry, RawReadings},
+        diagnostics::SensorFlags,
+        notification_client::Notification,
+    };
+    use tokio::sync::mpsc::{self, Receiver};
+
+    struct Harness {
+        db: DatabaseClient,
+        config: BatteryConfig,
+        templates: NotificationTemplates,
+        notify: NotifySender,
+        receiver: Receiver<Notification>,
+    }
+
+    impl Harness {
+        async fn new() -> Self {
+            let config = Config {
+                database: DatabaseConfig::Memory {
+                    devices: vec!["AA:BB:CC:DD:EE:FF".into()],
+                },
+                ..Default::default()
+            };
+            let (notify, receiver) = mpsc::channel(8);
+
+            Self {
+                db: DatabaseClient::new(&config).await.unwrap(),
+                config: BatteryConfig {
+                    notify_charging: true,
+                    ..Default::default()
+                },
+                templates: NotificationTemplates::new(&NotificationTemplatesConfig::default())
+                    .unwrap(),
+                notify,
+                receiver,
+            }
+        }
+
+        /// Post a measurement with the battery voltage and return the notifications it caused.
+        async fn post(&mut self, battery: BatteryVoltage) -> Vec<NotificationKind> {
+            let measurement = MeasurementEntry {
+                when: Utc::now(),
+                temperature: 20.0,
+                humidity: 50,
+                air_pressure: None,
+                cpu_temp: 30.0,
+                battery,
+                wifi_ssid: "Home".to_string(),
+                wifi_rssi: -60,
+                flags: SensorFlags::default(),
+                raw: RawReadings {
+                    temperature: 20.0,
+                    humidity: 50,
+                    air_pressure: None,
+                    battery,
+                },
+            };
+
+            self.db.post_measurements(1, &measurement).await.unwrap();
+            check(&self.config, &self.notify, &self.templates, 1, &self.db)
+                .await
+                .unwrap();
+
+            let mut kinds = Vec::new();
+            while let Ok(notification) = self.receiver.try_recv() {
+                kinds.push(notification.kind);
+            }
+
+            kinds
+        }
+    }
+
+    #[tokio::test]
+    async fn notifies_once_per_charge() {
+        let mut harness = Harness::new().await;
+
+        assert_eq!(harness.post(3.6).await, []);
+        assert_eq!(harness.post(3.8).await, [NotificationKind::BatteryCharging]);
+        assert_eq!(harness.post(4.0).await, []);
+        assert_eq!(harness.post(4.1).await, []);
+
+        // Discharging again ends the charge, so the next one is notified
+        assert_eq!(harness.post(4.09).await, []);
+        assert_eq!(harness.post(4.2).await, [NotificationKind::BatteryCharging]);
+    }
+
+    #[tokio::test]
+    async fn notifies_low_battery_once() {
+        let mut harness = Harness::new().await;
+        harness.config.alert_voltage = Some(3.5);
+
+        assert_eq!(harness.post(3.6).await, []);
+        assert_eq!(harness.post(3.45).await, [NotificationKind::BatteryLow]);
+        assert_eq!(harness.post(3.44).await, []);
+    }
+
+    #[tokio::test]
+    async fn uses_only_latest_samples() {
+        let mut harness = Harness::new().await;
+        harness.config.max_samples = 2;
+
+        for battery in [4.0, 3.9, 3.8] {
+            harness.post(battery).await;
+        }
+
+        let samples = harness
+            .db
+            .get_battery_history(1, harness.config.window, harness.config.max_samples)
+            .await
+            .unwrap();
+        let voltages: Vec<_> = samples.iter().map(|sample| sample.voltage).collect();
+        assert_eq!(voltages, [3.9, 3.8]);
+    }
+}
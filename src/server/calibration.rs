@@ -0,0 +1,120 @@
+use super::db::MeasurementEntry;
+use pwmp_client::pwmp_msg::aliases::{AirPressure, Humidity};
+
+/// Corrections for the known biases of a node's sensors.
+///
+/// Temperatures are corrected as `raw * temperature_scale + temperature_offset`, battery voltages as `raw * battery_scale`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    pub temperature_offset: f32,
+    pub temperature_scale: f32,
+    pub humidity_offset: f32,
+    pub air_pressure_offset: f32,
+    /// Corrects the ratio of the battery voltage divider.
+    pub battery_scale: f32,
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self {
+            temperature_offset: 0.0,
+            temperature_scale: 1.0,
+            humidity_offset: 0.0,
+            air_pressure_offset: 0.0,
+            battery_scale: 1.0,
+        }
+    }
+}
+
+impl Calibration {
+    /// Correct the readings of a measurement, based on its raw readings.
+    pub fn apply(&self, measurement: &mut MeasurementEntry) {
+        let raw = &measurement.raw;
+
+        measurement.temperature = raw
+            .temperature
+            .mul_add(self.temperature_scale, self.temperature_offset);
+        measurement.humidity = offset_humidity(raw.humidity, self.humidity_offset);
+        measurement.air_pressure = raw
+            .air_pressure
+            .map(|air_pressure| offset_air_pressure(air_pressure, self.air_pressure_offset));
+        measurement.battery = raw.battery * self.battery_scale;
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // clamped to the valid range
+fn offset_humidity(humidity: Humidity, offset: f32) -> Humidity {
+    (f32::from(humidity) + offset).round().clamp(0.0, 100.0) as Humidity
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // clamped to the valid range
+fn offset_air_pressure(air_pressure: AirPressure, offset: f32) -> AirPressure {
+    (f32::from(air_pressure) + offset)
+        .round()
+        .clamp(0.0, f32::from(AirPressure::MAX)) as AirPressure
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{
+        config::{Config, DatabaseConfig, ValidationConfig},
+        db::{DatabaseBackend, DatabaseClient, RawReadings},
+        diagnostics::SensorFlags,
+    };
+    use chrono::Utc;
+
+    fn measurement(temperature: f32) -> MeasurementEntry {
+        MeasurementEntry {
+            when: Utc::now(),
+            temperature,
+            humidity: 50,
+            air_pressure: Some(1000),
+            cpu_temp: 30.0,
+            battery: 3.9,
+            wifi_ssid: "Home".to_string(),
+            wifi_rssi: -60,
+            flags: SensorFlags::default(),
+            raw: RawReadings {
+                temperature,
+                humidity: 50,
+                air_pressure: Some(1000),
+                battery: 3.9,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn recalibration_skips_implausible_readings() {
+        let config = Config {
+            database: DatabaseConfig::Memory {
+                devices: vec!["AA:BB:CC:DD:EE:FF".into()],
+            },
+            ..Default::default()
+        };
+        let db = DatabaseClient::new(&config).await.unwrap();
+
+        db.post_measurements(1, &measurement(20.0)).await.unwrap();
+        db.post_measurements(1, &measurement(95.0)).await.unwrap();
+
+        let calibration = Calibration {
+            temperature_offset: 10.0,
+            ..Default::default()
+        };
+        let (updated, skipped) = db
+            .recalibrate_measurements(1, &calibration, &ValidationConfig::default(), None)
+            .await
+            .unwrap();
+        assert_eq!((updated, skipped), (1, 1));
+
+        let mut temperatures: Vec<f32> = db
+            .get_recent_measurements(1, 2)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.temperature)
+            .collect();
+        temperatures.sort_by(f32::total_cmp);
+        assert_eq!(temperatures, [30.0, 95.0]);
+    }
+}
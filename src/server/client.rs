@@ -219,6 +219,12 @@ impl Client<Authenticated> {
         }
     }
 
+    /// Client of node `id` that skipped the handshake.
+    #[cfg(test)]
+    pub fn authorized(socket: TcpStream, peer_addr: SocketAddr, id: NodeId, mac: Mac) -> Self {
+        Self::new(Client::<Unathenticated>::new(socket, peer_addr), id, mac)
+    }
+
     pub const fn id(&self) -> NodeId {
         self.state.id
     }
@@ -8,10 +8,18 @@ use super::{
 use crate::{
     error::Error,
     server::{
-        config::NotificationEventsConfig,
-        db::{DatabaseBackend, NodeId},
+        alerts, battery,
+        config::NotificationConfig,
+        config::ValidationPolicy,
+        db::{DatabaseBackend, MeasurementEntry, NodeId, RawReadings},
+        diagnostics::{self, SensorFlags},
+        notification_client::{NotificationKind, NotificationTemplates},
+        notify::{notify_send, notify_send_as},
+        validation, wifi,
     },
 };
+use chrono::Utc;
+use minijinja::{Value, context};
 use pwmp_client::pwmp_msg::{request::Request, response::Response};
 use std::{io::Read, net::SocketAddr, sync::Arc};
 use tokio::{net::TcpStream, time::timeout};
@@ -26,6 +34,7 @@ pub async fn handle_client(
     peer_addr: SocketAddr,
     db: &DatabaseClient,
     notify: &NotifySender,
+    templates: &NotificationTemplates,
     config: Arc<Config>,
 ) -> Result<(), Error> {
     let client = Client::new(client, peer_addr);
@@ -62,15 +71,7 @@ pub async fn handle_client(
             break;
         }
 
-        match handle_request(
-            request,
-            &mut client,
-            db,
-            notify,
-            &config.notification.events,
-        )
-        .await
-        {
+        match handle_request(request, &mut client, db, notify, templates, &config).await {
             Ok(response) => {
                 if response.is_error() {
                     error!(
@@ -101,9 +102,11 @@ async fn handle_request(
     client: &mut Client<Authenticated>,
     db: &DatabaseClient,
     notify: &NotifySender,
-    notifs_cfg: &NotificationEventsConfig,
+    templates: &NotificationTemplates,
+    config: &Config,
 ) -> Result<Response, Error> {
     debug!("Handling {req:#?}");
+    let notifs_cfg = &config.notification;
 
     match req {
         Request::Ping => Ok(Response::Pong),
@@ -132,27 +135,125 @@ async fn handle_request(
                 "{}: {temperature:.02}°C, {humidity}%, {air_pressure:?}hPa",
                 client.id()
             );
-            db.post_measurements(
-                client.id(),
+            let mut measurement = MeasurementEntry {
+                when: Utc::now(),
                 temperature,
                 humidity,
                 air_pressure,
                 cpu_temp,
                 battery,
-                &wifi_ssid,
+                wifi_ssid: wifi_ssid.to_string(),
                 wifi_rssi,
+                flags: SensorFlags::default(),
+                raw: RawReadings {
+                    temperature,
+                    humidity,
+                    air_pressure,
+                    battery,
+                },
+            };
+
+            // Readings are validated after calibration, since that's what gets stored
+            if let Some(calibration) = db.get_calibration(client.id()).await? {
+                calibration.apply(&mut measurement);
+            }
+
+            let reasons = validation::validate(&config.validation, &measurement);
+            if !reasons.is_empty() {
+                let reason = reasons.join(", ");
+
+                match config.validation.policy {
+                    ValidationPolicy::Reject => {
+                        warn!("{}: Rejected measurements: {reason}", client.id());
+                        return Ok(Response::InvalidRequest);
+                    }
+                    ValidationPolicy::Quarantine => {
+                        warn!("{}: Quarantined measurements: {reason}", client.id());
+                        db.quarantine_measurements(client.id(), &measurement, &reason)
+                            .await?;
+                        return Ok(Response::Ok);
+                    }
+                    ValidationPolicy::Clamp => {
+                        validation::clamp(&config.validation, &mut measurement);
+
+                        if !validation::validate(&config.validation, &measurement).is_empty() {
+                            warn!(
+                                "{}: Rejected measurements that can't be clamped: {reason}",
+                                client.id()
+                            );
+                            return Ok(Response::InvalidRequest);
+                        }
+
+                        warn!("{}: Clamped measurements: {reason}", client.id());
+                    }
+                }
+            }
+
+            // Diagnostics only flag measurements, so a failure shouldn't reject them
+            match diagnostics::diagnose(&config.diagnostics, client.id(), &measurement, db).await {
+                Ok(flags) => measurement.flags = flags,
+                Err(why) => error!("{}: Failed to diagnose sensors: {why}", client.id()),
+            }
+
+            db.post_measurements(client.id(), &measurement).await?;
+
+            if let Err(why) = db
+                .refresh_rollups(Some(client.id()), Some(measurement.when))
+                .await
+            {
+                error!("{}: Failed to update rollups: {why}", client.id());
+            }
+
+            if config.diagnostics.notify
+                && let Err(why) =
+                    diagnostics::notify_faults(notify, templates, client.id(), db, &measurement)
+                        .await
+            {
+                error!(
+                    "{}: Failed to notify about sensor problems: {why}",
+                    client.id()
+                );
+            }
+
+            // The measurements are already stored, so a failing rule shouldn't reject them
+            if let Err(why) =
+                check_alerts(notify, templates, client.id(), db, notifs_cfg, &measurement).await
+            {
+                error!("{}: Failed to evaluate alert rules: {why}", client.id());
+            }
+
+            if let Err(why) =
+                battery::check(&config.battery, notify, templates, client.id(), db).await
+            {
+                error!("{}: Failed to check battery health: {why}", client.id());
+            }
+
+            if let Err(why) = wifi::check(
+                &config.wifi,
+                notify,
+                templates,
+                client.id(),
+                db,
+                &measurement,
             )
-            .await?;
+            .await
+            {
+                error!("{}: Failed to check Wi-Fi connection: {why}", client.id());
+            }
+
+            if notifs_cfg.events.on_measurements_posted {
+                let altitude = db
+                    .get_device(client.id())
+                    .await?
+                    .and_then(|device| device.altitude);
 
-            if notifs_cfg.on_measurements_posted {
                 notify_send(
                     notify,
+                    templates,
                     client.id(),
                     db,
-                    format!(
-                        "{temperature:.02}°C, {humidity}%, {}hPa",
-                        air_pressure.map_or_else(|| "-".to_string(), |val| val.to_string())
-                    ),
+                    NotificationKind::MeasurementsPosted,
+                    templates.measurements(&measurement, altitude),
                 )
                 .await?;
             }
@@ -160,7 +261,15 @@ async fn handle_request(
             Ok(Response::Ok)
         }
         Request::SendNotification(message) => {
-            notify_send(notify, client.id(), db, message).await?;
+            notify_send(
+                notify,
+                templates,
+                client.id(),
+                db,
+                NotificationKind::Custom,
+                context! { message },
+            )
+            .await?;
             Ok(Response::Ok)
         }
         Request::GetSettings => {
@@ -179,12 +288,17 @@ async fn handle_request(
             if let Some((version, firmware_blob)) = update_info {
                 client.store_update_check_result(current_ver, version, firmware_blob);
 
-                if notifs_cfg.on_update_discovered {
+                if notifs_cfg.events.on_update_discovered {
                     notify_send(
                         notify,
+                        templates,
                         client.id(),
                         db,
-                        format!("Update {version} available, currently running {current_ver}"),
+                        NotificationKind::UpdateDiscovered,
+                        context! {
+                            version => version.to_string(),
+                            current_version => current_ver.to_string(),
+                        },
                     )
                     .await?;
                 }
@@ -230,18 +344,20 @@ async fn handle_request(
                 return Ok(Response::UpdateEnd);
             }
 
-            Ok(Response::UpdatePart(buf.into_boxed_slice()))
+            Ok(Response::UpdatePart(chunk.into()))
         }
         Request::ReportFirmwareUpdate(success) => {
             match db.mark_os_update_stat(client.id(), success).await {
                 Err(Error::InvalidRequest) => Ok(Response::InvalidRequest),
                 Err(why) => {
-                    if notifs_cfg.on_update_failed {
+                    if notifs_cfg.events.on_update_failed {
                         notify_send(
                             notify,
+                            templates,
                             client.id(),
                             db,
-                            format!("Failed to updated to {}", client.update_version().unwrap()),
+                            NotificationKind::UpdateFailed,
+                            update_context(client),
                         )
                         .await?;
                     }
@@ -249,15 +365,14 @@ async fn handle_request(
                     Err(why)
                 }
                 Ok(()) => {
-                    if notifs_cfg.on_update_success {
+                    if notifs_cfg.events.on_update_success {
                         notify_send(
                             notify,
+                            templates,
                             client.id(),
                             db,
-                            format!(
-                                "Successfully to updated to {}",
-                                client.update_version().unwrap()
-                            ),
+                            NotificationKind::UpdateSuccess,
+                            update_context(client),
                         )
                         .await?;
                     }
@@ -270,21 +385,282 @@ async fn handle_request(
     }
 }
 
-async fn notify_send<S: AsRef<str>>(
+/// Values available to the templates of update notifications.
+fn update_context(client: &Client<Authenticated>) -> Value {
+    context! {
+        version => client.update_version().map(|version| version.to_string()),
+        current_version => client.current_version().map(|version| version.to_string()),
+    }
+}
+
+async fn check_alerts(
     notify: &NotifySender,
+    templates: &NotificationTemplates,
     node_id: NodeId,
     db_client: &DatabaseClient,
-    message: S,
+    notifs_cfg: &NotificationConfig,
+    measurement: &MeasurementEntry,
 ) -> Result<(), Error> {
-    // The push notification should include a node ID
-    let push_message = format!("[Node #{node_id}] {}", message.as_ref()).into_boxed_str();
+    let events = alerts::evaluate(&notifs_cfg.rules, node_id, measurement, db_client).await?;
 
-    // The database already links messages to nodes, so we don't need to include the ID
-    db_client
-        .create_notification(node_id, message.as_ref())
+    for event in events {
+        let (kind, severity) = if event.resolved {
+            (
+                NotificationKind::AlertResolved,
+                NotificationKind::AlertResolved.severity(),
+            )
+        } else {
+            (NotificationKind::AlertTriggered, event.rule.severity)
+        };
+
+        notify_send_as(
+            notify,
+            templates,
+            node_id,
+            db_client,
+            kind,
+            severity,
+            templates.alert(event.rule, event.value),
+        )
         .await?;
+    }
 
-    notify
-        .try_send(push_message)
-        .map_err(|_| Error::MpscTrySend)
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{
+        config::{DatabaseConfig, NotificationTemplatesConfig},
+        db::transfer::TransferTable,
+        notification_client::Notification,
+    };
+    use pwmp_client::pwmp_msg::{mac::Mac, version::Version};
+    use tokio::{
+        net::TcpListener,
+        sync::mpsc::{self, Receiver},
+    };
+
+    struct Harness {
+        db: DatabaseClient,
+        config: Config,
+        templates: NotificationTemplates,
+        notify: NotifySender,
+        receiver: Receiver<Notification>,
+        client: Client<Authenticated>,
+        // The other end of the client's socket
+        _node: TcpStream,
+    }
+
+    impl Harness {
+        /// Connect node #1 of a memory database with two nodes.
+        async fn new() -> Self {
+            let config = Config {
+                database: DatabaseConfig::Memory {
+                    devices: vec!["AA:BB:CC:DD:EE:01".into(), "AA:BB:CC:DD:EE:02".into()],
+                },
+                ..Default::default()
+            };
+            let (notify, receiver) = mpsc::channel(8);
+
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let node = TcpStream::connect(listener.local_addr().unwrap())
+                .await
+                .unwrap();
+            let (socket, peer_addr) = listener.accept().await.unwrap();
+            let Ok(mac) = "AA:BB:CC:DD:EE:01".parse::<Mac>() else {
+                unreachable!("valid MAC address")
+            };
+
+            Self {
+                db: DatabaseClient::new(&config).await.unwrap(),
+                config,
+                templates: NotificationTemplates::new(&NotificationTemplatesConfig::default())
+                    .unwrap(),
+                notify,
+                receiver,
+                client: Client::authorized(socket, peer_addr, 1, mac),
+                _node: node,
+            }
+        }
+
+        async fn handle(&mut self, request: Request) -> Response {
+            handle_request(
+                request,
+                &mut self.client,
+                &self.db,
+                &self.notify,
+                &self.templates,
+                &self.config,
+            )
+            .await
+            .unwrap()
+        }
+
+        fn notifications(&mut self) -> Vec<NotificationKind> {
+            let mut kinds = Vec::new();
+            while let Ok(notification) = self.receiver.try_recv() {
+                kinds.push(notification.kind);
+            }
+            kinds
+        }
+    }
+
+    fn post(temperature: f32) -> Request {
+        Request::PostMeasurements {
+            temperature,
+            humidity: 50,
+            air_pressure: Some(1000),
+            battery: 3.9,
+            cpu_temp: 30.0,
+            wifi_ssid: "Home".into(),
+            wifi_rssi: -60,
+        }
+    }
+
+    #[tokio::test]
+    async fn answers_pings_and_rejects_handshakes() {
+        let mut harness = Harness::new().await;
+        let Ok(mac) = "AA:BB:CC:DD:EE:01".parse::<Mac>() else {
+            unreachable!("valid MAC address")
+        };
+
+        assert_eq!(harness.handle(Request::Ping).await, Response::Pong);
+        assert_eq!(
+            harness.handle(Request::Handshake { mac }).await,
+            Response::InvalidRequest
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_settings() {
+        let mut harness = Harness::new().await;
+
+        let Response::Settings(Some(settings)) = harness.handle(Request::GetSettings).await else {
+            panic!("no settings");
+        };
+        assert_eq!(settings.sleep_time, 60);
+    }
+
+    #[tokio::test]
+    async fn stores_measurements() {
+        let mut harness = Harness::new().await;
+
+        assert_eq!(harness.handle(post(21.5)).await, Response::Ok);
+
+        let stored = harness.db.get_last_measurement(1).await.unwrap().unwrap();
+        assert!((stored.temperature - 21.5).abs() < f32::EPSILON);
+        assert!(harness.db.get_last_measurement(2).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn rejects_implausible_measurements() {
+        let mut harness = Harness::new().await;
+
+        assert_eq!(harness.handle(post(150.0)).await, Response::InvalidRequest);
+        assert!(harness.db.get_last_measurement(1).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn quarantines_implausible_measurements() {
+        let mut harness = Harness::new().await;
+        harness.config.validation.policy = ValidationPolicy::Quarantine;
+
+        assert_eq!(harness.handle(post(150.0)).await, Response::Ok);
+        assert!(harness.db.get_last_measurement(1).await.unwrap().is_none());
+        assert_eq!(
+            harness
+                .db
+                .get_table_state(TransferTable::Quarantine)
+                .await
+                .unwrap()
+                .rows,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_firmware_update() {
+        let mut harness = Harness::new().await;
+        let blob: Vec<u8> = (0..10).collect();
+        harness.config.notification.events.on_update_success = true;
+
+        harness
+            .db
+            .upload_firmware(blob.clone(), Version::new(2, 0, 0), Some(vec![1]))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            harness
+                .handle(Request::UpdateCheck(Version::new(2, 0, 0)))
+                .await,
+            Response::FirmwareUpToDate
+        );
+        assert_eq!(
+            harness
+                .handle(Request::UpdateCheck(Version::new(1, 0, 0)))
+                .await,
+            Response::UpdateAvailable(Version::new(2, 0, 0))
+        );
+
+        let mut received = Vec::new();
+        loop {
+            match harness.handle(Request::NextUpdateChunk(4)).await {
+                Response::UpdatePart(chunk) => received.extend_from_slice(&chunk),
+                Response::UpdateEnd => break,
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+        assert_eq!(received, blob);
+
+        assert_eq!(
+            harness.handle(Request::ReportFirmwareUpdate(true)).await,
+            Response::Ok
+        );
+        assert_eq!(harness.notifications(), [NotificationKind::UpdateSuccess]);
+
+        // The update was reported already
+        assert_eq!(
+            harness.handle(Request::ReportFirmwareUpdate(true)).await,
+            Response::InvalidRequest
+        );
+
+        // And it's not offered again
+        assert_eq!(
+            harness
+                .handle(Request::UpdateCheck(Version::new(1, 0, 0)))
+                .await,
+            Response::FirmwareUpToDate
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_chunks_without_update() {
+        let mut harness = Harness::new().await;
+        harness
+            .db
+            .upload_firmware(vec![0xE9; 4], Version::new(2, 0, 0), Some(vec![2]))
+            .await
+            .unwrap();
+
+        // The firmware is restricted to node #2
+        assert_eq!(
+            harness
+                .handle(Request::UpdateCheck(Version::new(1, 0, 0)))
+                .await,
+            Response::FirmwareUpToDate
+        );
+        assert_eq!(
+            harness.handle(Request::NextUpdateChunk(4)).await,
+            Response::InvalidRequest
+        );
+        assert_eq!(
+            harness
+                .handle(Request::NextUpdateChunk(MAX_OTA_CHUNK_SIZE + 1))
+                .await,
+            Response::InvalidRequest
+        );
+    }
 }
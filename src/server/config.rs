@@ -1,9 +1,17 @@
 #![allow(clippy::module_name_repetitions)]
 
-use crate::error::Error;
+use crate::{
+    error::Error,
+    server::{
+        db::{NodeId, PrunableTable},
+        notification_client::{NotificationKind, Severity, SeverityMap},
+    },
+};
+use pwmp_client::pwmp_msg::aliases::{AirPressure, BatteryVoltage, Humidity, Rssi, Temperature};
 use serde::{Deserialize, Serialize};
 use serde_with::{DurationSeconds, serde_as};
 use std::{
+    collections::{HashMap, HashSet},
     net::{Ipv4Addr, SocketAddrV4},
     path::PathBuf,
     time::Duration,
@@ -20,6 +28,16 @@ pub struct Config {
     pub rate_limits: RateLimitConfig,
     pub logging: LogConfig,
     pub notification: NotificationConfig,
+    #[serde(default)]
+    pub battery: BatteryConfig,
+    #[serde(default)]
+    pub wifi: WifiConfig,
+    #[serde(default)]
+    pub diagnostics: DiagnosticsConfig,
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -41,6 +59,21 @@ pub enum DatabaseConfig {
     Sqlite {
         file: PathBuf,
     },
+    /// `MySQL` or `MariaDB`.
+    Mysql {
+        host: Box<str>,
+        port: u16,
+        user: Box<str>,
+        password: Box<str>,
+        name: Box<str>,
+        ssl: bool,
+    },
+    /// Lost when the server stops, for tests and demos.
+    Memory {
+        /// MAC addresses of the devices to create, with default settings.
+        #[serde(default)]
+        devices: Vec<Box<str>>,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -77,6 +110,254 @@ pub struct LogConfig {
 pub struct NotificationConfig {
     pub push_backend: Option<NotificationServiceConfig>,
     pub events: NotificationEventsConfig,
+    #[serde(default)]
+    pub templates: NotificationTemplatesConfig,
+    #[serde(default)]
+    pub escalation: Option<EscalationConfig>,
+    #[serde(default)]
+    pub rules: Vec<AlertRuleConfig>,
+    #[serde(default)]
+    pub watchdog: Option<WatchdogConfig>,
+}
+
+/// Battery health tracking and low battery alerts.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatteryConfig {
+    /// Voltage at which a node stops working, used to predict the remaining battery life.
+    pub cutoff_voltage: f32,
+    /// How far back to look when estimating the discharge rate.
+    #[serde_as(as = "DurationSeconds")]
+    pub window: Duration,
+    /// Maximum number of the latest measurements in `window` to use, so a check doesn't load the whole window.
+    pub max_samples: u32,
+    /// Minimum voltage rise between two measurements that counts as a charge.
+    pub charge_threshold: f32,
+    /// Notify when the voltage drops below this value.
+    pub alert_voltage: Option<f32>,
+    /// Notify when the predicted remaining battery life drops below this many days.
+    pub alert_days: Option<f32>,
+    /// Notify when a node starts charging.
+    pub notify_charging: bool,
+}
+
+/// Wi-Fi connectivity reports and alerts.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WifiConfig {
+    /// How far back to look when building reports.
+    #[serde_as(as = "DurationSeconds")]
+    pub window: Duration,
+    /// Notify when the signal strength drops below this value (in dBm).
+    pub alert_rssi: Option<Rssi>,
+    /// Number of consecutive readings that must be below `alert_rssi` before notifying.
+    pub consecutive: u32,
+    /// Notify when a node connects to a network that's not on this list. Disabled if empty.
+    pub expected_ssids: Vec<Box<str>>,
+}
+
+/// Plausibility checks of posted measurements, done before they're stored.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidationConfig {
+    /// What to do with measurements outside of the plausible ranges.
+    pub policy: ValidationPolicy,
+    pub temperature: PlausibleRange<Temperature>,
+    pub humidity: PlausibleRange<Humidity>,
+    pub air_pressure: PlausibleRange<AirPressure>,
+    pub cpu_temperature: PlausibleRange<Temperature>,
+    pub battery: PlausibleRange<BatteryVoltage>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+pub enum ValidationPolicy {
+    /// Respond with `InvalidRequest` and don't store the measurements.
+    #[default]
+    Reject,
+    /// Store the measurements in the `quarantine` table instead, and respond with `Ok`.
+    Quarantine,
+    /// Clamp the readings to the plausible ranges and store them.
+    Clamp,
+}
+
+/// Inclusive range of plausible values.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PlausibleRange<T> {
+    pub min: T,
+    pub max: T,
+}
+
+/// How long rows are kept before they're pruned. Tables without a retention are kept forever.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    #[serde_as(as = "Option<DurationSeconds>")]
+    pub measurements: Option<Duration>,
+    #[serde_as(as = "Option<DurationSeconds>")]
+    pub notifications: Option<Duration>,
+    #[serde_as(as = "Option<DurationSeconds>")]
+    pub firmware_stats: Option<Duration>,
+    #[serde_as(as = "Option<DurationSeconds>")]
+    pub quarantine: Option<Duration>,
+    /// How often the server prunes old rows.
+    #[serde_as(as = "DurationSeconds")]
+    pub interval: Duration,
+    /// Maximum number of rows deleted at once, so tables are not locked for long.
+    pub batch_size: u32,
+    /// Reclaim disk space after pruning rows.
+    pub vacuum: bool,
+}
+
+/// Sensor health checks, their results are stored as flags on every measurement. Every check is opt-in.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct DiagnosticsConfig {
+    /// Flag readings that haven't changed at all for this many measurements.
+    pub stuck_readings: Option<u32>,
+    /// Flag humidity pinned at 0% or 100%.
+    pub saturation: bool,
+    /// Flag temperatures changing faster than this many °C per hour.
+    pub max_temperature_rate: Option<f32>,
+    /// Flag humidity changing faster than this many % per hour.
+    pub max_humidity_rate: Option<f32>,
+    /// Flag air pressure changing faster than this many hPa per hour.
+    pub max_air_pressure_rate: Option<f32>,
+    /// Flag readings far from the readings of other nodes.
+    pub neighbors: Option<NeighborConfig>,
+    /// Notify when a node's measurements start getting flagged.
+    pub notify: bool,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NeighborConfig {
+    /// Maximum difference from the median temperature of the other nodes, in °C.
+    pub max_temperature_deviation: Option<f32>,
+    /// Maximum difference from the median humidity of the other nodes, in %.
+    pub max_humidity_deviation: Option<f32>,
+    /// Only compare against measurements younger than this.
+    #[serde_as(as = "DurationSeconds")]
+    pub max_age: Duration,
+    /// Nodes that are not comparable to the others (eg. indoor nodes).
+    #[serde(default)]
+    pub exclude: Vec<NodeId>,
+}
+
+/// Detects nodes that stopped posting measurements.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    /// How often to check the nodes.
+    #[serde_as(as = "DurationSeconds")]
+    pub check_interval: Duration,
+    /// Number of sleep cycles a node may miss before it's considered offline.
+    pub missed_cycles: u32,
+    /// Multiplier of the sleep time, to account for the time it takes a node to wake up, connect and post.
+    pub grace_factor: f32,
+    /// Overrides for specific nodes.
+    #[serde(default)]
+    pub nodes: HashMap<NodeId, WatchdogNodeConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WatchdogNodeConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub missed_cycles: Option<u32>,
+    pub grace_factor: Option<f32>,
+}
+
+/// A threshold alert, evaluated on every posted measurement.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AlertRuleConfig {
+    /// Unique name of the rule, used to keep track of its state.
+    pub name: Box<str>,
+    pub metric: Metric,
+    pub condition: AlertCondition,
+    /// Threshold in metric units (°C, %, hPa, V, dBm), regardless of the configured unit system.
+    pub threshold: f32,
+    /// How far the value has to get back past the threshold to resolve the alert.
+    #[serde(default)]
+    pub hysteresis: f32,
+    /// Number of consecutive readings that have to breach the threshold to trigger the alert.
+    #[serde(default = "default_consecutive")]
+    pub consecutive: u32,
+    /// Nodes the rule applies to, or all of them if empty.
+    #[serde(default)]
+    pub nodes: Vec<NodeId>,
+    #[serde(default = "default_alert_severity")]
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Metric {
+    Temperature,
+    Humidity,
+    AirPressure,
+    Battery,
+    CpuTemperature,
+    WifiRssi,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum AlertCondition {
+    Below,
+    Above,
+}
+
+/// Re-sends notifications that have not been marked as read in time.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EscalationConfig {
+    /// Only notifications with at least this severity are escalated.
+    pub min_severity: Severity,
+    /// Time after which an unread notification is escalated (again).
+    #[serde_as(as = "DurationSeconds")]
+    pub after: Duration,
+    /// How many times a notification may be escalated.
+    pub max_escalations: u32,
+    /// Backend to escalate through. If unset, the push backend is used.
+    pub backend: Option<NotificationServiceConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationTemplatesConfig {
+    pub units: UnitSystem,
+    /// Wraps the body of every notification, eg. to add the node's name.
+    pub message: Box<str>,
+    pub custom: TemplateConfig,
+    pub measurements_posted: TemplateConfig,
+    pub update_discovered: TemplateConfig,
+    pub update_success: TemplateConfig,
+    pub update_failed: TemplateConfig,
+    pub alert_triggered: TemplateConfig,
+    pub alert_resolved: TemplateConfig,
+    pub node_offline: TemplateConfig,
+    pub node_online: TemplateConfig,
+    pub battery_low: TemplateConfig,
+    pub battery_charging: TemplateConfig,
+    pub wifi_weak: TemplateConfig,
+    pub wifi_unexpected_ssid: TemplateConfig,
+    pub sensor_fault: TemplateConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateConfig {
+    pub title: Box<str>,
+    pub body: Box<str>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+pub enum UnitSystem {
+    /// °C and hPa.
+    #[default]
+    Metric,
+    /// °F and inHg.
+    Imperial,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -88,17 +369,110 @@ pub struct NotificationEventsConfig {
     pub on_measurements_posted: bool,
 }
 
+#[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 pub enum NotificationServiceConfig {
     Pushsafer {
         private_key: Box<str>,
         device: Box<str>,
+        #[serde(default = "default_pushsafer_styles")]
+        styles: SeverityMap<PushsaferStyle>,
     },
     HassNotify {
         url: Box<str>,
         token: Box<str>,
         target: Box<str>,
     },
+    Ntfy {
+        url: Box<str>,
+        topic: Box<str>,
+        auth: Option<HttpAuth>,
+        #[serde(default = "default_ntfy_priority")]
+        priority: SeverityMap<u8>,
+        #[serde(default)]
+        tags: Vec<Box<str>>,
+        click: Option<Box<str>>,
+    },
+    Gotify {
+        url: Box<str>,
+        auth: HttpAuth,
+        #[serde(default = "default_gotify_priority")]
+        priority: SeverityMap<u8>,
+        click: Option<Box<str>>,
+    },
+    Smtp {
+        host: Box<str>,
+        port: u16,
+        tls: SmtpTls,
+        credentials: Option<SmtpCredentials>,
+        from: Box<str>,
+        to: Vec<Box<str>>,
+        subject: Box<str>,
+        body: Box<str>,
+        #[serde_as(as = "Option<DurationSeconds>")]
+        digest: Option<Duration>,
+    },
+    Telegram {
+        token: Box<str>,
+        chat_ids: Vec<i64>,
+        api_url: Option<Box<str>>,
+        commands: bool,
+    },
+    Exec {
+        command: PathBuf,
+        #[serde(default)]
+        args: Vec<Box<str>>,
+        input: ExecInput,
+        #[serde_as(as = "DurationSeconds")]
+        timeout: Duration,
+    },
+}
+
+/// How notification details are passed to an executable.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ExecInput {
+    /// As `PWMP_*` environment variables.
+    Env,
+    /// As a JSON object on stdin.
+    Json,
+}
+
+/// How Pushsafer presents notifications of a given severity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushsaferStyle {
+    /// Priority from -2 (lowest) to 2 (highest).
+    pub priority: i8,
+    /// Icon number, see the Pushsafer API documentation.
+    pub icon: u8,
+    /// Sound number, the device's default sound is used if unset.
+    pub sound: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum SmtpTls {
+    Plain,
+    StartTls,
+    Implicit,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SmtpCredentials {
+    pub username: Box<str>,
+    pub password: Box<str>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HttpAuth {
+    Basic {
+        username: Box<str>,
+        password: Box<str>,
+    },
+    Bearer {
+        token: Box<str>,
+    },
+    AppToken {
+        token: Box<str>,
+    },
 }
 
 impl Default for RateLimitConfig {
@@ -132,6 +506,195 @@ impl Default for DatabaseConfig {
     }
 }
 
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        Self {
+            cutoff_voltage: 3.3,
+            window: Duration::from_hours(7 * 24),
+            max_samples: 500,
+            charge_threshold: 0.05,
+            alert_voltage: None,
+            alert_days: None,
+            notify_charging: false,
+        }
+    }
+}
+
+impl Default for WifiConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_hours(7 * 24),
+            alert_rssi: None,
+            consecutive: 3,
+            expected_ssids: Vec::new(),
+        }
+    }
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        // The temperature, humidity and battery ranges are the constraints of the `measurements` table,
+        // the CPU temperature is not constrained by it
+        Self {
+            policy: ValidationPolicy::default(),
+            temperature: PlausibleRange {
+                min: -99.9,
+                max: 99.9,
+            },
+            humidity: PlausibleRange { min: 0, max: 100 },
+            air_pressure: PlausibleRange {
+                min: 300,
+                max: 1100,
+            },
+            cpu_temperature: PlausibleRange {
+                min: -99.9,
+                max: 125.0,
+            },
+            battery: PlausibleRange {
+                min: 0.01,
+                max: 4.99,
+            },
+        }
+    }
+}
+
+impl ValidationConfig {
+    /// Check that the ranges are within the constraints of the `measurements` table, so that every accepted
+    /// or clamped measurement can be stored.
+    fn validate(&self) -> Result<(), Error> {
+        self.temperature.validate("temperature")?;
+        self.humidity.validate("humidity")?;
+        self.air_pressure.validate("air_pressure")?;
+        self.cpu_temperature.validate("cpu_temperature")?;
+        self.battery.validate("battery")?;
+
+        if self.temperature.min <= -100.0 || self.temperature.max >= 100.0 {
+            return Err(Error::InvalidConfig(
+                "validation.temperature must be between -100 and 100 °C exclusive".into(),
+            ));
+        }
+
+        if self.humidity.max > 100 {
+            return Err(Error::InvalidConfig(
+                "validation.humidity must be between 0 and 100 %".into(),
+            ));
+        }
+
+        if i16::try_from(self.air_pressure.max).is_err() {
+            return Err(Error::InvalidConfig(format!(
+                "validation.air_pressure must be at most {} hPa",
+                i16::MAX
+            )));
+        }
+
+        if self.battery.min <= 0.0 || self.battery.max >= 5.0 {
+            return Err(Error::InvalidConfig(
+                "validation.battery must be between 0 and 5 V exclusive".into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl WatchdogConfig {
+    fn validate(&self) -> Result<(), Error> {
+        if self.check_interval.is_zero() {
+            return Err(Error::InvalidConfig(
+                "notification.watchdog.check_interval must not be 0".into(),
+            ));
+        }
+
+        validate_cycles(
+            "notification.watchdog",
+            Some(self.missed_cycles),
+            Some(self.grace_factor),
+        )?;
+
+        for (node, config) in &self.nodes {
+            validate_cycles(
+                &format!("notification.watchdog.nodes.{node}"),
+                config.missed_cycles,
+                config.grace_factor,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_cycles(
+    name: &str,
+    missed_cycles: Option<u32>,
+    grace_factor: Option<f32>,
+) -> Result<(), Error> {
+    if missed_cycles == Some(0) {
+        return Err(Error::InvalidConfig(format!(
+            "{name}.missed_cycles must be greater than 0"
+        )));
+    }
+
+    if grace_factor.is_some_and(|factor| !factor.is_finite() || factor <= 0.0) {
+        return Err(Error::InvalidConfig(format!(
+            "{name}.grace_factor must be greater than 0"
+        )));
+    }
+
+    Ok(())
+}
+
+impl NotificationServiceConfig {
+    fn validate(&self, name: &str) -> Result<(), Error> {
+        match self {
+            Self::Smtp {
+                digest: Some(digest),
+                ..
+            } if digest.is_zero() => Err(Error::InvalidConfig(format!(
+                "{name}.digest must not be 0, omit it to send every notification right away"
+            ))),
+            Self::Gotify {
+                auth: HttpAuth::Basic { .. },
+                ..
+            } => Err(Error::InvalidConfig(format!(
+                "{name}.auth must be an application token, Gotify doesn't accept `Basic` authentication for sending messages"
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// Retention of a table, `None` if it's kept forever.
+    pub const fn get(&self, table: PrunableTable) -> Option<Duration> {
+        match table {
+            PrunableTable::Measurements => self.measurements,
+            PrunableTable::Notifications => self.notifications,
+            PrunableTable::FirmwareStats => self.firmware_stats,
+            PrunableTable::Quarantine => self.quarantine,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        PrunableTable::ALL
+            .iter()
+            .any(|table| self.get(*table).is_some())
+    }
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            measurements: None,
+            notifications: None,
+            firmware_stats: None,
+            quarantine: None,
+            interval: Duration::from_hours(1),
+            batch_size: 1000,
+            vacuum: true,
+        }
+    }
+}
+
 impl Default for CacheConfig {
     fn default() -> Self {
         Self {
@@ -153,22 +716,264 @@ impl Default for LimitsConfig {
     }
 }
 
+const fn default_pushsafer_styles() -> SeverityMap<PushsaferStyle> {
+    SeverityMap {
+        info: PushsaferStyle {
+            priority: 0,
+            icon: 2,
+            sound: None,
+        },
+        warning: PushsaferStyle {
+            priority: 1,
+            icon: 2,
+            sound: None,
+        },
+        critical: PushsaferStyle {
+            priority: 2,
+            icon: 2,
+            sound: None,
+        },
+    }
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+const fn default_consecutive() -> u32 {
+    1
+}
+
+const fn default_alert_severity() -> Severity {
+    Severity::Warning
+}
+
+impl<T: PartialOrd + Copy> PlausibleRange<T> {
+    pub fn contains(&self, value: T) -> bool {
+        self.min <= value && value <= self.max
+    }
+
+    /// Check that the range is not empty and that neither bound is NaN.
+    fn validate(&self, name: &str) -> Result<(), Error> {
+        if self.min <= self.max {
+            Ok(())
+        } else {
+            Err(Error::InvalidConfig(format!(
+                "validation.{name}.min must not be greater than validation.{name}.max"
+            )))
+        }
+    }
+
+    /// Values that are not comparable (ie. NaN) are returned as-is.
+    pub fn clamp(&self, value: T) -> T {
+        if value < self.min {
+            self.min
+        } else if value > self.max {
+            self.max
+        } else {
+            value
+        }
+    }
+}
+
+impl Metric {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Temperature => "temperature",
+            Self::Humidity => "humidity",
+            Self::AirPressure => "air_pressure",
+            Self::Battery => "battery",
+            Self::CpuTemperature => "cpu_temperature",
+            Self::WifiRssi => "wifi_rssi",
+        }
+    }
+}
+
+impl AlertCondition {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Below => "below",
+            Self::Above => "above",
+        }
+    }
+}
+
+const fn default_ntfy_priority() -> SeverityMap<u8> {
+    SeverityMap {
+        info: 3,
+        warning: 4,
+        critical: 5,
+    }
+}
+
+const fn default_gotify_priority() -> SeverityMap<u8> {
+    SeverityMap {
+        info: 4,
+        warning: 7,
+        critical: 10,
+    }
+}
+
+impl Default for NotificationTemplatesConfig {
+    fn default() -> Self {
+        let template = |kind: NotificationKind, body: &str| TemplateConfig {
+            title: kind.title().into(),
+            body: body.into(),
+        };
+
+        Self {
+            units: UnitSystem::default(),
+            message: "[{{ node.note or 'Node #' ~ node.id }}] {{ body }}".into(),
+            custom: template(NotificationKind::Custom, "{{ message }}"),
+            measurements_posted: template(
+                NotificationKind::MeasurementsPosted,
+                "{{ temperature|round(2) }}{{ units.temperature }}, {{ humidity }}%, \
+                 {{ air_pressure|round(2) if air_pressure is not none else '-' }}{{ units.air_pressure }}",
+            ),
+            update_discovered: template(
+                NotificationKind::UpdateDiscovered,
+                "Update {{ version }} available, currently running {{ current_version }}",
+            ),
+            update_success: template(
+                NotificationKind::UpdateSuccess,
+                "Successfully updated to {{ version }}",
+            ),
+            update_failed: template(
+                NotificationKind::UpdateFailed,
+                "Failed to update to {{ version }}",
+            ),
+            alert_triggered: TemplateConfig {
+                title: "Alert: {{ rule }}".into(),
+                body: "{{ rule }}: {{ value|round(2) }}{{ unit }} \
+                       ({{ condition }} {{ threshold|round(2) }}{{ unit }})"
+                    .into(),
+            },
+            alert_resolved: TemplateConfig {
+                title: "Resolved: {{ rule }}".into(),
+                body: "{{ rule }}: back to {{ value|round(2) }}{{ unit }}".into(),
+            },
+            node_offline: template(
+                NotificationKind::NodeOffline,
+                "No measurements for {{ silent_for }}, last seen {{ last_seen }}",
+            ),
+            node_online: template(
+                NotificationKind::NodeOnline,
+                "Posting measurements again, last seen {{ last_seen }}",
+            ),
+            battery_low: template(
+                NotificationKind::BatteryLow,
+                "Battery at {{ voltage|round(2) }}V\
+                 {% if days_remaining is not none %}, about {{ days_remaining|round(1) }} days left{% endif %}",
+            ),
+            battery_charging: template(
+                NotificationKind::BatteryCharging,
+                "Battery charged to {{ voltage|round(2) }}V",
+            ),
+            wifi_weak: template(
+                NotificationKind::WifiWeak,
+                "Signal strength of {{ ssid }} dropped to {{ rssi }}dBm (below {{ threshold }}dBm)",
+            ),
+            wifi_unexpected_ssid: template(
+                NotificationKind::WifiUnexpectedSsid,
+                "Connected to unexpected network {{ ssid }} ({{ rssi }}dBm)",
+            ),
+            sensor_fault: template(
+                NotificationKind::SensorFault,
+                "Suspicious readings ({{ flags|join(', ') }}): {{ temperature|round(2) }}{{ units.temperature }}, \
+                 {{ humidity }}%",
+            ),
+        }
+    }
+}
+
+impl NotificationTemplatesConfig {
+    pub const fn event(&self, kind: NotificationKind) -> &TemplateConfig {
+        match kind {
+            NotificationKind::Custom => &self.custom,
+            NotificationKind::MeasurementsPosted => &self.measurements_posted,
+            NotificationKind::UpdateDiscovered => &self.update_discovered,
+            NotificationKind::UpdateSuccess => &self.update_success,
+            NotificationKind::UpdateFailed => &self.update_failed,
+            NotificationKind::AlertTriggered => &self.alert_triggered,
+            NotificationKind::AlertResolved => &self.alert_resolved,
+            NotificationKind::NodeOffline => &self.node_offline,
+            NotificationKind::NodeOnline => &self.node_online,
+            NotificationKind::BatteryLow => &self.battery_low,
+            NotificationKind::BatteryCharging => &self.battery_charging,
+            NotificationKind::WifiWeak => &self.wifi_weak,
+            NotificationKind::WifiUnexpectedSsid => &self.wifi_unexpected_ssid,
+            NotificationKind::SensorFault => &self.sensor_fault,
+        }
+    }
+}
+
+impl UnitSystem {
+    pub fn temperature(self, celsius: f64) -> f64 {
+        match self {
+            Self::Metric => celsius,
+            Self::Imperial => celsius.mul_add(1.8, 32.0),
+        }
+    }
+
+    pub fn air_pressure(self, hpa: f64) -> f64 {
+        match self {
+            Self::Metric => hpa,
+            Self::Imperial => hpa * 0.029_53,
+        }
+    }
+
+    pub const fn temperature_unit(self) -> &'static str {
+        match self {
+            Self::Metric => "°C",
+            Self::Imperial => "°F",
+        }
+    }
+
+    pub const fn air_pressure_unit(self) -> &'static str {
+        match self {
+            Self::Metric => "hPa",
+            Self::Imperial => "inHg",
+        }
+    }
+
+    /// Convert a metric value to this unit system.
+    pub fn metric(self, metric: Metric, value: f64) -> f64 {
+        match metric {
+            Metric::Temperature | Metric::CpuTemperature => self.temperature(value),
+            Metric::AirPressure => self.air_pressure(value),
+            Metric::Humidity | Metric::Battery | Metric::WifiRssi => value,
+        }
+    }
+
+    pub const fn metric_unit(self, metric: Metric) -> &'static str {
+        match metric {
+            Metric::Temperature | Metric::CpuTemperature => self.temperature_unit(),
+            Metric::AirPressure => self.air_pressure_unit(),
+            Metric::Humidity => "%",
+            Metric::Battery => "V",
+            Metric::WifiRssi => "dBm",
+        }
+    }
+}
+
 impl DatabaseConfig {
     pub fn host(&self) -> String {
         match self {
-            Self::Postgres { host, .. } => host.to_string(),
+            Self::Postgres { host, .. } | Self::Mysql { host, .. } => host.to_string(),
             Self::Sqlite { file } => file.display().to_string(),
+            Self::Memory { .. } => "memory".to_string(),
         }
     }
 
     pub fn name(&self) -> String {
         match self {
-            Self::Postgres { name, .. } => name.to_string(),
+            Self::Postgres { name, .. } | Self::Mysql { name, .. } => name.to_string(),
             Self::Sqlite { file } => file
                 .file_name()
                 .unwrap_or_default()
                 .to_string_lossy()
                 .to_string(),
+            Self::Memory { .. } => "memory".to_string(),
         }
     }
 }
@@ -184,11 +989,238 @@ impl Config {
     pub const fn server_bind_addr(&self) -> SocketAddrV4 {
         SocketAddrV4::new(self.server.host, self.server.port)
     }
+
+    /// Check what can't be expressed by the types of the configuration.
+    pub fn validate(&self) -> Result<(), Error> {
+        let mut names = HashSet::new();
+
+        for rule in &self.notification.rules {
+            // Built-in alerts keep their state under names starting with `@`
+            if rule.name.starts_with('@') {
+                return Err(Error::InvalidConfig(format!(
+                    "alert rule name `{}` is reserved, names starting with `@` are used by built-in alerts",
+                    rule.name
+                )));
+            }
+
+            if !names.insert(&rule.name) {
+                return Err(Error::InvalidConfig(format!(
+                    "alert rule name `{}` is used more than once",
+                    rule.name
+                )));
+            }
+        }
+
+        if let Some(watchdog) = &self.notification.watchdog {
+            watchdog.validate()?;
+        }
+
+        if let Some(backend) = &self.notification.push_backend {
+            backend.validate("notification.push_backend")?;
+        }
+
+        if let Some(backend) = self
+            .notification
+            .escalation
+            .as_ref()
+            .and_then(|escalation| escalation.backend.as_ref())
+        {
+            backend.validate("notification.escalation.backend")?;
+        }
+
+        if self.retention.is_enabled() && self.retention.interval.is_zero() {
+            return Err(Error::InvalidConfig(
+                "retention.interval must not be 0".into(),
+            ));
+        }
+
+        self.validation.validate()
+    }
 }
 
 pub fn setup(config_path: &PathBuf) -> Result<(Config, bool), Error> {
     let first_run = !config_path.exists();
     let config: Config = confy::load_path(config_path)?;
+    config.validate()?;
 
     Ok((config, first_run))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str) -> AlertRuleConfig {
+        AlertRuleConfig {
+            name: name.into(),
+            metric: Metric::Temperature,
+            condition: AlertCondition::Below,
+            threshold: 0.0,
+            hysteresis: 0.0,
+            consecutive: 1,
+            nodes: Vec::new(),
+            severity: Severity::Warning,
+        }
+    }
+
+    fn config(rules: &[&str]) -> Config {
+        let mut config = Config::default();
+        config.notification.rules = rules.iter().map(|name| rule(name)).collect();
+        config
+    }
+
+    #[test]
+    fn default_is_valid() {
+        Config::default().validate().unwrap();
+    }
+
+    #[test]
+    fn accepts_unique_rule_names() {
+        config(&["Frost", "Heat"]).validate().unwrap();
+    }
+
+    #[test]
+    fn rejects_duplicate_rule_names() {
+        assert!(config(&["Frost", "Heat", "Frost"]).validate().is_err());
+    }
+
+    #[test]
+    fn rejects_reserved_rule_names() {
+        for name in ["@offline", "@battery", "@wifi_weak", "@sensor"] {
+            assert!(config(&[name]).validate().is_err());
+        }
+    }
+
+    #[test]
+    fn accepts_ranges_within_constraints() {
+        let mut config = Config::default();
+        config.validation.temperature = PlausibleRange {
+            min: -60.0,
+            max: 60.0,
+        };
+        config.validation.battery = PlausibleRange { min: 2.5, max: 4.2 };
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn rejects_ranges_outside_constraints() {
+        let mut config = Config::default();
+        config.validation.temperature.max = 100.0;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.validation.humidity.max = 101;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.validation.air_pressure.max = 40_000;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.validation.battery.max = 5.0;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.validation.battery.min = 0.0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn rejects_empty_and_nan_ranges() {
+        let mut config = Config::default();
+        config.validation.humidity = PlausibleRange { min: 80, max: 20 };
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.validation.temperature.min = f32::NAN;
+        assert!(config.validate().is_err());
+    }
+
+    fn watchdog() -> WatchdogConfig {
+        WatchdogConfig {
+            check_interval: Duration::from_mins(1),
+            missed_cycles: 3,
+            grace_factor: 1.5,
+            nodes: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn rejects_zero_intervals() {
+        let mut config = Config::default();
+        config.notification.watchdog = Some(WatchdogConfig {
+            check_interval: Duration::ZERO,
+            ..watchdog()
+        });
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.retention.measurements = Some(Duration::from_hours(24));
+        config.retention.interval = Duration::ZERO;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.notification.push_backend = Some(NotificationServiceConfig::Smtp {
+            host: "localhost".into(),
+            port: 25,
+            tls: SmtpTls::Plain,
+            credentials: None,
+            from: "pwmp@localhost".into(),
+            to: vec!["admin@localhost".into()],
+            subject: "PixelWeather".into(),
+            body: "{{ message }}".into(),
+            digest: Some(Duration::ZERO),
+        });
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn rejects_gotify_basic_auth() {
+        let gotify = |auth| NotificationServiceConfig::Gotify {
+            url: "https://example.com/gotify".into(),
+            auth,
+            priority: default_gotify_priority(),
+            click: None,
+        };
+
+        let mut config = Config::default();
+        config.notification.push_backend = Some(gotify(HttpAuth::AppToken {
+            token: "abc123".into(),
+        }));
+        assert!(config.validate().is_ok());
+
+        config.notification.push_backend = Some(gotify(HttpAuth::Basic {
+            username: "user".into(),
+            password: "pass".into(),
+        }));
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn rejects_non_positive_watchdog_factors() {
+        for grace_factor in [0.0, -1.0, f32::NAN] {
+            let mut config = Config::default();
+            config.notification.watchdog = Some(WatchdogConfig {
+                grace_factor,
+                ..watchdog()
+            });
+            assert!(config.validate().is_err(), "{grace_factor}");
+        }
+
+        let mut config = Config::default();
+        let mut overridden = watchdog();
+        overridden.nodes.insert(
+            3,
+            WatchdogNodeConfig {
+                enabled: true,
+                missed_cycles: Some(0),
+                grace_factor: None,
+            },
+        );
+        config.notification.watchdog = Some(overridden);
+        assert!(config.validate().is_err());
+
+        config.notification.watchdog = Some(watchdog());
+        config.validate().unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+//! Tests shared by every backend, run against a client that has two nodes with IDs 1 and 2 and nothing else.
+
+use super::{DatabaseBackend, EraseOptions, NodeId, migrations, transfer::TransferTable};
+use crate::server::calibration::Calibration;
+use pwmp_client::pwmp_msg::version::Version;
+
+async fn update(client: &impl DatabaseBackend, node: NodeId, current: Version) -> Option<Version> {
+    client
+        .check_os_update(node, current)
+        .await
+        .unwrap()
+        .map(|(version, _)| version)
+}
+
+async fn fill(client: &impl DatabaseBackend) {
+    client
+        .set_calibration(1, &Calibration::default())
+        .await
+        .unwrap();
+    client.set_alert_state(1, "Frost", true, 1).await.unwrap();
+    client
+        .upload_firmware(vec![0xE9; 4], Version::new(2, 0, 0), None)
+        .await
+        .unwrap();
+    client
+        .send_os_update_stat(1, Version::new(1, 0, 0), Version::new(2, 0, 0))
+        .await
+        .unwrap();
+}
+
+async fn rows(client: &impl DatabaseBackend, table: TransferTable) -> u64 {
+    client.get_table_state(table).await.unwrap().rows
+}
+
+pub async fn offers_newest_permitted_firmware(client: &impl DatabaseBackend) {
+    for (version, restrict_nodes) in [
+        (Version::new(1, 1, 0), None),
+        (Version::new(2, 0, 0), None),
+        (Version::new(3, 0, 0), Some(vec![2])),
+    ] {
+        client
+            .upload_firmware(vec![0xE9; 4], version, restrict_nodes)
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        update(client, 1, Version::new(1, 0, 0)).await,
+        Some(Version::new(2, 0, 0))
+    );
+    assert_eq!(
+        update(client, 2, Version::new(1, 0, 0)).await,
+        Some(Version::new(3, 0, 0))
+    );
+    assert_eq!(update(client, 1, Version::new(2, 0, 0)).await, None);
+    assert_eq!(update(client, 2, Version::new(3, 0, 0)).await, None);
+}
+
+pub async fn skips_attempted_firmware(client: &impl DatabaseBackend) {
+    for version in [Version::new(1, 1, 0), Version::new(2, 0, 0)] {
+        client
+            .upload_firmware(vec![0xE9; 4], version, None)
+            .await
+            .unwrap();
+    }
+
+    // A failed update isn't offered again, whether it was reported or not
+    client
+        .send_os_update_stat(1, Version::new(1, 0, 0), Version::new(2, 0, 0))
+        .await
+        .unwrap();
+    assert_eq!(
+        update(client, 1, Version::new(1, 0, 0)).await,
+        Some(Version::new(1, 1, 0))
+    );
+
+    client.mark_os_update_stat(1, false).await.unwrap();
+    assert_eq!(
+        update(client, 1, Version::new(1, 0, 0)).await,
+        Some(Version::new(1, 1, 0))
+    );
+
+    // Attempts of other nodes don't matter
+    assert_eq!(
+        update(client, 2, Version::new(1, 0, 0)).await,
+        Some(Version::new(2, 0, 0))
+    );
+}
+
+pub async fn erases_content_but_keeps_devices(client: &impl DatabaseBackend) {
+    fill(client).await;
+
+    client
+        .erase(EraseOptions::ContentOnly { keep_devices: true })
+        .await
+        .unwrap();
+
+    assert_eq!(rows(client, TransferTable::Devices).await, 2);
+    assert_eq!(rows(client, TransferTable::Settings).await, 2);
+    assert_eq!(rows(client, TransferTable::Calibrations).await, 1);
+    for table in [
+        TransferTable::AlertStates,
+        TransferTable::Firmwares,
+        TransferTable::FirmwareStats,
+    ] {
+        assert_eq!(rows(client, table).await, 0, "{}", table.as_str());
+    }
+    assert_eq!(
+        client.get_schema_version().await.unwrap(),
+        migrations::SCHEMA_VERSION
+    );
+}
+
+pub async fn erases_content(client: &impl DatabaseBackend) {
+    fill(client).await;
+
+    client
+        .erase(EraseOptions::ContentOnly {
+            keep_devices: false,
+        })
+        .await
+        .unwrap();
+
+    for table in TransferTable::ALL {
+        assert_eq!(rows(client, table).await, 0, "{}", table.as_str());
+    }
+    assert_eq!(
+        client.get_schema_version().await.unwrap(),
+        migrations::SCHEMA_VERSION
+    );
+}
+
+pub async fn erases_everything(client: &impl DatabaseBackend) {
+    fill(client).await;
+
+    client.erase(EraseOptions::Everything).await.unwrap();
+    assert!(client.get_applied_migrations().await.unwrap().is_empty());
+
+    // SQL backends drop the tables, so they're recreated before counting their rows
+    client.run_migrations().await.unwrap();
+    for table in TransferTable::ALL {
+        assert_eq!(rows(client, table).await, 0, "{}", table.as_str());
+    }
+}
@@ -0,0 +1,1728 @@
+//! Database that only lives in memory, for tests and demos. Everything is lost when the server stops.
+//!
+//! Tables hold the same rows as the other backends and enforce the same references, so it behaves like an SQL database.
+//! Timestamps are stored with one-second precision, like in `SQLite`.
+
+// Every operation holds the lock until it's done, so it's atomic like a transaction
+#![allow(clippy::significant_drop_tightening)]
+
+use super::{
+    AggregateEntry, AggregateStream, AlertStateEntry, BatterySample, CalibrationEntry, DeviceEntry,
+    EraseOptions, FirmwareBlob, FirmwareEntry, MeasurementEntry, MeasurementRange,
+    MeasurementStream, NodeId, NotificationEntry, NotificationId, PrunableTable, RawReadings,
+    SettingsEntry, Summary, UpdateStatId, WifiSample,
+    migrations::{self, AppliedMigration, Migration},
+    transfer::{
+        AlertStateRow, CalibrationRow, DeviceRow, FirmwareRow, FirmwareStatRow, MeasurementRow,
+        NotificationRow, QuarantineRow, RollupRow, SettingsRow, TableState, TransferRow,
+        TransferTable,
+    },
+};
+use crate::{
+    error::Error,
+    server::{
+        calibration::Calibration,
+        config::ValidationConfig,
+        diagnostics::SensorFlags,
+        notification_client::{Notification, Severity},
+    },
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use futures_util::{StreamExt, stream};
+use pwmp_client::pwmp_msg::{
+    aliases::AirPressure, mac::Mac, settings::NodeSettings, version::Version,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+
+pub struct MemoryClient(Mutex<State>);
+
+#[derive(Default)]
+struct State {
+    migrations: Vec<AppliedMigration>,
+    devices: Table<DeviceRow>,
+    settings: Table<SettingsRow>,
+    measurements: Table<MeasurementRow>,
+    notifications: Table<NotificationRow>,
+    firmwares: Table<FirmwareRow>,
+    firmware_stats: Table<FirmwareStatRow>,
+    quarantine: Table<QuarantineRow>,
+    calibrations: BTreeMap<NodeId, Calibration>,
+    alert_states: BTreeMap<(NodeId, String), AlertStateRow>,
+    rollups_hourly: BTreeMap<(NodeId, DateTime<Utc>), RollupRow>,
+    rollups_daily: BTreeMap<(NodeId, DateTime<Utc>), RollupRow>,
+}
+
+/// Rows by ID, which is assigned from a sequence like `AUTOINCREMENT` columns.
+struct Table<T> {
+    rows: BTreeMap<i32, T>,
+    sequence: i32,
+}
+
+/// Rows with a creation time, which are pruned by their age.
+trait Timestamped {
+    fn when(&self) -> DateTime<Utc>;
+}
+
+impl MemoryClient {
+    /// Create an empty database at the current schema version, with the given devices and their default settings.
+    #[tracing::instrument(name = "MemoryClient::new()", level = "debug", err, skip_all)]
+    pub fn new(devices: &[Box<str>]) -> Result<Self, Error> {
+        let mut state = State::default();
+
+        for migration in migrations::POSTGRES {
+            state.record_migration(migration);
+        }
+
+        for mac in devices {
+            if state.device_by_mac(mac).is_some() {
+                return Err(Error::Constraint(format!("device {mac} exists already")));
+            }
+
+            let node = state.devices.insert(|id| DeviceRow {
+                id,
+                mac_address: mac.to_string(),
+                location: None,
+                note: None,
+                altitude: None,
+            });
+            state.settings.insert(|id| SettingsRow {
+                id,
+                node,
+                battery_ignore: false,
+                ota: false,
+                sleep_time: 60,
+                sbop: true,
+                mute_notifications: false,
+                device_specific: "{}".to_string(),
+                muted_until: None,
+            });
+        }
+
+        Ok(Self(Mutex::new(state)))
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.0.lock().unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+impl super::DatabaseBackend for MemoryClient {
+    #[tracing::instrument(
+        name = "MemoryClient::authorize_device()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn authorize_device(&self, mac: &Mac) -> Result<Option<NodeId>, Error> {
+        Ok(self.state().device_by_mac(&mac.to_string()))
+    }
+
+    #[tracing::instrument(name = "MemoryClient::get_devices()", level = "debug", skip(self), err)]
+    async fn get_devices(&self) -> Result<Vec<DeviceEntry>, Error> {
+        let results = self
+            .state()
+            .devices
+            .rows
+            .values()
+            .map(device_entry)
+            .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(name = "MemoryClient::get_device()", level = "debug", skip(self), err)]
+    async fn get_device(&self, node: NodeId) -> Result<Option<DeviceEntry>, Error> {
+        Ok(self.state().devices.rows.get(&node).map(device_entry))
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::create_notification()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn create_notification(&self, notification: &Notification) -> Result<(), Error> {
+        let mut state = self.state();
+        state.check_node(notification.node)?;
+
+        state.notifications.insert(|id| NotificationRow {
+            id,
+            node: notification.node,
+            when: now(),
+            content: notification.body.to_string(),
+            read: false,
+            severity: notification.severity.as_str().to_string(),
+            kind: notification.kind.as_str().to_string(),
+            title: notification.title.to_string(),
+            escalations: 0,
+            escalated: None,
+        });
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::get_notifications()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_notifications(
+        &self,
+        unread_only: bool,
+        node: Option<NodeId>,
+        since: Option<Duration>,
+    ) -> Result<Vec<NotificationEntry>, Error> {
+        let since = since.map(ago);
+
+        let mut results: Vec<_> = self
+            .state()
+            .notifications
+            .rows
+            .values()
+            .filter(|row| !(unread_only && row.read))
+            .filter(|row| node.is_none_or(|node| row.node == node))
+            .filter(|row| since.is_none_or(|since| row.when >= since))
+            .map(notification_entry)
+            .collect();
+        results.sort_by_key(|entry| std::cmp::Reverse((entry.when, entry.id)));
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::mark_notifications_read()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn mark_notifications_read(&self, id: Option<NotificationId>) -> Result<u64, Error> {
+        let mut changed = 0;
+
+        for row in self.state().notifications.rows.values_mut() {
+            if !row.read && id.is_none_or(|id| row.id == id) {
+                row.read = true;
+                changed += 1;
+            }
+        }
+
+        Ok(changed)
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::purge_notifications()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn purge_notifications(&self, older_than: Duration) -> Result<u64, Error> {
+        Ok(self.state().notifications.prune(ago(older_than), u32::MAX))
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::get_unacknowledged_notifications()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_unacknowledged_notifications(
+        &self,
+        after: Duration,
+        max_escalations: u32,
+        min_severity: Severity,
+    ) -> Result<Vec<NotificationEntry>, Error> {
+        let cutoff = ago(after);
+        let max_escalations: i32 = max_escalations.try_into()?;
+
+        let mut results: Vec<_> = self
+            .state()
+            .notifications
+            .rows
+            .values()
+            .filter(|row| !row.read && row.escalations < max_escalations)
+            .filter(|row| row.escalated.unwrap_or(row.when) <= cutoff)
+            .filter(|row| severity_level(&row.severity) >= min_severity.level())
+            .map(notification_entry)
+            .collect();
+        results.sort_by_key(|entry| (entry.when, entry.id));
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::mark_notification_escalated()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn mark_notification_escalated(&self, id: NotificationId) -> Result<(), Error> {
+        if let Some(row) = self.state().notifications.rows.get_mut(&id) {
+            row.escalations += 1;
+            row.escalated = Some(now());
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::get_settings_entry()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn get_settings_entry(&self, node_id: NodeId) -> Result<Option<SettingsEntry>, Error> {
+        let state = self.state();
+        let Some(row) = state.settings.rows.values().find(|row| row.node == node_id) else {
+            return Ok(None);
+        };
+
+        Ok(Some(SettingsEntry {
+            settings: NodeSettings {
+                battery_ignore: row.battery_ignore,
+                ota: row.ota,
+                sleep_time: row.sleep_time.try_into()?,
+                sbop: row.sbop,
+                mute_notifications: row.mute_notifications,
+            },
+            muted_until: row.muted_until,
+        }))
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::set_device_altitude()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn set_device_altitude(
+        &self,
+        node: NodeId,
+        altitude: Option<f32>,
+    ) -> Result<bool, Error> {
+        let mut state = self.state();
+        let Some(row) = state.devices.rows.get_mut(&node) else {
+            return Ok(false);
+        };
+
+        row.altitude = altitude;
+        Ok(true)
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::set_notifications_muted()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn set_notifications_muted(
+        &self,
+        node_id: NodeId,
+        muted: bool,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<bool, Error> {
+        let mut state = self.state();
+        let Some(row) = state
+            .settings
+            .rows
+            .values_mut()
+            .find(|row| row.node == node_id)
+        else {
+            return Ok(false);
+        };
+
+        row.mute_notifications = muted;
+        row.muted_until = until;
+        Ok(true)
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::post_results()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn post_measurements(
+        &self,
+        node: NodeId,
+        measurement: &MeasurementEntry,
+    ) -> Result<(), Error> {
+        let mut state = self.state();
+        state.check_node(node)?;
+
+        let mut row = measurement_row(node, now(), measurement)?;
+        row.raw_temperature = Some(measurement.raw.temperature);
+        row.raw_humidity = Some(measurement.raw.humidity.into());
+        row.raw_air_pressure = measurement
+            .raw
+            .air_pressure
+            .map(i16::try_from)
+            .transpose()?;
+        row.raw_battery = Some(measurement.raw.battery);
+
+        state.measurements.insert(|id| MeasurementRow { id, ..row });
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::get_calibration()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_calibration(&self, node: NodeId) -> Result<Option<Calibration>, Error> {
+        Ok(self.state().calibrations.get(&node).copied())
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::get_calibrations()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_calibrations(&self) -> Result<Vec<CalibrationEntry>, Error> {
+        let results = self
+            .state()
+            .calibrations
+            .iter()
+            .map(|(&node, &calibration)| CalibrationEntry { node, calibration })
+            .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::set_calibration()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn set_calibration(&self, node: NodeId, calibration: &Calibration) -> Result<(), Error> {
+        let mut state = self.state();
+        state.check_node(node)?;
+
+        state.calibrations.insert(node, *calibration);
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::delete_calibration()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn delete_calibration(&self, node: NodeId) -> Result<bool, Error> {
+        Ok(self.state().calibrations.remove(&node).is_some())
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::recalibrate_measurements()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn recalibrate_measurements(
+        &self,
+        node: NodeId,
+        calibration: &Calibration,
+        validation: &ValidationConfig,
+        since: Option<Duration>,
+    ) -> Result<(u64, u64), Error> {
+        let since = since.map(ago);
+        let mut updated = 0;
+        let mut skipped = 0;
+
+        for row in self.state().measurements.rows.values_mut() {
+            if row.node != node || since.is_some_and(|since| row.when < since) {
+                continue;
+            }
+
+            let mut entry = measurement_entry(row)?;
+            calibration.apply(&mut entry);
+
+            if !validation.temperature.contains(entry.temperature)
+                || !validation.humidity.contains(entry.humidity)
+                || entry
+                    .air_pressure
+                    .is_some_and(|air_pressure| !validation.air_pressure.contains(air_pressure))
+                || !validation.battery.contains(entry.battery)
+            {
+                skipped += 1;
+                continue;
+            }
+
+            row.raw_temperature = Some(entry.raw.temperature);
+            row.raw_humidity = Some(entry.raw.humidity.into());
+            row.raw_air_pressure = entry.raw.air_pressure.map(i16::try_from).transpose()?;
+            row.raw_battery = Some(entry.raw.battery);
+            row.temperature = entry.temperature;
+            row.humidity = entry.humidity.into();
+            row.air_pressure = entry.air_pressure.map(i16::try_from).transpose()?;
+            row.battery = entry.battery;
+            updated += 1;
+        }
+
+        Ok((updated, skipped))
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::import_measurements()",
+        level = "debug",
+        skip(self, measurements),
+        fields(count = measurements.len()),
+        err,
+        ret
+    )]
+    async fn import_measurements(
+        &self,
+        node: NodeId,
+        measurements: &[MeasurementEntry],
+        skip_duplicates: bool,
+        dry_run: bool,
+    ) -> Result<u64, Error> {
+        let mut state = self.state();
+        state.check_node(node)?;
+
+        // Everything is checked before anything is stored, like in a transaction
+        let mut taken: BTreeSet<i64> = state
+            .measurements
+            .rows
+            .values()
+            .filter(|row| row.node == node)
+            .map(|row| row.when.timestamp())
+            .collect();
+        let mut rows = Vec::with_capacity(measurements.len());
+
+        for measurement in measurements {
+            let when = measurement.when.timestamp();
+
+            if !taken.insert(when) && skip_duplicates {
+                continue;
+            }
+
+            let mut row = measurement_row(node, super::timestamp(when), measurement)?;
+            row.flags = 0;
+            rows.push(row);
+        }
+
+        let inserted = rows.len() as u64;
+
+        if !dry_run {
+            for row in rows {
+                state.measurements.insert(|id| MeasurementRow { id, ..row });
+            }
+        }
+
+        Ok(inserted)
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::refresh_rollups()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn refresh_rollups(
+        &self,
+        node: Option<NodeId>,
+        from: Option<DateTime<Utc>>,
+    ) -> Result<u64, Error> {
+        let mut state = self.state();
+        let from = from.map(|from| from.timestamp());
+        let mut updated = 0;
+
+        for period in [HOUR, DAY] {
+            let mut buckets: BTreeMap<(NodeId, i64), Vec<&MeasurementRow>> = BTreeMap::new();
+
+            for row in state.measurements.rows.values() {
+                let bucket = row.when.timestamp().div_euclid(period) * period;
+
+                // Whole buckets are recomputed
+                if node.is_none_or(|node| row.node == node)
+                    && from.is_none_or(|from| bucket >= from.div_euclid(period) * period)
+                {
+                    buckets.entry((row.node, bucket)).or_default().push(row);
+                }
+            }
+
+            let rollups = buckets
+                .into_iter()
+                .map(|((node, bucket), rows)| {
+                    aggregate_entry(node, bucket, &rows).and_then(rollup_row)
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let table = if period == HOUR {
+                &mut state.rollups_hourly
+            } else {
+                &mut state.rollups_daily
+            };
+
+            updated += rollups.len() as u64;
+            table.extend(rollups.into_iter().map(|row| ((row.node, row.bucket), row)));
+        }
+
+        Ok(updated)
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::quarantine_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn quarantine_measurements(
+        &self,
+        node: NodeId,
+        measurement: &MeasurementEntry,
+        reason: &str,
+    ) -> Result<(), Error> {
+        let mut state = self.state();
+        state.check_node(node)?;
+
+        state.quarantine.insert(|id| QuarantineRow {
+            id,
+            node,
+            when: now(),
+            temperature: measurement.temperature,
+            humidity: measurement.humidity.into(),
+            air_pressure: measurement.air_pressure.map(i32::from),
+            cpu_temp: measurement.cpu_temp,
+            battery: measurement.battery,
+            wifi_ssid: measurement.wifi_ssid.clone(),
+            wifi_rssi: measurement.wifi_rssi.into(),
+            reason: reason.to_string(),
+        });
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::get_last_measurement()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_last_measurement(&self, node: NodeId) -> Result<Option<MeasurementEntry>, Error> {
+        self.state()
+            .node_measurements(node)
+            .last()
+            .map(|row| measurement_entry(row))
+            .transpose()
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::get_latest_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_latest_measurements(
+        &self,
+        max_age: Duration,
+    ) -> Result<Vec<(NodeId, MeasurementEntry)>, Error> {
+        let since = ago(max_age);
+        let state = self.state();
+        let mut latest: BTreeMap<NodeId, &MeasurementRow> = BTreeMap::new();
+
+        for row in state.measurements.rows.values() {
+            if row.when < since {
+                continue;
+            }
+
+            let newer = latest
+                .get(&row.node)
+                .is_none_or(|other| (row.when, row.id) > (other.when, other.id));
+            if newer {
+                latest.insert(row.node, row);
+            }
+        }
+
+        latest
+            .into_iter()
+            .map(|(node, row)| Ok((node, measurement_entry(row)?)))
+            .collect()
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::get_recent_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_recent_measurements(
+        &self,
+        node: NodeId,
+        count: u32,
+    ) -> Result<Vec<MeasurementEntry>, Error> {
+        self.state()
+            .node_measurements(node)
+            .into_iter()
+            .rev()
+            .take(count.try_into()?)
+            .map(measurement_entry)
+            .collect()
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::stream_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn stream_measurements(
+        &self,
+        range: MeasurementRange,
+    ) -> Result<MeasurementStream<'_>, Error> {
+        let state = self.state();
+        let mut rows: Vec<_> = state
+            .measurements
+            .rows
+            .values()
+            .filter(|row| range.contains(row))
+            .collect();
+        rows.sort_by_key(|row| (row.when, row.node, row.id));
+
+        let results: Vec<_> = rows
+            .into_iter()
+            .map(|row| Ok((row.node, measurement_entry(row)?)))
+            .collect();
+
+        Ok(stream::iter(results).boxed())
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::stream_aggregates()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn stream_aggregates(
+        &self,
+        range: MeasurementRange,
+        period: Duration,
+    ) -> Result<AggregateStream<'_>, Error> {
+        let period: i64 = period.as_secs().try_into()?;
+        let state = self.state();
+        let mut buckets: BTreeMap<(i64, NodeId), Vec<&MeasurementRow>> = BTreeMap::new();
+
+        for row in state.measurements.rows.values() {
+            if range.contains(row) {
+                let bucket = row.when.timestamp().div_euclid(period) * period;
+                buckets.entry((bucket, row.node)).or_default().push(row);
+            }
+        }
+
+        let results: Vec<_> = buckets
+            .into_iter()
+            .map(|((bucket, node), rows)| aggregate_entry(node, bucket, &rows))
+            .collect();
+
+        Ok(stream::iter(results).boxed())
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::get_battery_history()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_battery_history(
+        &self,
+        node: NodeId,
+        window: Duration,
+        limit: u32,
+    ) -> Result<Vec<BatterySample>, Error> {
+        let since = ago(window);
+
+        let mut results: Vec<_> = self
+            .state()
+            .node_measurements(node)
+            .into_iter()
+            .filter(|row| row.when >= since)
+            .map(|row| BatterySample {
+                when: row.when,
+                voltage: row.battery,
+            })
+            .collect();
+        let excess = results.len().saturating_sub(limit as usize);
+        results.drain(..excess);
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::get_wifi_history()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_wifi_history(
+        &self,
+        node: NodeId,
+        window: Duration,
+    ) -> Result<Vec<WifiSample>, Error> {
+        let since = ago(window);
+
+        self.state()
+            .node_measurements(node)
+            .into_iter()
+            .filter(|row| row.when >= since)
+            .map(|row| {
+                Ok(WifiSample {
+                    when: row.when,
+                    ssid: row.wifi_ssid.clone(),
+                    rssi: row.wifi_rssi.try_into()?,
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::get_alert_states()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_alert_states(&self, node: NodeId) -> Result<Vec<AlertStateEntry>, Error> {
+        self.state()
+            .alert_states
+            .values()
+            .filter(|row| row.node == node)
+            .map(|row| {
+                Ok(AlertStateEntry {
+                    rule: row.rule.clone(),
+                    active: row.active,
+                    streak: row.streak.try_into()?,
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::set_alert_state()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn set_alert_state(
+        &self,
+        node: NodeId,
+        rule: &str,
+        active: bool,
+        streak: u32,
+    ) -> Result<(), Error> {
+        let mut state = self.state();
+        state.check_node(node)?;
+
+        let streak = streak.try_into()?;
+        let changed = match state.alert_states.get(&(node, rule.to_string())) {
+            Some(row) if row.active == active => row.changed,
+            _ => now(),
+        };
+
+        state.alert_states.insert(
+            (node, rule.to_string()),
+            AlertStateRow {
+                node,
+                rule: rule.to_string(),
+                active,
+                streak,
+                changed,
+            },
+        );
+        Ok(())
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        // There's no schema to change, so only the versions of the migrations are recorded
+        migrations::POSTGRES
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::get_applied_migrations()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_applied_migrations(&self) -> Result<Vec<AppliedMigration>, Error> {
+        Ok(self.state().migrations.clone())
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::apply_migration()",
+        level = "debug",
+        skip(self, migration),
+        fields(version = migration.version),
+        err
+    )]
+    async fn apply_migration(&self, migration: &Migration) -> Result<(), Error> {
+        self.state().record_migration(migration);
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::check_os_update()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn check_os_update(
+        &self,
+        node: NodeId,
+        current_ver: Version,
+    ) -> Result<Option<(Version, FirmwareBlob)>, Error> {
+        let current = current_ver.to_signed_triple();
+        let state = self.state();
+
+        // Newest firmware the node may get, that is newer than its current one and that it hasn't tried yet
+        let result = state
+            .firmwares
+            .rows
+            .values()
+            .filter(|fw| {
+                fw.restrict_nodes
+                    .as_ref()
+                    .is_none_or(|nodes| nodes.contains(&node))
+            })
+            .filter(|fw| fw.version() > current)
+            .filter(|fw| {
+                !state
+                    .firmware_stats
+                    .rows
+                    .values()
+                    .any(|stat| stat.node == node && stat.to_version() == fw.version())
+            })
+            .max_by_key(|fw| fw.version());
+
+        match result {
+            Some(fw) => {
+                let new_version = Version::new(
+                    fw.version_major.try_into()?,
+                    fw.version_middle.try_into()?,
+                    fw.version_minor.try_into()?,
+                );
+                Ok(Some((new_version, fw.firmware.clone().into_boxed_slice())))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::send_os_update_stat()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn send_os_update_stat(
+        &self,
+        node_id: NodeId,
+        old_ver: Version,
+        new_ver: Version,
+    ) -> Result<UpdateStatId, Error> {
+        let (from_version_major, from_version_middle, from_version_minor) =
+            old_ver.to_signed_triple();
+        let (to_version_major, to_version_middle, to_version_minor) = new_ver.to_signed_triple();
+
+        let mut state = self.state();
+        state.check_node(node_id)?;
+
+        Ok(state.firmware_stats.insert(|id| FirmwareStatRow {
+            id,
+            node: node_id,
+            from_version_major,
+            from_version_middle,
+            from_version_minor,
+            to_version_major,
+            to_version_middle,
+            to_version_minor,
+            when: now(),
+            success: None,
+        }))
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::mark_os_update_stat()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn mark_os_update_stat(&self, node_id: NodeId, success: bool) -> Result<(), Error> {
+        let mut state = self.state();
+        let Some(stat) = state
+            .firmware_stats
+            .rows
+            .values_mut()
+            .filter(|stat| stat.node == node_id && stat.success.is_none())
+            .max_by_key(|stat| (stat.when, stat.id))
+        else {
+            tracing::error!("Node {node_id} did not pull the entire firmware blob");
+            return Err(Error::InvalidRequest);
+        };
+
+        stat.success = Some(success);
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "MemoryClient::erase()", level = "debug", skip(self), err)]
+    async fn erase(&self, options: EraseOptions) -> Result<(), Error> {
+        let mut state = self.state();
+
+        match options {
+            EraseOptions::Everything => *state = State::default(),
+            EraseOptions::ContentOnly { keep_devices } => {
+                if !keep_devices {
+                    state.devices.clear();
+                    state.settings.clear();
+                    state.calibrations.clear();
+                }
+
+                state.measurements.clear();
+                state.notifications.clear();
+                state.firmwares.clear();
+                state.firmware_stats.clear();
+                state.quarantine.clear();
+                state.alert_states.clear();
+                state.rollups_hourly.clear();
+                state.rollups_daily.clear();
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "MemoryClient::prune()", level = "debug", skip(self), err, ret)]
+    async fn prune(
+        &self,
+        table: PrunableTable,
+        older_than: Duration,
+        limit: u32,
+    ) -> Result<u64, Error> {
+        let cutoff = ago(older_than);
+        let mut state = self.state();
+
+        let pruned = match table {
+            PrunableTable::Measurements => state.measurements.prune(cutoff, limit),
+            PrunableTable::Notifications => state.notifications.prune(cutoff, limit),
+            PrunableTable::FirmwareStats => state.firmware_stats.prune(cutoff, limit),
+            PrunableTable::Quarantine => state.quarantine.prune(cutoff, limit),
+        };
+
+        Ok(pruned)
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::count_prunable()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn count_prunable(
+        &self,
+        table: PrunableTable,
+        older_than: Duration,
+    ) -> Result<u64, Error> {
+        let cutoff = ago(older_than);
+        let state = self.state();
+
+        let count = match table {
+            PrunableTable::Measurements => state.measurements.count_older(cutoff),
+            PrunableTable::Notifications => state.notifications.count_older(cutoff),
+            PrunableTable::FirmwareStats => state.firmware_stats.count_older(cutoff),
+            PrunableTable::Quarantine => state.quarantine.count_older(cutoff),
+        };
+
+        Ok(count)
+    }
+
+    #[tracing::instrument(name = "MemoryClient::compact()", level = "debug", skip(self), err)]
+    async fn compact(&self, _full: bool) -> Result<(), Error> {
+        // Deleted rows are freed right away
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::get_table_state()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn get_table_state(&self, table: TransferTable) -> Result<TableState, Error> {
+        let state = self.state();
+
+        let result = match table {
+            TransferTable::Devices => state.devices.state(),
+            TransferTable::Settings => state.settings.state(),
+            TransferTable::Measurements => state.measurements.state(),
+            TransferTable::Notifications => state.notifications.state(),
+            TransferTable::Firmwares => state.firmwares.state(),
+            TransferTable::FirmwareStats => state.firmware_stats.state(),
+            TransferTable::Quarantine => state.quarantine.state(),
+            TransferTable::Calibrations => keyed_state(&state.calibrations),
+            TransferTable::AlertStates => keyed_state(&state.alert_states),
+            TransferTable::RollupsHourly => keyed_state(&state.rollups_hourly),
+            TransferTable::RollupsDaily => keyed_state(&state.rollups_daily),
+        };
+
+        Ok(result)
+    }
+
+    #[tracing::instrument(name = "MemoryClient::read_rows()", level = "debug", skip(self), err)]
+    async fn read_rows(
+        &self,
+        table: TransferTable,
+        after: i32,
+        limit: u32,
+    ) -> Result<Vec<TransferRow>, Error> {
+        let state = self.state();
+        let limit = limit.try_into()?;
+
+        let results = match table {
+            TransferTable::Devices => state.devices.read(after, limit, TransferRow::Device),
+            TransferTable::Settings => state.settings.read(after, limit, TransferRow::Settings),
+            TransferTable::Measurements => {
+                state
+                    .measurements
+                    .read(after, limit, TransferRow::Measurement)
+            }
+            TransferTable::Notifications => {
+                state
+                    .notifications
+                    .read(after, limit, TransferRow::Notification)
+            }
+            TransferTable::Firmwares => state.firmwares.read(after, limit, TransferRow::Firmware),
+            TransferTable::FirmwareStats => {
+                state
+                    .firmware_stats
+                    .read(after, limit, TransferRow::FirmwareStat)
+            }
+            TransferTable::Quarantine => {
+                state.quarantine.read(after, limit, TransferRow::Quarantine)
+            }
+            TransferTable::Calibrations => {
+                let skip = after.try_into()?;
+
+                state
+                    .calibrations
+                    .iter()
+                    .skip(skip)
+                    .take(limit)
+                    .map(|(&node, calibration)| {
+                        TransferRow::Calibration(calibration_row(node, calibration))
+                    })
+                    .collect()
+            }
+            TransferTable::AlertStates => {
+                read_keyed(&state.alert_states, after, limit, TransferRow::AlertState)?
+            }
+            TransferTable::RollupsHourly => read_keyed(
+                &state.rollups_hourly,
+                after,
+                limit,
+                TransferRow::RollupHourly,
+            )?,
+            TransferTable::RollupsDaily => {
+                read_keyed(&state.rollups_daily, after, limit, TransferRow::RollupDaily)?
+            }
+        };
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::write_rows()",
+        level = "debug",
+        skip(self, rows),
+        err
+    )]
+    async fn write_rows(&self, rows: &[TransferRow]) -> Result<(), Error> {
+        let mut state = self.state();
+
+        // Everything is checked before anything is stored, like in a transaction
+        let mut previous = None;
+        for row in rows {
+            if previous.is_some_and(|previous| row.id() <= Some(previous)) {
+                return Err(Error::Constraint(format!(
+                    "rows of {} are not by ascending ID",
+                    row.table().as_str()
+                )));
+            }
+            previous = row.id();
+
+            state.check_row(row)?;
+        }
+
+        for row in rows {
+            match row.clone() {
+                TransferRow::Device(row) => state.devices.insert_with_id(row.id, row),
+                TransferRow::Settings(row) => state.settings.insert_with_id(row.id, row),
+                TransferRow::Measurement(row) => state.measurements.insert_with_id(row.id, row),
+                TransferRow::Notification(row) => state.notifications.insert_with_id(row.id, row),
+                TransferRow::Firmware(row) => state.firmwares.insert_with_id(row.id, row),
+                TransferRow::FirmwareStat(row) => state.firmware_stats.insert_with_id(row.id, row),
+                TransferRow::Quarantine(row) => state.quarantine.insert_with_id(row.id, row),
+                TransferRow::Calibration(row) => {
+                    state.calibrations.insert(
+                        row.node,
+                        Calibration {
+                            temperature_offset: row.temperature_offset,
+                            temperature_scale: row.temperature_scale,
+                            humidity_offset: row.humidity_offset,
+                            air_pressure_offset: row.air_pressure_offset,
+                            battery_scale: row.battery_scale,
+                        },
+                    );
+                }
+                TransferRow::AlertState(row) => {
+                    state.alert_states.insert((row.node, row.rule.clone()), row);
+                }
+                TransferRow::RollupHourly(row) => {
+                    state.rollups_hourly.insert((row.node, row.bucket), row);
+                }
+                TransferRow::RollupDaily(row) => {
+                    state.rollups_daily.insert((row.node, row.bucket), row);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::get_firmwares()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_firmwares(&self) -> Result<Vec<FirmwareEntry>, Error> {
+        self.state()
+            .firmwares
+            .rows
+            .values()
+            .map(|row| {
+                Ok(FirmwareEntry {
+                    id: row.id,
+                    version: Version::new(
+                        row.version_major.try_into()?,
+                        row.version_middle.try_into()?,
+                        row.version_minor.try_into()?,
+                    ),
+                    size: row.firmware.len().try_into()?,
+                    blob: row.firmware.clone(),
+                    added: row.added_date.format("%d.%m.%Y %H:%M:%S").to_string(),
+                    restrict: row.restrict_nodes.clone(),
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(
+        name = "MemoryClient::upload_firmware()",
+        level = "debug",
+        skip(self, blob),
+        err
+    )]
+    async fn upload_firmware(
+        &self,
+        blob: Vec<u8>,
+        version: Version,
+        restrict_nodes: Option<Vec<NodeId>>,
+    ) -> Result<(), Error> {
+        self.state().firmwares.insert(|id| FirmwareRow {
+            id,
+            version_major: version.major().into(),
+            version_middle: version.middle().into(),
+            version_minor: version.minor().into(),
+            firmware: blob,
+            added_date: now(),
+            restrict_nodes,
+        });
+        Ok(())
+    }
+}
+
+impl State {
+    fn record_migration(&mut self, migration: &Migration) {
+        self.migrations.push(AppliedMigration {
+            version: migration.version,
+            name: migration.name.to_string(),
+            applied: Some(now()),
+        });
+    }
+
+    fn device_by_mac(&self, mac: &str) -> Option<NodeId> {
+        self.devices
+            .rows
+            .values()
+            .find(|row| row.mac_address == mac)
+            .map(|row| row.id)
+    }
+
+    /// Make sure a referenced node exists.
+    fn check_node(&self, node: NodeId) -> Result<(), Error> {
+        if self.devices.rows.contains_key(&node) {
+            Ok(())
+        } else {
+            Err(Error::Constraint(format!("node {node} doesn't exist")))
+        }
+    }
+
+    /// Make sure a row can be inserted with its ID.
+    fn check_row(&self, row: &TransferRow) -> Result<(), Error> {
+        let exists = match row {
+            TransferRow::Device(row) => {
+                if self.device_by_mac(&row.mac_address).is_some() {
+                    return Err(Error::Constraint(format!(
+                        "device {} exists already",
+                        row.mac_address
+                    )));
+                }
+
+                self.devices.rows.contains_key(&row.id)
+            }
+            TransferRow::Settings(row) => {
+                self.check_node(row.node)?;
+
+                if self
+                    .settings
+                    .rows
+                    .values()
+                    .any(|other| other.node == row.node)
+                {
+                    return Err(Error::Constraint(format!(
+                        "node {} has settings already",
+                        row.node
+                    )));
+                }
+
+                self.settings.rows.contains_key(&row.id)
+            }
+            TransferRow::Measurement(row) => {
+                self.check_node(row.node)?;
+                self.measurements.rows.contains_key(&row.id)
+            }
+            TransferRow::Notification(row) => {
+                self.check_node(row.node)?;
+                self.notifications.rows.contains_key(&row.id)
+            }
+            TransferRow::Firmware(row) => self.firmwares.rows.contains_key(&row.id),
+            TransferRow::FirmwareStat(row) => {
+                self.check_node(row.node)?;
+                self.firmware_stats.rows.contains_key(&row.id)
+            }
+            TransferRow::Quarantine(row) => {
+                self.check_node(row.node)?;
+                self.quarantine.rows.contains_key(&row.id)
+            }
+            // Rows without an ID replace the existing ones
+            TransferRow::Calibration(CalibrationRow { node, .. })
+            | TransferRow::AlertState(AlertStateRow { node, .. })
+            | TransferRow::RollupHourly(RollupRow { node, .. })
+            | TransferRow::RollupDaily(RollupRow { node, .. }) => {
+                self.check_node(*node)?;
+                false
+            }
+        };
+
+        if exists {
+            return Err(Error::Constraint(format!(
+                "row {} of {} exists already",
+                row.id().unwrap_or_default(),
+                row.table().as_str()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Measurements of a node, oldest first.
+    fn node_measurements(&self, node: NodeId) -> Vec<&MeasurementRow> {
+        let mut rows: Vec<_> = self
+            .measurements
+            .rows
+            .values()
+            .filter(|row| row.node == node)
+            .collect();
+        rows.sort_by_key(|row| (row.when, row.id));
+
+        rows
+    }
+}
+
+impl<T> Default for Table<T> {
+    fn default() -> Self {
+        Self {
+            rows: BTreeMap::new(),
+            sequence: 0,
+        }
+    }
+}
+
+impl<T> Table<T> {
+    /// Insert a row with the next ID of the sequence, which is returned.
+    fn insert(&mut self, row: impl FnOnce(i32) -> T) -> i32 {
+        self.sequence += 1;
+        self.rows.insert(self.sequence, row(self.sequence));
+
+        self.sequence
+    }
+
+    /// Insert a row with its own ID, advancing the sequence past it.
+    fn insert_with_id(&mut self, id: i32, row: T) {
+        self.sequence = self.sequence.max(id);
+        self.rows.insert(id, row);
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn state(&self) -> TableState {
+        TableState {
+            rows: self.rows.len() as u64,
+            last_id: self.rows.last_key_value().map(|(&id, _)| id),
+        }
+    }
+}
+
+impl<T: Clone> Table<T> {
+    fn read(&self, after: i32, limit: usize, wrap: fn(T) -> TransferRow) -> Vec<TransferRow> {
+        self.rows
+            .range(after.saturating_add(1)..)
+            .take(limit)
+            .map(|(_, row)| wrap(row.clone()))
+            .collect()
+    }
+}
+
+impl<T: Timestamped> Table<T> {
+    /// Delete up to `limit` rows created before `cutoff`. Returns the number of deleted rows.
+    fn prune(&mut self, cutoff: DateTime<Utc>, limit: u32) -> u64 {
+        let ids: Vec<_> = self
+            .rows
+            .iter()
+            .filter(|(_, row)| row.when() < cutoff)
+            .map(|(&id, _)| id)
+            .take(limit.try_into().unwrap_or(usize::MAX))
+            .collect();
+
+        for id in &ids {
+            self.rows.remove(id);
+        }
+
+        ids.len() as u64
+    }
+
+    fn count_older(&self, cutoff: DateTime<Utc>) -> u64 {
+        self.rows.values().filter(|row| row.when() < cutoff).count() as u64
+    }
+}
+
+impl Timestamped for MeasurementRow {
+    fn when(&self) -> DateTime<Utc> {
+        self.when
+    }
+}
+
+impl Timestamped for NotificationRow {
+    fn when(&self) -> DateTime<Utc> {
+        self.when
+    }
+}
+
+impl Timestamped for FirmwareStatRow {
+    fn when(&self) -> DateTime<Utc> {
+        self.when
+    }
+}
+
+impl Timestamped for QuarantineRow {
+    fn when(&self) -> DateTime<Utc> {
+        self.when
+    }
+}
+
+impl FirmwareRow {
+    const fn version(&self) -> (i16, i16, i16) {
+        (self.version_major, self.version_middle, self.version_minor)
+    }
+}
+
+impl FirmwareStatRow {
+    const fn to_version(&self) -> (i16, i16, i16) {
+        (
+            self.to_version_major,
+            self.to_version_middle,
+            self.to_version_minor,
+        )
+    }
+}
+
+impl MeasurementRange {
+    fn contains(&self, row: &MeasurementRow) -> bool {
+        self.node.is_none_or(|node| row.node == node)
+            && self.from.is_none_or(|from| row.when >= from)
+            && self.to.is_none_or(|to| row.when < to)
+    }
+}
+
+/// Current time, with the precision of the stored timestamps.
+fn now() -> DateTime<Utc> {
+    super::timestamp(Utc::now().timestamp())
+}
+
+/// Time the given age ago.
+fn ago(age: Duration) -> DateTime<Utc> {
+    TimeDelta::from_std(age)
+        .ok()
+        .and_then(|age| now().checked_sub_signed(age))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+/// Row of a measurement, without raw readings.
+fn measurement_row(
+    node: NodeId,
+    when: DateTime<Utc>,
+    measurement: &MeasurementEntry,
+) -> Result<MeasurementRow, Error> {
+    Ok(MeasurementRow {
+        id: 0,
+        node,
+        when,
+        temperature: measurement.temperature,
+        humidity: measurement.humidity.into(),
+        air_pressure: measurement.air_pressure.map(i16::try_from).transpose()?,
+        cpu_temp: measurement.cpu_temp,
+        battery: measurement.battery,
+        wifi_ssid: measurement.wifi_ssid.clone(),
+        wifi_rssi: measurement.wifi_rssi.into(),
+        flags: measurement.flags.bits().into(),
+        raw_temperature: None,
+        raw_humidity: None,
+        raw_air_pressure: None,
+        raw_battery: None,
+    })
+}
+
+fn measurement_entry(row: &MeasurementRow) -> Result<MeasurementEntry, Error> {
+    let air_pressure = |value: Option<i16>| value.map(AirPressure::try_from).transpose();
+
+    Ok(MeasurementEntry {
+        when: row.when,
+        temperature: row.temperature,
+        humidity: row.humidity.try_into()?,
+        air_pressure: air_pressure(row.air_pressure)?,
+        cpu_temp: row.cpu_temp,
+        battery: row.battery,
+        wifi_ssid: row.wifi_ssid.clone(),
+        wifi_rssi: row.wifi_rssi.try_into()?,
+        flags: SensorFlags::from_bits(row.flags.try_into()?),
+        raw: RawReadings {
+            temperature: row.raw_temperature.unwrap_or(row.temperature),
+            humidity: row.raw_humidity.unwrap_or(row.humidity).try_into()?,
+            air_pressure: air_pressure(row.raw_air_pressure.or(row.air_pressure))?,
+            battery: row.raw_battery.unwrap_or(row.battery),
+        },
+    })
+}
+
+fn aggregate_entry(
+    node: NodeId,
+    bucket: i64,
+    rows: &[&MeasurementRow],
+) -> Result<AggregateEntry, Error> {
+    // Every bucket has at least one measurement
+    let summary =
+        |value: fn(&MeasurementRow) -> f64| summarize(rows.iter().map(|row| value(row))).unwrap();
+
+    Ok(AggregateEntry {
+        node,
+        bucket: super::timestamp(bucket),
+        samples: rows.len().try_into()?,
+        temperature: summary(|row| row.temperature.into()),
+        humidity: summary(|row| row.humidity.into()),
+        air_pressure: summarize(
+            rows.iter()
+                .filter_map(|row| row.air_pressure.map(f64::from)),
+        ),
+        battery: summary(|row| row.battery.into()),
+        wifi_rssi: summary(|row| row.wifi_rssi.into()),
+    })
+}
+
+#[allow(clippy::cast_possible_truncation)] // extremes of integer columns are within their range
+fn rollup_row(entry: AggregateEntry) -> Result<RollupRow, Error> {
+    Ok(RollupRow {
+        node: entry.node,
+        bucket: entry.bucket,
+        samples: entry.samples.try_into()?,
+        temperature_min: entry.temperature.min as f32,
+        temperature_max: entry.temperature.max as f32,
+        temperature_avg: entry.temperature.avg as f32,
+        humidity_min: entry.humidity.min as i16,
+        humidity_max: entry.humidity.max as i16,
+        humidity_avg: entry.humidity.avg as f32,
+        air_pressure_min: entry.air_pressure.map(|summary| summary.min as i16),
+        air_pressure_max: entry.air_pressure.map(|summary| summary.max as i16),
+        air_pressure_avg: entry.air_pressure.map(|summary| summary.avg as f32),
+        battery_min: entry.battery.min as f32,
+        battery_max: entry.battery.max as f32,
+        battery_avg: entry.battery.avg as f32,
+        rssi_min: entry.wifi_rssi.min as i16,
+        rssi_max: entry.wifi_rssi.max as i16,
+        rssi_avg: entry.wifi_rssi.avg as f32,
+    })
+}
+
+const fn calibration_row(node: NodeId, calibration: &Calibration) -> CalibrationRow {
+    CalibrationRow {
+        node,
+        temperature_offset: calibration.temperature_offset,
+        temperature_scale: calibration.temperature_scale,
+        humidity_offset: calibration.humidity_offset,
+        air_pressure_offset: calibration.air_pressure_offset,
+        battery_scale: calibration.battery_scale,
+    }
+}
+
+/// State of a table without an ID.
+fn keyed_state<K, V>(rows: &BTreeMap<K, V>) -> TableState {
+    TableState {
+        rows: rows.len() as u64,
+        last_id: None,
+    }
+}
+
+/// Read rows of a table without an ID by their key, skipping the first `after` of them.
+fn read_keyed<K, V: Clone>(
+    rows: &BTreeMap<K, V>,
+    after: i32,
+    limit: usize,
+    wrap: fn(V) -> TransferRow,
+) -> Result<Vec<TransferRow>, Error> {
+    Ok(rows
+        .values()
+        .skip(after.try_into()?)
+        .take(limit)
+        .map(|row| wrap(row.clone()))
+        .collect())
+}
+
+/// Minimum, maximum and average of the values, if there are any.
+fn summarize(values: impl Iterator<Item = f64>) -> Option<Summary> {
+    let (mut count, mut sum) = (0_u32, 0.0);
+    let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
+
+    for value in values {
+        count += 1;
+        sum += value;
+        min = min.min(value);
+        max = max.max(value);
+    }
+
+    (count > 0).then(|| Summary {
+        min,
+        max,
+        avg: sum / f64::from(count),
+    })
+}
+
+fn device_entry(row: &DeviceRow) -> DeviceEntry {
+    DeviceEntry {
+        id: row.id,
+        mac: row.mac_address.clone(),
+        note: row.note.clone(),
+        altitude: row.altitude,
+    }
+}
+
+/// Unknown severities rank like [`Severity::Info`], as in the SQL backends.
+fn severity_level(severity: &str) -> i16 {
+    Severity::parse(severity).map_or(0, Severity::level)
+}
+
+fn notification_entry(row: &NotificationRow) -> NotificationEntry {
+    NotificationEntry {
+        id: row.id,
+        node: row.node,
+        when: row.when,
+        content: row.content.clone(),
+        read: row.read,
+        severity: row.severity.clone(),
+        kind: row.kind.clone(),
+        title: row.title.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::db::{DatabaseBackend, backend_tests};
+
+    fn client() -> MemoryClient {
+        MemoryClient::new(&["AA:BB:CC:DD:EE:01".into(), "AA:BB:CC:DD:EE:02".into()]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn offers_newest_permitted_firmware() {
+        backend_tests::offers_newest_permitted_firmware(&client()).await;
+    }
+
+    #[tokio::test]
+    async fn skips_attempted_firmware() {
+        backend_tests::skips_attempted_firmware(&client()).await;
+    }
+
+    #[tokio::test]
+    async fn rejects_reports_without_download() {
+        let client = client();
+
+        assert!(matches!(
+            client.mark_os_update_stat(1, true).await,
+            Err(Error::InvalidRequest)
+        ));
+
+        client
+            .send_os_update_stat(1, Version::new(1, 0, 0), Version::new(2, 0, 0))
+            .await
+            .unwrap();
+        client.mark_os_update_stat(1, true).await.unwrap();
+
+        // Every download is reported once
+        assert!(matches!(
+            client.mark_os_update_stat(1, true).await,
+            Err(Error::InvalidRequest)
+        ));
+    }
+
+    #[tokio::test]
+    async fn erases_content_but_keeps_devices() {
+        backend_tests::erases_content_but_keeps_devices(&client()).await;
+    }
+
+    #[tokio::test]
+    async fn erases_content() {
+        backend_tests::erases_content(&client()).await;
+    }
+
+    #[tokio::test]
+    async fn erases_everything() {
+        backend_tests::erases_everything(&client()).await;
+    }
+}
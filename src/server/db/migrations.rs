@@ -0,0 +1,178 @@
+//! Versioned schema migrations.
+//!
+//! Every backend has the same migrations under the same versions, so a schema version means the same thing regardless of the backend.
+//! Migrations are never modified once released, schema changes are added as new migrations instead.
+
+use chrono::{DateTime, Utc};
+
+pub type SchemaVersion = i32;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: SchemaVersion,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: SchemaVersion,
+    pub name: String,
+    /// Not known for databases created before migrations were versioned.
+    pub applied: Option<DateTime<Utc>>,
+}
+
+/// Name of the first migration, which databases created before migrations were versioned are assumed to be at.
+pub const INITIAL: &str = "initial";
+
+/// Schema version this server requires.
+pub const SCHEMA_VERSION: SchemaVersion = POSTGRES[POSTGRES.len() - 1].version;
+
+pub const POSTGRES: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: INITIAL,
+        sql: include_str!("../../../queries/postgres/migrations/0001_initial.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "notification_metadata",
+        sql: include_str!("../../../queries/postgres/migrations/0002_notification_metadata.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "alert_states",
+        sql: include_str!("../../../queries/postgres/migrations/0003_alert_states.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "sensor_flags",
+        sql: include_str!("../../../queries/postgres/migrations/0004_sensor_flags.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "quarantine",
+        sql: include_str!("../../../queries/postgres/migrations/0005_quarantine.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "calibration",
+        sql: include_str!("../../../queries/postgres/migrations/0006_calibration.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "device_altitude",
+        sql: include_str!("../../../queries/postgres/migrations/0007_device_altitude.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "rollups",
+        sql: include_str!("../../../queries/postgres/migrations/0008_rollups.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "mute_expiry",
+        sql: include_str!("../../../queries/postgres/migrations/0009_mute_expiry.sql"),
+    },
+];
+
+pub const SQLITE: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: INITIAL,
+        sql: include_str!("../../../queries/sqlite/migrations/0001_initial.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "notification_metadata",
+        sql: include_str!("../../../queries/sqlite/migrations/0002_notification_metadata.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "alert_states",
+        sql: include_str!("../../../queries/sqlite/migrations/0003_alert_states.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "sensor_flags",
+        sql: include_str!("../../../queries/sqlite/migrations/0004_sensor_flags.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "quarantine",
+        sql: include_str!("../../../queries/sqlite/migrations/0005_quarantine.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "calibration",
+        sql: include_str!("../../../queries/sqlite/migrations/0006_calibration.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "device_altitude",
+        sql: include_str!("../../../queries/sqlite/migrations/0007_device_altitude.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "rollups",
+        sql: include_str!("../../../queries/sqlite/migrations/0008_rollups.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "mute_expiry",
+        sql: include_str!("../../../queries/sqlite/migrations/0009_mute_expiry.sql"),
+    },
+];
+
+/// DDL is committed implicitly, so unlike with the other backends a failed migration is not rolled back.
+pub const MYSQL: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: INITIAL,
+        sql: include_str!("../../../queries/mysql/migrations/0001_initial.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "notification_metadata",
+        sql: include_str!("../../../queries/mysql/migrations/0002_notification_metadata.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "alert_states",
+        sql: include_str!("../../../queries/mysql/migrations/0003_alert_states.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "sensor_flags",
+        sql: include_str!("../../../queries/mysql/migrations/0004_sensor_flags.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "quarantine",
+        sql: include_str!("../../../queries/mysql/migrations/0005_quarantine.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "calibration",
+        sql: include_str!("../../../queries/mysql/migrations/0006_calibration.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "device_altitude",
+        sql: include_str!("../../../queries/mysql/migrations/0007_device_altitude.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "rollups",
+        sql: include_str!("../../../queries/mysql/migrations/0008_rollups.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "mute_expiry",
+        sql: include_str!("../../../queries/mysql/migrations/0009_mute_expiry.sql"),
+    },
+];
+
+// All backends must be at the same version
+const _: () = assert!(SQLITE[SQLITE.len() - 1].version == SCHEMA_VERSION);
+const _: () = assert!(MYSQL[MYSQL.len() - 1].version == SCHEMA_VERSION);
@@ -1,10 +1,22 @@
 use crate::{
     error::Error,
     server::{
-        config::{Config, DatabaseConfig},
-        db::{postgres::PostgresClient, sqlite::SqliteClient},
+        calibration::Calibration,
+        config::{Config, DatabaseConfig, ValidationConfig},
+        db::{
+            memory::MemoryClient,
+            migrations::{AppliedMigration, Migration, SchemaVersion},
+            mysql::MysqlClient,
+            postgres::PostgresClient,
+            sqlite::SqliteClient,
+            transfer::{TableState, TransferRow, TransferTable},
+        },
+        diagnostics::SensorFlags,
+        notification_client::{Notification, Severity},
     },
 };
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
 use moka::future::Cache;
 use pwmp_client::pwmp_msg::{
     aliases::{AirPressure, BatteryVoltage, Humidity, Rssi, Temperature},
@@ -12,19 +24,32 @@ use pwmp_client::pwmp_msg::{
     settings::NodeSettings,
     version::Version,
 };
+use std::time::Duration;
 use tracing::debug;
 
+#[cfg(test)]
+mod backend_tests;
+mod memory;
+pub mod migrations;
+mod mysql;
 mod postgres;
 mod sqlite;
+pub mod transfer;
 
 pub type NodeId = i32;
 pub type MeasurementId = i32;
+pub type NotificationId = i32;
 pub type FirmwareBlob = Box<[u8]>;
 pub type UpdateStatId = i32;
 pub type SleepTime = i16;
 
+/// Measurements of a node, oldest first.
+pub type MeasurementStream<'a> = BoxStream<'a, Result<(NodeId, MeasurementEntry), Error>>;
+/// Summaries of measurements, oldest bucket first.
+pub type AggregateStream<'a> = BoxStream<'a, Result<AggregateEntry, Error>>;
+
 type NodeIdCache = Cache<Mac, Option<NodeId>>;
-type NodeSettingsCache = Cache<NodeId, Option<NodeSettings>>;
+type NodeSettingsCache = Cache<NodeId, Option<SettingsEntry>>;
 
 pub struct DatabaseClient {
     backend: Box<dyn DatabaseBackend>,
@@ -32,6 +57,15 @@ pub struct DatabaseClient {
     node_settings_cache: NodeSettingsCache,
 }
 
+/// Tables whose rows are pruned once they're older than their retention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrunableTable {
+    Measurements,
+    Notifications,
+    FirmwareStats,
+    Quarantine,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum EraseOptions {
     Everything,
@@ -48,28 +82,345 @@ pub struct FirmwareEntry {
     pub restrict: Option<Vec<NodeId>>,
 }
 
+#[derive(Debug, Clone)]
+pub struct DeviceEntry {
+    pub id: NodeId,
+    pub mac: String,
+    pub note: Option<String>,
+    /// Altitude above sea level in meters.
+    pub altitude: Option<f32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NotificationEntry {
+    pub id: NotificationId,
+    pub node: NodeId,
+    pub when: DateTime<Utc>,
+    pub content: String,
+    pub read: bool,
+    pub severity: String,
+    pub kind: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BatterySample {
+    pub when: DateTime<Utc>,
+    pub voltage: BatteryVoltage,
+}
+
+#[derive(Debug, Clone)]
+pub struct WifiSample {
+    pub when: DateTime<Utc>,
+    pub ssid: String,
+    pub rssi: Rssi,
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertStateEntry {
+    pub rule: String,
+    pub active: bool,
+    /// Number of consecutive readings that breached the rule's threshold.
+    pub streak: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct MeasurementEntry {
+    pub when: DateTime<Utc>,
+    pub temperature: Temperature,
+    pub humidity: Humidity,
+    pub air_pressure: Option<AirPressure>,
+    pub cpu_temp: Temperature,
+    pub battery: BatteryVoltage,
+    pub wifi_ssid: String,
+    pub wifi_rssi: Rssi,
+    pub flags: SensorFlags,
+    /// Readings as posted by the node, before calibration.
+    pub raw: RawReadings,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RawReadings {
+    pub temperature: Temperature,
+    pub humidity: Humidity,
+    pub air_pressure: Option<AirPressure>,
+    pub battery: BatteryVoltage,
+}
+
+/// Selection of measurements to export.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MeasurementRange {
+    /// Only measurements of this node.
+    pub node: Option<NodeId>,
+    /// Only measurements taken at or after this time.
+    pub from: Option<DateTime<Utc>>,
+    /// Only measurements taken before this time.
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Summary {
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+}
+
+/// Summary of the measurements of a node in a time bucket.
+#[derive(Debug, Clone, Copy)]
+pub struct AggregateEntry {
+    pub node: NodeId,
+    /// Start of the bucket.
+    pub bucket: DateTime<Utc>,
+    pub samples: i64,
+    pub temperature: Summary,
+    pub humidity: Summary,
+    /// Not available if none of the measurements in the bucket have an air pressure reading.
+    pub air_pressure: Option<Summary>,
+    pub battery: Summary,
+    pub wifi_rssi: Summary,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CalibrationEntry {
+    pub node: NodeId,
+    pub calibration: Calibration,
+}
+
+/// Stored settings of a node, with the expiry of a timed mute.
+#[derive(Debug, Clone, Copy)]
+pub struct SettingsEntry {
+    pub settings: NodeSettings,
+    pub muted_until: Option<DateTime<Utc>>,
+}
+
+impl SettingsEntry {
+    /// Settings at `now`, where an expired timed mute counts as unmuted.
+    pub fn at(self, now: DateTime<Utc>) -> NodeSettings {
+        NodeSettings {
+            mute_notifications: self.settings.mute_notifications
+                && self.muted_until.is_none_or(|until| until > now),
+            ..self.settings
+        }
+    }
+}
+
 #[async_trait::async_trait]
 pub trait DatabaseBackend: Send + Sync {
     async fn authorize_device(&self, mac: &Mac) -> Result<Option<NodeId>, Error>;
 
-    async fn create_notification(&self, node_id: NodeId, content: &str) -> Result<(), Error>;
+    async fn get_devices(&self) -> Result<Vec<DeviceEntry>, Error>;
+
+    async fn get_device(&self, node: NodeId) -> Result<Option<DeviceEntry>, Error>;
+
+    /// Set the altitude of a node, used to reduce its air pressure to sea level. Returns `false` if the node doesn't exist.
+    async fn set_device_altitude(&self, node: NodeId, altitude: Option<f32>)
+    -> Result<bool, Error>;
+
+    async fn create_notification(&self, notification: &Notification) -> Result<(), Error>;
 
-    async fn get_settings(&self, node_id: NodeId) -> Result<Option<NodeSettings>, Error>;
+    /// Get notifications, newest first. `since` only includes notifications younger than the given age.
+    async fn get_notifications(
+        &self,
+        unread_only: bool,
+        node: Option<NodeId>,
+        since: Option<Duration>,
+    ) -> Result<Vec<NotificationEntry>, Error>;
+
+    /// Mark a notification, or all of them if `id` is `None`, as read. Returns the number of changed notifications.
+    async fn mark_notifications_read(&self, id: Option<NotificationId>) -> Result<u64, Error>;
+
+    /// Delete notifications older than the given age. Returns the number of deleted notifications.
+    async fn purge_notifications(&self, older_than: Duration) -> Result<u64, Error>;
+
+    /// Get unread notifications of at least `min_severity` that have been created or last escalated at least `after` ago,
+    /// and have been escalated less than `max_escalations` times.
+    async fn get_unacknowledged_notifications(
+        &self,
+        after: Duration,
+        max_escalations: u32,
+        min_severity: Severity,
+    ) -> Result<Vec<NotificationEntry>, Error>;
+
+    /// Record that a notification has been escalated.
+    async fn mark_notification_escalated(&self, id: NotificationId) -> Result<(), Error>;
+
+    async fn get_settings_entry(&self, node_id: NodeId) -> Result<Option<SettingsEntry>, Error>;
+
+    /// Get the current settings of a node.
+    async fn get_settings(&self, node_id: NodeId) -> Result<Option<NodeSettings>, Error> {
+        Ok(self
+            .get_settings_entry(node_id)
+            .await?
+            .map(|entry| entry.at(Utc::now())))
+    }
+
+    /// Set the `mute_notifications` setting of a node, optionally only `until` the given time.
+    /// Returns `false` if the node has no settings.
+    async fn set_notifications_muted(
+        &self,
+        node_id: NodeId,
+        muted: bool,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<bool, Error>;
 
-    #[allow(clippy::too_many_arguments)]
     async fn post_measurements(
         &self,
         node: NodeId,
-        temp: Temperature,
-        hum: Humidity,
-        air_p: Option<AirPressure>,
-        cpu_temp: Temperature,
-        battery: BatteryVoltage,
-        wifi_ssid: &str,
-        wifi_rssi: Rssi,
+        measurement: &MeasurementEntry,
+    ) -> Result<(), Error>;
+
+    async fn get_calibration(&self, node: NodeId) -> Result<Option<Calibration>, Error>;
+
+    async fn get_calibrations(&self) -> Result<Vec<CalibrationEntry>, Error>;
+
+    async fn set_calibration(&self, node: NodeId, calibration: &Calibration) -> Result<(), Error>;
+
+    /// Remove the calibration of a node. Returns `false` if it had none.
+    async fn delete_calibration(&self, node: NodeId) -> Result<bool, Error>;
+
+    /// Recompute stored measurements of a node from their raw readings, optionally only those younger than `since`.
+    ///
+    /// Measurements whose recomputed temperature, humidity, air pressure or battery voltage is outside of the
+    /// plausible ranges of `validation` are left as they are.
+    /// Returns the number of updated and skipped measurements.
+    async fn recalibrate_measurements(
+        &self,
+        node: NodeId,
+        calibration: &Calibration,
+        validation: &ValidationConfig,
+        since: Option<Duration>,
+    ) -> Result<(u64, u64), Error>;
+
+    /// Insert historical measurements of a node in one transaction, keeping their timestamps.
+    ///
+    /// With `skip_duplicates`, measurements taken within a second of an existing measurement of the node are skipped.
+    /// With `dry_run`, the transaction is rolled back, so the database constraints are checked but nothing is stored.
+    /// Returns the number of inserted measurements.
+    async fn import_measurements(
+        &self,
+        node: NodeId,
+        measurements: &[MeasurementEntry],
+        skip_duplicates: bool,
+        dry_run: bool,
+    ) -> Result<u64, Error>;
+
+    /// Recompute the hourly and daily rollups of the buckets containing measurements taken at or after `from`,
+    /// optionally only of one node. Returns the number of updated buckets.
+    async fn refresh_rollups(
+        &self,
+        node: Option<NodeId>,
+        from: Option<DateTime<Utc>>,
+    ) -> Result<u64, Error>;
+
+    /// Store measurements that failed validation, along with the reason.
+    async fn quarantine_measurements(
+        &self,
+        node: NodeId,
+        measurement: &MeasurementEntry,
+        reason: &str,
+    ) -> Result<(), Error>;
+
+    async fn get_last_measurement(&self, node: NodeId) -> Result<Option<MeasurementEntry>, Error>;
+
+    /// Get the last measurement of every node that has posted in the last `max_age`.
+    async fn get_latest_measurements(
+        &self,
+        max_age: Duration,
+    ) -> Result<Vec<(NodeId, MeasurementEntry)>, Error>;
+
+    /// Get the last `count` measurements of a node, newest first.
+    async fn get_recent_measurements(
+        &self,
+        node: NodeId,
+        count: u32,
+    ) -> Result<Vec<MeasurementEntry>, Error>;
+
+    /// Stream the measurements in the given range, oldest first, without loading them all into memory.
+    async fn stream_measurements(
+        &self,
+        range: MeasurementRange,
+    ) -> Result<MeasurementStream<'_>, Error>;
+
+    /// Stream summaries of the measurements in the given range, grouped by node and buckets of `period` aligned to the
+    /// Unix epoch, oldest bucket first.
+    async fn stream_aggregates(
+        &self,
+        range: MeasurementRange,
+        period: Duration,
+    ) -> Result<AggregateStream<'_>, Error>;
+
+    /// Get the latest `limit` battery voltages posted by a node in the given time window, oldest first.
+    async fn get_battery_history(
+        &self,
+        node: NodeId,
+        window: Duration,
+        limit: u32,
+    ) -> Result<Vec<BatterySample>, Error>;
+
+    /// Get the Wi-Fi networks and signal strengths reported by a node in the given time window, oldest first.
+    async fn get_wifi_history(
+        &self,
+        node: NodeId,
+        window: Duration,
+    ) -> Result<Vec<WifiSample>, Error>;
+
+    async fn get_alert_states(&self, node: NodeId) -> Result<Vec<AlertStateEntry>, Error>;
+
+    async fn set_alert_state(
+        &self,
+        node: NodeId,
+        rule: &str,
+        active: bool,
+        streak: u32,
     ) -> Result<(), Error>;
 
-    async fn run_migrations(&self) -> Result<(), Error>;
+    /// Schema migrations of the backend, oldest first.
+    fn migrations(&self) -> &'static [Migration];
+
+    /// Get the applied schema migrations, oldest first.
+    ///
+    /// Databases created before migrations were versioned report only the initial migration, without a time.
+    async fn get_applied_migrations(&self) -> Result<Vec<AppliedMigration>, Error>;
+
+    /// Apply a schema migration and record it, in a single transaction.
+    async fn apply_migration(&self, migration: &Migration) -> Result<(), Error>;
+
+    /// Get the schema version of the database, `0` if it's empty.
+    async fn get_schema_version(&self) -> Result<SchemaVersion, Error> {
+        Ok(self
+            .get_applied_migrations()
+            .await?
+            .iter()
+            .map(|migration| migration.version)
+            .max()
+            .unwrap_or_default())
+    }
+
+    /// Get the schema migrations that are not applied yet, oldest first.
+    async fn get_pending_migrations(&self) -> Result<Vec<&'static Migration>, Error> {
+        let version = self.get_schema_version().await?;
+
+        Ok(self
+            .migrations()
+            .iter()
+            .filter(|migration| migration.version > version)
+            .collect())
+    }
+
+    /// Apply every pending schema migration, returning the applied ones.
+    async fn run_migrations(&self) -> Result<Vec<&'static Migration>, Error> {
+        let pending = self.get_pending_migrations().await?;
+
+        for migration in &pending {
+            debug!(
+                "Applying migration {} ({})",
+                migration.version, migration.name
+            );
+            self.apply_migration(migration).await?;
+        }
+
+        Ok(pending)
+    }
 
     async fn check_os_update(
         &self,
@@ -88,6 +439,43 @@ pub trait DatabaseBackend: Send + Sync {
 
     async fn erase(&self, options: EraseOptions) -> Result<(), Error>;
 
+    /// Delete up to `limit` rows of `table` older than the given age. Returns the number of deleted rows.
+    async fn prune(
+        &self,
+        table: PrunableTable,
+        older_than: Duration,
+        limit: u32,
+    ) -> Result<u64, Error>;
+
+    /// Count the rows of `table` older than the given age.
+    async fn count_prunable(
+        &self,
+        table: PrunableTable,
+        older_than: Duration,
+    ) -> Result<u64, Error>;
+
+    /// Reclaim the disk space of deleted rows.
+    ///
+    /// A `full` compaction rewrites the tables, which reclaims more space but takes longer, locks the tables
+    /// and needs about as much free disk space as they take.
+    async fn compact(&self, full: bool) -> Result<(), Error>;
+
+    /// Count the rows of `table` and get their highest ID.
+    async fn get_table_state(&self, table: TransferTable) -> Result<TableState, Error>;
+
+    /// Get up to `limit` rows of `table` with an ID greater than `after`, by ascending ID.
+    /// Rows of tables without an ID are read by their primary key, skipping the first `after` of them.
+    async fn read_rows(
+        &self,
+        table: TransferTable,
+        after: i32,
+        limit: u32,
+    ) -> Result<Vec<TransferRow>, Error>;
+
+    /// Insert rows of one table with their IDs, by ascending ID, in a single transaction.
+    /// Rows of tables without an ID replace the existing rows with the same primary key.
+    async fn write_rows(&self, rows: &[TransferRow]) -> Result<(), Error>;
+
     async fn get_firmwares(&self) -> Result<Vec<FirmwareEntry>, Error>;
 
     async fn upload_firmware(
@@ -140,6 +528,23 @@ impl DatabaseClient {
                 node_id_cache,
                 node_settings_cache,
             }),
+            DatabaseConfig::Mysql {
+                host,
+                port,
+                user,
+                password,
+                name,
+                ssl,
+            } => Ok(Self {
+                backend: Box::new(MysqlClient::new(host, *port, user, password, name, *ssl).await?),
+                node_id_cache,
+                node_settings_cache,
+            }),
+            DatabaseConfig::Memory { devices } => Ok(Self {
+                backend: Box::new(MemoryClient::new(devices)?),
+                node_id_cache,
+                node_settings_cache,
+            }),
         }
     }
 }
@@ -159,43 +564,235 @@ impl DatabaseBackend for DatabaseClient {
         Ok(maybe_id)
     }
 
-    async fn create_notification(&self, node_id: NodeId, content: &str) -> Result<(), Error> {
-        self.backend.create_notification(node_id, content).await
+    async fn get_devices(&self) -> Result<Vec<DeviceEntry>, Error> {
+        self.backend.get_devices().await
     }
 
-    async fn get_settings(&self, node_id: NodeId) -> Result<Option<NodeSettings>, Error> {
-        if let Some(settings) = self.node_settings_cache.get(&node_id).await {
+    async fn get_device(&self, node: NodeId) -> Result<Option<DeviceEntry>, Error> {
+        self.backend.get_device(node).await
+    }
+
+    async fn set_device_altitude(
+        &self,
+        node: NodeId,
+        altitude: Option<f32>,
+    ) -> Result<bool, Error> {
+        self.backend.set_device_altitude(node, altitude).await
+    }
+
+    async fn create_notification(&self, notification: &Notification) -> Result<(), Error> {
+        self.backend.create_notification(notification).await
+    }
+
+    async fn get_notifications(
+        &self,
+        unread_only: bool,
+        node: Option<NodeId>,
+        since: Option<Duration>,
+    ) -> Result<Vec<NotificationEntry>, Error> {
+        self.backend
+            .get_notifications(unread_only, node, since)
+            .await
+    }
+
+    async fn mark_notifications_read(&self, id: Option<NotificationId>) -> Result<u64, Error> {
+        self.backend.mark_notifications_read(id).await
+    }
+
+    async fn purge_notifications(&self, older_than: Duration) -> Result<u64, Error> {
+        self.backend.purge_notifications(older_than).await
+    }
+
+    async fn get_unacknowledged_notifications(
+        &self,
+        after: Duration,
+        max_escalations: u32,
+        min_severity: Severity,
+    ) -> Result<Vec<NotificationEntry>, Error> {
+        self.backend
+            .get_unacknowledged_notifications(after, max_escalations, min_severity)
+            .await
+    }
+
+    async fn mark_notification_escalated(&self, id: NotificationId) -> Result<(), Error> {
+        self.backend.mark_notification_escalated(id).await
+    }
+
+    // Timed mutes expire when the settings are read, so they can be cached for longer
+    async fn get_settings_entry(&self, node_id: NodeId) -> Result<Option<SettingsEntry>, Error> {
+        if let Some(entry) = self.node_settings_cache.get(&node_id).await {
             debug!("Settings cache hit for '{node_id}'");
-            return Ok(settings);
+            return Ok(entry);
         }
 
         debug!("Settings cache miss for '{node_id}'");
 
-        let settings = self.backend.get_settings(node_id).await?;
-        self.node_settings_cache.insert(node_id, settings).await;
-        Ok(settings)
+        let entry = self.backend.get_settings_entry(node_id).await?;
+        self.node_settings_cache.insert(node_id, entry).await;
+        Ok(entry)
+    }
+
+    async fn set_notifications_muted(
+        &self,
+        node_id: NodeId,
+        muted: bool,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<bool, Error> {
+        let updated = self
+            .backend
+            .set_notifications_muted(node_id, muted, until)
+            .await?;
+
+        // Make sure the change is visible immediately
+        self.node_settings_cache.invalidate(&node_id).await;
+
+        Ok(updated)
     }
 
     async fn post_measurements(
         &self,
         node: NodeId,
-        temp: Temperature,
-        hum: Humidity,
-        air_p: Option<AirPressure>,
-        cpu_temp: Temperature,
-        battery: BatteryVoltage,
-        wifi_ssid: &str,
-        wifi_rssi: i8,
+        measurement: &MeasurementEntry,
     ) -> Result<(), Error> {
+        self.backend.post_measurements(node, measurement).await
+    }
+
+    async fn get_calibration(&self, node: NodeId) -> Result<Option<Calibration>, Error> {
+        self.backend.get_calibration(node).await
+    }
+
+    async fn get_calibrations(&self) -> Result<Vec<CalibrationEntry>, Error> {
+        self.backend.get_calibrations().await
+    }
+
+    async fn set_calibration(&self, node: NodeId, calibration: &Calibration) -> Result<(), Error> {
+        self.backend.set_calibration(node, calibration).await
+    }
+
+    async fn delete_calibration(&self, node: NodeId) -> Result<bool, Error> {
+        self.backend.delete_calibration(node).await
+    }
+
+    async fn recalibrate_measurements(
+        &self,
+        node: NodeId,
+        calibration: &Calibration,
+        validation: &ValidationConfig,
+        since: Option<Duration>,
+    ) -> Result<(u64, u64), Error> {
+        self.backend
+            .recalibrate_measurements(node, calibration, validation, since)
+            .await
+    }
+
+    async fn import_measurements(
+        &self,
+        node: NodeId,
+        measurements: &[MeasurementEntry],
+        skip_duplicates: bool,
+        dry_run: bool,
+    ) -> Result<u64, Error> {
         self.backend
-            .post_measurements(
-                node, temp, hum, air_p, cpu_temp, battery, wifi_ssid, wifi_rssi,
-            )
+            .import_measurements(node, measurements, skip_duplicates, dry_run)
             .await
     }
 
-    async fn run_migrations(&self) -> Result<(), Error> {
-        self.backend.run_migrations().await
+    async fn refresh_rollups(
+        &self,
+        node: Option<NodeId>,
+        from: Option<DateTime<Utc>>,
+    ) -> Result<u64, Error> {
+        self.backend.refresh_rollups(node, from).await
+    }
+
+    async fn quarantine_measurements(
+        &self,
+        node: NodeId,
+        measurement: &MeasurementEntry,
+        reason: &str,
+    ) -> Result<(), Error> {
+        self.backend
+            .quarantine_measurements(node, measurement, reason)
+            .await
+    }
+
+    async fn get_last_measurement(&self, node: NodeId) -> Result<Option<MeasurementEntry>, Error> {
+        self.backend.get_last_measurement(node).await
+    }
+
+    async fn get_latest_measurements(
+        &self,
+        max_age: Duration,
+    ) -> Result<Vec<(NodeId, MeasurementEntry)>, Error> {
+        self.backend.get_latest_measurements(max_age).await
+    }
+
+    async fn get_recent_measurements(
+        &self,
+        node: NodeId,
+        count: u32,
+    ) -> Result<Vec<MeasurementEntry>, Error> {
+        self.backend.get_recent_measurements(node, count).await
+    }
+
+    async fn stream_measurements(
+        &self,
+        range: MeasurementRange,
+    ) -> Result<MeasurementStream<'_>, Error> {
+        self.backend.stream_measurements(range).await
+    }
+
+    async fn stream_aggregates(
+        &self,
+        range: MeasurementRange,
+        period: Duration,
+    ) -> Result<AggregateStream<'_>, Error> {
+        self.backend.stream_aggregates(range, period).await
+    }
+
+    async fn get_battery_history(
+        &self,
+        node: NodeId,
+        window: Duration,
+        limit: u32,
+    ) -> Result<Vec<BatterySample>, Error> {
+        self.backend.get_battery_history(node, window, limit).await
+    }
+
+    async fn get_wifi_history(
+        &self,
+        node: NodeId,
+        window: Duration,
+    ) -> Result<Vec<WifiSample>, Error> {
+        self.backend.get_wifi_history(node, window).await
+    }
+
+    async fn get_alert_states(&self, node: NodeId) -> Result<Vec<AlertStateEntry>, Error> {
+        self.backend.get_alert_states(node).await
+    }
+
+    async fn set_alert_state(
+        &self,
+        node: NodeId,
+        rule: &str,
+        active: bool,
+        streak: u32,
+    ) -> Result<(), Error> {
+        self.backend
+            .set_alert_state(node, rule, active, streak)
+            .await
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        self.backend.migrations()
+    }
+
+    async fn get_applied_migrations(&self) -> Result<Vec<AppliedMigration>, Error> {
+        self.backend.get_applied_migrations().await
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> Result<(), Error> {
+        self.backend.apply_migration(migration).await
     }
 
     async fn check_os_update(
@@ -225,6 +822,44 @@ impl DatabaseBackend for DatabaseClient {
         self.backend.erase(options).await
     }
 
+    async fn prune(
+        &self,
+        table: PrunableTable,
+        older_than: Duration,
+        limit: u32,
+    ) -> Result<u64, Error> {
+        self.backend.prune(table, older_than, limit).await
+    }
+
+    async fn count_prunable(
+        &self,
+        table: PrunableTable,
+        older_than: Duration,
+    ) -> Result<u64, Error> {
+        self.backend.count_prunable(table, older_than).await
+    }
+
+    async fn compact(&self, full: bool) -> Result<(), Error> {
+        self.backend.compact(full).await
+    }
+
+    async fn get_table_state(&self, table: TransferTable) -> Result<TableState, Error> {
+        self.backend.get_table_state(table).await
+    }
+
+    async fn read_rows(
+        &self,
+        table: TransferTable,
+        after: i32,
+        limit: u32,
+    ) -> Result<Vec<TransferRow>, Error> {
+        self.backend.read_rows(table, after, limit).await
+    }
+
+    async fn write_rows(&self, rows: &[TransferRow]) -> Result<(), Error> {
+        self.backend.write_rows(rows).await
+    }
+
     async fn get_firmwares(&self) -> Result<Vec<FirmwareEntry>, Error> {
         self.backend.get_firmwares().await
     }
@@ -241,6 +876,29 @@ impl DatabaseBackend for DatabaseClient {
     }
 }
 
+/// Convert a UNIX timestamp returned by a query to a date-time.
+const fn timestamp(secs: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(secs, 0).expect("Invalid timestamp")
+}
+
+impl PrunableTable {
+    pub const ALL: [Self; 4] = [
+        Self::Measurements,
+        Self::Notifications,
+        Self::FirmwareStats,
+        Self::Quarantine,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Measurements => "measurements",
+            Self::Notifications => "notifications",
+            Self::FirmwareStats => "firmware_stats",
+            Self::Quarantine => "quarantine",
+        }
+    }
+}
+
 impl EraseOptions {
     pub const fn new(content_only: bool, keep_devices: bool) -> Self {
         if content_only {
@@ -250,3 +908,41 @@ impl EraseOptions {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    #[tokio::test]
+    async fn cached_timed_mutes_expire() {
+        let config = Config {
+            database: DatabaseConfig::Memory {
+                devices: vec!["AA:BB:CC:DD:EE:FF".into()],
+            },
+            ..Default::default()
+        };
+        let db = DatabaseClient::new(&config).await.unwrap();
+        let until = Utc::now() + TimeDelta::hours(2);
+
+        db.set_notifications_muted(1, true, Some(until))
+            .await
+            .unwrap();
+        assert!(
+            db.get_settings(1)
+                .await
+                .unwrap()
+                .unwrap()
+                .mute_notifications
+        );
+
+        // The cached entry is muted only until the expiry
+        let entry = db.get_settings_entry(1).await.unwrap().unwrap();
+        assert!(entry.at(until - TimeDelta::seconds(1)).mute_notifications);
+        assert!(!entry.at(until + TimeDelta::seconds(1)).mute_notifications);
+
+        db.set_notifications_muted(1, true, None).await.unwrap();
+        let entry = db.get_settings_entry(1).await.unwrap().unwrap();
+        assert!(entry.at(until + TimeDelta::days(365)).mute_notifications);
+    }
+}
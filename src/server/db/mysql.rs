@@ -0,0 +1,1874 @@
+use super::{
+    AggregateEntry, AggregateStream, AlertStateEntry, BatterySample, CalibrationEntry, DeviceEntry,
+    EraseOptions, FirmwareBlob, FirmwareEntry, MeasurementEntry, MeasurementRange,
+    MeasurementStream, NodeId, NotificationEntry, NotificationId, PrunableTable, RawReadings,
+    SettingsEntry, SleepTime, Summary, UpdateStatId, WifiSample,
+    migrations::{self, AppliedMigration, Migration},
+    transfer::{
+        AlertStateRow, CalibrationRow, DeviceRow, FirmwareRow, FirmwareStatRow, MeasurementRow,
+        NotificationRow, QuarantineRow, RollupRow, SettingsRow, TableState, TransferRow,
+        TransferTable,
+    },
+};
+use crate::{
+    error::Error,
+    server::{
+        calibration::Calibration,
+        config::ValidationConfig,
+        diagnostics::SensorFlags,
+        notification_client::{Notification, Severity},
+    },
+};
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use pwmp_client::pwmp_msg::{mac::Mac, settings::NodeSettings, version::Version};
+use sqlx::{
+    MySql, Pool, Row,
+    mysql::{MySqlConnectOptions, MySqlPoolOptions, MySqlRow, MySqlSslMode},
+};
+use std::time::Duration;
+use tracing::debug;
+
+pub struct MysqlClient(Pool<MySql>);
+
+impl MysqlClient {
+    #[tracing::instrument(name = "MysqlClient::new()", level = "debug", err, skip_all)]
+    pub async fn new(
+        host: &str,
+        port: u16,
+        user: &str,
+        password: &str,
+        name: &str,
+        ssl: bool,
+    ) -> Result<Self, Error> {
+        // The session time zone is UTC by default, which the timestamp conversions rely on
+        let mut opts = MySqlConnectOptions::new()
+            .host(host)
+            .port(port)
+            .username(user)
+            .password(password)
+            .database(name);
+
+        if ssl {
+            opts = opts.ssl_mode(MySqlSslMode::Required);
+        }
+
+        let pool = MySqlPoolOptions::new()
+            .max_connections(3)
+            .min_connections(1)
+            .acquire_timeout(Duration::from_secs(3))
+            .idle_timeout(Duration::from_hours(1))
+            .max_lifetime(None)
+            .connect_with(opts)
+            .await?;
+
+        Ok(Self(pool))
+    }
+
+    pub async fn get_last_os_update_stat_for_node(
+        &self,
+        node_id: NodeId,
+    ) -> Result<Option<UpdateStatId>, Error> {
+        let row = sqlx::query(include_str!(
+            "../../../queries/mysql/get_last_update_event.sql"
+        ))
+        .bind(node_id)
+        .fetch_optional(&self.0)
+        .await?;
+
+        row.map_or_else(|| Ok(None), |row| Ok(Some(row.get(0))))
+    }
+
+    async fn table_exists(&self, table: &str) -> Result<bool, Error> {
+        let row = sqlx::query(include_str!("../../../queries/mysql/table_exists.sql"))
+            .bind(table)
+            .fetch_one(&self.0)
+            .await?;
+
+        Ok(row.get(0))
+    }
+}
+
+#[async_trait::async_trait]
+impl super::DatabaseBackend for MysqlClient {
+    #[tracing::instrument(
+        name = "MysqlClient::authorize_device()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn authorize_device(&self, mac: &Mac) -> Result<Option<NodeId>, Error> {
+        let mac = mac.to_string();
+
+        let id = sqlx::query_scalar(include_str!("../../../queries/mysql/get_device_by_mac.sql"))
+            .bind(mac)
+            .fetch_optional(&self.0)
+            .await?;
+
+        Ok(id)
+    }
+
+    #[tracing::instrument(name = "MysqlClient::get_devices()", level = "debug", skip(self), err)]
+    async fn get_devices(&self) -> Result<Vec<DeviceEntry>, Error> {
+        let results = sqlx::query(include_str!("../../../queries/mysql/get_devices.sql"))
+            .fetch_all(&self.0)
+            .await?
+            .iter()
+            .map(device_entry)
+            .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(name = "MysqlClient::get_device()", level = "debug", skip(self), err)]
+    async fn get_device(&self, node: NodeId) -> Result<Option<DeviceEntry>, Error> {
+        let result = sqlx::query(include_str!("../../../queries/mysql/get_device.sql"))
+            .bind(node)
+            .fetch_optional(&self.0)
+            .await?;
+
+        Ok(result.as_ref().map(device_entry))
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::create_notification()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn create_notification(&self, notification: &Notification) -> Result<(), Error> {
+        sqlx::query(include_str!(
+            "../../../queries/mysql/create_notification.sql"
+        ))
+        .bind(notification.node)
+        .bind(notification.body.as_ref())
+        .bind(notification.severity.as_str())
+        .bind(notification.kind.as_str())
+        .bind(notification.title.as_ref())
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::get_notifications()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_notifications(
+        &self,
+        unread_only: bool,
+        node: Option<NodeId>,
+        since: Option<Duration>,
+    ) -> Result<Vec<NotificationEntry>, Error> {
+        let since: Option<i64> = match since {
+            Some(age) => Some(age.as_secs().try_into()?),
+            None => None,
+        };
+
+        let results = sqlx::query(include_str!("../../../queries/mysql/get_notifications.sql"))
+            .bind(unread_only)
+            .bind(node)
+            .bind(node)
+            .bind(since)
+            .bind(since)
+            .fetch_all(&self.0)
+            .await?
+            .iter()
+            .map(notification_entry)
+            .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::mark_notifications_read()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn mark_notifications_read(&self, id: Option<NotificationId>) -> Result<u64, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/mysql/mark_notifications_read.sql"
+        ))
+        .bind(id)
+        .bind(id)
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::purge_notifications()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn purge_notifications(&self, older_than: Duration) -> Result<u64, Error> {
+        let older_than: i64 = older_than.as_secs().try_into()?;
+
+        let result = sqlx::query(include_str!(
+            "../../../queries/mysql/purge_notifications.sql"
+        ))
+        .bind(older_than)
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::get_unacknowledged_notifications()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_unacknowledged_notifications(
+        &self,
+        after: Duration,
+        max_escalations: u32,
+        min_severity: Severity,
+    ) -> Result<Vec<NotificationEntry>, Error> {
+        let after: i64 = after.as_secs().try_into()?;
+        let max_escalations: i32 = max_escalations.try_into()?;
+
+        let results = sqlx::query(include_str!(
+            "../../../queries/mysql/get_unacknowledged_notifications.sql"
+        ))
+        .bind(max_escalations)
+        .bind(after)
+        .bind(min_severity.level())
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(notification_entry)
+        .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::mark_notification_escalated()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn mark_notification_escalated(&self, id: NotificationId) -> Result<(), Error> {
+        sqlx::query(include_str!(
+            "../../../queries/mysql/mark_notification_escalated.sql"
+        ))
+        .bind(id)
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::get_settings_entry()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn get_settings_entry(&self, node_id: NodeId) -> Result<Option<SettingsEntry>, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/mysql/get_device_settings.sql"
+        ))
+        .bind(node_id)
+        .fetch_optional(&self.0)
+        .await?;
+
+        let result = match result {
+            Some(row) => Some(SettingsEntry {
+                settings: NodeSettings {
+                    battery_ignore: row.get(0),
+                    ota: row.get(1),
+                    sleep_time: row.get::<SleepTime, _>(2).try_into()?,
+                    sbop: row.get(3),
+                    mute_notifications: row.get(4),
+                },
+                muted_until: row.get::<Option<i64>, _>(5).map(super::timestamp),
+            }),
+            None => None,
+        };
+
+        Ok(result)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::set_device_altitude()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn set_device_altitude(
+        &self,
+        node: NodeId,
+        altitude: Option<f32>,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/mysql/set_device_altitude.sql"
+        ))
+        .bind(altitude)
+        .bind(node)
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::set_notifications_muted()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn set_notifications_muted(
+        &self,
+        node_id: NodeId,
+        muted: bool,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/mysql/set_notifications_muted.sql"
+        ))
+        .bind(muted)
+        .bind(until.map(|when| when.timestamp()))
+        .bind(node_id)
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::post_results()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn post_measurements(
+        &self,
+        node: NodeId,
+        measurement: &MeasurementEntry,
+    ) -> Result<(), Error> {
+        sqlx::query(include_str!("../../../queries/mysql/post_measurements.sql"))
+            .bind(node)
+            .bind(measurement.temperature)
+            .bind(measurement.humidity)
+            .bind(measurement.air_pressure)
+            .bind(measurement.cpu_temp)
+            .bind(measurement.battery)
+            .bind(&measurement.wifi_ssid)
+            .bind(measurement.wifi_rssi)
+            .bind(measurement.flags.bits())
+            .bind(measurement.raw.temperature)
+            .bind(measurement.raw.humidity)
+            .bind(measurement.raw.air_pressure)
+            .bind(measurement.raw.battery)
+            .execute(&self.0)
+            .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::get_calibration()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_calibration(&self, node: NodeId) -> Result<Option<Calibration>, Error> {
+        let result = sqlx::query(include_str!("../../../queries/mysql/get_calibration.sql"))
+            .bind(node)
+            .fetch_optional(&self.0)
+            .await?;
+
+        Ok(result.map(|row| Calibration {
+            temperature_offset: row.get(0),
+            temperature_scale: row.get(1),
+            humidity_offset: row.get(2),
+            air_pressure_offset: row.get(3),
+            battery_scale: row.get(4),
+        }))
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::get_calibrations()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_calibrations(&self) -> Result<Vec<CalibrationEntry>, Error> {
+        let results = sqlx::query(include_str!("../../../queries/mysql/get_calibrations.sql"))
+            .fetch_all(&self.0)
+            .await?
+            .iter()
+            .map(|row| CalibrationEntry {
+                node: row.get(0),
+                calibration: Calibration {
+                    temperature_offset: row.get(1),
+                    temperature_scale: row.get(2),
+                    humidity_offset: row.get(3),
+                    air_pressure_offset: row.get(4),
+                    battery_scale: row.get(5),
+                },
+            })
+            .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::set_calibration()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn set_calibration(&self, node: NodeId, calibration: &Calibration) -> Result<(), Error> {
+        sqlx::query(include_str!("../../../queries/mysql/set_calibration.sql"))
+            .bind(node)
+            .bind(calibration.temperature_offset)
+            .bind(calibration.temperature_scale)
+            .bind(calibration.humidity_offset)
+            .bind(calibration.air_pressure_offset)
+            .bind(calibration.battery_scale)
+            .execute(&self.0)
+            .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::delete_calibration()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn delete_calibration(&self, node: NodeId) -> Result<bool, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/mysql/delete_calibration.sql"
+        ))
+        .bind(node)
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::recalibrate_measurements()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn recalibrate_measurements(
+        &self,
+        node: NodeId,
+        calibration: &Calibration,
+        validation: &ValidationConfig,
+        since: Option<Duration>,
+    ) -> Result<(u64, u64), Error> {
+        let since: Option<i64> = match since {
+            Some(since) => Some(since.as_secs().try_into()?),
+            None => None,
+        };
+
+        let mut tx = self.0.begin().await?;
+
+        let total: i64 = sqlx::query(include_str!(
+            "../../../queries/mysql/count_node_measurements.sql"
+        ))
+        .bind(node)
+        .bind(since)
+        .bind(since)
+        .fetch_one(&mut *tx)
+        .await?
+        .get(0);
+
+        let result = sqlx::query(include_str!(
+            "../../../queries/mysql/recalibrate_measurements.sql"
+        ))
+        .bind(calibration.temperature_scale)
+        .bind(calibration.temperature_offset)
+        .bind(calibration.humidity_offset)
+        .bind(calibration.air_pressure_offset)
+        .bind(calibration.battery_scale)
+        .bind(node)
+        .bind(since)
+        .bind(since)
+        .bind(calibration.temperature_scale)
+        .bind(calibration.temperature_offset)
+        .bind(validation.temperature.min)
+        .bind(validation.temperature.max)
+        .bind(calibration.humidity_offset)
+        .bind(validation.humidity.min)
+        .bind(validation.humidity.max)
+        .bind(calibration.air_pressure_offset)
+        .bind(validation.air_pressure.min)
+        .bind(validation.air_pressure.max)
+        .bind(calibration.battery_scale)
+        .bind(validation.battery.min)
+        .bind(validation.battery.max)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let updated = result.rows_affected();
+        Ok((updated, u64::try_from(total)? - updated))
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::import_measurements()",
+        level = "debug",
+        skip(self, measurements),
+        fields(count = measurements.len()),
+        err,
+        ret
+    )]
+    async fn import_measurements(
+        &self,
+        node: NodeId,
+        measurements: &[MeasurementEntry],
+        skip_duplicates: bool,
+        dry_run: bool,
+    ) -> Result<u64, Error> {
+        let mut tx = self.0.begin().await?;
+        let mut inserted = 0;
+
+        for measurement in measurements {
+            inserted += sqlx::query(include_str!(
+                "../../../queries/mysql/import_measurements.sql"
+            ))
+            .bind(node)
+            .bind(measurement.when.timestamp())
+            .bind(measurement.temperature)
+            .bind(measurement.humidity)
+            .bind(measurement.air_pressure)
+            .bind(measurement.cpu_temp)
+            .bind(measurement.battery)
+            .bind(&measurement.wifi_ssid)
+            .bind(measurement.wifi_rssi)
+            .bind(skip_duplicates)
+            .bind(node)
+            .bind(measurement.when.timestamp())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+
+        Ok(inserted)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::refresh_rollups()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn refresh_rollups(
+        &self,
+        node: Option<NodeId>,
+        from: Option<DateTime<Utc>>,
+    ) -> Result<u64, Error> {
+        let from = from.map(|from| from.timestamp());
+        let mut tx = self.0.begin().await?;
+        let mut updated = 0;
+
+        // An upsert counts updated rows twice, so the buckets are replaced instead
+        for (clear, refresh) in [
+            (
+                include_str!("../../../queries/mysql/clear_rollups_hourly.sql"),
+                include_str!("../../../queries/mysql/refresh_rollups_hourly.sql"),
+            ),
+            (
+                include_str!("../../../queries/mysql/clear_rollups_daily.sql"),
+                include_str!("../../../queries/mysql/refresh_rollups_daily.sql"),
+            ),
+        ] {
+            sqlx::query(clear)
+                .bind(node)
+                .bind(node)
+                .bind(from)
+                .bind(from)
+                .execute(&mut *tx)
+                .await?;
+            updated += sqlx::query(refresh)
+                .bind(node)
+                .bind(node)
+                .bind(from)
+                .bind(from)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+
+        tx.commit().await?;
+        Ok(updated)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::quarantine_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn quarantine_measurements(
+        &self,
+        node: NodeId,
+        measurement: &MeasurementEntry,
+        reason: &str,
+    ) -> Result<(), Error> {
+        sqlx::query(include_str!(
+            "../../../queries/mysql/quarantine_measurements.sql"
+        ))
+        .bind(node)
+        .bind(measurement.temperature)
+        .bind(measurement.humidity)
+        .bind(measurement.air_pressure)
+        .bind(measurement.cpu_temp)
+        .bind(measurement.battery)
+        .bind(&measurement.wifi_ssid)
+        .bind(measurement.wifi_rssi)
+        .bind(reason)
+        .execute(&self.0)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::get_last_measurement()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_last_measurement(&self, node: NodeId) -> Result<Option<MeasurementEntry>, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/mysql/get_last_measurement.sql"
+        ))
+        .bind(node)
+        .fetch_optional(&self.0)
+        .await?;
+
+        let result = match result {
+            Some(row) => Some(measurement_entry(&row)?),
+            None => None,
+        };
+
+        Ok(result)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::get_latest_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_latest_measurements(
+        &self,
+        max_age: Duration,
+    ) -> Result<Vec<(NodeId, MeasurementEntry)>, Error> {
+        let max_age: i64 = max_age.as_secs().try_into()?;
+
+        sqlx::query(include_str!(
+            "../../../queries/mysql/get_latest_measurements.sql"
+        ))
+        .bind(max_age)
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(|row| Ok((row.get(13), measurement_entry(row)?)))
+        .collect()
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::get_recent_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_recent_measurements(
+        &self,
+        node: NodeId,
+        count: u32,
+    ) -> Result<Vec<MeasurementEntry>, Error> {
+        sqlx::query(include_str!(
+            "../../../queries/mysql/get_recent_measurements.sql"
+        ))
+        .bind(node)
+        .bind(i64::from(count))
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(measurement_entry)
+        .collect()
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::stream_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn stream_measurements(
+        &self,
+        range: MeasurementRange,
+    ) -> Result<MeasurementStream<'_>, Error> {
+        let from = range.from.map(|from| from.timestamp());
+        let to = range.to.map(|to| to.timestamp());
+
+        let stream = sqlx::query(include_str!(
+            "../../../queries/mysql/export_measurements.sql"
+        ))
+        .bind(range.node)
+        .bind(range.node)
+        .bind(from)
+        .bind(from)
+        .bind(to)
+        .bind(to)
+        .fetch(&self.0)
+        .map(|row| -> Result<_, Error> {
+            let row = row?;
+            Ok((row.get(13), measurement_entry(&row)?))
+        });
+
+        Ok(stream.boxed())
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::stream_aggregates()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn stream_aggregates(
+        &self,
+        range: MeasurementRange,
+        period: Duration,
+    ) -> Result<AggregateStream<'_>, Error> {
+        let period: i64 = period.as_secs().try_into()?;
+        let from = range.from.map(|from| from.timestamp());
+        let to = range.to.map(|to| to.timestamp());
+
+        let stream = sqlx::query(include_str!("../../../queries/mysql/export_aggregates.sql"))
+            .bind(period)
+            .bind(period)
+            .bind(range.node)
+            .bind(range.node)
+            .bind(from)
+            .bind(from)
+            .bind(to)
+            .bind(to)
+            .fetch(&self.0)
+            .map(|row| Ok(aggregate_entry(&row?)));
+
+        Ok(stream.boxed())
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::get_battery_history()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_battery_history(
+        &self,
+        node: NodeId,
+        window: Duration,
+        limit: u32,
+    ) -> Result<Vec<BatterySample>, Error> {
+        let window: i64 = window.as_secs().try_into()?;
+
+        let results = sqlx::query(include_str!(
+            "../../../queries/mysql/get_battery_history.sql"
+        ))
+        .bind(node)
+        .bind(window)
+        .bind(i64::from(limit))
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(|row| BatterySample {
+            when: super::timestamp(row.get(0)),
+            voltage: row.get(1),
+        })
+        .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::get_wifi_history()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_wifi_history(
+        &self,
+        node: NodeId,
+        window: Duration,
+    ) -> Result<Vec<WifiSample>, Error> {
+        let window: i64 = window.as_secs().try_into()?;
+
+        let results = sqlx::query(include_str!("../../../queries/mysql/get_wifi_history.sql"))
+            .bind(node)
+            .bind(window)
+            .fetch_all(&self.0)
+            .await?
+            .iter()
+            .map(|row| {
+                Ok(WifiSample {
+                    when: super::timestamp(row.get(0)),
+                    ssid: row.get(1),
+                    rssi: row.get::<i16, _>(2).try_into()?,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::get_alert_states()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_alert_states(&self, node: NodeId) -> Result<Vec<AlertStateEntry>, Error> {
+        let rows = sqlx::query(include_str!("../../../queries/mysql/get_alert_states.sql"))
+            .bind(node)
+            .fetch_all(&self.0)
+            .await?;
+
+        let mut results = Vec::with_capacity(rows.len());
+        for row in rows {
+            results.push(AlertStateEntry {
+                rule: row.get(0),
+                active: row.get(1),
+                streak: row.get::<i32, _>(2).try_into()?,
+            });
+        }
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::set_alert_state()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn set_alert_state(
+        &self,
+        node: NodeId,
+        rule: &str,
+        active: bool,
+        streak: u32,
+    ) -> Result<(), Error> {
+        let streak: i32 = streak.try_into()?;
+
+        sqlx::query(include_str!("../../../queries/mysql/set_alert_state.sql"))
+            .bind(node)
+            .bind(rule)
+            .bind(active)
+            .bind(streak)
+            .execute(&self.0)
+            .await?;
+        Ok(())
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        migrations::MYSQL
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::get_applied_migrations()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_applied_migrations(&self) -> Result<Vec<AppliedMigration>, Error> {
+        if self.table_exists("schema_migrations").await? {
+            let results = sqlx::query(include_str!(
+                "../../../queries/mysql/get_applied_migrations.sql"
+            ))
+            .fetch_all(&self.0)
+            .await?
+            .iter()
+            .map(|row| AppliedMigration {
+                version: row.get(0),
+                name: row.get(1),
+                applied: Some(super::timestamp(row.get(2))),
+            })
+            .collect();
+
+            return Ok(results);
+        }
+
+        Ok(Vec::new())
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::apply_migration()",
+        level = "debug",
+        skip(self, migration),
+        fields(version = migration.version),
+        err
+    )]
+    async fn apply_migration(&self, migration: &Migration) -> Result<(), Error> {
+        // DDL commits implicitly and can't be rolled back, so the executed statements are recorded one by one,
+        // and a migration that failed halfway resumes after the last executed statement
+        let mut conn = self.0.acquire().await?;
+
+        sqlx::raw_sql(include_str!(
+            "../../../queries/mysql/create_schema_migrations.sql"
+        ))
+        .execute(&mut *conn)
+        .await?;
+
+        let executed: i32 = sqlx::query(include_str!(
+            "../../../queries/mysql/get_migration_progress.sql"
+        ))
+        .bind(migration.version)
+        .fetch_optional(&mut *conn)
+        .await?
+        .map_or(0, |row| row.get(0));
+
+        if executed > 0 {
+            debug!("Resuming after statement {executed}");
+        }
+
+        for (statement, sql) in (1..).zip(statements(migration.sql)) {
+            if statement <= executed {
+                continue;
+            }
+
+            sqlx::raw_sql(sql).execute(&mut *conn).await?;
+            sqlx::query(include_str!(
+                "../../../queries/mysql/set_migration_progress.sql"
+            ))
+            .bind(migration.version)
+            .bind(statement)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+
+        sqlx::query(include_str!("../../../queries/mysql/record_migration.sql"))
+            .bind(migration.version)
+            .bind(migration.name)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(include_str!(
+            "../../../queries/mysql/delete_migration_progress.sql"
+        ))
+        .bind(migration.version)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::check_os_update()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn check_os_update(
+        &self,
+        node: NodeId,
+        current_ver: Version,
+    ) -> Result<Option<(Version, FirmwareBlob)>, Error> {
+        let (version_major, version_middle, version_minor) = current_ver.to_signed_triple();
+
+        let result = sqlx::query(include_str!("../../../queries/mysql/get_os_update.sql"))
+            .bind(node)
+            .bind(version_major)
+            .bind(version_middle)
+            .bind(version_minor)
+            .bind(node)
+            .fetch_optional(&self.0)
+            .await?;
+
+        match result {
+            Some(row) => {
+                let new_version = Version::new(
+                    row.get::<i16, _>(0).try_into()?,
+                    row.get::<i16, _>(1).try_into()?,
+                    row.get::<i16, _>(2).try_into()?,
+                );
+                let blob = row.get::<Vec<u8>, _>(3).into_boxed_slice();
+                Ok(Some((new_version, blob)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::send_os_update_stat()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn send_os_update_stat(
+        &self,
+        node_id: NodeId,
+        old_ver: Version,
+        new_ver: Version,
+    ) -> Result<UpdateStatId, Error> {
+        let (old_version_major, old_version_middle, old_version_minor) = old_ver.to_signed_triple();
+        let (new_version_major, new_version_middle, new_version_minor) = new_ver.to_signed_triple();
+
+        let result = sqlx::query(include_str!(
+            "../../../queries/mysql/send_os_update_event.sql"
+        ))
+        .bind(node_id)
+        .bind(old_version_major)
+        .bind(old_version_middle)
+        .bind(old_version_minor)
+        .bind(new_version_major)
+        .bind(new_version_middle)
+        .bind(new_version_minor)
+        .execute(&self.0)
+        .await?;
+
+        // There is no RETURNING
+        Ok(result.last_insert_id().try_into()?)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::mark_os_update_stat()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn mark_os_update_stat(&self, node_id: NodeId, success: bool) -> Result<(), Error> {
+        let Some(update_stat_id) = self.get_last_os_update_stat_for_node(node_id).await? else {
+            tracing::error!("Node {node_id} did not pull the entire firmware blob");
+            return Err(Error::InvalidRequest);
+        };
+
+        sqlx::query(include_str!(
+            "../../../queries/mysql/update_os_update_event.sql"
+        ))
+        .bind(success)
+        .bind(update_stat_id)
+        .execute(&self.0)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "MysqlClient::erase()", level = "debug", skip(self), err)]
+    async fn erase(&self, options: EraseOptions) -> Result<(), Error> {
+        let sql = match options {
+            EraseOptions::Everything => {
+                include_str!("../../../queries/mysql/erase_database.sql")
+            }
+            EraseOptions::ContentOnly { keep_devices } => {
+                if keep_devices {
+                    include_str!(
+                        "../../../queries/mysql/erase_database_content_keep_devices_and_settings.sql"
+                    )
+                } else {
+                    include_str!("../../../queries/mysql/erase_database_content.sql")
+                }
+            }
+        };
+
+        sqlx::raw_sql(sql).execute(&self.0).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "MysqlClient::prune()", level = "debug", skip(self), err, ret)]
+    async fn prune(
+        &self,
+        table: PrunableTable,
+        older_than: Duration,
+        limit: u32,
+    ) -> Result<u64, Error> {
+        let older_than: i64 = older_than.as_secs().try_into()?;
+        let sql = match table {
+            PrunableTable::Measurements => {
+                include_str!("../../../queries/mysql/prune_measurements.sql")
+            }
+            PrunableTable::Notifications => {
+                include_str!("../../../queries/mysql/prune_notifications.sql")
+            }
+            PrunableTable::FirmwareStats => {
+                include_str!("../../../queries/mysql/prune_firmware_stats.sql")
+            }
+            PrunableTable::Quarantine => {
+                include_str!("../../../queries/mysql/prune_quarantine.sql")
+            }
+        };
+
+        let result = sqlx::query(sql)
+            .bind(older_than)
+            .bind(i64::from(limit))
+            .execute(&self.0)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::count_prunable()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn count_prunable(
+        &self,
+        table: PrunableTable,
+        older_than: Duration,
+    ) -> Result<u64, Error> {
+        let older_than: i64 = older_than.as_secs().try_into()?;
+        let sql = match table {
+            PrunableTable::Measurements => {
+                include_str!("../../../queries/mysql/count_prunable_measurements.sql")
+            }
+            PrunableTable::Notifications => {
+                include_str!("../../../queries/mysql/count_prunable_notifications.sql")
+            }
+            PrunableTable::FirmwareStats => {
+                include_str!("../../../queries/mysql/count_prunable_firmware_stats.sql")
+            }
+            PrunableTable::Quarantine => {
+                include_str!("../../../queries/mysql/count_prunable_quarantine.sql")
+            }
+        };
+
+        let count: i64 = sqlx::query(sql)
+            .bind(older_than)
+            .fetch_one(&self.0)
+            .await?
+            .get(0);
+
+        Ok(count.try_into()?)
+    }
+
+    #[tracing::instrument(name = "MysqlClient::compact()", level = "debug", skip(self), err)]
+    async fn compact(&self, full: bool) -> Result<(), Error> {
+        // InnoDB reuses the space of deleted rows, only rebuilding a table returns it to the file system
+        let sql = if full {
+            include_str!("../../../queries/mysql/optimize.sql")
+        } else {
+            include_str!("../../../queries/mysql/analyze.sql")
+        };
+
+        sqlx::raw_sql(sql).execute(&self.0).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::get_table_state()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn get_table_state(&self, table: TransferTable) -> Result<TableState, Error> {
+        let sql = match table {
+            TransferTable::Devices => {
+                include_str!("../../../queries/mysql/transfer_state_devices.sql")
+            }
+            TransferTable::Settings => {
+                include_str!("../../../queries/mysql/transfer_state_settings.sql")
+            }
+            TransferTable::Measurements => {
+                include_str!("../../../queries/mysql/transfer_state_measurements.sql")
+            }
+            TransferTable::Notifications => {
+                include_str!("../../../queries/mysql/transfer_state_notifications.sql")
+            }
+            TransferTable::Firmwares => {
+                include_str!("../../../queries/mysql/transfer_state_firmwares.sql")
+            }
+            TransferTable::FirmwareStats => {
+                include_str!("../../../queries/mysql/transfer_state_firmware_stats.sql")
+            }
+            TransferTable::Quarantine => {
+                include_str!("../../../queries/mysql/transfer_state_quarantine.sql")
+            }
+            TransferTable::Calibrations => {
+                include_str!("../../../queries/mysql/transfer_state_calibrations.sql")
+            }
+            TransferTable::AlertStates => {
+                include_str!("../../../queries/mysql/transfer_state_alert_states.sql")
+            }
+            TransferTable::RollupsHourly => {
+                include_str!("../../../queries/mysql/transfer_state_rollups_hourly.sql")
+            }
+            TransferTable::RollupsDaily => {
+                include_str!("../../../queries/mysql/transfer_state_rollups_daily.sql")
+            }
+        };
+
+        let row = sqlx::query(sql).fetch_one(&self.0).await?;
+
+        Ok(TableState {
+            rows: row.get::<i64, _>(0).try_into()?,
+            last_id: row.get(1),
+        })
+    }
+
+    #[tracing::instrument(name = "MysqlClient::read_rows()", level = "debug", skip(self), err)]
+    async fn read_rows(
+        &self,
+        table: TransferTable,
+        after: i32,
+        limit: u32,
+    ) -> Result<Vec<TransferRow>, Error> {
+        let sql = match table {
+            TransferTable::Devices => {
+                include_str!("../../../queries/mysql/transfer_read_devices.sql")
+            }
+            TransferTable::Settings => {
+                include_str!("../../../queries/mysql/transfer_read_settings.sql")
+            }
+            TransferTable::Measurements => {
+                include_str!("../../../queries/mysql/transfer_read_measurements.sql")
+            }
+            TransferTable::Notifications => {
+                include_str!("../../../queries/mysql/transfer_read_notifications.sql")
+            }
+            TransferTable::Firmwares => {
+                include_str!("../../../queries/mysql/transfer_read_firmwares.sql")
+            }
+            TransferTable::FirmwareStats => {
+                include_str!("../../../queries/mysql/transfer_read_firmware_stats.sql")
+            }
+            TransferTable::Quarantine => {
+                include_str!("../../../queries/mysql/transfer_read_quarantine.sql")
+            }
+            TransferTable::Calibrations => {
+                include_str!("../../../queries/mysql/transfer_read_calibrations.sql")
+            }
+            TransferTable::AlertStates => {
+                include_str!("../../../queries/mysql/transfer_read_alert_states.sql")
+            }
+            TransferTable::RollupsHourly => {
+                include_str!("../../../queries/mysql/transfer_read_rollups_hourly.sql")
+            }
+            TransferTable::RollupsDaily => {
+                include_str!("../../../queries/mysql/transfer_read_rollups_daily.sql")
+            }
+        };
+
+        let rows = sqlx::query(sql)
+            .bind(after)
+            .bind(i64::from(limit))
+            .fetch_all(&self.0)
+            .await?;
+
+        rows.iter().map(|row| transfer_row(table, row)).collect()
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::write_rows()",
+        level = "debug",
+        skip(self, rows),
+        err
+    )]
+    async fn write_rows(&self, rows: &[TransferRow]) -> Result<(), Error> {
+        let mut tx = self.0.begin().await?;
+
+        for row in rows {
+            let query = match row {
+                TransferRow::Device(row) => sqlx::query(include_str!(
+                    "../../../queries/mysql/transfer_write_devices.sql"
+                ))
+                .bind(row.id)
+                .bind(&row.mac_address)
+                .bind(&row.location)
+                .bind(&row.note)
+                .bind(row.altitude),
+                TransferRow::Settings(row) => sqlx::query(include_str!(
+                    "../../../queries/mysql/transfer_write_settings.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.battery_ignore)
+                .bind(row.ota)
+                .bind(row.sleep_time)
+                .bind(row.sbop)
+                .bind(row.mute_notifications)
+                .bind(&row.device_specific)
+                .bind(row.muted_until.map(|when| when.timestamp())),
+                TransferRow::Measurement(row) => sqlx::query(include_str!(
+                    "../../../queries/mysql/transfer_write_measurements.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.when.timestamp())
+                .bind(row.temperature)
+                .bind(row.humidity)
+                .bind(row.air_pressure)
+                .bind(row.cpu_temp)
+                .bind(row.battery)
+                .bind(&row.wifi_ssid)
+                .bind(row.wifi_rssi)
+                .bind(row.flags)
+                .bind(row.raw_temperature)
+                .bind(row.raw_humidity)
+                .bind(row.raw_air_pressure)
+                .bind(row.raw_battery),
+                TransferRow::Notification(row) => sqlx::query(include_str!(
+                    "../../../queries/mysql/transfer_write_notifications.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.when.timestamp())
+                .bind(&row.content)
+                .bind(row.read)
+                .bind(&row.severity)
+                .bind(&row.kind)
+                .bind(&row.title)
+                .bind(row.escalations)
+                .bind(row.escalated.map(|t| t.timestamp())),
+                TransferRow::Firmware(row) => sqlx::query(include_str!(
+                    "../../../queries/mysql/transfer_write_firmwares.sql"
+                ))
+                .bind(row.id)
+                .bind(row.version_major)
+                .bind(row.version_middle)
+                .bind(row.version_minor)
+                .bind(&row.firmware)
+                .bind(row.added_date.timestamp())
+                .bind(
+                    row.restrict_nodes
+                        .as_ref()
+                        .map(serde_json::to_string)
+                        .transpose()?,
+                ),
+                TransferRow::FirmwareStat(row) => sqlx::query(include_str!(
+                    "../../../queries/mysql/transfer_write_firmware_stats.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.from_version_major)
+                .bind(row.from_version_middle)
+                .bind(row.from_version_minor)
+                .bind(row.to_version_major)
+                .bind(row.to_version_middle)
+                .bind(row.to_version_minor)
+                .bind(row.when.timestamp())
+                .bind(row.success),
+                TransferRow::Quarantine(row) => sqlx::query(include_str!(
+                    "../../../queries/mysql/transfer_write_quarantine.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.when.timestamp())
+                .bind(row.temperature)
+                .bind(row.humidity)
+                .bind(row.air_pressure)
+                .bind(row.cpu_temp)
+                .bind(row.battery)
+                .bind(&row.wifi_ssid)
+                .bind(row.wifi_rssi)
+                .bind(&row.reason),
+                TransferRow::Calibration(row) => sqlx::query(include_str!(
+                    "../../../queries/mysql/transfer_write_calibrations.sql"
+                ))
+                .bind(row.node)
+                .bind(row.temperature_offset)
+                .bind(row.temperature_scale)
+                .bind(row.humidity_offset)
+                .bind(row.air_pressure_offset)
+                .bind(row.battery_scale),
+                TransferRow::AlertState(row) => sqlx::query(include_str!(
+                    "../../../queries/mysql/transfer_write_alert_states.sql"
+                ))
+                .bind(row.node)
+                .bind(&row.rule)
+                .bind(row.active)
+                .bind(row.streak)
+                .bind(row.changed.timestamp()),
+                TransferRow::RollupHourly(rollup) | TransferRow::RollupDaily(rollup) => {
+                    let sql = if matches!(row, TransferRow::RollupHourly(_)) {
+                        include_str!("../../../queries/mysql/transfer_write_rollups_hourly.sql")
+                    } else {
+                        include_str!("../../../queries/mysql/transfer_write_rollups_daily.sql")
+                    };
+
+                    sqlx::query(sql)
+                        .bind(rollup.node)
+                        .bind(rollup.bucket.timestamp())
+                        .bind(rollup.samples)
+                        .bind(rollup.temperature_min)
+                        .bind(rollup.temperature_max)
+                        .bind(rollup.temperature_avg)
+                        .bind(rollup.humidity_min)
+                        .bind(rollup.humidity_max)
+                        .bind(rollup.humidity_avg)
+                        .bind(rollup.air_pressure_min)
+                        .bind(rollup.air_pressure_max)
+                        .bind(rollup.air_pressure_avg)
+                        .bind(rollup.battery_min)
+                        .bind(rollup.battery_max)
+                        .bind(rollup.battery_avg)
+                        .bind(rollup.rssi_min)
+                        .bind(rollup.rssi_max)
+                        .bind(rollup.rssi_avg)
+                }
+            };
+
+            query.execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::get_firmwares()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_firmwares(&self) -> Result<Vec<FirmwareEntry>, Error> {
+        let raw_results = sqlx::query(include_str!("../../../queries/mysql/get_firmwares.sql"))
+            .fetch_all(&self.0)
+            .await?;
+
+        // MySQL does not have arrays so we need to deal with JSON
+
+        let mut results = Vec::with_capacity(raw_results.len());
+
+        for result in raw_results {
+            let restrict_json: Option<String> = result.get(5);
+
+            let restrict = restrict_json.map(|json| {
+                serde_json::from_str::<Vec<i32>>(&json).expect("Invalid firmware restrict format")
+            });
+
+            let fwe = FirmwareEntry {
+                id: result.get(0),
+                version: Version::parse(result.get::<&str, _>(1)).expect("Invalid version string"),
+                size: result.get(2),
+                blob: result.get(3),
+                added: result.get(4),
+                restrict,
+            };
+
+            results.push(fwe);
+        }
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "MysqlClient::upload_firmware()",
+        level = "debug",
+        skip(self, blob),
+        err
+    )]
+    async fn upload_firmware(
+        &self,
+        blob: Vec<u8>,
+        version: Version,
+        restrict_nodes: Option<Vec<NodeId>>,
+    ) -> Result<(), Error> {
+        let restrict_json = restrict_nodes.map(|array| {
+            serde_json::to_string(&array).expect("Failed to serialize restrict_nodes to JSON")
+        });
+
+        sqlx::query(include_str!("../../../queries/mysql/push_firmware.sql"))
+            .bind(i16::from(version.major()))
+            .bind(i16::from(version.middle()))
+            .bind(i16::from(version.minor()))
+            .bind(blob)
+            .bind(restrict_json)
+            .execute(&self.0)
+            .await?;
+        Ok(())
+    }
+}
+
+impl Drop for MysqlClient {
+    fn drop(&mut self) {
+        debug!("Closing MySQL database");
+        tokio::task::block_in_place(move || {
+            tokio::runtime::Handle::current().block_on(async move {
+                self.0.close().await;
+            });
+        });
+    }
+}
+
+fn measurement_entry(row: &MySqlRow) -> Result<MeasurementEntry, Error> {
+    Ok(MeasurementEntry {
+        when: super::timestamp(row.get(0)),
+        temperature: row.get(1),
+        humidity: row.get::<i16, _>(2).try_into()?,
+        air_pressure: match row.get::<Option<i16>, _>(3) {
+            Some(value) => Some(value.try_into()?),
+            None => None,
+        },
+        cpu_temp: row.get(4),
+        battery: row.get(5),
+        wifi_ssid: row.get(6),
+        wifi_rssi: row.get::<i16, _>(7).try_into()?,
+        flags: SensorFlags::from_bits(row.get::<i32, _>(8).try_into()?),
+        raw: RawReadings {
+            temperature: row.get(9),
+            humidity: row.get::<i16, _>(10).try_into()?,
+            air_pressure: match row.get::<Option<i16>, _>(11) {
+                Some(value) => Some(value.try_into()?),
+                None => None,
+            },
+            battery: row.get(12),
+        },
+    })
+}
+
+fn aggregate_entry(row: &MySqlRow) -> AggregateEntry {
+    let summary = |first| Summary {
+        min: row.get(first),
+        max: row.get(first + 1),
+        avg: row.get(first + 2),
+    };
+
+    AggregateEntry {
+        node: row.get(0),
+        bucket: super::timestamp(row.get(1)),
+        samples: row.get(2),
+        temperature: summary(3),
+        humidity: summary(6),
+        air_pressure: row.get::<Option<f64>, _>(9).is_some().then(|| summary(9)),
+        battery: summary(12),
+        wifi_rssi: summary(15),
+    }
+}
+
+fn device_entry(row: &MySqlRow) -> DeviceEntry {
+    DeviceEntry {
+        id: row.get(0),
+        mac: row.get(1),
+        note: row.get(2),
+        altitude: row.get(3),
+    }
+}
+
+fn notification_entry(row: &MySqlRow) -> NotificationEntry {
+    NotificationEntry {
+        id: row.get(0),
+        node: row.get(1),
+        when: super::timestamp(row.get(2)),
+        content: row.get(3),
+        read: row.get(4),
+        severity: row.get(5),
+        kind: row.get(6),
+        title: row.get(7),
+    }
+}
+
+#[allow(clippy::too_many_lines)] // one arm per table
+fn transfer_row(table: TransferTable, row: &MySqlRow) -> Result<TransferRow, Error> {
+    let row = match table {
+        TransferTable::Devices => TransferRow::Device(DeviceRow {
+            id: row.get(0),
+            mac_address: row.get(1),
+            location: row.get(2),
+            note: row.get(3),
+            altitude: row.get(4),
+        }),
+        TransferTable::Settings => TransferRow::Settings(SettingsRow {
+            id: row.get(0),
+            node: row.get(1),
+            battery_ignore: row.get(2),
+            ota: row.get(3),
+            sleep_time: row.get(4),
+            sbop: row.get(5),
+            mute_notifications: row.get(6),
+            device_specific: row.get(7),
+            muted_until: row.get::<Option<i64>, _>(8).map(super::timestamp),
+        }),
+        TransferTable::Measurements => TransferRow::Measurement(MeasurementRow {
+            id: row.get(0),
+            node: row.get(1),
+            when: super::timestamp(row.get(2)),
+            temperature: row.get(3),
+            humidity: row.get(4),
+            air_pressure: row.get(5),
+            cpu_temp: row.get(6),
+            battery: row.get(7),
+            wifi_ssid: row.get(8),
+            wifi_rssi: row.get(9),
+            flags: row.get(10),
+            raw_temperature: row.get(11),
+            raw_humidity: row.get(12),
+            raw_air_pressure: row.get(13),
+            raw_battery: row.get(14),
+        }),
+        TransferTable::Notifications => TransferRow::Notification(NotificationRow {
+            id: row.get(0),
+            node: row.get(1),
+            when: super::timestamp(row.get(2)),
+            content: row.get(3),
+            read: row.get(4),
+            severity: row.get(5),
+            kind: row.get(6),
+            title: row.get(7),
+            escalations: row.get(8),
+            escalated: row.get::<Option<i64>, _>(9).map(super::timestamp),
+        }),
+        TransferTable::Firmwares => TransferRow::Firmware(FirmwareRow {
+            id: row.get(0),
+            version_major: row.get(1),
+            version_middle: row.get(2),
+            version_minor: row.get(3),
+            firmware: row.get(4),
+            added_date: super::timestamp(row.get(5)),
+            restrict_nodes: row
+                .get::<Option<&str>, _>(6)
+                .map(serde_json::from_str)
+                .transpose()?,
+        }),
+        TransferTable::FirmwareStats => TransferRow::FirmwareStat(FirmwareStatRow {
+            id: row.get(0),
+            node: row.get(1),
+            from_version_major: row.get(2),
+            from_version_middle: row.get(3),
+            from_version_minor: row.get(4),
+            to_version_major: row.get(5),
+            to_version_middle: row.get(6),
+            to_version_minor: row.get(7),
+            when: super::timestamp(row.get(8)),
+            success: row.get(9),
+        }),
+        TransferTable::Quarantine => TransferRow::Quarantine(QuarantineRow {
+            id: row.get(0),
+            node: row.get(1),
+            when: super::timestamp(row.get(2)),
+            temperature: row.get(3),
+            humidity: row.get(4),
+            air_pressure: row.get(5),
+            cpu_temp: row.get(6),
+            battery: row.get(7),
+            wifi_ssid: row.get(8),
+            wifi_rssi: row.get(9),
+            reason: row.get(10),
+        }),
+        TransferTable::Calibrations => TransferRow::Calibration(CalibrationRow {
+            node: row.get(0),
+            temperature_offset: row.get(1),
+            temperature_scale: row.get(2),
+            humidity_offset: row.get(3),
+            air_pressure_offset: row.get(4),
+            battery_scale: row.get(5),
+        }),
+        TransferTable::AlertStates => TransferRow::AlertState(AlertStateRow {
+            node: row.get(0),
+            rule: row.get(1),
+            active: row.get(2),
+            streak: row.get(3),
+            changed: super::timestamp(row.get(4)),
+        }),
+        TransferTable::RollupsHourly => TransferRow::RollupHourly(rollup_row(row)),
+        TransferTable::RollupsDaily => TransferRow::RollupDaily(rollup_row(row)),
+    };
+
+    Ok(row)
+}
+
+fn rollup_row(row: &MySqlRow) -> RollupRow {
+    RollupRow {
+        node: row.get(0),
+        bucket: super::timestamp(row.get(1)),
+        samples: row.get(2),
+        temperature_min: row.get(3),
+        temperature_max: row.get(4),
+        temperature_avg: row.get(5),
+        humidity_min: row.get(6),
+        humidity_max: row.get(7),
+        humidity_avg: row.get(8),
+        air_pressure_min: row.get(9),
+        air_pressure_max: row.get(10),
+        air_pressure_avg: row.get(11),
+        battery_min: row.get(12),
+        battery_max: row.get(13),
+        battery_avg: row.get(14),
+        rssi_min: row.get(15),
+        rssi_max: row.get(16),
+        rssi_avg: row.get(17),
+    }
+}
+
+/// Statements of a migration, which are separated by semicolons at the end of a line. Comments are kept.
+fn statements(sql: &str) -> impl Iterator<Item = &str> {
+    sql.split(";\n").map(str::trim).filter(|statement| {
+        statement
+            .lines()
+            .any(|line| !line.trim().is_empty() && !line.trim_start().starts_with("--"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::db::{DatabaseBackend, backend_tests, migrations::SCHEMA_VERSION};
+    use std::{env, sync::LazyLock};
+    use tokio::sync::{Mutex, MutexGuard};
+
+    /// Every test uses the same database, so they must not run at the same time.
+    static DATABASE: LazyLock<Mutex<()>> = LazyLock::new(Mutex::default);
+
+    /// Connects to the database given by the `PWMP_TEST_MYSQL_*` variables, which defaults to the one started
+    /// by the command in the README. Everything in it is erased.
+    async fn connect() -> (MutexGuard<'static, ()>, MysqlClient) {
+        let guard = DATABASE.lock().await;
+        let var = |name: &str, default: &str| {
+            env::var(format!("PWMP_TEST_MYSQL_{name}")).unwrap_or_else(|_| default.to_string())
+        };
+        let client = MysqlClient::new(
+            &var("HOST", "127.0.0.1"),
+            var("PORT", "3306").parse().unwrap(),
+            &var("USER", "pixelweather"),
+            &var("PASSWORD", "pixelweather"),
+            &var("NAME", "pixelweather"),
+            false,
+        )
+        .await
+        .unwrap();
+        client.erase(EraseOptions::Everything).await.unwrap();
+
+        (guard, client)
+    }
+
+    /// Like [`connect()`], but with the schema migrated and two nodes with IDs 1 and 2.
+    async fn client() -> (MutexGuard<'static, ()>, MysqlClient) {
+        let (guard, client) = connect().await;
+        client.run_migrations().await.unwrap();
+
+        sqlx::raw_sql(
+            "INSERT INTO devices (mac_address) VALUES ('AA:BB:CC:DD:EE:01'), ('AA:BB:CC:DD:EE:02');
+            INSERT INTO settings (node) VALUES (1), (2);",
+        )
+        .execute(&client.0)
+        .await
+        .unwrap();
+
+        (guard, client)
+    }
+
+    #[test]
+    fn splits_migrations_into_statements() {
+        let initial: Vec<_> = statements(migrations::MYSQL[0].sql).collect();
+
+        // Six tables and five indexes
+        assert_eq!(initial.len(), 11);
+        assert!(initial[0].starts_with("CREATE TABLE"));
+        assert!(initial[6].ends_with(
+            "CREATE INDEX idx_measurements_node_when ON measurements (node, `when` DESC)"
+        ));
+
+        for migration in migrations::MYSQL {
+            assert_ne!(statements(migration.sql).count(), 0, "{}", migration.name);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MariaDB or MySQL database"]
+    async fn resumes_interrupted_migration() {
+        let (_guard, client) = connect().await;
+
+        // The first migration failed after its first two statements
+        let initial = &migrations::MYSQL[0];
+        sqlx::raw_sql(include_str!(
+            "../../../queries/mysql/create_schema_migrations.sql"
+        ))
+        .execute(&client.0)
+        .await
+        .unwrap();
+        for sql in statements(initial.sql).take(2) {
+            sqlx::raw_sql(sql).execute(&client.0).await.unwrap();
+        }
+        sqlx::query(include_str!(
+            "../../../queries/mysql/set_migration_progress.sql"
+        ))
+        .bind(initial.version)
+        .bind(2)
+        .execute(&client.0)
+        .await
+        .unwrap();
+
+        assert_eq!(
+            client.run_migrations().await.unwrap().len(),
+            migrations::MYSQL.len()
+        );
+        assert_eq!(client.get_schema_version().await.unwrap(), SCHEMA_VERSION);
+
+        let progress = sqlx::query(include_str!(
+            "../../../queries/mysql/get_migration_progress.sql"
+        ))
+        .bind(initial.version)
+        .fetch_optional(&client.0)
+        .await
+        .unwrap();
+        assert!(progress.is_none());
+
+        client.erase(EraseOptions::Everything).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MariaDB or MySQL database"]
+    async fn offers_newest_permitted_firmware() {
+        let (_guard, client) = client().await;
+        backend_tests::offers_newest_permitted_firmware(&client).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MariaDB or MySQL database"]
+    async fn skips_attempted_firmware() {
+        let (_guard, client) = client().await;
+        backend_tests::skips_attempted_firmware(&client).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MariaDB or MySQL database"]
+    async fn erases_content_but_keeps_devices() {
+        let (_guard, client) = client().await;
+        backend_tests::erases_content_but_keeps_devices(&client).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MariaDB or MySQL database"]
+    async fn erases_content() {
+        let (_guard, client) = client().await;
+        backend_tests::erases_content(&client).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MariaDB or MySQL database"]
+    async fn erases_everything() {
+        let (_guard, client) = client().await;
+        backend_tests::erases_everything(&client).await;
+    }
+}
@@ -1,16 +1,32 @@
-use super::{EraseOptions, FirmwareBlob, FirmwareEntry, NodeId, SleepTime, UpdateStatId};
-use crate::error::Error;
-use pwmp_client::pwmp_msg::{
-    aliases::{AirPressure, BatteryVoltage, Humidity, Rssi, Temperature},
-    mac::Mac,
-    settings::NodeSettings,
-    version::Version,
+use super::{
+    AggregateEntry, AggregateStream, AlertStateEntry, BatterySample, CalibrationEntry, DeviceEntry,
+    EraseOptions, FirmwareBlob, FirmwareEntry, MeasurementEntry, MeasurementRange,
+    MeasurementStream, NodeId, NotificationEntry, NotificationId, PrunableTable, RawReadings,
+    SettingsEntry, SleepTime, Summary, UpdateStatId, WifiSample,
+    migrations::{self, AppliedMigration, Migration},
+    transfer::{
+        AlertStateRow, CalibrationRow, DeviceRow, FirmwareRow, FirmwareStatRow, MeasurementRow,
+        NotificationRow, QuarantineRow, RollupRow, SettingsRow, TableState, TransferRow,
+        TransferTable,
+    },
 };
+use crate::{
+    error::Error,
+    server::{
+        calibration::Calibration,
+        config::ValidationConfig,
+        diagnostics::SensorFlags,
+        notification_client::{Notification, Severity},
+    },
+};
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use pwmp_client::pwmp_msg::{mac::Mac, settings::NodeSettings, version::Version};
 use sqlx::{
     Pool, Postgres, Row,
-    postgres::{PgConnectOptions, PgPoolOptions, PgSslMode},
+    postgres::{PgConnectOptions, PgPoolOptions, PgRow, PgSslMode},
 };
-use std::time::Duration;
+use std::{io::Write, time::Duration};
 use tracing::debug;
 
 pub struct PostgresClient(Pool<Postgres>);
@@ -33,155 +49,938 @@ impl PostgresClient {
             .database(name)
             .application_name("pwmp-server");
 
-        if ssl {
-            opts = opts.ssl_mode(PgSslMode::Require);
-        }
+        if ssl {
+            opts = opts.ssl_mode(PgSslMode::Require);
+        }
+
+        let pool = PgPoolOptions::new()
+            .max_connections(3)
+            .min_connections(1)
+            .acquire_timeout(Duration::from_secs(3))
+            .idle_timeout(Duration::from_hours(1))
+            .max_lifetime(None)
+            .connect_with(opts)
+            .await?;
+
+        Ok(Self(pool))
+    }
+
+    pub async fn get_last_os_update_stat_for_node(
+        &self,
+        node_id: NodeId,
+    ) -> Result<Option<UpdateStatId>, Error> {
+        let row = sqlx::query(include_str!(
+            "../../../queries/postgres/get_last_update_event.sql"
+        ))
+        .bind(node_id)
+        .fetch_optional(&self.0)
+        .await?;
+
+        row.map_or_else(|| Ok(None), |row| Ok(Some(row.get(0))))
+    }
+
+    async fn table_exists(&self, table: &str) -> Result<bool, Error> {
+        let row = sqlx::query(include_str!("../../../queries/postgres/table_exists.sql"))
+            .bind(table)
+            .fetch_one(&self.0)
+            .await?;
+
+        Ok(row.get(0))
+    }
+}
+
+#[async_trait::async_trait]
+impl super::DatabaseBackend for PostgresClient {
+    #[tracing::instrument(
+        name = "PostgresClient::authorize_device()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn authorize_device(&self, mac: &Mac) -> Result<Option<NodeId>, Error> {
+        let mac = mac.to_string();
+
+        let id = sqlx::query_scalar(include_str!(
+            "../../../queries/postgres/get_device_by_mac.sql"
+        ))
+        .bind(mac)
+        .fetch_optional(&self.0)
+        .await?;
+
+        Ok(id)
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::get_devices()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_devices(&self) -> Result<Vec<DeviceEntry>, Error> {
+        let results = sqlx::query(include_str!("../../../queries/postgres/get_devices.sql"))
+            .fetch_all(&self.0)
+            .await?
+            .iter()
+            .map(device_entry)
+            .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::get_device()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_device(&self, node: NodeId) -> Result<Option<DeviceEntry>, Error> {
+        let result = sqlx::query(include_str!("../../../queries/postgres/get_device.sql"))
+            .bind(node)
+            .fetch_optional(&self.0)
+            .await?;
+
+        Ok(result.as_ref().map(device_entry))
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::create_notification()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn create_notification(&self, notification: &Notification) -> Result<(), Error> {
+        sqlx::query(include_str!(
+            "../../../queries/postgres/create_notification.sql"
+        ))
+        .bind(notification.node)
+        .bind(notification.body.as_ref())
+        .bind(notification.severity.as_str())
+        .bind(notification.kind.as_str())
+        .bind(notification.title.as_ref())
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::get_notifications()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_notifications(
+        &self,
+        unread_only: bool,
+        node: Option<NodeId>,
+        since: Option<Duration>,
+    ) -> Result<Vec<NotificationEntry>, Error> {
+        let since: Option<i64> = match since {
+            Some(age) => Some(age.as_secs().try_into()?),
+            None => None,
+        };
+
+        let results = sqlx::query(include_str!(
+            "../../../queries/postgres/get_notifications.sql"
+        ))
+        .bind(unread_only)
+        .bind(node)
+        .bind(since)
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(notification_entry)
+        .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::mark_notifications_read()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn mark_notifications_read(&self, id: Option<NotificationId>) -> Result<u64, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/postgres/mark_notifications_read.sql"
+        ))
+        .bind(id)
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::purge_notifications()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn purge_notifications(&self, older_than: Duration) -> Result<u64, Error> {
+        let older_than: i64 = older_than.as_secs().try_into()?;
+
+        let result = sqlx::query(include_str!(
+            "../../../queries/postgres/purge_notifications.sql"
+        ))
+        .bind(older_than)
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::get_unacknowledged_notifications()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_unacknowledged_notifications(
+        &self,
+        after: Duration,
+        max_escalations: u32,
+        min_severity: Severity,
+    ) -> Result<Vec<NotificationEntry>, Error> {
+        let after: i64 = after.as_secs().try_into()?;
+        let max_escalations: i32 = max_escalations.try_into()?;
+
+        let results = sqlx::query(include_str!(
+            "../../../queries/postgres/get_unacknowledged_notifications.sql"
+        ))
+        .bind(after)
+        .bind(max_escalations)
+        .bind(min_severity.level())
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(notification_entry)
+        .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::mark_notification_escalated()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn mark_notification_escalated(&self, id: NotificationId) -> Result<(), Error> {
+        sqlx::query(include_str!(
+            "../../../queries/postgres/mark_notification_escalated.sql"
+        ))
+        .bind(id)
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::get_settings_entry()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn get_settings_entry(&self, node_id: NodeId) -> Result<Option<SettingsEntry>, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/postgres/get_device_settings.sql"
+        ))
+        .bind(node_id)
+        .fetch_optional(&self.0)
+        .await?;
+
+        let result = match result {
+            Some(row) => Some(SettingsEntry {
+                settings: NodeSettings {
+                    battery_ignore: row.get(0),
+                    ota: row.get(1),
+                    sleep_time: row.get::<SleepTime, _>(2).try_into()?,
+                    sbop: row.get(3),
+                    mute_notifications: row.get(4),
+                },
+                muted_until: row.get::<Option<i64>, _>(5).map(super::timestamp),
+            }),
+            None => None,
+        };
+
+        Ok(result)
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::set_device_altitude()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn set_device_altitude(
+        &self,
+        node: NodeId,
+        altitude: Option<f32>,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/postgres/set_device_altitude.sql"
+        ))
+        .bind(node)
+        .bind(altitude)
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::set_notifications_muted()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn set_notifications_muted(
+        &self,
+        node_id: NodeId,
+        muted: bool,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/postgres/set_notifications_muted.sql"
+        ))
+        .bind(node_id)
+        .bind(muted)
+        .bind(until.map(|when| when.timestamp()))
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::post_results()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn post_measurements(
+        &self,
+        node: NodeId,
+        measurement: &MeasurementEntry,
+    ) -> Result<(), Error> {
+        let air_pressure: Option<i16> = match measurement.air_pressure {
+            Some(value) => Some(value.try_into()?),
+            None => None,
+        };
+        let raw_air_pressure: Option<i16> = match measurement.raw.air_pressure {
+            Some(value) => Some(value.try_into()?),
+            None => None,
+        };
+
+        sqlx::query(include_str!(
+            "../../../queries/postgres/post_measurements.sql"
+        ))
+        .bind(node)
+        .bind(measurement.temperature)
+        .bind(i16::from(measurement.humidity))
+        .bind(air_pressure)
+        .bind(measurement.cpu_temp)
+        .bind(measurement.battery)
+        .bind(&measurement.wifi_ssid)
+        .bind(i16::from(measurement.wifi_rssi))
+        .bind(i32::from(measurement.flags.bits()))
+        .bind(measurement.raw.temperature)
+        .bind(i16::from(measurement.raw.humidity))
+        .bind(raw_air_pressure)
+        .bind(measurement.raw.battery)
+        .execute(&self.0)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::get_calibration()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_calibration(&self, node: NodeId) -> Result<Option<Calibration>, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/postgres/get_calibration.sql"
+        ))
+        .bind(node)
+        .fetch_optional(&self.0)
+        .await?;
+
+        Ok(result.map(|row| Calibration {
+            temperature_offset: row.get(0),
+            temperature_scale: row.get(1),
+            humidity_offset: row.get(2),
+            air_pressure_offset: row.get(3),
+            battery_scale: row.get(4),
+        }))
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::get_calibrations()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_calibrations(&self) -> Result<Vec<CalibrationEntry>, Error> {
+        let results = sqlx::query(include_str!(
+            "../../../queries/postgres/get_calibrations.sql"
+        ))
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(|row| CalibrationEntry {
+            node: row.get(0),
+            calibration: Calibration {
+                temperature_offset: row.get(1),
+                temperature_scale: row.get(2),
+                humidity_offset: row.get(3),
+                air_pressure_offset: row.get(4),
+                battery_scale: row.get(5),
+            },
+        })
+        .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::set_calibration()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn set_calibration(&self, node: NodeId, calibration: &Calibration) -> Result<(), Error> {
+        sqlx::query(include_str!(
+            "../../../queries/postgres/set_calibration.sql"
+        ))
+        .bind(node)
+        .bind(calibration.temperature_offset)
+        .bind(calibration.temperature_scale)
+        .bind(calibration.humidity_offset)
+        .bind(calibration.air_pressure_offset)
+        .bind(calibration.battery_scale)
+        .execute(&self.0)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::delete_calibration()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn delete_calibration(&self, node: NodeId) -> Result<bool, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/postgres/delete_calibration.sql"
+        ))
+        .bind(node)
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::recalibrate_measurements()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn recalibrate_measurements(
+        &self,
+        node: NodeId,
+        calibration: &Calibration,
+        validation: &ValidationConfig,
+        since: Option<Duration>,
+    ) -> Result<(u64, u64), Error> {
+        let since: Option<i64> = match since {
+            Some(since) => Some(since.as_secs().try_into()?),
+            None => None,
+        };
+
+        let mut tx = self.0.begin().await?;
+
+        let total: i64 = sqlx::query(include_str!(
+            "../../../queries/postgres/count_node_measurements.sql"
+        ))
+        .bind(node)
+        .bind(since)
+        .fetch_one(&mut *tx)
+        .await?
+        .get(0);
+
+        let result = sqlx::query(include_str!(
+            "../../../queries/postgres/recalibrate_measurements.sql"
+        ))
+        .bind(node)
+        .bind(calibration.temperature_offset)
+        .bind(calibration.temperature_scale)
+        .bind(calibration.humidity_offset)
+        .bind(calibration.air_pressure_offset)
+        .bind(calibration.battery_scale)
+        .bind(since)
+        .bind(validation.temperature.min)
+        .bind(validation.temperature.max)
+        .bind(i16::from(validation.humidity.min))
+        .bind(i16::from(validation.humidity.max))
+        .bind(i32::from(validation.air_pressure.min))
+        .bind(i32::from(validation.air_pressure.max))
+        .bind(validation.battery.min)
+        .bind(validation.battery.max)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let updated = result.rows_affected();
+        Ok((updated, u64::try_from(total)? - updated))
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::import_measurements()",
+        level = "debug",
+        skip(self, measurements),
+        fields(count = measurements.len()),
+        err,
+        ret
+    )]
+    async fn import_measurements(
+        &self,
+        node: NodeId,
+        measurements: &[MeasurementEntry],
+        skip_duplicates: bool,
+        dry_run: bool,
+    ) -> Result<u64, Error> {
+        // COPY is much faster than separate inserts, but can't skip duplicates, so the rows go through a temporary table
+        let mut data = Vec::new();
+
+        for measurement in measurements {
+            writeln!(
+                data,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                measurement.when.timestamp(),
+                measurement.temperature,
+                measurement.humidity,
+                measurement
+                    .air_pressure
+                    .map_or_else(|| "\\N".to_string(), |value| value.to_string()),
+                measurement.cpu_temp,
+                measurement.battery,
+                copy_text(&measurement.wifi_ssid),
+                measurement.wifi_rssi
+            )?;
+        }
+
+        let mut tx = self.0.begin().await?;
+
+        sqlx::raw_sql(include_str!(
+            "../../../queries/postgres/create_measurements_import.sql"
+        ))
+        .execute(&mut *tx)
+        .await?;
+
+        let mut copy = tx
+            .copy_in_raw(include_str!(
+                "../../../queries/postgres/copy_measurements_import.sql"
+            ))
+            .await?;
+        copy.send(data).await?;
+        copy.finish().await?;
+
+        let inserted = sqlx::query(include_str!(
+            "../../../queries/postgres/import_measurements.sql"
+        ))
+        .bind(node)
+        .bind(skip_duplicates)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+
+        Ok(inserted)
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::refresh_rollups()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn refresh_rollups(
+        &self,
+        node: Option<NodeId>,
+        from: Option<DateTime<Utc>>,
+    ) -> Result<u64, Error> {
+        let from = from.map(|from| from.timestamp());
+        let mut updated = 0;
+
+        for sql in [
+            include_str!("../../../queries/postgres/refresh_rollups_hourly.sql"),
+            include_str!("../../../queries/postgres/refresh_rollups_daily.sql"),
+        ] {
+            updated += sqlx::query(sql)
+                .bind(node)
+                .bind(from)
+                .execute(&self.0)
+                .await?
+                .rows_affected();
+        }
+
+        Ok(updated)
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::quarantine_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn quarantine_measurements(
+        &self,
+        node: NodeId,
+        measurement: &MeasurementEntry,
+        reason: &str,
+    ) -> Result<(), Error> {
+        sqlx::query(include_str!(
+            "../../../queries/postgres/quarantine_measurements.sql"
+        ))
+        .bind(node)
+        .bind(measurement.temperature)
+        .bind(i16::from(measurement.humidity))
+        .bind(measurement.air_pressure.map(i32::from))
+        .bind(measurement.cpu_temp)
+        .bind(measurement.battery)
+        .bind(&measurement.wifi_ssid)
+        .bind(i16::from(measurement.wifi_rssi))
+        .bind(reason)
+        .execute(&self.0)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::get_last_measurement()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_last_measurement(&self, node: NodeId) -> Result<Option<MeasurementEntry>, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/postgres/get_last_measurement.sql"
+        ))
+        .bind(node)
+        .fetch_optional(&self.0)
+        .await?;
+
+        let result = match result {
+            Some(row) => Some(measurement_entry(&row)?),
+            None => None,
+        };
+
+        Ok(result)
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::get_latest_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_latest_measurements(
+        &self,
+        max_age: Duration,
+    ) -> Result<Vec<(NodeId, MeasurementEntry)>, Error> {
+        let max_age: i64 = max_age.as_secs().try_into()?;
+
+        sqlx::query(include_str!(
+            "../../../queries/postgres/get_latest_measurements.sql"
+        ))
+        .bind(max_age)
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(|row| Ok((row.get(13), measurement_entry(row)?)))
+        .collect()
+    }
 
-        let pool = PgPoolOptions::new()
-            .max_connections(3)
-            .min_connections(1)
-            .acquire_timeout(Duration::from_secs(3))
-            .idle_timeout(Duration::from_hours(1))
-            .max_lifetime(None)
-            .connect_with(opts)
-            .await?;
+    #[tracing::instrument(
+        name = "PostgresClient::get_recent_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_recent_measurements(
+        &self,
+        node: NodeId,
+        count: u32,
+    ) -> Result<Vec<MeasurementEntry>, Error> {
+        sqlx::query(include_str!(
+            "../../../queries/postgres/get_recent_measurements.sql"
+        ))
+        .bind(node)
+        .bind(i64::from(count))
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(measurement_entry)
+        .collect()
+    }
 
-        Ok(Self(pool))
+    #[tracing::instrument(
+        name = "PostgresClient::stream_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn stream_measurements(
+        &self,
+        range: MeasurementRange,
+    ) -> Result<MeasurementStream<'_>, Error> {
+        let stream = sqlx::query(include_str!(
+            "../../../queries/postgres/export_measurements.sql"
+        ))
+        .bind(range.node)
+        .bind(range.from.map(|from| from.timestamp()))
+        .bind(range.to.map(|to| to.timestamp()))
+        .fetch(&self.0)
+        .map(|row| -> Result<_, Error> {
+            let row = row?;
+            Ok((row.get(13), measurement_entry(&row)?))
+        });
+
+        Ok(stream.boxed())
     }
 
-    pub async fn get_last_os_update_stat_for_node(
+    #[tracing::instrument(
+        name = "PostgresClient::stream_aggregates()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn stream_aggregates(
         &self,
-        node_id: NodeId,
-    ) -> Result<Option<UpdateStatId>, Error> {
-        let row = sqlx::query(include_str!(
-            "../../../queries/postgres/get_last_update_event.sql"
+        range: MeasurementRange,
+        period: Duration,
+    ) -> Result<AggregateStream<'_>, Error> {
+        let period: i64 = period.as_secs().try_into()?;
+
+        let stream = sqlx::query(include_str!(
+            "../../../queries/postgres/export_aggregates.sql"
         ))
-        .bind(node_id)
-        .fetch_optional(&self.0)
-        .await?;
+        .bind(range.node)
+        .bind(range.from.map(|from| from.timestamp()))
+        .bind(range.to.map(|to| to.timestamp()))
+        .bind(period)
+        .fetch(&self.0)
+        .map(|row| Ok(aggregate_entry(&row?)));
 
-        row.map_or_else(|| Ok(None), |row| Ok(Some(row.get(0))))
+        Ok(stream.boxed())
     }
-}
 
-#[async_trait::async_trait]
-impl super::DatabaseBackend for PostgresClient {
     #[tracing::instrument(
-        name = "PostgresClient::authorize_device()",
+        name = "PostgresClient::get_battery_history()",
         level = "debug",
         skip(self),
-        err,
-        ret
+        err
     )]
-    async fn authorize_device(&self, mac: &Mac) -> Result<Option<NodeId>, Error> {
-        let mac = mac.to_string();
+    async fn get_battery_history(
+        &self,
+        node: NodeId,
+        window: Duration,
+        limit: u32,
+    ) -> Result<Vec<BatterySample>, Error> {
+        let window: i64 = window.as_secs().try_into()?;
 
-        let id = sqlx::query_scalar(include_str!(
-            "../../../queries/postgres/get_device_by_mac.sql"
+        let results = sqlx::query(include_str!(
+            "../../../queries/postgres/get_battery_history.sql"
         ))
-        .bind(mac)
-        .fetch_optional(&self.0)
-        .await?;
+        .bind(node)
+        .bind(window)
+        .bind(i64::from(limit))
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(|row| BatterySample {
+            when: super::timestamp(row.get(0)),
+            voltage: row.get(1),
+        })
+        .collect();
 
-        Ok(id)
+        Ok(results)
     }
 
     #[tracing::instrument(
-        name = "PostgresClient::create_notification()",
+        name = "PostgresClient::get_wifi_history()",
         level = "debug",
         skip(self),
         err
     )]
-    async fn create_notification(&self, node_id: NodeId, content: &str) -> Result<(), Error> {
-        sqlx::query(include_str!(
-            "../../../queries/postgres/create_notification.sql"
+    async fn get_wifi_history(
+        &self,
+        node: NodeId,
+        window: Duration,
+    ) -> Result<Vec<WifiSample>, Error> {
+        let window: i64 = window.as_secs().try_into()?;
+
+        let results = sqlx::query(include_str!(
+            "../../../queries/postgres/get_wifi_history.sql"
         ))
-        .bind(node_id)
-        .bind(content)
-        .execute(&self.0)
-        .await?;
-        Ok(())
+        .bind(node)
+        .bind(window)
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(|row| {
+            Ok(WifiSample {
+                when: super::timestamp(row.get(0)),
+                ssid: row.get(1),
+                rssi: row.get::<i16, _>(2).try_into()?,
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+
+        Ok(results)
     }
 
     #[tracing::instrument(
-        name = "PostgresClient::get_settings()",
+        name = "PostgresClient::get_alert_states()",
         level = "debug",
         skip(self),
-        err,
-        ret
+        err
     )]
-    async fn get_settings(&self, node_id: NodeId) -> Result<Option<NodeSettings>, Error> {
-        let result = sqlx::query(include_str!(
-            "../../../queries/postgres/get_device_settings.sql"
+    async fn get_alert_states(&self, node: NodeId) -> Result<Vec<AlertStateEntry>, Error> {
+        let rows = sqlx::query(include_str!(
+            "../../../queries/postgres/get_alert_states.sql"
         ))
-        .bind(node_id)
-        .fetch_optional(&self.0)
+        .bind(node)
+        .fetch_all(&self.0)
         .await?;
 
-        let result = match result {
-            Some(row) => Some(NodeSettings {
-                battery_ignore: row.get(0),
-                ota: row.get(1),
-                sleep_time: row.get::<SleepTime, _>(2).try_into()?,
-                sbop: row.get(3),
-                mute_notifications: row.get(4),
-            }),
-            None => None,
-        };
+        let mut results = Vec::with_capacity(rows.len());
+        for row in rows {
+            results.push(AlertStateEntry {
+                rule: row.get(0),
+                active: row.get(1),
+                streak: row.get::<i32, _>(2).try_into()?,
+            });
+        }
 
-        Ok(result)
+        Ok(results)
     }
 
     #[tracing::instrument(
-        name = "PostgresClient::post_results()",
+        name = "PostgresClient::set_alert_state()",
         level = "debug",
         skip(self),
-        err,
-        ret
+        err
     )]
-    async fn post_measurements(
+    async fn set_alert_state(
         &self,
         node: NodeId,
-        temp: Temperature,
-        hum: Humidity,
-        air_p: Option<AirPressure>,
-        cpu_temp: Temperature,
-        battery: BatteryVoltage,
-        wifi_ssid: &str,
-        wifi_rssi: Rssi,
+        rule: &str,
+        active: bool,
+        streak: u32,
     ) -> Result<(), Error> {
-        let signed_air_p: Option<i16> = match air_p {
-            Some(value) => Some(value.try_into()?),
-            None => None,
-        };
+        let streak: i32 = streak.try_into()?;
 
         sqlx::query(include_str!(
-            "../../../queries/postgres/post_measurements.sql"
+            "../../../queries/postgres/set_alert_state.sql"
         ))
         .bind(node)
-        .bind(temp)
-        .bind(i16::from(hum))
-        .bind(signed_air_p)
-        .bind(cpu_temp)
-        .bind(battery)
-        .bind(wifi_ssid)
-        .bind(i16::from(wifi_rssi))
+        .bind(rule)
+        .bind(active)
+        .bind(streak)
         .execute(&self.0)
         .await?;
-
         Ok(())
     }
 
+    fn migrations(&self) -> &'static [Migration] {
+        migrations::POSTGRES
+    }
+
     #[tracing::instrument(
-        name = "PostgresClient::run_migrations()",
+        name = "PostgresClient::get_applied_migrations()",
         level = "debug",
         skip(self),
         err
     )]
-    async fn run_migrations(&self) -> Result<(), Error> {
-        sqlx::raw_sql(include_str!("../../../queries/postgres/migrate.sql"))
-            .execute(&self.0)
-            .await?;
+    async fn get_applied_migrations(&self) -> Result<Vec<AppliedMigration>, Error> {
+        if self.table_exists("schema_migrations").await? {
+            let results = sqlx::query(include_str!(
+                "../../../queries/postgres/get_applied_migrations.sql"
+            ))
+            .fetch_all(&self.0)
+            .await?
+            .iter()
+            .map(|row| AppliedMigration {
+                version: row.get(0),
+                name: row.get(1),
+                applied: Some(super::timestamp(row.get(2))),
+            })
+            .collect();
+
+            return Ok(results);
+        }
+
+        // Created before migrations were versioned
+        if self.table_exists("devices").await? {
+            return Ok(vec![AppliedMigration {
+                version: 1,
+                name: migrations::INITIAL.to_string(),
+                applied: None,
+            }]);
+        }
+
+        Ok(Vec::new())
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::apply_migration()",
+        level = "debug",
+        skip(self, migration),
+        fields(version = migration.version),
+        err
+    )]
+    async fn apply_migration(&self, migration: &Migration) -> Result<(), Error> {
+        let mut tx = self.0.begin().await?;
+
+        sqlx::raw_sql(include_str!(
+            "../../../queries/postgres/create_schema_migrations.sql"
+        ))
+        .execute(&mut *tx)
+        .await?;
+        sqlx::raw_sql(migration.sql).execute(&mut *tx).await?;
+        sqlx::query(include_str!(
+            "../../../queries/postgres/record_migration.sql"
+        ))
+        .bind(migration.version)
+        .bind(migration.name)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
         Ok(())
     }
 
@@ -296,6 +1095,362 @@ impl super::DatabaseBackend for PostgresClient {
         Ok(())
     }
 
+    #[tracing::instrument(
+        name = "PostgresClient::prune()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn prune(
+        &self,
+        table: PrunableTable,
+        older_than: Duration,
+        limit: u32,
+    ) -> Result<u64, Error> {
+        let older_than: i64 = older_than.as_secs().try_into()?;
+        let sql = match table {
+            PrunableTable::Measurements => {
+                include_str!("../../../queries/postgres/prune_measurements.sql")
+            }
+            PrunableTable::Notifications => {
+                include_str!("../../../queries/postgres/prune_notifications.sql")
+            }
+            PrunableTable::FirmwareStats => {
+                include_str!("../../../queries/postgres/prune_firmware_stats.sql")
+            }
+            PrunableTable::Quarantine => {
+                include_str!("../../../queries/postgres/prune_quarantine.sql")
+            }
+        };
+
+        let result = sqlx::query(sql)
+            .bind(older_than)
+            .bind(i64::from(limit))
+            .execute(&self.0)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::count_prunable()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn count_prunable(
+        &self,
+        table: PrunableTable,
+        older_than: Duration,
+    ) -> Result<u64, Error> {
+        let older_than: i64 = older_than.as_secs().try_into()?;
+        let sql = match table {
+            PrunableTable::Measurements => {
+                include_str!("../../../queries/postgres/count_prunable_measurements.sql")
+            }
+            PrunableTable::Notifications => {
+                include_str!("../../../queries/postgres/count_prunable_notifications.sql")
+            }
+            PrunableTable::FirmwareStats => {
+                include_str!("../../../queries/postgres/count_prunable_firmware_stats.sql")
+            }
+            PrunableTable::Quarantine => {
+                include_str!("../../../queries/postgres/count_prunable_quarantine.sql")
+            }
+        };
+
+        let count: i64 = sqlx::query(sql)
+            .bind(older_than)
+            .fetch_one(&self.0)
+            .await?
+            .get(0);
+
+        Ok(count.try_into()?)
+    }
+
+    #[tracing::instrument(name = "PostgresClient::compact()", level = "debug", skip(self), err)]
+    async fn compact(&self, full: bool) -> Result<(), Error> {
+        let sql = if full {
+            include_str!("../../../queries/postgres/vacuum_full.sql")
+        } else {
+            include_str!("../../../queries/postgres/vacuum.sql")
+        };
+
+        sqlx::raw_sql(sql).execute(&self.0).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::get_table_state()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn get_table_state(&self, table: TransferTable) -> Result<TableState, Error> {
+        let sql = match table {
+            TransferTable::Devices => {
+                include_str!("../../../queries/postgres/transfer_state_devices.sql")
+            }
+            TransferTable::Settings => {
+                include_str!("../../../queries/postgres/transfer_state_settings.sql")
+            }
+            TransferTable::Measurements => {
+                include_str!("../../../queries/postgres/transfer_state_measurements.sql")
+            }
+            TransferTable::Notifications => {
+                include_str!("../../../queries/postgres/transfer_state_notifications.sql")
+            }
+            TransferTable::Firmwares => {
+                include_str!("../../../queries/postgres/transfer_state_firmwares.sql")
+            }
+            TransferTable::FirmwareStats => {
+                include_str!("../../../queries/postgres/transfer_state_firmware_stats.sql")
+            }
+            TransferTable::Quarantine => {
+                include_str!("../../../queries/postgres/transfer_state_quarantine.sql")
+            }
+            TransferTable::Calibrations => {
+                include_str!("../../../queries/postgres/transfer_state_calibrations.sql")
+            }
+            TransferTable::AlertStates => {
+                include_str!("../../../queries/postgres/transfer_state_alert_states.sql")
+            }
+            TransferTable::RollupsHourly => {
+                include_str!("../../../queries/postgres/transfer_state_rollups_hourly.sql")
+            }
+            TransferTable::RollupsDaily => {
+                include_str!("../../../queries/postgres/transfer_state_rollups_daily.sql")
+            }
+        };
+
+        let row = sqlx::query(sql).fetch_one(&self.0).await?;
+
+        Ok(TableState {
+            rows: row.get::<i64, _>(0).try_into()?,
+            last_id: row.get(1),
+        })
+    }
+
+    #[tracing::instrument(name = "PostgresClient::read_rows()", level = "debug", skip(self), err)]
+    async fn read_rows(
+        &self,
+        table: TransferTable,
+        after: i32,
+        limit: u32,
+    ) -> Result<Vec<TransferRow>, Error> {
+        let sql = match table {
+            TransferTable::Devices => {
+                include_str!("../../../queries/postgres/transfer_read_devices.sql")
+            }
+            TransferTable::Settings => {
+                include_str!("../../../queries/postgres/transfer_read_settings.sql")
+            }
+            TransferTable::Measurements => {
+                include_str!("../../../queries/postgres/transfer_read_measurements.sql")
+            }
+            TransferTable::Notifications => {
+                include_str!("../../../queries/postgres/transfer_read_notifications.sql")
+            }
+            TransferTable::Firmwares => {
+                include_str!("../../../queries/postgres/transfer_read_firmwares.sql")
+            }
+            TransferTable::FirmwareStats => {
+                include_str!("../../../queries/postgres/transfer_read_firmware_stats.sql")
+            }
+            TransferTable::Quarantine => {
+                include_str!("../../../queries/postgres/transfer_read_quarantine.sql")
+            }
+            TransferTable::Calibrations => {
+                include_str!("../../../queries/postgres/transfer_read_calibrations.sql")
+            }
+            TransferTable::AlertStates => {
+                include_str!("../../../queries/postgres/transfer_read_alert_states.sql")
+            }
+            TransferTable::RollupsHourly => {
+                include_str!("../../../queries/postgres/transfer_read_rollups_hourly.sql")
+            }
+            TransferTable::RollupsDaily => {
+                include_str!("../../../queries/postgres/transfer_read_rollups_daily.sql")
+            }
+        };
+
+        let rows = sqlx::query(sql)
+            .bind(after)
+            .bind(i64::from(limit))
+            .fetch_all(&self.0)
+            .await?;
+
+        Ok(rows.iter().map(|row| transfer_row(table, row)).collect())
+    }
+
+    #[tracing::instrument(
+        name = "PostgresClient::write_rows()",
+        level = "debug",
+        skip(self, rows),
+        err
+    )]
+    async fn write_rows(&self, rows: &[TransferRow]) -> Result<(), Error> {
+        let mut tx = self.0.begin().await?;
+
+        for row in rows {
+            let query = match row {
+                TransferRow::Device(row) => sqlx::query(include_str!(
+                    "../../../queries/postgres/transfer_write_devices.sql"
+                ))
+                .bind(row.id)
+                .bind(&row.mac_address)
+                .bind(&row.location)
+                .bind(&row.note)
+                .bind(row.altitude),
+                TransferRow::Settings(row) => sqlx::query(include_str!(
+                    "../../../queries/postgres/transfer_write_settings.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.battery_ignore)
+                .bind(row.ota)
+                .bind(row.sleep_time)
+                .bind(row.sbop)
+                .bind(row.mute_notifications)
+                .bind(&row.device_specific)
+                .bind(row.muted_until.map(|when| when.timestamp())),
+                TransferRow::Measurement(row) => sqlx::query(include_str!(
+                    "../../../queries/postgres/transfer_write_measurements.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.when.timestamp())
+                .bind(row.temperature)
+                .bind(row.humidity)
+                .bind(row.air_pressure)
+                .bind(row.cpu_temp)
+                .bind(row.battery)
+                .bind(&row.wifi_ssid)
+                .bind(row.wifi_rssi)
+                .bind(row.flags)
+                .bind(row.raw_temperature)
+                .bind(row.raw_humidity)
+                .bind(row.raw_air_pressure)
+                .bind(row.raw_battery),
+                TransferRow::Notification(row) => sqlx::query(include_str!(
+                    "../../../queries/postgres/transfer_write_notifications.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.when.timestamp())
+                .bind(&row.content)
+                .bind(row.read)
+                .bind(&row.severity)
+                .bind(&row.kind)
+                .bind(&row.title)
+                .bind(row.escalations)
+                .bind(row.escalated.map(|t| t.timestamp())),
+                TransferRow::Firmware(row) => sqlx::query(include_str!(
+                    "../../../queries/postgres/transfer_write_firmwares.sql"
+                ))
+                .bind(row.id)
+                .bind(row.version_major)
+                .bind(row.version_middle)
+                .bind(row.version_minor)
+                .bind(&row.firmware)
+                .bind(row.added_date.timestamp())
+                .bind(&row.restrict_nodes),
+                TransferRow::FirmwareStat(row) => sqlx::query(include_str!(
+                    "../../../queries/postgres/transfer_write_firmware_stats.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.from_version_major)
+                .bind(row.from_version_middle)
+                .bind(row.from_version_minor)
+                .bind(row.to_version_major)
+                .bind(row.to_version_middle)
+                .bind(row.to_version_minor)
+                .bind(row.when.timestamp())
+                .bind(row.success),
+                TransferRow::Quarantine(row) => sqlx::query(include_str!(
+                    "../../../queries/postgres/transfer_write_quarantine.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.when.timestamp())
+                .bind(row.temperature)
+                .bind(row.humidity)
+                .bind(row.air_pressure)
+                .bind(row.cpu_temp)
+                .bind(row.battery)
+                .bind(&row.wifi_ssid)
+                .bind(row.wifi_rssi)
+                .bind(&row.reason),
+                TransferRow::Calibration(row) => sqlx::query(include_str!(
+                    "../../../queries/postgres/transfer_write_calibrations.sql"
+                ))
+                .bind(row.node)
+                .bind(row.temperature_offset)
+                .bind(row.temperature_scale)
+                .bind(row.humidity_offset)
+                .bind(row.air_pressure_offset)
+                .bind(row.battery_scale),
+                TransferRow::AlertState(row) => sqlx::query(include_str!(
+                    "../../../queries/postgres/transfer_write_alert_states.sql"
+                ))
+                .bind(row.node)
+                .bind(&row.rule)
+                .bind(row.active)
+                .bind(row.streak)
+                .bind(row.changed.timestamp()),
+                TransferRow::RollupHourly(rollup) | TransferRow::RollupDaily(rollup) => {
+                    let sql = if matches!(row, TransferRow::RollupHourly(_)) {
+                        include_str!("../../../queries/postgres/transfer_write_rollups_hourly.sql")
+                    } else {
+                        include_str!("../../../queries/postgres/transfer_write_rollups_daily.sql")
+                    };
+
+                    sqlx::query(sql)
+                        .bind(rollup.node)
+                        .bind(rollup.bucket.timestamp())
+                        .bind(rollup.samples)
+                        .bind(rollup.temperature_min)
+                        .bind(rollup.temperature_max)
+                        .bind(rollup.temperature_avg)
+                        .bind(rollup.humidity_min)
+                        .bind(rollup.humidity_max)
+                        .bind(rollup.humidity_avg)
+                        .bind(rollup.air_pressure_min)
+                        .bind(rollup.air_pressure_max)
+                        .bind(rollup.air_pressure_avg)
+                        .bind(rollup.battery_min)
+                        .bind(rollup.battery_max)
+                        .bind(rollup.battery_avg)
+                        .bind(rollup.rssi_min)
+                        .bind(rollup.rssi_max)
+                        .bind(rollup.rssi_avg)
+                }
+            };
+
+            query.execute(&mut *tx).await?;
+        }
+
+        // Explicit IDs don't advance the sequences, so new rows would collide with the inserted ones
+        if let Some(last) = rows.last()
+            && let Some(id) = last.id()
+        {
+            sqlx::query(include_str!(
+                "../../../queries/postgres/transfer_set_sequence.sql"
+            ))
+            .bind(last.table().as_str())
+            .bind(i64::from(id))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
     #[tracing::instrument(
         name = "PostgresClient::get_firmwares()",
         level = "debug",
@@ -356,3 +1511,205 @@ impl Drop for PostgresClient {
         });
     }
 }
+
+fn measurement_entry(row: &PgRow) -> Result<MeasurementEntry, Error> {
+    Ok(MeasurementEntry {
+        when: super::timestamp(row.get(0)),
+        temperature: row.get(1),
+        humidity: row.get::<i16, _>(2).try_into()?,
+        air_pressure: match row.get::<Option<i16>, _>(3) {
+            Some(value) => Some(value.try_into()?),
+            None => None,
+        },
+        cpu_temp: row.get(4),
+        battery: row.get(5),
+        wifi_ssid: row.get(6),
+        wifi_rssi: row.get::<i16, _>(7).try_into()?,
+        flags: SensorFlags::from_bits(row.get::<i32, _>(8).try_into()?),
+        raw: RawReadings {
+            temperature: row.get(9),
+            humidity: row.get::<i16, _>(10).try_into()?,
+            air_pressure: match row.get::<Option<i16>, _>(11) {
+                Some(value) => Some(value.try_into()?),
+                None => None,
+            },
+            battery: row.get(12),
+        },
+    })
+}
+
+fn aggregate_entry(row: &PgRow) -> AggregateEntry {
+    let summary = |first| Summary {
+        min: row.get(first),
+        max: row.get(first + 1),
+        avg: row.get(first + 2),
+    };
+
+    AggregateEntry {
+        node: row.get(0),
+        bucket: super::timestamp(row.get(1)),
+        samples: row.get(2),
+        temperature: summary(3),
+        humidity: summary(6),
+        air_pressure: row.get::<Option<f64>, _>(9).is_some().then(|| summary(9)),
+        battery: summary(12),
+        wifi_rssi: summary(15),
+    }
+}
+
+/// Escape a value for the text format of COPY.
+fn copy_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn device_entry(row: &PgRow) -> DeviceEntry {
+    DeviceEntry {
+        id: row.get(0),
+        mac: row.get(1),
+        note: row.get(2),
+        altitude: row.get(3),
+    }
+}
+
+fn notification_entry(row: &PgRow) -> NotificationEntry {
+    NotificationEntry {
+        id: row.get(0),
+        node: row.get(1),
+        when: super::timestamp(row.get(2)),
+        content: row.get(3),
+        read: row.get(4),
+        severity: row.get(5),
+        kind: row.get(6),
+        title: row.get(7),
+    }
+}
+
+fn transfer_row(table: TransferTable, row: &PgRow) -> TransferRow {
+    match table {
+        TransferTable::Devices => TransferRow::Device(DeviceRow {
+            id: row.get(0),
+            mac_address: row.get(1),
+            location: row.get(2),
+            note: row.get(3),
+            altitude: row.get(4),
+        }),
+        TransferTable::Settings => TransferRow::Settings(SettingsRow {
+            id: row.get(0),
+            node: row.get(1),
+            battery_ignore: row.get(2),
+            ota: row.get(3),
+            sleep_time: row.get(4),
+            sbop: row.get(5),
+            mute_notifications: row.get(6),
+            device_specific: row.get(7),
+            muted_until: row.get::<Option<i64>, _>(8).map(super::timestamp),
+        }),
+        TransferTable::Measurements => TransferRow::Measurement(MeasurementRow {
+            id: row.get(0),
+            node: row.get(1),
+            when: super::timestamp(row.get(2)),
+            temperature: row.get(3),
+            humidity: row.get(4),
+            air_pressure: row.get(5),
+            cpu_temp: row.get(6),
+            battery: row.get(7),
+            wifi_ssid: row.get(8),
+            wifi_rssi: row.get(9),
+            flags: row.get(10),
+            raw_temperature: row.get(11),
+            raw_humidity: row.get(12),
+            raw_air_pressure: row.get(13),
+            raw_battery: row.get(14),
+        }),
+        TransferTable::Notifications => TransferRow::Notification(NotificationRow {
+            id: row.get(0),
+            node: row.get(1),
+            when: super::timestamp(row.get(2)),
+            content: row.get(3),
+            read: row.get(4),
+            severity: row.get(5),
+            kind: row.get(6),
+            title: row.get(7),
+            escalations: row.get(8),
+            escalated: row.get::<Option<i64>, _>(9).map(super::timestamp),
+        }),
+        TransferTable::Firmwares => TransferRow::Firmware(FirmwareRow {
+            id: row.get(0),
+            version_major: row.get(1),
+            version_middle: row.get(2),
+            version_minor: row.get(3),
+            firmware: row.get(4),
+            added_date: super::timestamp(row.get(5)),
+            restrict_nodes: row.get(6),
+        }),
+        TransferTable::FirmwareStats => TransferRow::FirmwareStat(FirmwareStatRow {
+            id: row.get(0),
+            node: row.get(1),
+            from_version_major: row.get(2),
+            from_version_middle: row.get(3),
+            from_version_minor: row.get(4),
+            to_version_major: row.get(5),
+            to_version_middle: row.get(6),
+            to_version_minor: row.get(7),
+            when: super::timestamp(row.get(8)),
+            success: row.get(9),
+        }),
+        TransferTable::Quarantine => TransferRow::Quarantine(QuarantineRow {
+            id: row.get(0),
+            node: row.get(1),
+            when: super::timestamp(row.get(2)),
+            temperature: row.get(3),
+            humidity: row.get(4),
+            air_pressure: row.get(5),
+            cpu_temp: row.get(6),
+            battery: row.get(7),
+            wifi_ssid: row.get(8),
+            wifi_rssi: row.get(9),
+            reason: row.get(10),
+        }),
+        TransferTable::Calibrations => TransferRow::Calibration(CalibrationRow {
+            node: row.get(0),
+            temperature_offset: row.get(1),
+            temperature_scale: row.get(2),
+            humidity_offset: row.get(3),
+            air_pressure_offset: row.get(4),
+            battery_scale: row.get(5),
+        }),
+        TransferTable::AlertStates => TransferRow::AlertState(AlertStateRow {
+            node: row.get(0),
+            rule: row.get(1),
+            active: row.get(2),
+            streak: row.get(3),
+            changed: super::timestamp(row.get(4)),
+        }),
+        TransferTable::RollupsHourly => TransferRow::RollupHourly(rollup_row(row)),
+        TransferTable::RollupsDaily => TransferRow::RollupDaily(rollup_row(row)),
+    }
+}
+
+fn rollup_row(row: &PgRow) -> RollupRow {
+    RollupRow {
+        node: row.get(0),
+        bucket: super::timestamp(row.get(1)),
+        samples: row.get(2),
+        temperature_min: row.get(3),
+        temperature_max: row.get(4),
+        temperature_avg: row.get(5),
+        humidity_min: row.get(6),
+        humidity_max: row.get(7),
+        humidity_avg: row.get(8),
+        air_pressure_min: row.get(9),
+        air_pressure_max: row.get(10),
+        air_pressure_avg: row.get(11),
+        battery_min: row.get(12),
+        battery_max: row.get(13),
+        battery_avg: row.get(14),
+        rssi_min: row.get(15),
+        rssi_max: row.get(16),
+        rssi_avg: row.get(17),
+    }
+}
@@ -1,14 +1,33 @@
-use super::{EraseOptions, FirmwareBlob, FirmwareEntry, NodeId, SleepTime, UpdateStatId};
-use crate::error::Error;
-use pwmp_client::pwmp_msg::{
-    aliases::{AirPressure, BatteryVoltage, Humidity, Rssi, Temperature},
-    mac::Mac,
-    settings::NodeSettings,
-    version::Version,
+use super::{
+    AggregateEntry, AggregateStream, AlertStateEntry, BatterySample, CalibrationEntry, DeviceEntry,
+    EraseOptions, FirmwareBlob, FirmwareEntry, MeasurementEntry, MeasurementRange,
+    MeasurementStream, NodeId, NotificationEntry, NotificationId, PrunableTable, RawReadings,
+    SettingsEntry, SleepTime, Summary, UpdateStatId, WifiSample,
+    migrations::{self, AppliedMigration, Migration},
+    transfer::{
+        AlertStateRow, CalibrationRow, DeviceRow, FirmwareRow, FirmwareStatRow, MeasurementRow,
+        NotificationRow, QuarantineRow, RollupRow, SettingsRow, TableState, TransferRow,
+        TransferTable,
+    },
 };
+use crate::{
+    error::Error,
+    server::{
+        calibration::Calibration,
+        config::ValidationConfig,
+        diagnostics::SensorFlags,
+        notification_client::{Notification, Severity},
+    },
+};
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use pwmp_client::pwmp_msg::{mac::Mac, settings::NodeSettings, version::Version};
 use sqlx::{
     Pool, Row, Sqlite,
-    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
+    sqlite::{
+        SqliteAutoVacuum, SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteRow,
+        SqliteSynchronous,
+    },
 };
 use std::{path::PathBuf, time::Duration};
 use tracing::debug;
@@ -28,6 +47,8 @@ impl SqliteClient {
             .read_only(false)
             .busy_timeout(Duration::from_secs(3))
             .optimize_on_close(true, None)
+            // Only takes effect on new databases, existing ones are converted by a full vacuum
+            .auto_vacuum(SqliteAutoVacuum::Incremental)
             .journal_mode(SqliteJournalMode::Wal)
             .synchronous(SqliteSynchronous::Normal);
 
@@ -53,6 +74,15 @@ impl SqliteClient {
 
         row.map_or_else(|| Ok(None), |row| Ok(Some(row.get(0))))
     }
+
+    async fn table_exists(&self, table: &str) -> Result<bool, Error> {
+        let row = sqlx::query(include_str!("../../../queries/sqlite/table_exists.sql"))
+            .bind(table)
+            .fetch_one(&self.0)
+            .await?;
+
+        Ok(row.get(0))
+    }
 }
 
 #[async_trait::async_trait]
@@ -77,31 +107,172 @@ impl super::DatabaseBackend for SqliteClient {
         Ok(id)
     }
 
+    #[tracing::instrument(name = "SqliteClient::get_devices()", level = "debug", skip(self), err)]
+    async fn get_devices(&self) -> Result<Vec<DeviceEntry>, Error> {
+        let results = sqlx::query(include_str!("../../../queries/sqlite/get_devices.sql"))
+            .fetch_all(&self.0)
+            .await?
+            .iter()
+            .map(device_entry)
+            .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(name = "SqliteClient::get_device()", level = "debug", skip(self), err)]
+    async fn get_device(&self, node: NodeId) -> Result<Option<DeviceEntry>, Error> {
+        let result = sqlx::query(include_str!("../../../queries/sqlite/get_device.sql"))
+            .bind(node)
+            .fetch_optional(&self.0)
+            .await?;
+
+        Ok(result.as_ref().map(device_entry))
+    }
+
     #[tracing::instrument(
         name = "SqliteClient::create_notification()",
         level = "debug",
         skip(self),
         err
     )]
-    async fn create_notification(&self, node_id: NodeId, content: &str) -> Result<(), Error> {
+    async fn create_notification(&self, notification: &Notification) -> Result<(), Error> {
         sqlx::query(include_str!(
             "../../../queries/sqlite/create_notification.sql"
         ))
-        .bind(node_id)
-        .bind(content)
+        .bind(notification.node)
+        .bind(notification.body.as_ref())
+        .bind(notification.severity.as_str())
+        .bind(notification.kind.as_str())
+        .bind(notification.title.as_ref())
         .execute(&self.0)
         .await?;
         Ok(())
     }
 
     #[tracing::instrument(
-        name = "SqliteClient::get_settings()",
+        name = "SqliteClient::get_notifications()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_notifications(
+        &self,
+        unread_only: bool,
+        node: Option<NodeId>,
+        since: Option<Duration>,
+    ) -> Result<Vec<NotificationEntry>, Error> {
+        let since: Option<i64> = match since {
+            Some(age) => Some(age.as_secs().try_into()?),
+            None => None,
+        };
+
+        let results = sqlx::query(include_str!(
+            "../../../queries/sqlite/get_notifications.sql"
+        ))
+        .bind(unread_only)
+        .bind(node)
+        .bind(since)
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(notification_entry)
+        .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::mark_notifications_read()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn mark_notifications_read(&self, id: Option<NotificationId>) -> Result<u64, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/sqlite/mark_notifications_read.sql"
+        ))
+        .bind(id)
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::purge_notifications()",
         level = "debug",
         skip(self),
         err,
         ret
     )]
-    async fn get_settings(&self, node_id: NodeId) -> Result<Option<NodeSettings>, Error> {
+    async fn purge_notifications(&self, older_than: Duration) -> Result<u64, Error> {
+        let older_than: i64 = older_than.as_secs().try_into()?;
+
+        let result = sqlx::query(include_str!(
+            "../../../queries/sqlite/purge_notifications.sql"
+        ))
+        .bind(older_than)
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::get_unacknowledged_notifications()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_unacknowledged_notifications(
+        &self,
+        after: Duration,
+        max_escalations: u32,
+        min_severity: Severity,
+    ) -> Result<Vec<NotificationEntry>, Error> {
+        let after: i64 = after.as_secs().try_into()?;
+        let max_escalations: i32 = max_escalations.try_into()?;
+
+        let results = sqlx::query(include_str!(
+            "../../../queries/sqlite/get_unacknowledged_notifications.sql"
+        ))
+        .bind(after)
+        .bind(max_escalations)
+        .bind(min_severity.level())
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(notification_entry)
+        .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::mark_notification_escalated()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn mark_notification_escalated(&self, id: NotificationId) -> Result<(), Error> {
+        sqlx::query(include_str!(
+            "../../../queries/sqlite/mark_notification_escalated.sql"
+        ))
+        .bind(id)
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::get_settings_entry()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn get_settings_entry(&self, node_id: NodeId) -> Result<Option<SettingsEntry>, Error> {
         let result = sqlx::query(include_str!(
             "../../../queries/sqlite/get_device_settings.sql"
         ))
@@ -110,12 +281,15 @@ impl super::DatabaseBackend for SqliteClient {
         .await?;
 
         let result = match result {
-            Some(row) => Some(NodeSettings {
-                battery_ignore: row.get(0),
-                ota: row.get(1),
-                sleep_time: row.get::<SleepTime, _>(2).try_into()?,
-                sbop: row.get(3),
-                mute_notifications: row.get(4),
+            Some(row) => Some(SettingsEntry {
+                settings: NodeSettings {
+                    battery_ignore: row.get(0),
+                    ota: row.get(1),
+                    sleep_time: row.get::<SleepTime, _>(2).try_into()?,
+                    sbop: row.get(3),
+                    mute_notifications: row.get(4),
+                },
+                muted_until: row.get::<Option<i64>, _>(5).map(super::timestamp),
             }),
             None => None,
         };
@@ -123,6 +297,54 @@ impl super::DatabaseBackend for SqliteClient {
         Ok(result)
     }
 
+    #[tracing::instrument(
+        name = "SqliteClient::set_device_altitude()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn set_device_altitude(
+        &self,
+        node: NodeId,
+        altitude: Option<f32>,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/sqlite/set_device_altitude.sql"
+        ))
+        .bind(node)
+        .bind(altitude)
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::set_notifications_muted()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn set_notifications_muted(
+        &self,
+        node_id: NodeId,
+        muted: bool,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/sqlite/set_notifications_muted.sql"
+        ))
+        .bind(node_id)
+        .bind(muted)
+        .bind(until.map(|when| when.timestamp()))
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     #[tracing::instrument(
         name = "SqliteClient::post_results()",
         level = "debug",
@@ -133,25 +355,24 @@ impl super::DatabaseBackend for SqliteClient {
     async fn post_measurements(
         &self,
         node: NodeId,
-        temp: Temperature,
-        hum: Humidity,
-        air_p: Option<AirPressure>,
-        cpu_temp: Temperature,
-        battery: BatteryVoltage,
-        wifi_ssid: &str,
-        wifi_rssi: Rssi,
+        measurement: &MeasurementEntry,
     ) -> Result<(), Error> {
         sqlx::query(include_str!(
             "../../../queries/sqlite/post_measurements.sql"
         ))
         .bind(node)
-        .bind(temp)
-        .bind(hum)
-        .bind(air_p)
-        .bind(cpu_temp)
-        .bind(battery)
-        .bind(wifi_ssid)
-        .bind(wifi_rssi)
+        .bind(measurement.temperature)
+        .bind(measurement.humidity)
+        .bind(measurement.air_pressure)
+        .bind(measurement.cpu_temp)
+        .bind(measurement.battery)
+        .bind(&measurement.wifi_ssid)
+        .bind(measurement.wifi_rssi)
+        .bind(measurement.flags.bits())
+        .bind(measurement.raw.temperature)
+        .bind(measurement.raw.humidity)
+        .bind(measurement.raw.air_pressure)
+        .bind(measurement.raw.battery)
         .execute(&self.0)
         .await?;
 
@@ -159,15 +380,546 @@ impl super::DatabaseBackend for SqliteClient {
     }
 
     #[tracing::instrument(
-        name = "SqliteClient::run_migrations()",
+        name = "SqliteClient::get_calibration()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_calibration(&self, node: NodeId) -> Result<Option<Calibration>, Error> {
+        let result = sqlx::query(include_str!("../../../queries/sqlite/get_calibration.sql"))
+            .bind(node)
+            .fetch_optional(&self.0)
+            .await?;
+
+        Ok(result.map(|row| Calibration {
+            temperature_offset: row.get(0),
+            temperature_scale: row.get(1),
+            humidity_offset: row.get(2),
+            air_pressure_offset: row.get(3),
+            battery_scale: row.get(4),
+        }))
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::get_calibrations()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_calibrations(&self) -> Result<Vec<CalibrationEntry>, Error> {
+        let results = sqlx::query(include_str!("../../../queries/sqlite/get_calibrations.sql"))
+            .fetch_all(&self.0)
+            .await?
+            .iter()
+            .map(|row| CalibrationEntry {
+                node: row.get(0),
+                calibration: Calibration {
+                    temperature_offset: row.get(1),
+                    temperature_scale: row.get(2),
+                    humidity_offset: row.get(3),
+                    air_pressure_offset: row.get(4),
+                    battery_scale: row.get(5),
+                },
+            })
+            .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::set_calibration()",
         level = "debug",
         skip(self),
         err
     )]
-    async fn run_migrations(&self) -> Result<(), Error> {
-        sqlx::raw_sql(include_str!("../../../queries/sqlite/migrate.sql"))
+    async fn set_calibration(&self, node: NodeId, calibration: &Calibration) -> Result<(), Error> {
+        sqlx::query(include_str!("../../../queries/sqlite/set_calibration.sql"))
+            .bind(node)
+            .bind(calibration.temperature_offset)
+            .bind(calibration.temperature_scale)
+            .bind(calibration.humidity_offset)
+            .bind(calibration.air_pressure_offset)
+            .bind(calibration.battery_scale)
             .execute(&self.0)
             .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::delete_calibration()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn delete_calibration(&self, node: NodeId) -> Result<bool, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/sqlite/delete_calibration.sql"
+        ))
+        .bind(node)
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::recalibrate_measurements()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn recalibrate_measurements(
+        &self,
+        node: NodeId,
+        calibration: &Calibration,
+        validation: &ValidationConfig,
+        since: Option<Duration>,
+    ) -> Result<(u64, u64), Error> {
+        let since: Option<i64> = match since {
+            Some(since) => Some(since.as_secs().try_into()?),
+            None => None,
+        };
+
+        let mut tx = self.0.begin().await?;
+
+        let total: i64 = sqlx::query(include_str!(
+            "../../../queries/sqlite/count_node_measurements.sql"
+        ))
+        .bind(node)
+        .bind(since)
+        .fetch_one(&mut *tx)
+        .await?
+        .get(0);
+
+        let result = sqlx::query(include_str!(
+            "../../../queries/sqlite/recalibrate_measurements.sql"
+        ))
+        .bind(node)
+        .bind(calibration.temperature_offset)
+        .bind(calibration.temperature_scale)
+        .bind(calibration.humidity_offset)
+        .bind(calibration.air_pressure_offset)
+        .bind(calibration.battery_scale)
+        .bind(since)
+        .bind(validation.temperature.min)
+        .bind(validation.temperature.max)
+        .bind(i16::from(validation.humidity.min))
+        .bind(i16::from(validation.humidity.max))
+        .bind(i32::from(validation.air_pressure.min))
+        .bind(i32::from(validation.air_pressure.max))
+        .bind(validation.battery.min)
+        .bind(validation.battery.max)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let updated = result.rows_affected();
+        Ok((updated, u64::try_from(total)? - updated))
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::import_measurements()",
+        level = "debug",
+        skip(self, measurements),
+        fields(count = measurements.len()),
+        err,
+        ret
+    )]
+    async fn import_measurements(
+        &self,
+        node: NodeId,
+        measurements: &[MeasurementEntry],
+        skip_duplicates: bool,
+        dry_run: bool,
+    ) -> Result<u64, Error> {
+        let mut tx = self.0.begin().await?;
+        let mut inserted = 0;
+
+        for measurement in measurements {
+            inserted += sqlx::query(include_str!(
+                "../../../queries/sqlite/import_measurements.sql"
+            ))
+            .bind(node)
+            .bind(measurement.when.timestamp())
+            .bind(measurement.temperature)
+            .bind(measurement.humidity)
+            .bind(measurement.air_pressure)
+            .bind(measurement.cpu_temp)
+            .bind(measurement.battery)
+            .bind(&measurement.wifi_ssid)
+            .bind(measurement.wifi_rssi)
+            .bind(skip_duplicates)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+
+        Ok(inserted)
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::refresh_rollups()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn refresh_rollups(
+        &self,
+        node: Option<NodeId>,
+        from: Option<DateTime<Utc>>,
+    ) -> Result<u64, Error> {
+        let from = from.map(|from| from.timestamp());
+        let mut updated = 0;
+
+        for sql in [
+            include_str!("../../../queries/sqlite/refresh_rollups_hourly.sql"),
+            include_str!("../../../queries/sqlite/refresh_rollups_daily.sql"),
+        ] {
+            updated += sqlx::query(sql)
+                .bind(node)
+                .bind(from)
+                .execute(&self.0)
+                .await?
+                .rows_affected();
+        }
+
+        Ok(updated)
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::quarantine_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn quarantine_measurements(
+        &self,
+        node: NodeId,
+        measurement: &MeasurementEntry,
+        reason: &str,
+    ) -> Result<(), Error> {
+        sqlx::query(include_str!(
+            "../../../queries/sqlite/quarantine_measurements.sql"
+        ))
+        .bind(node)
+        .bind(measurement.temperature)
+        .bind(measurement.humidity)
+        .bind(measurement.air_pressure)
+        .bind(measurement.cpu_temp)
+        .bind(measurement.battery)
+        .bind(&measurement.wifi_ssid)
+        .bind(measurement.wifi_rssi)
+        .bind(reason)
+        .execute(&self.0)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::get_last_measurement()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_last_measurement(&self, node: NodeId) -> Result<Option<MeasurementEntry>, Error> {
+        let result = sqlx::query(include_str!(
+            "../../../queries/sqlite/get_last_measurement.sql"
+        ))
+        .bind(node)
+        .fetch_optional(&self.0)
+        .await?;
+
+        Ok(result.as_ref().map(measurement_entry))
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::get_latest_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_latest_measurements(
+        &self,
+        max_age: Duration,
+    ) -> Result<Vec<(NodeId, MeasurementEntry)>, Error> {
+        let max_age: i64 = max_age.as_secs().try_into()?;
+
+        let results = sqlx::query(include_str!(
+            "../../../queries/sqlite/get_latest_measurements.sql"
+        ))
+        .bind(max_age)
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(|row| (row.get(13), measurement_entry(row)))
+        .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::get_recent_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_recent_measurements(
+        &self,
+        node: NodeId,
+        count: u32,
+    ) -> Result<Vec<MeasurementEntry>, Error> {
+        let results = sqlx::query(include_str!(
+            "../../../queries/sqlite/get_recent_measurements.sql"
+        ))
+        .bind(node)
+        .bind(count)
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(measurement_entry)
+        .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::stream_measurements()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn stream_measurements(
+        &self,
+        range: MeasurementRange,
+    ) -> Result<MeasurementStream<'_>, Error> {
+        let stream = sqlx::query(include_str!(
+            "../../../queries/sqlite/export_measurements.sql"
+        ))
+        .bind(range.node)
+        .bind(range.from.map(|from| from.timestamp()))
+        .bind(range.to.map(|to| to.timestamp()))
+        .fetch(&self.0)
+        .map(|row| -> Result<_, Error> {
+            let row = row?;
+            Ok((row.get(13), measurement_entry(&row)))
+        });
+
+        Ok(stream.boxed())
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::stream_aggregates()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn stream_aggregates(
+        &self,
+        range: MeasurementRange,
+        period: Duration,
+    ) -> Result<AggregateStream<'_>, Error> {
+        let period: i64 = period.as_secs().try_into()?;
+
+        let stream = sqlx::query(include_str!(
+            "../../../queries/sqlite/export_aggregates.sql"
+        ))
+        .bind(range.node)
+        .bind(range.from.map(|from| from.timestamp()))
+        .bind(range.to.map(|to| to.timestamp()))
+        .bind(period)
+        .fetch(&self.0)
+        .map(|row| Ok(aggregate_entry(&row?)));
+
+        Ok(stream.boxed())
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::get_battery_history()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_battery_history(
+        &self,
+        node: NodeId,
+        window: Duration,
+        limit: u32,
+    ) -> Result<Vec<BatterySample>, Error> {
+        let window: i64 = window.as_secs().try_into()?;
+
+        let results = sqlx::query(include_str!(
+            "../../../queries/sqlite/get_battery_history.sql"
+        ))
+        .bind(node)
+        .bind(window)
+        .bind(i64::from(limit))
+        .fetch_all(&self.0)
+        .await?
+        .iter()
+        .map(|row| BatterySample {
+            when: super::timestamp(row.get(0)),
+            voltage: row.get(1),
+        })
+        .collect();
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::get_wifi_history()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_wifi_history(
+        &self,
+        node: NodeId,
+        window: Duration,
+    ) -> Result<Vec<WifiSample>, Error> {
+        let window: i64 = window.as_secs().try_into()?;
+
+        let results = sqlx::query(include_str!("../../../queries/sqlite/get_wifi_history.sql"))
+            .bind(node)
+            .bind(window)
+            .fetch_all(&self.0)
+            .await?
+            .iter()
+            .map(|row| {
+                Ok(WifiSample {
+                    when: super::timestamp(row.get(0)),
+                    ssid: row.get(1),
+                    rssi: row.get::<i16, _>(2).try_into()?,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::get_alert_states()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_alert_states(&self, node: NodeId) -> Result<Vec<AlertStateEntry>, Error> {
+        let rows = sqlx::query(include_str!("../../../queries/sqlite/get_alert_states.sql"))
+            .bind(node)
+            .fetch_all(&self.0)
+            .await?;
+
+        let mut results = Vec::with_capacity(rows.len());
+        for row in rows {
+            results.push(AlertStateEntry {
+                rule: row.get(0),
+                active: row.get(1),
+                streak: row.get::<i32, _>(2).try_into()?,
+            });
+        }
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::set_alert_state()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn set_alert_state(
+        &self,
+        node: NodeId,
+        rule: &str,
+        active: bool,
+        streak: u32,
+    ) -> Result<(), Error> {
+        let streak: i32 = streak.try_into()?;
+
+        sqlx::query(include_str!("../../../queries/sqlite/set_alert_state.sql"))
+            .bind(node)
+            .bind(rule)
+            .bind(active)
+            .bind(streak)
+            .execute(&self.0)
+            .await?;
+        Ok(())
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        migrations::SQLITE
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::get_applied_migrations()",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn get_applied_migrations(&self) -> Result<Vec<AppliedMigration>, Error> {
+        if self.table_exists("schema_migrations").await? {
+            let results = sqlx::query(include_str!(
+                "../../../queries/sqlite/get_applied_migrations.sql"
+            ))
+            .fetch_all(&self.0)
+            .await?
+            .iter()
+            .map(|row| AppliedMigration {
+                version: row.get(0),
+                name: row.get(1),
+                applied: Some(super::timestamp(row.get(2))),
+            })
+            .collect();
+
+            return Ok(results);
+        }
+
+        // Created before migrations were versioned
+        if self.table_exists("devices").await? {
+            return Ok(vec![AppliedMigration {
+                version: 1,
+                name: migrations::INITIAL.to_string(),
+                applied: None,
+            }]);
+        }
+
+        Ok(Vec::new())
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::apply_migration()",
+        level = "debug",
+        skip(self, migration),
+        fields(version = migration.version),
+        err
+    )]
+    async fn apply_migration(&self, migration: &Migration) -> Result<(), Error> {
+        let mut tx = self.0.begin().await?;
+
+        sqlx::raw_sql(include_str!(
+            "../../../queries/sqlite/create_schema_migrations.sql"
+        ))
+        .execute(&mut *tx)
+        .await?;
+        sqlx::raw_sql(migration.sql).execute(&mut *tx).await?;
+        sqlx::query(include_str!("../../../queries/sqlite/record_migration.sql"))
+            .bind(migration.version)
+            .bind(migration.name)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
         Ok(())
     }
 
@@ -282,6 +1034,354 @@ impl super::DatabaseBackend for SqliteClient {
         Ok(())
     }
 
+    #[tracing::instrument(name = "SqliteClient::prune()", level = "debug", skip(self), err, ret)]
+    async fn prune(
+        &self,
+        table: PrunableTable,
+        older_than: Duration,
+        limit: u32,
+    ) -> Result<u64, Error> {
+        let older_than: i64 = older_than.as_secs().try_into()?;
+        let sql = match table {
+            PrunableTable::Measurements => {
+                include_str!("../../../queries/sqlite/prune_measurements.sql")
+            }
+            PrunableTable::Notifications => {
+                include_str!("../../../queries/sqlite/prune_notifications.sql")
+            }
+            PrunableTable::FirmwareStats => {
+                include_str!("../../../queries/sqlite/prune_firmware_stats.sql")
+            }
+            PrunableTable::Quarantine => {
+                include_str!("../../../queries/sqlite/prune_quarantine.sql")
+            }
+        };
+
+        let result = sqlx::query(sql)
+            .bind(older_than)
+            .bind(i64::from(limit))
+            .execute(&self.0)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::count_prunable()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn count_prunable(
+        &self,
+        table: PrunableTable,
+        older_than: Duration,
+    ) -> Result<u64, Error> {
+        let older_than: i64 = older_than.as_secs().try_into()?;
+        let sql = match table {
+            PrunableTable::Measurements => {
+                include_str!("../../../queries/sqlite/count_prunable_measurements.sql")
+            }
+            PrunableTable::Notifications => {
+                include_str!("../../../queries/sqlite/count_prunable_notifications.sql")
+            }
+            PrunableTable::FirmwareStats => {
+                include_str!("../../../queries/sqlite/count_prunable_firmware_stats.sql")
+            }
+            PrunableTable::Quarantine => {
+                include_str!("../../../queries/sqlite/count_prunable_quarantine.sql")
+            }
+        };
+
+        let count: i64 = sqlx::query(sql)
+            .bind(older_than)
+            .fetch_one(&self.0)
+            .await?
+            .get(0);
+
+        Ok(count.try_into()?)
+    }
+
+    #[tracing::instrument(name = "SqliteClient::compact()", level = "debug", skip(self), err)]
+    async fn compact(&self, full: bool) -> Result<(), Error> {
+        // An incremental vacuum only truncates the free pages, instead of rewriting the whole database file
+        let sql = if full {
+            include_str!("../../../queries/sqlite/vacuum.sql")
+        } else {
+            include_str!("../../../queries/sqlite/incremental_vacuum.sql")
+        };
+
+        sqlx::raw_sql(sql).execute(&self.0).await?;
+
+        // Vacuuming goes through the WAL, so it has to be checkpointed afterwards to shrink it
+        sqlx::raw_sql(include_str!("../../../queries/sqlite/checkpoint_wal.sql"))
+            .execute(&self.0)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::get_table_state()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    async fn get_table_state(&self, table: TransferTable) -> Result<TableState, Error> {
+        let sql = match table {
+            TransferTable::Devices => {
+                include_str!("../../../queries/sqlite/transfer_state_devices.sql")
+            }
+            TransferTable::Settings => {
+                include_str!("../../../queries/sqlite/transfer_state_settings.sql")
+            }
+            TransferTable::Measurements => {
+                include_str!("../../../queries/sqlite/transfer_state_measurements.sql")
+            }
+            TransferTable::Notifications => {
+                include_str!("../../../queries/sqlite/transfer_state_notifications.sql")
+            }
+            TransferTable::Firmwares => {
+                include_str!("../../../queries/sqlite/transfer_state_firmwares.sql")
+            }
+            TransferTable::FirmwareStats => {
+                include_str!("../../../queries/sqlite/transfer_state_firmware_stats.sql")
+            }
+            TransferTable::Quarantine => {
+                include_str!("../../../queries/sqlite/transfer_state_quarantine.sql")
+            }
+            TransferTable::Calibrations => {
+                include_str!("../../../queries/sqlite/transfer_state_calibrations.sql")
+            }
+            TransferTable::AlertStates => {
+                include_str!("../../../queries/sqlite/transfer_state_alert_states.sql")
+            }
+            TransferTable::RollupsHourly => {
+                include_str!("../../../queries/sqlite/transfer_state_rollups_hourly.sql")
+            }
+            TransferTable::RollupsDaily => {
+                include_str!("../../../queries/sqlite/transfer_state_rollups_daily.sql")
+            }
+        };
+
+        let row = sqlx::query(sql).fetch_one(&self.0).await?;
+
+        Ok(TableState {
+            rows: row.get::<i64, _>(0).try_into()?,
+            last_id: row.get(1),
+        })
+    }
+
+    #[tracing::instrument(name = "SqliteClient::read_rows()", level = "debug", skip(self), err)]
+    async fn read_rows(
+        &self,
+        table: TransferTable,
+        after: i32,
+        limit: u32,
+    ) -> Result<Vec<TransferRow>, Error> {
+        let sql = match table {
+            TransferTable::Devices => {
+                include_str!("../../../queries/sqlite/transfer_read_devices.sql")
+            }
+            TransferTable::Settings => {
+                include_str!("../../../queries/sqlite/transfer_read_settings.sql")
+            }
+            TransferTable::Measurements => {
+                include_str!("../../../queries/sqlite/transfer_read_measurements.sql")
+            }
+            TransferTable::Notifications => {
+                include_str!("../../../queries/sqlite/transfer_read_notifications.sql")
+            }
+            TransferTable::Firmwares => {
+                include_str!("../../../queries/sqlite/transfer_read_firmwares.sql")
+            }
+            TransferTable::FirmwareStats => {
+                include_str!("../../../queries/sqlite/transfer_read_firmware_stats.sql")
+            }
+            TransferTable::Quarantine => {
+                include_str!("../../../queries/sqlite/transfer_read_quarantine.sql")
+            }
+            TransferTable::Calibrations => {
+                include_str!("../../../queries/sqlite/transfer_read_calibrations.sql")
+            }
+            TransferTable::AlertStates => {
+                include_str!("../../../queries/sqlite/transfer_read_alert_states.sql")
+            }
+            TransferTable::RollupsHourly => {
+                include_str!("../../../queries/sqlite/transfer_read_rollups_hourly.sql")
+            }
+            TransferTable::RollupsDaily => {
+                include_str!("../../../queries/sqlite/transfer_read_rollups_daily.sql")
+            }
+        };
+
+        let rows = sqlx::query(sql)
+            .bind(after)
+            .bind(i64::from(limit))
+            .fetch_all(&self.0)
+            .await?;
+
+        rows.iter().map(|row| transfer_row(table, row)).collect()
+    }
+
+    #[tracing::instrument(
+        name = "SqliteClient::write_rows()",
+        level = "debug",
+        skip(self, rows),
+        err
+    )]
+    async fn write_rows(&self, rows: &[TransferRow]) -> Result<(), Error> {
+        let mut tx = self.0.begin().await?;
+
+        for row in rows {
+            let query = match row {
+                TransferRow::Device(row) => sqlx::query(include_str!(
+                    "../../../queries/sqlite/transfer_write_devices.sql"
+                ))
+                .bind(row.id)
+                .bind(&row.mac_address)
+                .bind(&row.location)
+                .bind(&row.note)
+                .bind(row.altitude),
+                TransferRow::Settings(row) => sqlx::query(include_str!(
+                    "../../../queries/sqlite/transfer_write_settings.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.battery_ignore)
+                .bind(row.ota)
+                .bind(row.sleep_time)
+                .bind(row.sbop)
+                .bind(row.mute_notifications)
+                .bind(&row.device_specific)
+                .bind(row.muted_until.map(|when| when.timestamp())),
+                TransferRow::Measurement(row) => sqlx::query(include_str!(
+                    "../../../queries/sqlite/transfer_write_measurements.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.when.timestamp())
+                .bind(row.temperature)
+                .bind(row.humidity)
+                .bind(row.air_pressure)
+                .bind(row.cpu_temp)
+                .bind(row.battery)
+                .bind(&row.wifi_ssid)
+                .bind(row.wifi_rssi)
+                .bind(row.flags)
+                .bind(row.raw_temperature)
+                .bind(row.raw_humidity)
+                .bind(row.raw_air_pressure)
+                .bind(row.raw_battery),
+                TransferRow::Notification(row) => sqlx::query(include_str!(
+                    "../../../queries/sqlite/transfer_write_notifications.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.when.timestamp())
+                .bind(&row.content)
+                .bind(row.read)
+                .bind(&row.severity)
+                .bind(&row.kind)
+                .bind(&row.title)
+                .bind(row.escalations)
+                .bind(row.escalated.map(|t| t.timestamp())),
+                TransferRow::Firmware(row) => sqlx::query(include_str!(
+                    "../../../queries/sqlite/transfer_write_firmwares.sql"
+                ))
+                .bind(row.id)
+                .bind(row.version_major)
+                .bind(row.version_middle)
+                .bind(row.version_minor)
+                .bind(&row.firmware)
+                .bind(row.added_date.timestamp())
+                .bind(
+                    row.restrict_nodes
+                        .as_ref()
+                        .map(serde_json::to_string)
+                        .transpose()?,
+                ),
+                TransferRow::FirmwareStat(row) => sqlx::query(include_str!(
+                    "../../../queries/sqlite/transfer_write_firmware_stats.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.from_version_major)
+                .bind(row.from_version_middle)
+                .bind(row.from_version_minor)
+                .bind(row.to_version_major)
+                .bind(row.to_version_middle)
+                .bind(row.to_version_minor)
+                .bind(row.when.timestamp())
+                .bind(row.success),
+                TransferRow::Quarantine(row) => sqlx::query(include_str!(
+                    "../../../queries/sqlite/transfer_write_quarantine.sql"
+                ))
+                .bind(row.id)
+                .bind(row.node)
+                .bind(row.when.timestamp())
+                .bind(row.temperature)
+                .bind(row.humidity)
+                .bind(row.air_pressure)
+                .bind(row.cpu_temp)
+                .bind(row.battery)
+                .bind(&row.wifi_ssid)
+                .bind(row.wifi_rssi)
+                .bind(&row.reason),
+                TransferRow::Calibration(row) => sqlx::query(include_str!(
+                    "../../../queries/sqlite/transfer_write_calibrations.sql"
+                ))
+                .bind(row.node)
+                .bind(row.temperature_offset)
+                .bind(row.temperature_scale)
+                .bind(row.humidity_offset)
+                .bind(row.air_pressure_offset)
+                .bind(row.battery_scale),
+                TransferRow::AlertState(row) => sqlx::query(include_str!(
+                    "../../../queries/sqlite/transfer_write_alert_states.sql"
+                ))
+                .bind(row.node)
+                .bind(&row.rule)
+                .bind(row.active)
+                .bind(row.streak)
+                .bind(row.changed.timestamp()),
+                TransferRow::RollupHourly(rollup) | TransferRow::RollupDaily(rollup) => {
+                    let sql = if matches!(row, TransferRow::RollupHourly(_)) {
+                        include_str!("../../../queries/sqlite/transfer_write_rollups_hourly.sql")
+                    } else {
+                        include_str!("../../../queries/sqlite/transfer_write_rollups_daily.sql")
+                    };
+
+                    sqlx::query(sql)
+                        .bind(rollup.node)
+                        .bind(rollup.bucket.timestamp())
+                        .bind(rollup.samples)
+                        .bind(rollup.temperature_min)
+                        .bind(rollup.temperature_max)
+                        .bind(rollup.temperature_avg)
+                        .bind(rollup.humidity_min)
+                        .bind(rollup.humidity_max)
+                        .bind(rollup.humidity_avg)
+                        .bind(rollup.air_pressure_min)
+                        .bind(rollup.air_pressure_max)
+                        .bind(rollup.air_pressure_avg)
+                        .bind(rollup.battery_min)
+                        .bind(rollup.battery_max)
+                        .bind(rollup.battery_avg)
+                        .bind(rollup.rssi_min)
+                        .bind(rollup.rssi_max)
+                        .bind(rollup.rssi_avg)
+                }
+            };
+
+            query.execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
     #[tracing::instrument(
         name = "SqliteClient::get_firmwares()",
         level = "debug",
@@ -357,3 +1457,196 @@ impl Drop for SqliteClient {
         });
     }
 }
+
+fn measurement_entry(row: &SqliteRow) -> MeasurementEntry {
+    MeasurementEntry {
+        when: super::timestamp(row.get(0)),
+        temperature: row.get(1),
+        humidity: row.get(2),
+        air_pressure: row.get(3),
+        cpu_temp: row.get(4),
+        battery: row.get(5),
+        wifi_ssid: row.get(6),
+        wifi_rssi: row.get(7),
+        flags: SensorFlags::from_bits(row.get(8)),
+        raw: RawReadings {
+            temperature: row.get(9),
+            humidity: row.get(10),
+            air_pressure: row.get(11),
+            battery: row.get(12),
+        },
+    }
+}
+
+fn aggregate_entry(row: &SqliteRow) -> AggregateEntry {
+    let summary = |first| Summary {
+        min: row.get(first),
+        max: row.get(first + 1),
+        avg: row.get(first + 2),
+    };
+
+    AggregateEntry {
+        node: row.get(0),
+        bucket: super::timestamp(row.get(1)),
+        samples: row.get(2),
+        temperature: summary(3),
+        humidity: summary(6),
+        air_pressure: row.get::<Option<f64>, _>(9).is_some().then(|| summary(9)),
+        battery: summary(12),
+        wifi_rssi: summary(15),
+    }
+}
+
+fn device_entry(row: &SqliteRow) -> DeviceEntry {
+    DeviceEntry {
+        id: row.get(0),
+        mac: row.get(1),
+        note: row.get(2),
+        altitude: row.get(3),
+    }
+}
+
+fn notification_entry(row: &SqliteRow) -> NotificationEntry {
+    NotificationEntry {
+        id: row.get(0),
+        node: row.get(1),
+        when: super::timestamp(row.get(2)),
+        content: row.get(3),
+        read: row.get(4),
+        severity: row.get(5),
+        kind: row.get(6),
+        title: row.get(7),
+    }
+}
+
+#[allow(clippy::too_many_lines)] // one arm per table
+fn transfer_row(table: TransferTable, row: &SqliteRow) -> Result<TransferRow, Error> {
+    let row = match table {
+        TransferTable::Devices => TransferRow::Device(DeviceRow {
+            id: row.get(0),
+            mac_address: row.get(1),
+            location: row.get(2),
+            note: row.get(3),
+            altitude: row.get(4),
+        }),
+        TransferTable::Settings => TransferRow::Settings(SettingsRow {
+            id: row.get(0),
+            node: row.get(1),
+            battery_ignore: row.get(2),
+            ota: row.get(3),
+            sleep_time: row.get(4),
+            sbop: row.get(5),
+            mute_notifications: row.get(6),
+            device_specific: row.get(7),
+            muted_until: row.get::<Option<i64>, _>(8).map(super::timestamp),
+        }),
+        TransferTable::Measurements => TransferRow::Measurement(MeasurementRow {
+            id: row.get(0),
+            node: row.get(1),
+            when: super::timestamp(row.get(2)),
+            temperature: row.get(3),
+            humidity: row.get(4),
+            air_pressure: row.get(5),
+            cpu_temp: row.get(6),
+            battery: row.get(7),
+            wifi_ssid: row.get(8),
+            wifi_rssi: row.get(9),
+            flags: row.get(10),
+            raw_temperature: row.get(11),
+            raw_humidity: row.get(12),
+            raw_air_pressure: row.get(13),
+            raw_battery: row.get(14),
+        }),
+        TransferTable::Notifications => TransferRow::Notification(NotificationRow {
+            id: row.get(0),
+            node: row.get(1),
+            when: super::timestamp(row.get(2)),
+            content: row.get(3),
+            read: row.get(4),
+            severity: row.get(5),
+            kind: row.get(6),
+            title: row.get(7),
+            escalations: row.get(8),
+            escalated: row.get::<Option<i64>, _>(9).map(super::timestamp),
+        }),
+        TransferTable::Firmwares => TransferRow::Firmware(FirmwareRow {
+            id: row.get(0),
+            version_major: row.get(1),
+            version_middle: row.get(2),
+            version_minor: row.get(3),
+            firmware: row.get(4),
+            added_date: super::timestamp(row.get(5)),
+            restrict_nodes: row
+                .get::<Option<&str>, _>(6)
+                .map(serde_json::from_str)
+                .transpose()?,
+        }),
+        TransferTable::FirmwareStats => TransferRow::FirmwareStat(FirmwareStatRow {
+            id: row.get(0),
+            node: row.get(1),
+            from_version_major: row.get(2),
+            from_version_middle: row.get(3),
+            from_version_minor: row.get(4),
+            to_version_major: row.get(5),
+            to_version_middle: row.get(6),
+            to_version_minor: row.get(7),
+            when: super::timestamp(row.get(8)),
+            success: row.get(9),
+        }),
+        TransferTable::Quarantine => TransferRow::Quarantine(QuarantineRow {
+            id: row.get(0),
+            node: row.get(1),
+            when: super::timestamp(row.get(2)),
+            temperature: row.get(3),
+            humidity: row.get(4),
+            air_pressure: row.get(5),
+            cpu_temp: row.get(6),
+            battery: row.get(7),
+            wifi_ssid: row.get(8),
+            wifi_rssi: row.get(9),
+            reason: row.get(10),
+        }),
+        TransferTable::Calibrations => TransferRow::Calibration(CalibrationRow {
+            node: row.get(0),
+            temperature_offset: row.get(1),
+            temperature_scale: row.get(2),
+            humidity_offset: row.get(3),
+            air_pressure_offset: row.get(4),
+            battery_scale: row.get(5),
+        }),
+        TransferTable::AlertStates => TransferRow::AlertState(AlertStateRow {
+            node: row.get(0),
+            rule: row.get(1),
+            active: row.get(2),
+            streak: row.get(3),
+            changed: super::timestamp(row.get(4)),
+        }),
+        TransferTable::RollupsHourly => TransferRow::RollupHourly(rollup_row(row)),
+        TransferTable::RollupsDaily => TransferRow::RollupDaily(rollup_row(row)),
+    };
+
+    Ok(row)
+}
+
+fn rollup_row(row: &SqliteRow) -> RollupRow {
+    RollupRow {
+        node: row.get(0),
+        bucket: super::timestamp(row.get(1)),
+        samples: row.get(2),
+        temperature_min: row.get(3),
+        temperature_max: row.get(4),
+        temperature_avg: row.get(5),
+        humidity_min: row.get(6),
+        humidity_max: row.get(7),
+        humidity_avg: row.get(8),
+        air_pressure_min: row.get(9),
+        air_pressure_max: row.get(10),
+        air_pressure_avg: row.get(11),
+        battery_min: row.get(12),
+        battery_max: row.get(13),
+        battery_avg: row.get(14),
+        rssi_min: row.get(15),
+        rssi_max: row.get(16),
+        rssi_avg: row.get(17),
+    }
+}
@@ -0,0 +1,450 @@
+//! Backend-independent rows of the tables that are copied between databases.
+//!
+//! Rows keep their IDs, so references between them stay valid. Tables are copied in the order of [`TransferTable::ALL`],
+//! which satisfies their references, and by ascending ID, so an interrupted copy resumes after the highest ID in the target.
+//! Tables without an ID are read by their primary key and upserted, so they're copied again in full when a copy resumes.
+
+use super::{DatabaseBackend, DatabaseClient, NodeId};
+use crate::error::Error;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferTable {
+    Devices,
+    Settings,
+    Measurements,
+    Notifications,
+    Firmwares,
+    FirmwareStats,
+    Quarantine,
+    Calibrations,
+    AlertStates,
+    RollupsHourly,
+    RollupsDaily,
+}
+
+/// Number of rows of a table and their highest ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableState {
+    pub rows: u64,
+    /// Always `None` for tables without an ID.
+    pub last_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum TransferRow {
+    Device(DeviceRow),
+    Settings(SettingsRow),
+    Measurement(MeasurementRow),
+    Notification(NotificationRow),
+    Firmware(FirmwareRow),
+    FirmwareStat(FirmwareStatRow),
+    Quarantine(QuarantineRow),
+    Calibration(CalibrationRow),
+    AlertState(AlertStateRow),
+    RollupHourly(RollupRow),
+    RollupDaily(RollupRow),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceRow {
+    pub id: NodeId,
+    pub mac_address: String,
+    /// Textual representation of the point, eg. `(48.1,17.1)`.
+    pub location: Option<String>,
+    pub note: Option<String>,
+    pub altitude: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // mirrors the table
+pub struct SettingsRow {
+    pub id: i32,
+    pub node: NodeId,
+    pub battery_ignore: bool,
+    pub ota: bool,
+    pub sleep_time: i16,
+    pub sbop: bool,
+    pub mute_notifications: bool,
+    /// JSON object.
+    pub device_specific: String,
+    pub muted_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeasurementRow {
+    pub id: i32,
+    pub node: NodeId,
+    pub when: DateTime<Utc>,
+    pub temperature: f32,
+    pub humidity: i16,
+    pub air_pressure: Option<i16>,
+    pub cpu_temp: f32,
+    pub battery: f32,
+    pub wifi_ssid: String,
+    pub wifi_rssi: i16,
+    pub flags: i32,
+    pub raw_temperature: Option<f32>,
+    pub raw_humidity: Option<i16>,
+    pub raw_air_pressure: Option<i16>,
+    pub raw_battery: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationRow {
+    pub id: i32,
+    pub node: NodeId,
+    pub when: DateTime<Utc>,
+    pub content: String,
+    pub read: bool,
+    pub severity: String,
+    pub kind: String,
+    pub title: String,
+    pub escalations: i32,
+    pub escalated: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirmwareRow {
+    pub id: i32,
+    pub version_major: i16,
+    pub version_middle: i16,
+    pub version_minor: i16,
+    /// Stored separately in backups.
+    #[serde(skip)]
+    pub firmware: Vec<u8>,
+    pub added_date: DateTime<Utc>,
+    pub restrict_nodes: Option<Vec<NodeId>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirmwareStatRow {
+    pub id: i32,
+    pub node: NodeId,
+    pub from_version_major: i16,
+    pub from_version_middle: i16,
+    pub from_version_minor: i16,
+    pub to_version_major: i16,
+    pub to_version_middle: i16,
+    pub to_version_minor: i16,
+    pub when: DateTime<Utc>,
+    pub success: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineRow {
+    pub id: i32,
+    pub node: NodeId,
+    pub when: DateTime<Utc>,
+    pub temperature: f32,
+    pub humidity: i16,
+    pub air_pressure: Option<i32>,
+    pub cpu_temp: f32,
+    pub battery: f32,
+    pub wifi_ssid: String,
+    pub wifi_rssi: i16,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationRow {
+    pub node: NodeId,
+    pub temperature_offset: f32,
+    pub temperature_scale: f32,
+    pub humidity_offset: f32,
+    pub air_pressure_offset: f32,
+    pub battery_scale: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertStateRow {
+    pub node: NodeId,
+    pub rule: String,
+    pub active: bool,
+    pub streak: i32,
+    pub changed: DateTime<Utc>,
+}
+
+/// Row of `rollups_hourly` or `rollups_daily`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollupRow {
+    pub node: NodeId,
+    pub bucket: DateTime<Utc>,
+    pub samples: i32,
+    pub temperature_min: f32,
+    pub temperature_max: f32,
+    pub temperature_avg: f32,
+    pub humidity_min: i16,
+    pub humidity_max: i16,
+    pub humidity_avg: f32,
+    pub air_pressure_min: Option<i16>,
+    pub air_pressure_max: Option<i16>,
+    pub air_pressure_avg: Option<f32>,
+    pub battery_min: f32,
+    pub battery_max: f32,
+    pub battery_avg: f32,
+    pub rssi_min: i16,
+    pub rssi_max: i16,
+    pub rssi_avg: f32,
+}
+
+impl TransferTable {
+    pub const ALL: [Self; 11] = [
+        Self::Devices,
+        Self::Settings,
+        Self::Measurements,
+        Self::Notifications,
+        Self::Firmwares,
+        Self::FirmwareStats,
+        Self::Quarantine,
+        Self::Calibrations,
+        Self::AlertStates,
+        Self::RollupsHourly,
+        Self::RollupsDaily,
+    ];
+
+    /// Number of rows to keep in memory at once when up to `batch_size` rows are wanted.
+    pub fn batch_limit(self, batch_size: u32) -> u32 {
+        match self {
+            // Firmware blobs can be large
+            Self::Firmwares => batch_size.min(8),
+            _ => batch_size,
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Devices => "devices",
+            Self::Settings => "settings",
+            Self::Measurements => "measurements",
+            Self::Notifications => "notifications",
+            Self::Firmwares => "firmwares",
+            Self::FirmwareStats => "firmware_stats",
+            Self::Quarantine => "quarantine",
+            Self::Calibrations => "calibrations",
+            Self::AlertStates => "alert_states",
+            Self::RollupsHourly => "rollups_hourly",
+            Self::RollupsDaily => "rollups_daily",
+        }
+    }
+}
+
+impl TransferRow {
+    pub const fn table(&self) -> TransferTable {
+        match self {
+            Self::Device(_) => TransferTable::Devices,
+            Self::Settings(_) => TransferTable::Settings,
+            Self::Measurement(_) => TransferTable::Measurements,
+            Self::Notification(_) => TransferTable::Notifications,
+            Self::Firmware(_) => TransferTable::Firmwares,
+            Self::FirmwareStat(_) => TransferTable::FirmwareStats,
+            Self::Quarantine(_) => TransferTable::Quarantine,
+            Self::Calibration(_) => TransferTable::Calibrations,
+            Self::AlertState(_) => TransferTable::AlertStates,
+            Self::RollupHourly(_) => TransferTable::RollupsHourly,
+            Self::RollupDaily(_) => TransferTable::RollupsDaily,
+        }
+    }
+
+    /// ID of the row, `None` if its table has no ID.
+    pub const fn id(&self) -> Option<i32> {
+        match self {
+            Self::Device(row) => Some(row.id),
+            Self::Settings(row) => Some(row.id),
+            Self::Measurement(row) => Some(row.id),
+            Self::Notification(row) => Some(row.id),
+            Self::Firmware(row) => Some(row.id),
+            Self::FirmwareStat(row) => Some(row.id),
+            Self::Quarantine(row) => Some(row.id),
+            Self::Calibration(_)
+            | Self::AlertState(_)
+            | Self::RollupHourly(_)
+            | Self::RollupDaily(_) => None,
+        }
+    }
+}
+
+/// Position to read the batch after `batch` from, which was read from `after`.
+///
+/// That's the highest ID in the batch, or the number of rows read so far for tables without an ID.
+pub fn next_after(after: i32, batch: &[TransferRow]) -> Result<i32, Error> {
+    match batch.last().and_then(TransferRow::id) {
+        Some(id) => Ok(id),
+        None => Ok(after + i32::try_from(batch.len())?),
+    }
+}
+
+/// Copy the rows of every table that are not in the target yet, in batches of `batch_size` rows.
+/// Returns the total number of copied rows.
+pub async fn copy(
+    source: &DatabaseClient,
+    target: &DatabaseClient,
+    batch_size: u32,
+) -> Result<u64, Error> {
+    let mut copied = 0;
+
+    for table in TransferTable::ALL {
+        let total = source.get_table_state(table).await?.rows;
+        let mut after = target.get_table_state(table).await?.last_id.unwrap_or(0);
+        let mut rows = 0;
+        let limit = table.batch_limit(batch_size);
+
+        if after > 0 {
+            info!("Resuming {} after ID {after}", table.as_str());
+        }
+
+        loop {
+            let batch = source.read_rows(table, after, limit).await?;
+            if batch.is_empty() {
+                break;
+            }
+
+            after = next_after(after, &batch)?;
+            target.write_rows(&batch).await?;
+            rows += batch.len() as u64;
+
+            info!("Copied {rows} row(s) of {} ({total} total)", table.as_str());
+        }
+
+        if rows == 0 {
+            info!("Table {} is up to date", table.as_str());
+        }
+
+        copied += rows;
+    }
+
+    Ok(copied)
+}
+
+/// Compare the row counts of every table. Returns the tables whose counts differ, with the source and target counts.
+pub async fn verify(
+    source: &DatabaseClient,
+    target: &DatabaseClient,
+) -> Result<Vec<(TransferTable, u64, u64)>, Error> {
+    let mut mismatches = Vec::new();
+
+    for table in TransferTable::ALL {
+        let expected = source.get_table_state(table).await?.rows;
+        let actual = target.get_table_state(table).await?.rows;
+
+        if expected != actual {
+            mismatches.push((table, expected, actual));
+        }
+    }
+
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{
+        calibration::Calibration,
+        config::{Config, DatabaseConfig},
+        db::{MeasurementEntry, RawReadings},
+        diagnostics::SensorFlags,
+        notification_client::{Notification, NotificationKind, Severity},
+    };
+    use pwmp_client::pwmp_msg::version::Version;
+
+    async fn memory(devices: &[&str]) -> DatabaseClient {
+        let config = Config {
+            database: DatabaseConfig::Memory {
+                devices: devices.iter().map(|&mac| mac.into()).collect(),
+            },
+            ..Default::default()
+        };
+        DatabaseClient::new(&config).await.unwrap()
+    }
+
+    fn measurement(temperature: f32) -> MeasurementEntry {
+        MeasurementEntry {
+            when: Utc::now(),
+            temperature,
+            humidity: 50,
+            air_pressure: Some(1000),
+            cpu_temp: 30.0,
+            battery: 3.9,
+            wifi_ssid: "Home".to_string(),
+            wifi_rssi: -60,
+            flags: SensorFlags::default(),
+            raw: RawReadings {
+                temperature,
+                humidity: 50,
+                air_pressure: Some(1000),
+                battery: 3.9,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn copies_every_table() {
+        let source = memory(&["AA:BB:CC:DD:EE:01", "AA:BB:CC:DD:EE:02"]).await;
+        for (node, temperature) in [(1, 20.0), (1, 21.0), (2, 22.0)] {
+            source
+                .post_measurements(node, &measurement(temperature))
+                .await
+                .unwrap();
+        }
+        source
+            .quarantine_measurements(1, &measurement(150.0), "Too hot")
+            .await
+            .unwrap();
+        source
+            .set_calibration(
+                2,
+                &Calibration {
+                    temperature_offset: -1.5,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        for rule in ["Frost", "Heat", "Humid"] {
+            source.set_alert_state(1, rule, true, 3).await.unwrap();
+        }
+        source.refresh_rollups(None, None).await.unwrap();
+        source
+            .create_notification(&Notification {
+                node: 2,
+                kind: NotificationKind::Custom,
+                severity: Severity::Warning,
+                title: "Door open".into(),
+                body: "Front door".into(),
+                message: "Front door".into(),
+                url: None,
+            })
+            .await
+            .unwrap();
+        source
+            .upload_firmware(vec![0xE9; 16], Version::new(2, 0, 0), None)
+            .await
+            .unwrap();
+        source
+            .send_os_update_stat(1, Version::new(1, 0, 0), Version::new(2, 0, 0))
+            .await
+            .unwrap();
+
+        let target = memory(&[]).await;
+        copy(&source, &target, 2).await.unwrap();
+        assert!(verify(&source, &target).await.unwrap().is_empty());
+
+        // Resuming copies nothing new and upserts the tables without an ID
+        copy(&source, &target, 2).await.unwrap();
+        assert!(verify(&source, &target).await.unwrap().is_empty());
+
+        for table in TransferTable::ALL {
+            let state = target.get_table_state(table).await.unwrap();
+            assert_ne!(state.rows, 0, "{} is empty", table.as_str());
+            assert_eq!(state, source.get_table_state(table).await.unwrap());
+        }
+
+        let calibration = target.get_calibration(2).await.unwrap().unwrap();
+        assert!((calibration.temperature_offset + 1.5).abs() < f32::EPSILON);
+        assert_eq!(target.get_alert_states(1).await.unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,375 @@
+use super::{
+    NotifySender,
+    config::{DiagnosticsConfig, NeighborConfig},
+    db::{DatabaseBackend, DatabaseClient, MeasurementEntry, NodeId},
+    notification_client::{NotificationKind, NotificationTemplates},
+    notify::notify_send,
+};
+use crate::error::Error;
+use minijinja::context;
+use std::fmt::{self, Display};
+use tracing::{debug, info};
+
+/// Sensor faults keep their state along with alert rules, under a name that rules don't use.
+const SENSOR_RULE: &str = "@sensor";
+
+/// Minimum number of neighbors needed to compare a reading against.
+const MIN_NEIGHBORS: usize = 2;
+
+/// Problems detected in a measurement, stored along with it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SensorFlags(u16);
+
+impl SensorFlags {
+    /// The readings haven't changed at all for several measurements.
+    pub const STUCK: Self = Self(1);
+    /// The humidity is pinned at 0% or 100%.
+    pub const SATURATED: Self = Self(1 << 1);
+    /// A reading changed faster than physically plausible.
+    pub const RATE_OF_CHANGE: Self = Self(1 << 2);
+    /// A reading is far from the readings of other nodes.
+    pub const NEIGHBOR_DEVIATION: Self = Self(1 << 3);
+
+    const NAMES: [(Self, &str); 4] = [
+        (Self::STUCK, "stuck"),
+        (Self::SATURATED, "saturated"),
+        (Self::RATE_OF_CHANGE, "rate_of_change"),
+        (Self::NEIGHBOR_DEVIATION, "neighbor_deviation"),
+    ];
+
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// Names of the set flags.
+    pub fn names(self) -> Vec<&'static str> {
+        Self::NAMES
+            .iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+impl Display for SensorFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "none");
+        }
+
+        write!(f, "{}", self.names().join(", "))
+    }
+}
+
+/// Check a new measurement of a node against its previous measurements and against other nodes.
+pub async fn diagnose(
+    config: &DiagnosticsConfig,
+    node: NodeId,
+    readings: &MeasurementEntry,
+    db: &DatabaseClient,
+) -> Result<SensorFlags, Error> {
+    let mut flags = SensorFlags::default();
+
+    if config.saturation && (readings.humidity == 0 || readings.humidity >= 100) {
+        flags.insert(SensorFlags::SATURATED);
+    }
+
+    // The new measurement counts as one of the stuck readings
+    let history = config
+        .stuck_readings
+        .map_or(1, |stuck_readings| stuck_readings.saturating_sub(1).max(1));
+    let previous = db.get_recent_measurements(node, history).await?;
+
+    if let Some(stuck_readings) = config.stuck_readings
+        && stuck_readings > 1
+        && previous.len() >= (stuck_readings - 1) as usize
+        && previous
+            .iter()
+            .all(|measurement| is_same(measurement, readings))
+    {
+        flags.insert(SensorFlags::STUCK);
+    }
+
+    if let Some(last) = previous.first()
+        && exceeds_rate(config, last, readings)
+    {
+        flags.insert(SensorFlags::RATE_OF_CHANGE);
+    }
+
+    if let Some(neighbors) = &config.neighbors
+        && deviates_from_neighbors(neighbors, node, readings, db).await?
+    {
+        flags.insert(SensorFlags::NEIGHBOR_DEVIATION);
+    }
+
+    if !flags.is_empty() {
+        debug!("Node #{node}: sensor flags {flags}");
+    }
+
+    Ok(flags)
+}
+
+#[allow(clippy::float_cmp)] // a frozen sensor reports exactly the same value
+fn is_same(measurement: &MeasurementEntry, readings: &MeasurementEntry) -> bool {
+    measurement.temperature == readings.temperature
+        && measurement.humidity == readings.humidity
+        && measurement.air_pressure == readings.air_pressure
+}
+
+/// Whether any reading changed faster than the configured rate per hour.
+///
+/// Readings less than an hour apart are compared as if they were an hour apart.
+#[allow(clippy::cast_precision_loss)] // the time difference is small
+fn exceeds_rate(
+    config: &DiagnosticsConfig,
+    last: &MeasurementEntry,
+    readings: &MeasurementEntry,
+) -> bool {
+    let hours = ((readings.when - last.when).num_seconds() as f64 / 3600.0).max(1.0);
+    let exceeds = |max_rate: Option<f32>, previous: f64, current: f64| {
+        max_rate.is_some_and(|max_rate| (current - previous).abs() / hours > f64::from(max_rate))
+    };
+
+    exceeds(
+        config.max_temperature_rate,
+        f64::from(last.temperature),
+        f64::from(readings.temperature),
+    ) || exceeds(
+        config.max_humidity_rate,
+        f64::from(last.humidity),
+        f64::from(readings.humidity),
+    ) || last
+        .air_pressure
+        .zip(readings.air_pressure)
+        .is_some_and(|(previous, current)| {
+            exceeds(
+                config.max_air_pressure_rate,
+                f64::from(previous),
+                f64::from(current),
+            )
+        })
+}
+
+/// Whether the temperature or humidity is far from the median of the latest readings of other nodes.
+async fn deviates_from_neighbors(
+    config: &NeighborConfig,
+    node: NodeId,
+    readings: &MeasurementEntry,
+    db: &DatabaseClient,
+) -> Result<bool, Error> {
+    if config.exclude.contains(&node) {
+        return Ok(false);
+    }
+
+    let mut temperatures = Vec::new();
+    let mut humidities = Vec::new();
+
+    for (other, last) in db.get_latest_measurements(config.max_age).await? {
+        // Flagged readings would skew the comparison
+        if other == node || config.exclude.contains(&other) || !last.flags.is_empty() {
+            continue;
+        }
+
+        temperatures.push(f64::from(last.temperature));
+        humidities.push(f64::from(last.humidity));
+    }
+
+    if temperatures.len() < MIN_NEIGHBORS {
+        return Ok(false);
+    }
+
+    let deviates = |max_deviation: Option<f32>, values: &mut Vec<f64>, value: f64| {
+        max_deviation
+            .is_some_and(|max_deviation| (value - median(values)).abs() > f64::from(max_deviation))
+    };
+
+    Ok(deviates(
+        config.max_temperature_deviation,
+        &mut temperatures,
+        f64::from(readings.temperature),
+    ) || deviates(
+        config.max_humidity_deviation,
+        &mut humidities,
+        f64::from(readings.humidity),
+    ))
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+
+    if values.len().is_multiple_of(2) {
+        f64::midpoint(values[middle - 1], values[middle])
+    } else {
+        values[middle]
+    }
+}
+
+/// Notify when a node's sensors start reporting problems. Notifies again only after a clean measurement.
+pub async fn notify_faults(
+    notify: &NotifySender,
+    templates: &NotificationTemplates,
+    node_id: NodeId,
+    db: &DatabaseClient,
+    measurement: &MeasurementEntry,
+) -> Result<(), Error> {
+    let flags = measurement.flags;
+    let faulty = !flags.is_empty();
+    let was_faulty = db
+        .get_alert_states(node_id)
+        .await?
+        .iter()
+        .any(|state| state.rule == SENSOR_RULE && state.active);
+
+    if faulty == was_faulty {
+        return Ok(());
+    }
+
+    db.set_alert_state(node_id, SENSOR_RULE, faulty, 0).await?;
+
+    if !faulty {
+        debug!("Node #{node_id}: sensors are healthy again");
+        return Ok(());
+    }
+
+    info!("Node #{node_id}: sensor problems detected ({flags})");
+    let altitude = db
+        .get_device(node_id)
+        .await?
+        .and_then(|device| device.altitude);
+
+    notify_send(
+        notify,
+        templates,
+        node_id,
+        db,
+        NotificationKind::SensorFault,
+        context! {
+            flags => flags.names(),
+            ..templates.measurements(measurement, altitude)
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{
+        config::{Config, DatabaseConfig},
+        db::RawReadings,
+    };
+    use chrono::Utc;
+    use std::time::Duration;
+
+    async fn database() -> DatabaseClient {
+        let config = Config {
+            database: DatabaseConfig::Memory {
+                devices: (1..=4)
+                    .map(|node| format!("AA:BB:CC:DD:EE:0{node}").into())
+                    .collect(),
+            },
+            ..Default::default()
+        };
+
+        DatabaseClient::new(&config).await.unwrap()
+    }
+
+    fn measurement(temperature: f32, humidity: u8, flags: SensorFlags) -> MeasurementEntry {
+        MeasurementEntry {
+            when: Utc::now(),
+            temperature,
+            humidity,
+            air_pressure: None,
+            cpu_temp: 30.0,
+            battery: 3.9,
+            wifi_ssid: "Home".to_string(),
+            wifi_rssi: -60,
+            flags,
+            raw: RawReadings {
+                temperature,
+                humidity,
+                air_pressure: None,
+                battery: 3.9,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn checks_are_opt_in() {
+        let db = database().await;
+
+        for _ in 0..20 {
+            db.post_measurements(1, &measurement(20.0, 100, SensorFlags::default()))
+                .await
+                .unwrap();
+        }
+
+        let flags = diagnose(
+            &DiagnosticsConfig::default(),
+            1,
+            &measurement(20.0, 100, SensorFlags::default()),
+            &db,
+        )
+        .await
+        .unwrap();
+        assert!(flags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn compares_with_unflagged_neighbors() {
+        let db = database().await;
+        let config = DiagnosticsConfig {
+            neighbors: Some(NeighborConfig {
+                max_temperature_deviation: Some(5.0),
+                max_humidity_deviation: None,
+                max_age: Duration::from_hours(1),
+                exclude: Vec::new(),
+            }),
+            ..Default::default()
+        };
+
+        db.post_measurements(2, &measurement(20.0, 50, SensorFlags::default()))
+            .await
+            .unwrap();
+        db.post_measurements(3, &measurement(30.0, 50, SensorFlags::default()))
+            .await
+            .unwrap();
+        db.post_measurements(4, &measurement(30.0, 50, SensorFlags::STUCK))
+            .await
+            .unwrap();
+
+        // The median is 25°C, since the flagged reading of node #4 is ignored
+        for (temperature, deviates) in [(20.0, false), (31.0, true), (19.0, true)] {
+            let flags = diagnose(
+                &config,
+                1,
+                &measurement(temperature, 50, SensorFlags::default()),
+                &db,
+            )
+            .await
+            .unwrap();
+            assert_eq!(
+                flags.contains(SensorFlags::NEIGHBOR_DEVIATION),
+                deviates,
+                "{temperature}°C"
+            );
+        }
+    }
+}
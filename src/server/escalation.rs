@@ -0,0 +1,194 @@
+use super::{
+    NotifySender,
+    config::{Config, EscalationConfig},
+    db::{DatabaseBackend, DatabaseClient},
+    notification_client::{NotificationClient, NotificationKind, NotificationTemplates, Severity},
+};
+use crate::error::Error;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+
+/// How often to look for notifications that need to be escalated.
+const CHECK_INTERVAL: Duration = Duration::from_mins(1);
+
+/// Periodically re-send unread notifications, either through the push backend or the escalation backend (`secondary`).
+pub async fn escalation_loop(
+    config: &Config,
+    secondary: Option<NotificationClient>,
+    db: &DatabaseClient,
+    notify: &NotifySender,
+    templates: &NotificationTemplates,
+) {
+    let Some(policy) = &config.notification.escalation else {
+        return;
+    };
+
+    info!(
+        "Escalating unread {:?}+ notifications after {}",
+        policy.min_severity,
+        humantime::format_duration(policy.after)
+    );
+    let mut timer = interval(CHECK_INTERVAL);
+
+    loop {
+        timer.tick().await;
+
+        if let Err(why) = escalate(policy, secondary.as_ref(), db, notify, templates).await {
+            error!("Failed to escalate notifications: {why}");
+        }
+    }
+}
+
+async fn escalate(
+    policy: &EscalationConfig,
+    secondary: Option<&NotificationClient>,
+    db: &DatabaseClient,
+    notify: &NotifySender,
+    templates: &NotificationTemplates,
+) -> Result<(), Error> {
+    let candidates = db
+        .get_unacknowledged_notifications(policy.after, policy.max_escalations, policy.min_severity)
+        .await?;
+
+    if candidates.is_empty() {
+        return Ok(());
+    }
+
+    let devices = db.get_devices().await?;
+
+    for entry in candidates {
+        let Some(severity) = Severity::parse(&entry.severity) else {
+            warn!(
+                "Notification #{} has an unknown severity '{}'",
+                entry.id, entry.severity
+            );
+            continue;
+        };
+
+        if db
+            .get_settings(entry.node)
+            .await?
+            .is_some_and(|settings| settings.mute_notifications)
+        {
+            debug!("Node #{} is muted, not escalating", entry.node);
+            continue;
+        }
+
+        let Some(device) = devices.iter().find(|device| device.id == entry.node) else {
+            continue;
+        };
+
+        let kind = NotificationKind::parse(&entry.kind).unwrap_or(NotificationKind::Custom);
+        let result = async {
+            let notification =
+                templates.restore(device, kind, severity, &entry.title, &entry.content)?;
+
+            match secondary {
+                Some(client) => client.send_notification(&notification).await,
+                None => notify
+                    .try_send(notification)
+                    .map_err(|_| Error::MpscTrySend),
+            }
+        }
+        .await;
+
+        // The rest of the batch is still escalated, this one is retried on the next check
+        if let Err(why) = result {
+            error!("Failed to escalate notification #{}: {why}", entry.id);
+            continue;
+        }
+
+        db.mark_notification_escalated(entry.id).await?;
+        info!(
+            "Escalated notification #{} of node #{}",
+            entry.id, entry.node
+        );
+    }
+
+    // The escalation backend has no notifier loop, so digests have to be sent right away
+    if let Some(client) = secondary {
+        client.flush().await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{
+        config::{
+            DatabaseConfig, ExecInput, NotificationServiceConfig, NotificationTemplatesConfig,
+        },
+        notification_client::Notification,
+    };
+    use tokio::sync::mpsc;
+
+    async fn database() -> DatabaseClient {
+        let config = Config {
+            database: DatabaseConfig::Memory {
+                devices: vec!["AA:BB:CC:DD:EE:FF".into()],
+            },
+            ..Default::default()
+        };
+
+        DatabaseClient::new(&config).await.unwrap()
+    }
+
+    async fn create(db: &DatabaseClient, title: &str, severity: Severity) {
+        db.create_notification(&Notification {
+            node: 1,
+            kind: NotificationKind::Custom,
+            severity,
+            title: title.into(),
+            body: title.into(),
+            message: title.into(),
+            url: None,
+        })
+        .await
+        .unwrap();
+    }
+
+    /// Titles of the notifications that can still be escalated.
+    async fn pending(db: &DatabaseClient) -> Vec<String> {
+        db.get_unacknowledged_notifications(Duration::ZERO, 1, Severity::Info)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.title)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn continues_after_failed_send() {
+        let db = database().await;
+        let templates =
+            NotificationTemplates::new(&NotificationTemplatesConfig::default()).unwrap();
+        let (notify, _receiver) = mpsc::channel(8);
+        let policy = EscalationConfig {
+            min_severity: Severity::Warning,
+            after: Duration::ZERO,
+            max_escalations: 1,
+            backend: None,
+        };
+        let secondary = NotificationClient::new(Some(&NotificationServiceConfig::Exec {
+            command: "/bin/sh".into(),
+            args: vec!["-c".into(), r#"test "$PWMP_TITLE" != Broken"#.into()],
+            input: ExecInput::Env,
+            timeout: Duration::from_secs(5),
+        }))
+        .unwrap();
+
+        create(&db, "Broken", Severity::Critical).await;
+        create(&db, "Working", Severity::Critical).await;
+        create(&db, "Minor", Severity::Info).await;
+
+        escalate(&policy, Some(&secondary), &db, &notify, &templates)
+            .await
+            .unwrap();
+
+        // The failed one is retried, and the informational one is never picked up
+        assert_eq!(pending(&db).await, ["Broken", "Minor"]);
+    }
+}
@@ -1,27 +1,32 @@
 use super::{
     NotifyReceiver, NotifySender, config::Config, db::DatabaseClient, rate_limit::RateLimiter,
 };
-use crate::server::{client_handle::handle_client, notification_client::NotificationClient};
+use crate::server::{
+    client_handle::handle_client,
+    notification_client::{NotificationClient, NotificationTemplates},
+};
 use semaphore::Semaphore;
-use std::{net::SocketAddr, panic, sync::Arc};
+use std::{future, net::SocketAddr, panic, sync::Arc};
 use tokio::{
     net::{TcpListener, TcpStream},
     runtime::Handle,
     select,
     signal::unix::Signal,
+    sync::oneshot,
+    time::{Interval, interval},
 };
 use tracing::{debug, error, info, warn};
 
 #[allow(clippy::needless_pass_by_value, clippy::cognitive_complexity)]
 pub async fn server_loop(
     server: &TcpListener,
-    db: DatabaseClient,
+    shared_db: Arc<DatabaseClient>,
     notify: NotifySender,
+    templates: Arc<NotificationTemplates>,
     config: Arc<Config>,
     mut stop_sig: Signal,
     mut ping_sig: Signal,
 ) {
-    let shared_db = Arc::new(db);
     let connections = Semaphore::new(config.limits.devices as _, ());
     let mut rate_limiter = RateLimiter::new(config.rate_limits.max_connections);
 
@@ -29,7 +34,7 @@ pub async fn server_loop(
         select! {
             res = server.accept() => {
                 match res {
-                    Ok(res) => handle_new_client(res.0, res.1, notify.clone(), Arc::clone(&shared_db), &connections, &mut rate_limiter, Arc::clone(&config)),
+                    Ok(res) => handle_new_client(res.0, res.1, notify.clone(), Arc::clone(&templates), Arc::clone(&shared_db), &connections, &mut rate_limiter, Arc::clone(&config)),
                     Err(why) => {
                         error!("Failed to accept connection: {why}");
                         return;
@@ -50,11 +55,12 @@ pub async fn server_loop(
     }
 }
 
-#[allow(clippy::cognitive_complexity)]
+#[allow(clippy::cognitive_complexity, clippy::too_many_arguments)]
 fn handle_new_client(
     client: TcpStream,
     peer_addr: SocketAddr,
     notify: NotifySender,
+    templates: Arc<NotificationTemplates>,
     shared_db: Arc<DatabaseClient>,
     connections: &Semaphore<()>,
     rate_limiter: &mut RateLimiter,
@@ -84,7 +90,7 @@ fn handle_new_client(
         let _semguard = semguard;
 
         debug!("Starting client handle");
-        match handle_client(client, peer_addr, &shared_db, &notify, config).await {
+        match handle_client(client, peer_addr, &shared_db, &notify, &templates, config).await {
             Ok(()) => {
                 debug!("{peer_addr}: Handled successfully");
             }
@@ -111,20 +117,59 @@ fn display_rt_metrics() {
     }
 }
 
-pub async fn notify_loop(mut receiver: NotifyReceiver, notify: NotificationClient) {
+/// Send notifications from `receiver` until `stop` fires, then deliver the ones still queued.
+pub async fn notify_loop(
+    mut receiver: NotifyReceiver,
+    notify: NotificationClient,
+    mut stop: oneshot::Receiver<()>,
+) {
     debug!("Starting notifier loop");
+    let mut digest_timer = notify.digest_interval().map(interval);
+    let mut stopping = false;
 
     loop {
-        let Some(message) = receiver.recv().await else {
-            debug!("Notifier loop received None, exiting");
-            break;
-        };
-
-        match notify.send_notification(&message).await {
-            Ok(()) => debug!("Notification sent successfully"),
-            Err(why) => error!("Failed to send notification: {why}"),
+        select! {
+            message = receiver.recv() => {
+                let Some(message) = message else {
+                    debug!("Notifier loop received None, exiting");
+                    break;
+                };
+
+                match notify.send_notification(&message).await {
+                    Ok(()) => debug!("Notification sent successfully"),
+                    Err(why) => error!("Failed to send notification: {why}"),
+                }
+            }
+
+            () = tick(digest_timer.as_mut()) => flush_notifications(&notify).await,
+
+            _ = &mut stop, if !stopping => {
+                // Senders live as long as the server, so close the channel and drain what's left
+                debug!("Stopping notifier loop");
+                stopping = true;
+                receiver.close();
+            }
         }
     }
 
+    // Don't lose notifications that are still waiting for the next digest
+    flush_notifications(&notify).await;
+
     debug!("Notifier loop exited");
 }
+
+async fn flush_notifications(notify: &NotificationClient) {
+    if let Err(why) = notify.flush().await {
+        error!("Failed to send queued notifications: {why}");
+    }
+}
+
+/// Wait for the next tick of an optional timer. If there is no timer, this never completes.
+async fn tick(timer: Option<&mut Interval>) {
+    match timer {
+        Some(timer) => {
+            timer.tick().await;
+        }
+        None => future::pending().await,
+    }
+}
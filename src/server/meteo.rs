@@ -0,0 +1,131 @@
+//! Meteorological metrics derived from the readings of a measurement.
+//!
+//! The dew point, its category and the sea-level pressure use the same formulas as the `pwmp_*` helper functions of the `PostgreSQL` schema,
+//! so both agree.
+
+use pwmp_client::pwmp_msg::aliases::{AirPressure, Humidity, Temperature};
+
+/// Magnus formula coefficients.
+const MAGNUS_A: f64 = 17.27;
+const MAGNUS_B: f64 = 237.3;
+
+/// Standard temperature lapse rate in K/m.
+const LAPSE_RATE: f64 = 0.0065;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DerivedMetrics {
+    /// Dew point in °C, not available if the humidity is 0%.
+    pub dew_point: Option<f64>,
+    /// Human comfort level of the dew point.
+    pub dew_point_category: Option<&'static str>,
+    /// Apparent temperature in °C, accounting for humidity (NOAA).
+    pub heat_index: f64,
+    /// Canadian humidex, not available if the humidity is 0%.
+    pub humidex: Option<f64>,
+    /// Water vapor density in g/m³.
+    pub absolute_humidity: f64,
+    /// Air pressure reduced to sea level in hPa, only available for nodes with an air pressure sensor and a known altitude.
+    pub sea_level_pressure: Option<f64>,
+}
+
+impl DerivedMetrics {
+    /// Derive the metrics from the readings of a measurement, `altitude` being the node's altitude in meters.
+    pub fn new(
+        temperature: Temperature,
+        humidity: Humidity,
+        air_pressure: Option<AirPressure>,
+        altitude: Option<f32>,
+    ) -> Self {
+        let celsius = f64::from(temperature);
+        let dew_point = dew_point(celsius, humidity);
+
+        Self {
+            dew_point,
+            dew_point_category: dew_point.map(dew_point_category),
+            heat_index: heat_index(celsius, humidity),
+            humidex: dew_point.map(|dew_point| humidex(celsius, dew_point)),
+            absolute_humidity: absolute_humidity(celsius, humidity),
+            sea_level_pressure: air_pressure
+                .zip(altitude)
+                .and_then(|(hpa, altitude)| sea_level_pressure(hpa, celsius, f64::from(altitude))),
+        }
+    }
+}
+
+/// Dew point in °C, using the Magnus formula.
+pub fn dew_point(celsius: f64, humidity: Humidity) -> Option<f64> {
+    // The logarithm of zero is undefined, this usually means the sensor glitched
+    if humidity == 0 {
+        return None;
+    }
+
+    let alpha = (MAGNUS_A * celsius) / (MAGNUS_B + celsius) + (f64::from(humidity) / 100.0).ln();
+    Some(MAGNUS_B * alpha / (MAGNUS_A - alpha))
+}
+
+/// Human comfort level of a dew point in °C.
+pub fn dew_point_category(dew_point: f64) -> &'static str {
+    match dew_point {
+        ..10.0 => "Dry",
+        ..=15.0 => "Comfortable",
+        ..=18.0 => "Humid",
+        ..=21.0 => "Muggy",
+        ..=24.0 => "Oppressive",
+        _ => "Dangerous",
+    }
+}
+
+/// Heat index in °C, using the NOAA approximation with the Rothfusz regression for hot conditions.
+#[allow(clippy::suboptimal_flops)] // written like the published regression
+pub fn heat_index(celsius: f64, humidity: Humidity) -> f64 {
+    let t = celsius.mul_add(1.8, 32.0);
+    let rh = f64::from(humidity);
+
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+
+    let fahrenheit = if f64::midpoint(simple, t) < 80.0 {
+        simple
+    } else {
+        let mut hi = -42.379 + 2.049_015_23 * t + 10.143_331_27 * rh
+            - 0.224_755_41 * t * rh
+            - 0.006_837_83 * t * t
+            - 0.054_817_17 * rh * rh
+            + 0.001_228_74 * t * t * rh
+            + 0.000_852_82 * t * rh * rh
+            - 0.000_001_99 * t * t * rh * rh;
+
+        if rh < 13.0 && (80.0..=112.0).contains(&t) {
+            hi -= ((13.0 - rh) / 4.0) * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+        } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+            hi += ((rh - 85.0) / 10.0) * ((87.0 - t) / 5.0);
+        }
+
+        hi
+    };
+
+    (fahrenheit - 32.0) / 1.8
+}
+
+/// Humidex, from the temperature and dew point in °C.
+pub fn humidex(celsius: f64, dew_point: f64) -> f64 {
+    let vapor_pressure = 6.11 * (5417.7530 * (1.0 / 273.16 - 1.0 / (273.15 + dew_point))).exp();
+    0.5555f64.mul_add(vapor_pressure - 10.0, celsius)
+}
+
+/// Absolute humidity in g/m³.
+pub fn absolute_humidity(celsius: f64, humidity: Humidity) -> f64 {
+    let saturation_pressure = 6.112 * ((17.67 * celsius) / (celsius + 243.5)).exp();
+    saturation_pressure * f64::from(humidity) * 2.1674 / (273.15 + celsius)
+}
+
+/// Air pressure reduced to sea level in hPa, using the barometric formula.
+pub fn sea_level_pressure(hpa: AirPressure, celsius: f64, altitude: f64) -> Option<f64> {
+    // Protect against absolute zero math errors
+    if celsius < -273.0 {
+        return None;
+    }
+
+    let lapse = LAPSE_RATE * altitude;
+    let reduction = 1.0 - lapse / (celsius + lapse + 273.15);
+    Some(f64::from(hpa) * reduction.powf(-5.257))
+}
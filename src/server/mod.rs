@@ -1,7 +1,7 @@
 use crate::server::{
-    db::DatabaseClient,
+    db::{DatabaseBackend, DatabaseClient, migrations::SCHEMA_VERSION},
     handle::{notify_loop, server_loop},
-    notification_client::NotificationClient,
+    notification_client::{Notification, NotificationClient, NotificationTemplates},
 };
 use config::Config;
 use socket2::SockRef;
@@ -9,34 +9,48 @@ use std::{io, os::fd::AsFd, process::exit, sync::Arc, time::Duration};
 use tokio::{
     net::TcpListener,
     signal::unix::{Signal, SignalKind, signal},
-    sync::mpsc,
+    sync::{mpsc, oneshot},
 };
 use tracing::{error, info};
 
-pub type NotifySender = mpsc::Sender<Box<str>>;
-pub type NotifyReceiver = mpsc::Receiver<Box<str>>;
+pub type NotifySender = mpsc::Sender<Notification>;
+pub type NotifyReceiver = mpsc::Receiver<Notification>;
 
+mod alerts;
+pub mod battery;
+pub mod calibration;
 mod client;
 mod client_handle;
 pub mod config;
 pub mod db;
+pub mod diagnostics;
+mod escalation;
 pub mod handle;
+pub mod meteo;
 pub mod notification_client;
+mod notify;
 pub mod rate_limit;
+pub mod retention;
+mod trend;
+pub mod validation;
+mod watchdog;
+pub mod wifi;
 
-#[allow(clippy::cognitive_complexity)]
+#[allow(clippy::cognitive_complexity, clippy::too_many_lines)]
 pub async fn main(config: Config) {
     let config = Arc::new(config);
 
     info!("Connecting to database at \"{}\"", config.database.host());
     let db = match DatabaseClient::new(&config).await {
-        Ok(db) => db,
+        Ok(db) => Arc::new(db),
         Err(why) => {
             error!("Failed to connect to database: {why}");
             exit(1);
         }
     };
 
+    check_schema_version(&db).await;
+
     info!("Setting up notification backend");
     let notify = match NotificationClient::new(config.notification.push_backend.as_ref()) {
         Ok(client) => client,
@@ -45,11 +59,71 @@ pub async fn main(config: Config) {
             exit(1);
         }
     };
+    let templates = match NotificationTemplates::new(&config.notification.templates) {
+        Ok(templates) => Arc::new(templates),
+        Err(why) => {
+            error!("Failed to compile notification templates: {why}");
+            exit(1);
+        }
+    };
+    if let Some(bot) = notify.telegram_bot() {
+        let db = Arc::clone(&db);
+        let config = Arc::clone(&config);
+        tokio::task::spawn(async move {
+            bot.command_loop(db, config).await;
+        });
+    }
+
     let (notify_sender, notify_receiver) = mpsc::channel(8);
-    tokio::task::spawn(async move {
-        notify_loop(notify_receiver, notify).await;
+    let (notify_stop, notify_stop_receiver) = oneshot::channel();
+    let notifier = tokio::task::spawn(async move {
+        notify_loop(notify_receiver, notify, notify_stop_receiver).await;
     });
 
+    if let Some(policy) = &config.notification.escalation {
+        let secondary = match policy
+            .backend
+            .as_ref()
+            .map(|backend| NotificationClient::new(Some(backend)))
+            .transpose()
+        {
+            Ok(client) => client,
+            Err(why) => {
+                error!("Failed to set up escalation backend: {why}");
+                exit(1);
+            }
+        };
+
+        let (config, db, notify, templates) = (
+            Arc::clone(&config),
+            Arc::clone(&db),
+            notify_sender.clone(),
+            Arc::clone(&templates),
+        );
+        tokio::task::spawn(async move {
+            escalation::escalation_loop(&config, secondary, &db, &notify, &templates).await;
+        });
+    }
+
+    if config.notification.watchdog.is_some() {
+        let (config, db, notify, templates) = (
+            Arc::clone(&config),
+            Arc::clone(&db),
+            notify_sender.clone(),
+            Arc::clone(&templates),
+        );
+        tokio::task::spawn(async move {
+            watchdog::watchdog_loop(&config, &db, &notify, &templates).await;
+        });
+    }
+
+    if config.retention.is_enabled() {
+        let (config, db) = (Arc::clone(&config), Arc::clone(&db));
+        tokio::task::spawn(async move {
+            retention::retention_loop(&config, &db).await;
+        });
+    }
+
     let server = match TcpListener::bind(config.server_bind_addr()).await {
         Ok(socket) => socket,
         Err(why) => {
@@ -66,7 +140,45 @@ pub async fn main(config: Config) {
     let (stop_sig, ping_sig) = setup_signals();
 
     info!("Server started on {}", config.server_bind_addr());
-    server_loop(&server, db, notify_sender, config, stop_sig, ping_sig).await;
+    server_loop(
+        &server,
+        db,
+        notify_sender,
+        templates,
+        config,
+        stop_sig,
+        ping_sig,
+    )
+    .await;
+
+    // Let the notifier send what it has queued, eg. a pending email digest
+    let _ = notify_stop.send(());
+    if let Err(why) = notifier.await {
+        error!("Notifier task failed: {why}");
+    }
+}
+
+/// Make sure the database schema is at the version this server requires.
+pub async fn check_schema_version(db: &DatabaseClient) {
+    match db.get_schema_version().await {
+        Ok(SCHEMA_VERSION) => (),
+        Ok(version) if version > SCHEMA_VERSION => {
+            error!(
+                "Database schema version {version} is newer than this server supports ({SCHEMA_VERSION}), upgrade the server"
+            );
+            exit(1);
+        }
+        Ok(version) => {
+            error!(
+                "Database schema version {version} is outdated, run `database migrate` to upgrade it to version {SCHEMA_VERSION}"
+            );
+            exit(1);
+        }
+        Err(why) => {
+            error!("Failed to check database schema version: {why}");
+            exit(1);
+        }
+    }
 }
 
 fn setup_signals() -> (Signal, Signal) {
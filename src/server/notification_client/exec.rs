@@ -0,0 +1,208 @@
+use super::Notification;
+use crate::{error::Error, server::config::ExecInput};
+use serde_json::json;
+use std::{path::PathBuf, process::Stdio, time::Duration};
+use tokio::{io::AsyncWriteExt, process::Command, time::timeout};
+use tracing::{error, warn};
+
+#[derive(Debug)]
+pub struct ExecClient {
+    command: PathBuf,
+    args: Box<[Box<str>]>,
+    input: ExecInput,
+    timeout: Duration,
+}
+
+impl ExecClient {
+    pub fn new(command: PathBuf, args: &[Box<str>], input: ExecInput, timeout: Duration) -> Self {
+        Self {
+            command,
+            args: args.into(),
+            input,
+            timeout,
+        }
+    }
+
+    #[tracing::instrument(
+        name = "ExecClient::send_notification()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    pub async fn send_notification(&self, notification: &Notification) -> Result<(), Error> {
+        let mut command = Command::new(&self.command);
+        command
+            .args(self.args.iter().map(AsRef::<str>::as_ref))
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        match self.input {
+            ExecInput::Env => {
+                command
+                    .stdin(Stdio::null())
+                    .env("PWMP_TITLE", notification.title.as_ref())
+                    .env("PWMP_MESSAGE", notification.body.as_ref())
+                    .env("PWMP_URL", notification.url.as_deref().unwrap_or_default())
+                    .env("PWMP_NODE", notification.node.to_string())
+                    .env("PWMP_EVENT", notification.kind.as_str())
+                    .env("PWMP_SEVERITY", notification.severity.as_str());
+            }
+            ExecInput::Json => {
+                command.stdin(Stdio::piped());
+            }
+        }
+
+        let mut child = command.spawn()?;
+        let stdin = child.stdin.take();
+
+        let run = async move {
+            if let Some(mut stdin) = stdin {
+                let payload = json!({
+                    "title": notification.title,
+                    "message": notification.body,
+                    "url": notification.url,
+                    "node": notification.node,
+                    "event": notification.kind.as_str(),
+                    "severity": notification.severity.as_str(),
+                });
+
+                // The command may exit without reading its input, its exit status is reported below
+                if let Err(why) = stdin.write_all(payload.to_string().as_bytes()).await {
+                    warn!(
+                        "Failed to write the notification to the input of {}: {why}",
+                        self.command.display()
+                    );
+                }
+                // `stdin` is dropped here, which closes the pipe
+            }
+
+            child.wait_with_output().await
+        };
+
+        // If the timeout elapses, the child is dropped, which kills it
+        let Ok(output) = timeout(self.timeout, run).await else {
+            error!(
+                "Notification command {} timed out after {:?}",
+                self.command.display(),
+                self.timeout
+            );
+            return Err(Error::SubprocessTimeout);
+        };
+        let output = output?;
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim_end();
+
+        if !output.status.success() {
+            error!(
+                "Notification command {} exited with {}: {stderr}",
+                self.command.display(),
+                output.status
+            );
+            return Err(Error::SubprocessExit);
+        }
+
+        if !stderr.is_empty() {
+            warn!(
+                "Notification command {} printed to stderr: {stderr}",
+                self.command.display()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::notification_client::{NotificationKind, Severity};
+
+    fn client(script: &str, input: ExecInput) -> ExecClient {
+        ExecClient::new(
+            "/bin/sh".into(),
+            &["-c".into(), script.into()],
+            input,
+            Duration::from_secs(5),
+        )
+    }
+
+    fn notification(body: &str) -> Notification {
+        Notification {
+            node: 3,
+            kind: NotificationKind::Custom,
+            severity: Severity::Warning,
+            title: "Door open".into(),
+            body: body.into(),
+            message: body.into(),
+            url: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn passes_environment() {
+        let client = client(
+            r#"test "$PWMP_TITLE $PWMP_MESSAGE $PWMP_NODE $PWMP_EVENT $PWMP_SEVERITY" = "Door open Front door 3 custom warning""#,
+            ExecInput::Env,
+        );
+
+        client
+            .send_notification(&notification("Front door"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn passes_json() {
+        let client = client(
+            r#"grep -q '"event":"custom".*"node":3.*"severity":"warning".*"title":"Door open"'"#,
+            ExecInput::Json,
+        );
+
+        client
+            .send_notification(&notification("Front door"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn fails_on_exit_status() {
+        let client = client("echo broken >&2; exit 3", ExecInput::Env);
+
+        let why = client
+            .send_notification(&notification("Front door"))
+            .await
+            .unwrap_err();
+        assert!(matches!(why, Error::SubprocessExit));
+    }
+
+    #[tokio::test]
+    async fn reports_exit_status_when_input_is_not_read() {
+        let client = client("exec 0<&-; exit 3", ExecInput::Json);
+
+        // Larger than a pipe buffer, so writing fails once the command closes its input
+        let why = client
+            .send_notification(&notification(&"x".repeat(1 << 20)))
+            .await
+            .unwrap_err();
+        assert!(matches!(why, Error::SubprocessExit));
+    }
+
+    #[tokio::test]
+    async fn kills_command_on_timeout() {
+        let client = ExecClient::new(
+            "/bin/sh".into(),
+            &["-c".into(), "sleep 10".into()],
+            ExecInput::Env,
+            Duration::from_millis(100),
+        );
+
+        let why = client
+            .send_notification(&notification("Front door"))
+            .await
+            .unwrap_err();
+        assert!(matches!(why, Error::SubprocessTimeout));
+    }
+}
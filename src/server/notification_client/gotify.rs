@@ -0,0 +1,74 @@
+use super::{Notification, SeverityMap, base_url};
+use crate::{error::Error, server::config::HttpAuth};
+use reqwest::{Client, Url};
+use serde_json::json;
+
+/// Highest priority accepted by Gotify.
+const MAX_PRIORITY: u8 = 10;
+
+#[derive(Debug)]
+pub struct GotifyClient {
+    client: Client,
+    url: Url,
+    auth: HttpAuth,
+    priorities: SeverityMap<u8>,
+    click: Option<Box<str>>,
+}
+
+impl GotifyClient {
+    pub fn new(
+        url: &str,
+        auth: &HttpAuth,
+        priorities: &SeverityMap<u8>,
+        click: Option<&str>,
+    ) -> Result<Self, Error> {
+        let client = reqwest::Client::new();
+
+        let url = base_url(url)?.join("message")?;
+
+        Ok(Self {
+            client,
+            url,
+            auth: auth.clone(),
+            priorities: SeverityMap {
+                info: priorities.info.min(MAX_PRIORITY),
+                warning: priorities.warning.min(MAX_PRIORITY),
+                critical: priorities.critical.min(MAX_PRIORITY),
+            },
+            click: click.map(Into::into),
+        })
+    }
+
+    #[tracing::instrument(
+        name = "GotifyClient::send_notification()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    pub async fn send_notification(&self, notification: &Notification) -> Result<(), Error> {
+        let mut body = json!({
+            "title": notification.title,
+            "message": notification.to_string(),
+            "priority": self.priorities.get(notification.severity),
+        });
+
+        if let Some(click) = notification.url.as_ref().or(self.click.as_ref()) {
+            body["extras"] = json!({
+                "client::notification": {
+                    "click": { "url": click }
+                }
+            });
+        }
+
+        let request = self.client.post(self.url.clone()).json(&body);
+        let request = match &self.auth {
+            HttpAuth::Basic { username, password } => request.basic_auth(username, Some(password)),
+            HttpAuth::Bearer { token } => request.bearer_auth(token),
+            HttpAuth::AppToken { token } => request.header("X-Gotify-Key", token.as_ref()),
+        };
+
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+}
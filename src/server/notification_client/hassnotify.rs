@@ -1,6 +1,7 @@
+use super::{Notification, Severity};
 use crate::error::Error;
 use reqwest::{Client, Url};
-use serde_json::json;
+use serde_json::{Value, json};
 
 #[derive(Debug)]
 pub struct HassNotifyClient {
@@ -31,14 +32,15 @@ impl HassNotifyClient {
         err,
         ret
     )]
-    pub async fn send_notification(&self, content: &str) -> Result<(), Error> {
+    pub async fn send_notification(&self, notification: &Notification) -> Result<(), Error> {
         self.client
             .post(self.url.clone())
             .header("Authorization", format!("Bearer {}", self.token))
             .header("Content-Type", "application/json")
             .json(&json!({
-                "title": "PixelWeather",
-                "message": content,
+                "title": notification.title,
+                "message": notification.to_string(),
+                "data": Self::data(notification),
             }))
             .send()
             .await?
@@ -46,4 +48,33 @@ impl HassNotifyClient {
 
         Ok(())
     }
+
+    /// Companion app specific options.
+    /// See <https://companion.home-assistant.io/docs/notifications/notifications-basic>.
+    fn data(notification: &Notification) -> Value {
+        let mut data = match notification.severity {
+            Severity::Info => json!({}),
+            Severity::Warning => json!({
+                "priority": "high",
+                "push": { "interruption-level": "time-sensitive" },
+            }),
+            Severity::Critical => json!({
+                "priority": "high",
+                "ttl": 0,
+                "channel": "alarm_stream",
+                "push": {
+                    "interruption-level": "critical",
+                    "sound": { "name": "default", "critical": 1, "volume": 1.0 },
+                },
+            }),
+        };
+
+        if let Some(link) = &notification.url {
+            // iOS and Android use different keys
+            data["url"] = json!(link);
+            data["clickAction"] = json!(link);
+        }
+
+        data
+    }
 }
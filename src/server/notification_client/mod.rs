@@ -1,9 +1,25 @@
+mod exec;
+mod gotify;
 mod hassnotify;
+mod notification;
+mod ntfy;
 mod pushsafer;
+mod smtp;
+mod telegram;
+mod templates;
 
 use crate::{error::Error, server::config::NotificationServiceConfig};
+use exec::ExecClient;
+use gotify::GotifyClient;
 use hassnotify::HassNotifyClient;
+pub use notification::{Notification, NotificationKind, Severity, SeverityMap};
+use ntfy::NtfyClient;
 use pushsafer::PushsaferClient;
+use reqwest::Url;
+use smtp::SmtpClient;
+use std::time::Duration;
+pub use telegram::TelegramClient;
+pub use templates::NotificationTemplates;
 use tracing::debug;
 
 #[derive(Debug)]
@@ -11,6 +27,11 @@ pub enum NotificationClient {
     None,
     HassNotify(HassNotifyClient),
     Pushsafer(PushsaferClient),
+    Ntfy(NtfyClient),
+    Gotify(GotifyClient),
+    Smtp(SmtpClient),
+    Telegram(TelegramClient),
+    Exec(ExecClient),
 }
 
 impl NotificationClient {
@@ -21,14 +42,85 @@ impl NotificationClient {
                 NotificationServiceConfig::Pushsafer {
                     private_key,
                     device,
+                    styles,
                 } => {
-                    let client = PushsaferClient::new(device, private_key)?;
+                    let client = PushsaferClient::new(device, private_key, styles.clone())?;
                     Ok(Self::Pushsafer(client))
                 }
                 NotificationServiceConfig::HassNotify { url, token, target } => {
                     let client = HassNotifyClient::new(target, token, url)?;
                     Ok(Self::HassNotify(client))
                 }
+                NotificationServiceConfig::Ntfy {
+                    url,
+                    topic,
+                    auth,
+                    priority,
+                    tags,
+                    click,
+                } => {
+                    let client = NtfyClient::new(
+                        url,
+                        topic,
+                        auth.as_ref(),
+                        priority,
+                        tags,
+                        click.as_deref(),
+                    )?;
+                    Ok(Self::Ntfy(client))
+                }
+                NotificationServiceConfig::Gotify {
+                    url,
+                    auth,
+                    priority,
+                    click,
+                } => {
+                    let client = GotifyClient::new(url, auth, priority, click.as_deref())?;
+                    Ok(Self::Gotify(client))
+                }
+                NotificationServiceConfig::Smtp {
+                    host,
+                    port,
+                    tls,
+                    credentials,
+                    from,
+                    to,
+                    subject,
+                    body,
+                    digest,
+                } => {
+                    let client = SmtpClient::new(
+                        host,
+                        *port,
+                        *tls,
+                        credentials.as_ref(),
+                        from,
+                        to,
+                        subject,
+                        body,
+                        *digest,
+                    )?;
+                    Ok(Self::Smtp(client))
+                }
+                NotificationServiceConfig::Telegram {
+                    token,
+                    chat_ids,
+                    api_url,
+                    commands,
+                } => {
+                    let client =
+                        TelegramClient::new(token, chat_ids, api_url.as_deref(), *commands)?;
+                    Ok(Self::Telegram(client))
+                }
+                NotificationServiceConfig::Exec {
+                    command,
+                    args,
+                    input,
+                    timeout,
+                } => {
+                    let client = ExecClient::new(command.clone(), args, *input, *timeout);
+                    Ok(Self::Exec(client))
+                }
             },
         }
     }
@@ -40,10 +132,15 @@ impl NotificationClient {
         err,
         ret
     )]
-    pub async fn send_notification(&self, content: &str) -> Result<(), Error> {
+    pub async fn send_notification(&self, notification: &Notification) -> Result<(), Error> {
         match self {
-            Self::HassNotify(client) => client.send_notification(content).await?,
-            Self::Pushsafer(client) => client.send_notification(content).await?,
+            Self::HassNotify(client) => client.send_notification(notification).await?,
+            Self::Pushsafer(client) => client.send_notification(notification).await?,
+            Self::Ntfy(client) => client.send_notification(notification).await?,
+            Self::Gotify(client) => client.send_notification(notification).await?,
+            Self::Smtp(client) => client.send_notification(notification).await?,
+            Self::Telegram(client) => client.send_notification(notification).await?,
+            Self::Exec(client) => client.send_notification(notification).await?,
             Self::None => {
                 debug!("No notification backend set up");
             }
@@ -51,4 +148,62 @@ impl NotificationClient {
 
         Ok(())
     }
+
+    /// Returns the interval in which [`flush()`](Self::flush) should be called, if the backend batches notifications.
+    pub const fn digest_interval(&self) -> Option<Duration> {
+        match self {
+            Self::Smtp(client) => client.digest_interval(),
+            _ => None,
+        }
+    }
+
+    /// Returns a handle to the Telegram bot, if bot commands are enabled.
+    pub fn telegram_bot(&self) -> Option<TelegramClient> {
+        match self {
+            Self::Telegram(client) if client.commands_enabled() => Some(client.clone()),
+            _ => None,
+        }
+    }
+
+    /// Send out any notifications that have been queued by the backend.
+    pub async fn flush(&self) -> Result<(), Error> {
+        match self {
+            Self::Smtp(client) => client.flush().await,
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parse the URL of a service, so that relative API paths are appended to it instead of replacing its path.
+fn base_url(url: &str) -> Result<Url, Error> {
+    let mut url = Url::parse(url)?;
+
+    if !url.path().ends_with('/') {
+        url.set_path(&format!("{}/", url.path()));
+    }
+
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_url_keeps_path() {
+        let join = |url, path| base_url(url).unwrap().join(path).unwrap().to_string();
+
+        assert_eq!(
+            join("https://example.com", "message"),
+            "https://example.com/message"
+        );
+        assert_eq!(
+            join("https://example.com/gotify", "message"),
+            "https://example.com/gotify/message"
+        );
+        assert_eq!(
+            join("https://example.com/ntfy/", ""),
+            "https://example.com/ntfy/"
+        );
+    }
 }
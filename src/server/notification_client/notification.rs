@@ -0,0 +1,182 @@
+use crate::server::db::NodeId;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+
+/// The event that caused a notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    /// Sent by the node itself.
+    Custom,
+    MeasurementsPosted,
+    UpdateDiscovered,
+    UpdateSuccess,
+    UpdateFailed,
+    /// A threshold alert rule has been triggered.
+    AlertTriggered,
+    /// A triggered alert rule is no longer breached.
+    AlertResolved,
+    /// A node has missed too many measurement cycles.
+    NodeOffline,
+    /// An offline node has posted measurements again.
+    NodeOnline,
+    /// A node's battery voltage or predicted remaining life is below the configured threshold.
+    BatteryLow,
+    /// A node's battery has been charged.
+    BatteryCharging,
+    /// A node's Wi-Fi signal strength has been below the configured threshold for several readings.
+    WifiWeak,
+    /// A node has connected to a network that's not expected.
+    WifiUnexpectedSsid,
+    /// The measurements of a node have been flagged by sensor diagnostics.
+    SensorFault,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub node: NodeId,
+    pub kind: NotificationKind,
+    pub severity: Severity,
+    pub title: Box<str>,
+    pub body: Box<str>,
+    /// The body wrapped in the `message` template, this is what gets pushed.
+    pub message: Box<str>,
+    pub url: Option<Box<str>>,
+}
+
+/// A value for each notification severity, used by the backends to map severities to their own priorities, icons, etc.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeverityMap<T> {
+    pub info: T,
+    pub warning: T,
+    pub critical: T,
+}
+
+impl NotificationKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Custom => "custom",
+            Self::MeasurementsPosted => "measurements_posted",
+            Self::UpdateDiscovered => "update_discovered",
+            Self::UpdateSuccess => "update_success",
+            Self::UpdateFailed => "update_failed",
+            Self::AlertTriggered => "alert_triggered",
+            Self::AlertResolved => "alert_resolved",
+            Self::NodeOffline => "node_offline",
+            Self::NodeOnline => "node_online",
+            Self::BatteryLow => "battery_low",
+            Self::BatteryCharging => "battery_charging",
+            Self::WifiWeak => "wifi_weak",
+            Self::WifiUnexpectedSsid => "wifi_unexpected_ssid",
+            Self::SensorFault => "sensor_fault",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "custom" => Some(Self::Custom),
+            "measurements_posted" => Some(Self::MeasurementsPosted),
+            "update_discovered" => Some(Self::UpdateDiscovered),
+            "update_success" => Some(Self::UpdateSuccess),
+            "update_failed" => Some(Self::UpdateFailed),
+            "alert_triggered" => Some(Self::AlertTriggered),
+            "alert_resolved" => Some(Self::AlertResolved),
+            "node_offline" => Some(Self::NodeOffline),
+            "node_online" => Some(Self::NodeOnline),
+            "battery_low" => Some(Self::BatteryLow),
+            "battery_charging" => Some(Self::BatteryCharging),
+            "wifi_weak" => Some(Self::WifiWeak),
+            "wifi_unexpected_ssid" => Some(Self::WifiUnexpectedSsid),
+            "sensor_fault" => Some(Self::SensorFault),
+            _ => None,
+        }
+    }
+
+    pub const fn severity(self) -> Severity {
+        match self {
+            Self::MeasurementsPosted
+            | Self::UpdateDiscovered
+            | Self::UpdateSuccess
+            | Self::AlertResolved
+            | Self::NodeOnline
+            | Self::BatteryCharging => Severity::Info,
+            // Nodes usually only notify about problems, alert rules can override this
+            Self::Custom
+            | Self::AlertTriggered
+            | Self::BatteryLow
+            | Self::WifiWeak
+            | Self::WifiUnexpectedSsid
+            | Self::SensorFault => Severity::Warning,
+            Self::UpdateFailed | Self::NodeOffline => Severity::Critical,
+        }
+    }
+
+    pub const fn title(self) -> &'static str {
+        match self {
+            Self::Custom => "PixelWeather",
+            Self::MeasurementsPosted => "New measurements",
+            Self::UpdateDiscovered => "Update available",
+            Self::UpdateSuccess => "Update installed",
+            Self::UpdateFailed => "Update failed",
+            Self::AlertTriggered => "Alert triggered",
+            Self::AlertResolved => "Alert resolved",
+            Self::NodeOffline => "Node offline",
+            Self::NodeOnline => "Node back online",
+            Self::BatteryLow => "Low battery",
+            Self::BatteryCharging => "Battery charged",
+            Self::WifiWeak => "Weak Wi-Fi signal",
+            Self::WifiUnexpectedSsid => "Unexpected Wi-Fi network",
+            Self::SensorFault => "Sensor problem",
+        }
+    }
+}
+
+impl Severity {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        }
+    }
+
+    /// Rank of the severity, as compared by database queries.
+    pub const fn level(self) -> i16 {
+        match self {
+            Self::Info => 0,
+            Self::Warning => 1,
+            Self::Critical => 2,
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "info" => Some(Self::Info),
+            "warning" => Some(Self::Warning),
+            "critical" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+}
+
+impl<T> SeverityMap<T> {
+    pub const fn get(&self, severity: Severity) -> &T {
+        match severity {
+            Severity::Info => &self.info,
+            Severity::Warning => &self.warning,
+            Severity::Critical => &self.critical,
+        }
+    }
+}
+
+impl Display for Notification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
@@ -0,0 +1,89 @@
+use super::{Notification, Severity, SeverityMap, base_url};
+use crate::{error::Error, server::config::HttpAuth};
+use reqwest::{Client, Url};
+use serde_json::json;
+
+/// Lowest and highest priority accepted by ntfy.
+const PRIORITY_RANGE: (u8, u8) = (1, 5);
+
+#[derive(Debug)]
+pub struct NtfyClient {
+    client: Client,
+    url: Url,
+    topic: Box<str>,
+    auth: Option<HttpAuth>,
+    priorities: SeverityMap<u8>,
+    tags: Box<[Box<str>]>,
+    click: Option<Box<str>>,
+}
+
+impl NtfyClient {
+    pub fn new(
+        url: &str,
+        topic: &str,
+        auth: Option<&HttpAuth>,
+        priorities: &SeverityMap<u8>,
+        tags: &[Box<str>],
+        click: Option<&str>,
+    ) -> Result<Self, Error> {
+        let client = reqwest::Client::new();
+
+        // Publishing as JSON requires posting to the base URL, the topic is part of the body.
+        let url = base_url(url)?;
+
+        let clamp = |priority: u8| priority.clamp(PRIORITY_RANGE.0, PRIORITY_RANGE.1);
+
+        Ok(Self {
+            client,
+            url,
+            topic: topic.into(),
+            auth: auth.cloned(),
+            priorities: SeverityMap {
+                info: clamp(priorities.info),
+                warning: clamp(priorities.warning),
+                critical: clamp(priorities.critical),
+            },
+            tags: tags.into(),
+            click: click.map(Into::into),
+        })
+    }
+
+    #[tracing::instrument(
+        name = "NtfyClient::send_notification()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    pub async fn send_notification(&self, notification: &Notification) -> Result<(), Error> {
+        let mut tags = self.tags.to_vec();
+        match notification.severity {
+            Severity::Info => (),
+            Severity::Warning => tags.push("warning".into()),
+            Severity::Critical => tags.push("rotating_light".into()),
+        }
+
+        let mut request = self.client.post(self.url.clone()).json(&json!({
+            "topic": self.topic,
+            "title": notification.title,
+            "message": notification.to_string(),
+            "priority": self.priorities.get(notification.severity),
+            "tags": tags,
+            "click": notification.url.as_ref().or(self.click.as_ref()),
+        }));
+
+        request = match &self.auth {
+            Some(HttpAuth::Basic { username, password }) => {
+                request.basic_auth(username, Some(password))
+            }
+            // ntfy access tokens are sent the same way as bearer tokens
+            Some(HttpAuth::Bearer { token } | HttpAuth::AppToken { token }) => {
+                request.bearer_auth(token)
+            }
+            None => request,
+        };
+
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+}
@@ -1,27 +1,31 @@
-use crate::error::Error;
+use super::{Notification, SeverityMap};
+use crate::{error::Error, server::config::PushsaferStyle};
 use reqwest::{Client, Url};
 
 #[derive(Debug)]
 pub struct PushsaferClient {
     client: Client,
     url: Url,
+    styles: SeverityMap<PushsaferStyle>,
 }
 
 impl PushsaferClient {
-    pub fn new(device: &str, api_key: &str) -> Result<Self, Error> {
+    pub fn new(
+        device: &str,
+        api_key: &str,
+        styles: SeverityMap<PushsaferStyle>,
+    ) -> Result<Self, Error> {
         let client = reqwest::Client::new();
         let url = Url::parse_with_params(
             "https://www.pushsafer.com/api",
-            &[
-                ("k", api_key),
-                ("d", device),
-                ("t", "PixelWeather"),
-                ("i", "2"),
-                ("pr", "1"),
-            ],
+            &[("k", api_key), ("d", device)],
         )?;
 
-        Ok(Self { client, url })
+        Ok(Self {
+            client,
+            url,
+            styles,
+        })
     }
 
     #[tracing::instrument(
@@ -31,9 +35,26 @@ impl PushsaferClient {
         err,
         ret
     )]
-    pub async fn send_notification(&self, content: &str) -> Result<(), Error> {
+    pub async fn send_notification(&self, notification: &Notification) -> Result<(), Error> {
+        let style = self.styles.get(notification.severity);
         let mut url = self.url.clone();
-        url.query_pairs_mut().append_pair("m", content);
+
+        {
+            let mut query = url.query_pairs_mut();
+            query
+                .append_pair("t", &notification.title)
+                .append_pair("m", &notification.to_string())
+                .append_pair("i", &style.icon.to_string())
+                .append_pair("pr", &style.priority.to_string());
+
+            if let Some(sound) = style.sound {
+                query.append_pair("s", &sound.to_string());
+            }
+
+            if let Some(link) = &notification.url {
+                query.append_pair("u", link);
+            }
+        }
 
         self.client.post(url).send().await?.error_for_status()?;
         Ok(())
@@ -0,0 +1,354 @@
+use super::{Notification, Severity};
+use crate::{
+    error::Error,
+    server::config::{SmtpCredentials, SmtpTls},
+};
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor, message::Mailbox,
+    message::header::ContentType, transport::smtp::authentication::Credentials,
+};
+use minijinja::{Environment, context};
+use std::{mem, sync::Mutex, time::Duration};
+use tracing::warn;
+
+/// Name of the email subject template.
+const SUBJECT_TEMPLATE: &str = "subject";
+
+/// Name of the email body template.
+const BODY_TEMPLATE: &str = "body";
+
+/// Maximum number of notifications queued for a digest. The oldest ones are dropped beyond this,
+/// so an unreachable mail server doesn't make the queue grow forever.
+const MAX_PENDING: usize = 100;
+
+#[derive(Debug)]
+pub struct SmtpClient {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Box<[Mailbox]>,
+    templates: Box<Environment<'static>>,
+    digest: Option<Duration>,
+    pending: Mutex<Vec<Notification>>,
+}
+
+impl SmtpClient {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        host: &str,
+        port: u16,
+        tls: SmtpTls,
+        credentials: Option<&SmtpCredentials>,
+        from: &str,
+        to: &[Box<str>],
+        subject: &str,
+        body: &str,
+        digest: Option<Duration>,
+    ) -> Result<Self, Error> {
+        let mut builder = match tls {
+            SmtpTls::Plain => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+            SmtpTls::Implicit => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+        }
+        .port(port);
+
+        if let Some(credentials) = credentials {
+            builder = builder.credentials(Credentials::new(
+                credentials.username.to_string(),
+                credentials.password.to_string(),
+            ));
+        }
+
+        let to = to
+            .iter()
+            .map(|address| address.parse())
+            .collect::<Result<_, _>>()?;
+
+        let mut templates = Environment::new();
+        templates.add_template_owned(SUBJECT_TEMPLATE, subject.to_string())?;
+        templates.add_template_owned(BODY_TEMPLATE, body.to_string())?;
+
+        Ok(Self {
+            transport: builder.build(),
+            from: from.parse()?,
+            to,
+            templates: Box::new(templates),
+            digest,
+            pending: Mutex::default(),
+        })
+    }
+
+    /// Returns the interval in which queued notifications should be sent as a digest, if digests are enabled.
+    pub const fn digest_interval(&self) -> Option<Duration> {
+        self.digest
+    }
+
+    #[tracing::instrument(
+        name = "SmtpClient::send_notification()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    pub async fn send_notification(&self, notification: &Notification) -> Result<(), Error> {
+        if self.digest.is_some() {
+            let mut pending = self.pending.lock().unwrap();
+            pending.push(notification.clone());
+            truncate_pending(&mut pending);
+            drop(pending);
+            return Ok(());
+        }
+
+        self.send_mail(std::slice::from_ref(notification)).await
+    }
+
+    /// Send all queued notifications in a single email.
+    #[tracing::instrument(name = "SmtpClient::flush()", level = "debug", skip(self), err)]
+    pub async fn flush(&self) -> Result<(), Error> {
+        let pending = mem::take(&mut *self.pending.lock().unwrap());
+
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        if let Err(why) = self.send_mail(&pending).await {
+            // Retried with the next digest, ahead of the notifications queued in the meantime
+            let mut queue = self.pending.lock().unwrap();
+            let newer = mem::replace(&mut *queue, pending);
+            queue.extend(newer);
+            truncate_pending(&mut queue);
+            drop(queue);
+            return Err(why);
+        }
+
+        Ok(())
+    }
+
+    async fn send_mail(&self, events: &[Notification]) -> Result<(), Error> {
+        let event_count = events.len();
+        let (event_title, event_list) = if let [single] = events {
+            let mut text = single.to_string();
+            if let Some(url) = &single.url {
+                text = format!("{text}\n{url}");
+            }
+
+            (single.title.to_string(), text)
+        } else {
+            let list = events
+                .iter()
+                .map(|event| format!("- {}: {event}", event.title))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            (format!("{event_count} notifications"), list)
+        };
+
+        // A digest is as severe as its most severe notification
+        let event_severity = events
+            .iter()
+            .map(|event| event.severity)
+            .max()
+            .unwrap_or(Severity::Info)
+            .as_str();
+
+        let ctx = context! {
+            count => event_count,
+            title => event_title,
+            severity => event_severity,
+            message => event_list,
+        };
+        let subject = self
+            .templates
+            .get_template(SUBJECT_TEMPLATE)?
+            .render(&ctx)?;
+        let body = self.templates.get_template(BODY_TEMPLATE)?.render(&ctx)?;
+
+        let mut builder = Message::builder().from(self.from.clone()).subject(subject);
+        for recipient in &self.to {
+            builder = builder.to(recipient.clone());
+        }
+
+        let email = builder.header(ContentType::TEXT_PLAIN).body(body)?;
+        self.transport.send(email).await?;
+
+        Ok(())
+    }
+}
+
+/// Drop the oldest queued notifications beyond [`MAX_PENDING`].
+fn truncate_pending(pending: &mut Vec<Notification>) {
+    let excess = pending.len().saturating_sub(MAX_PENDING);
+
+    if excess > 0 {
+        warn!("Dropping {excess} oldest notification(s) queued for the email digest");
+        pending.drain(..excess);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::notification_client::NotificationKind;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+        task::JoinHandle,
+    };
+
+    /// Minimal SMTP server that rejects the first `reject` emails and returns the data of the accepted ones.
+    async fn sink(mut reject: usize, accept: usize) -> (u16, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let task = tokio::spawn(async move {
+            let mut accepted = Vec::new();
+
+            while accepted.len() < accept {
+                let (stream, _) = listener.accept().await.unwrap();
+                let (read, mut write) = stream.into_split();
+                let mut lines = BufReader::new(read).lines();
+                let mut data: Option<String> = None;
+
+                write.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+
+                while let Some(line) = lines.next_line().await.unwrap() {
+                    if let Some(body) = data.as_mut() {
+                        if line != "." {
+                            body.push_str(&line);
+                            body.push('\n');
+                            continue;
+                        }
+
+                        if reject > 0 {
+                            reject -= 1;
+                            data = None;
+                            write.write_all(b"554 rejected\r\n").await.unwrap();
+                        } else {
+                            accepted.extend(data.take());
+                            write.write_all(b"250 queued\r\n").await.unwrap();
+                        }
+                        continue;
+                    }
+
+                    let command = line.get(..4).unwrap_or_default().to_ascii_uppercase();
+                    let reply: &[u8] = match command.as_str() {
+                        "DATA" => {
+                            data = Some(String::new());
+                            b"354 go ahead\r\n"
+                        }
+                        "QUIT" => b"221 bye\r\n",
+                        _ => b"250 ok\r\n",
+                    };
+                    write.write_all(reply).await.unwrap();
+                }
+            }
+
+            accepted
+        });
+
+        (port, task)
+    }
+
+    fn client(port: u16, digest: Option<Duration>) -> SmtpClient {
+        SmtpClient::new(
+            "127.0.0.1",
+            port,
+            SmtpTls::Plain,
+            None,
+            "server@example.com",
+            &["admin@example.com".into()],
+            "[{{ severity }}] {{ title }}",
+            "{{ message }}",
+            digest,
+        )
+        .unwrap()
+    }
+
+    fn notification(title: &str, severity: Severity) -> Notification {
+        Notification {
+            node: 1,
+            kind: NotificationKind::Custom,
+            severity,
+            title: title.into(),
+            body: format!("{title} happened").into(),
+            message: format!("{title} happened").into(),
+            url: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_single_notification() {
+        let (port, sink) = sink(0, 1).await;
+        let client = client(port, None);
+
+        client
+            .send_notification(&notification("Door open", Severity::Warning))
+            .await
+            .unwrap();
+
+        let mails = sink.await.unwrap();
+        assert!(mails[0].contains("Subject: [warning] Door open"));
+        assert!(mails[0].contains("Door open happened"));
+    }
+
+    #[tokio::test]
+    async fn sends_digest_as_single_email() {
+        let (port, sink) = sink(0, 1).await;
+        let client = client(port, Some(Duration::from_mins(1)));
+
+        client
+            .send_notification(&notification("First", Severity::Info))
+            .await
+            .unwrap();
+        client
+            .send_notification(&notification("Second", Severity::Critical))
+            .await
+            .unwrap();
+        client.flush().await.unwrap();
+
+        let mails = sink.await.unwrap();
+        assert_eq!(mails.len(), 1);
+        assert!(mails[0].contains("Subject: [critical] 2 notifications"));
+        assert!(mails[0].contains("- First: First happened"));
+        assert!(mails[0].contains("- Second: Second happened"));
+        assert!(client.pending.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn keeps_digest_when_sending_fails() {
+        let (port, sink) = sink(1, 1).await;
+        let client = client(port, Some(Duration::from_mins(1)));
+
+        client
+            .send_notification(&notification("First", Severity::Info))
+            .await
+            .unwrap();
+        assert!(client.flush().await.is_err());
+
+        client
+            .send_notification(&notification("Second", Severity::Info))
+            .await
+            .unwrap();
+        client.flush().await.unwrap();
+
+        let mails = sink.await.unwrap();
+        let first = mails[0].find("- First").unwrap();
+        let second = mails[0].find("- Second").unwrap();
+        assert!(first < second);
+    }
+
+    #[tokio::test]
+    async fn drops_oldest_pending_notifications() {
+        let client = client(0, Some(Duration::from_mins(1)));
+
+        for i in 0..MAX_PENDING + 5 {
+            client
+                .send_notification(&notification(&format!("#{i}"), Severity::Info))
+                .await
+                .unwrap();
+        }
+
+        let pending = mem::take(&mut *client.pending.lock().unwrap());
+        assert_eq!(pending.len(), MAX_PENDING);
+        assert_eq!(&*pending[0].title, "#5");
+    }
+}
@@ -0,0 +1,694 @@
+use super::{Notification, Severity};
+use crate::{
+    error::Error,
+    server::{
+        config::Config,
+        db::{DatabaseBackend, DatabaseClient, MeasurementEntry, NodeId},
+        wifi,
+    },
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use reqwest::{Client, Response, Url};
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    fmt::{self, Write},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::time::sleep;
+use tracing::{debug, error, info, warn};
+
+const DEFAULT_API_URL: &str = "https://api.telegram.org";
+
+/// How long the Bot API may hold a `getUpdates` request open.
+const POLL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long to wait before polling again after a failed request.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+const HELP: &str = "Available commands:
+/status - Overview of all nodes
+/node <id> - Details of a node
+/mute <id> [duration] - Mute notifications from a node, optionally only for a given time (eg. 2h)
+/unmute <id> - Unmute notifications from a node
+/firmware - List available firmwares
+/wifi <id> - Wi-Fi connectivity report of a node";
+
+#[derive(Clone)]
+pub struct TelegramClient {
+    client: Client,
+    url: Url,
+    chat_ids: Box<[i64]>,
+    commands: bool,
+}
+
+// The URL contains the bot token, so only its origin is shown
+impl fmt::Debug for TelegramClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TelegramClient")
+            .field("api", &self.url.origin().ascii_serialization())
+            .field("chat_ids", &self.chat_ids)
+            .field("commands", &self.commands)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdatesResponse {
+    result: Vec<Update>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Update {
+    update_id: i64,
+    message: Option<IncomingMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IncomingMessage {
+    chat: Chat,
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chat {
+    id: i64,
+}
+
+impl TelegramClient {
+    pub fn new(
+        token: &str,
+        chat_ids: &[i64],
+        api_url: Option<&str>,
+        commands: bool,
+    ) -> Result<Self, Error> {
+        // Long polling requests must not time out before the Bot API responds
+        let client = reqwest::Client::builder()
+            .timeout(POLL_TIMEOUT * 2)
+            .build()?;
+
+        let mut url = Url::parse(api_url.unwrap_or(DEFAULT_API_URL))?;
+        let resource = format!("bot{token}/");
+        url.set_path(&resource);
+
+        Ok(Self {
+            client,
+            url,
+            chat_ids: chat_ids.into(),
+            commands,
+        })
+    }
+
+    pub const fn commands_enabled(&self) -> bool {
+        self.commands
+    }
+
+    #[tracing::instrument(
+        name = "TelegramClient::send_notification()",
+        level = "debug",
+        skip(self),
+        err,
+        ret
+    )]
+    pub async fn send_notification(&self, notification: &Notification) -> Result<(), Error> {
+        let mut text = format!("{}\n{notification}", notification.title);
+        if let Some(url) = &notification.url {
+            let _ = write!(text, "\n{url}");
+        }
+
+        // Informational notifications are delivered without a sound
+        let silent = notification.severity == Severity::Info;
+
+        // A chat that fails doesn't keep the notification from the others
+        let mut result = Ok(());
+        let mut delivered = false;
+
+        for chat_id in &self.chat_ids {
+            match self.post_message(*chat_id, &text, silent).await {
+                Ok(()) => delivered = true,
+                Err(why) => {
+                    warn!("Failed to send notification to Telegram chat {chat_id}: {why}");
+                    result = Err(why);
+                }
+            }
+        }
+
+        if delivered { Ok(()) } else { result }
+    }
+
+    /// Long-poll the Bot API for commands and answer them. This never returns.
+    pub async fn command_loop(self, db: Arc<DatabaseClient>, config: Arc<Config>) {
+        info!("Listening for Telegram bot commands");
+        let mut offset = 0;
+
+        loop {
+            let updates = match self.get_updates(offset).await {
+                Ok(updates) => updates,
+                Err(why) => {
+                    error!("Failed to poll Telegram updates: {why}");
+                    sleep(RETRY_DELAY).await;
+                    continue;
+                }
+            };
+
+            for update in updates {
+                offset = update.update_id + 1;
+
+                let Some(IncomingMessage {
+                    chat,
+                    text: Some(text),
+                }) = update.message
+                else {
+                    continue;
+                };
+
+                if !self.chat_ids.contains(&chat.id) {
+                    warn!("Ignoring Telegram message from unknown chat {}", chat.id);
+                    continue;
+                }
+
+                debug!("Telegram command from chat {}: {text}", chat.id);
+                let reply = match handle_command(&text, &db, &config).await {
+                    Ok(reply) => reply,
+                    Err(why) => {
+                        error!("Failed to handle Telegram command '{text}': {why}");
+                        format!("Failed to handle command: {why}")
+                    }
+                };
+
+                if let Err(why) = self.send_message(chat.id, &reply).await {
+                    error!("Failed to answer Telegram command: {why}");
+                }
+            }
+        }
+    }
+
+    async fn get_updates(&self, offset: i64) -> Result<Vec<Update>, Error> {
+        let response: UpdatesResponse = self
+            .client
+            .post(self.method("getUpdates"))
+            .json(&json!({
+                "offset": offset,
+                "timeout": POLL_TIMEOUT.as_secs(),
+                "allowed_updates": ["message"],
+            }))
+            .send()
+            .await
+            .and_then(Response::error_for_status)
+            .map_err(redact)?
+            .json()
+            .await
+            .map_err(redact)?;
+
+        Ok(response.result)
+    }
+
+    async fn send_message(&self, chat_id: i64, text: &str) -> Result<(), Error> {
+        self.post_message(chat_id, text, false).await
+    }
+
+    async fn post_message(&self, chat_id: i64, text: &str, silent: bool) -> Result<(), Error> {
+        self.client
+            .post(self.method("sendMessage"))
+            .json(&json!({
+                "chat_id": chat_id,
+                "text": text,
+                "disable_notification": silent,
+            }))
+            .send()
+            .await
+            .and_then(Response::error_for_status)
+            .map_err(redact)?;
+
+        Ok(())
+    }
+
+    fn method(&self, name: &str) -> Url {
+        self.url.join(name).expect("Invalid Bot API method name")
+    }
+}
+
+/// Strip the URL from a request error, since it contains the bot token.
+fn redact(why: reqwest::Error) -> Error {
+    why.without_url().into()
+}
+
+async fn handle_command(text: &str, db: &DatabaseClient, config: &Config) -> Result<String, Error> {
+    let mut args = text.split_whitespace();
+
+    // Commands in group chats may be suffixed with the bot's name (eg. `/status@MyBot`)
+    let command = args
+        .next()
+        .unwrap_or_default()
+        .split('@')
+        .next()
+        .unwrap_or_default();
+    let node = args.next().map(str::parse::<NodeId>);
+    let duration = args.next().map(humantime::parse_duration);
+
+    match (command, node) {
+        ("/status", None) => status(db).await,
+        ("/node", Some(Ok(node))) => node_details(db, node).await,
+        ("/mute", Some(Ok(node))) => match duration {
+            None => mute(db, node, None).await,
+            Some(Ok(duration)) => mute(db, node, Some(duration)).await,
+            Some(Err(why)) => Ok(format!("Invalid duration: {why}")),
+        },
+        ("/unmute", Some(Ok(node))) => unmute(db, node).await,
+        ("/firmware", None) => firmware(db).await,
+        ("/wifi", Some(Ok(node))) => wifi_report(db, config, node).await,
+        ("/node" | "/mute" | "/unmute" | "/wifi", _) => Ok(format!("Usage: {command} <node id>")),
+        _ => Ok(HELP.to_string()),
+    }
+}
+
+async fn status(db: &DatabaseClient) -> Result<String, Error> {
+    let devices = db.get_devices().await?;
+
+    if devices.is_empty() {
+        return Ok("No nodes are configured".to_string());
+    }
+
+    let mut reply = String::new();
+
+    for device in devices {
+        let _ = write!(reply, "#{}", device.id);
+        if let Some(note) = &device.note {
+            let _ = write!(reply, " ({note})");
+        }
+
+        match db.get_last_measurement(device.id).await? {
+            Some(measurement) => {
+                let _ = writeln!(
+                    reply,
+                    ": {}, {:.02}V, last seen {}",
+                    format_readings(&measurement),
+                    measurement.battery,
+                    format_age(measurement.when)
+                );
+            }
+            None => reply.push_str(": never seen\n"),
+        }
+    }
+
+    Ok(reply)
+}
+
+async fn node_details(db: &DatabaseClient, node: NodeId) -> Result<String, Error> {
+    let Some(device) = db.get_device(node).await? else {
+        return Ok(format!("Node #{node} does not exist"));
+    };
+
+    let mut reply = format!("Node #{} ({})\n", device.id, device.mac);
+    if let Some(note) = &device.note {
+        let _ = writeln!(reply, "Note: {note}");
+    }
+
+    match db.get_last_measurement(node).await? {
+        Some(measurement) => {
+            let _ = writeln!(
+                reply,
+                "Last seen: {}\nReadings: {}\nBattery: {:.02}V\nCPU: {:.02}°C\nWi-Fi: {} ({}dBm)\nSensor flags: {}",
+                format_age(measurement.when),
+                format_readings(&measurement),
+                measurement.battery,
+                measurement.cpu_temp,
+                measurement.wifi_ssid,
+                measurement.wifi_rssi,
+                measurement.flags
+            );
+        }
+        None => reply.push_str("Last seen: never\n"),
+    }
+
+    match db.get_settings(node).await? {
+        Some(settings) => {
+            let _ = write!(
+                reply,
+                "Settings: sleep {}s, OTA {}, battery ignore {}, SBOP {}, muted {}",
+                settings.sleep_time,
+                on_off(settings.ota),
+                on_off(settings.battery_ignore),
+                on_off(settings.sbop),
+                on_off(settings.mute_notifications)
+            );
+        }
+        None => reply.push_str("Settings: undefined"),
+    }
+
+    Ok(reply)
+}
+
+async fn mute(
+    db: &DatabaseClient,
+    node: NodeId,
+    duration: Option<Duration>,
+) -> Result<String, Error> {
+    // The expiry is stored with the setting, so timed mutes survive restarts and a later mute replaces them
+    let until = match duration {
+        None => None,
+        Some(duration) => match TimeDelta::from_std(duration)
+            .ok()
+            .and_then(|duration| Utc::now().checked_add_signed(duration))
+        {
+            Some(until) => Some(until),
+            None => return Ok("Invalid duration: too long".to_string()),
+        },
+    };
+
+    if !db.set_notifications_muted(node, true, until).await? {
+        return Ok(format!("Node #{node} has no settings"));
+    }
+
+    Ok(duration.map_or_else(
+        || format!("Node #{node} muted"),
+        |duration| {
+            format!(
+                "Node #{node} muted for {}",
+                humantime::format_duration(duration)
+            )
+        },
+    ))
+}
+
+async fn unmute(db: &DatabaseClient, node: NodeId) -> Result<String, Error> {
+    if db.set_notifications_muted(node, false, None).await? {
+        Ok(format!("Node #{node} unmuted"))
+    } else {
+        Ok(format!("Node #{node} has no settings"))
+    }
+}
+
+async fn firmware(db: &DatabaseClient) -> Result<String, Error> {
+    let firmwares = db.get_firmwares().await?;
+
+    if firmwares.is_empty() {
+        return Ok("No firmwares available".to_string());
+    }
+
+    let mut reply = String::new();
+
+    for entry in firmwares {
+        let availability = match entry.restrict {
+            None => "public".to_string(),
+            Some(nodes) if nodes.is_empty() => "internal".to_string(),
+            Some(nodes) => format!("restricted ({nodes:?})"),
+        };
+
+        let _ = writeln!(
+            reply,
+            "#{}: {}, {} bytes, {}, {availability}",
+            entry.id, entry.version, entry.size, entry.added
+        );
+    }
+
+    Ok(reply)
+}
+
+async fn wifi_report(db: &DatabaseClient, config: &Config, node: NodeId) -> Result<String, Error> {
+    let samples = db.get_wifi_history(node, config.wifi.window).await?;
+
+    let Some(report) = wifi::analyze(&samples) else {
+        return Ok(format!(
+            "No measurements from node #{node} in the last {}",
+            humantime::format_duration(config.wifi.window)
+        ));
+    };
+
+    let mut reply = format!(
+        "Node #{node}: {} at {}dBm\nMin/avg/max: {}/{:.1}/{}dBm\n",
+        report.ssid, report.rssi, report.rssi_min, report.rssi_avg, report.rssi_max
+    );
+
+    match report.rssi_trend {
+        Some(trend) => {
+            let _ = writeln!(reply, "Trend: {trend:+.2}dBm/day");
+        }
+        None => reply.push_str("Trend: unknown\n"),
+    }
+
+    let _ = write!(
+        reply,
+        "Network changes: {} in {} measurement(s)",
+        report.roams.len(),
+        report.samples
+    );
+
+    if let Some(roam) = report.roams.last() {
+        let _ = write!(
+            reply,
+            ", last {} ({} -> {})",
+            format_age(roam.when),
+            roam.from,
+            roam.to
+        );
+    }
+
+    Ok(reply)
+}
+
+fn format_readings(measurement: &MeasurementEntry) -> String {
+    format!(
+        "{:.02}°C, {}%, {}hPa",
+        measurement.temperature,
+        measurement.humidity,
+        measurement
+            .air_pressure
+            .map_or_else(|| "-".to_string(), |val| val.to_string())
+    )
+}
+
+fn format_age(when: DateTime<Utc>) -> String {
+    let secs = (Utc::now() - when).num_seconds().max(0).unsigned_abs();
+    let rounded = Duration::from_secs(match secs {
+        0..60 => secs,
+        60..3600 => secs / 60 * 60,
+        _ => secs / 3600 * 3600,
+    });
+
+    format!("{} ago", humantime::format_duration(rounded))
+}
+
+const fn on_off(value: bool) -> &'static str {
+    if value { "on" } else { "off" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{
+        config::DatabaseConfig,
+        db::transfer::{TransferRow, TransferTable},
+        notification_client::NotificationKind,
+    };
+    use serde_json::Value;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+        task::JoinHandle,
+    };
+
+    const TOKEN: &str = "123:secret";
+
+    /// Minimal Bot API that answers a request with each of `statuses` and returns their paths and bodies.
+    async fn bot_api(statuses: &[u16]) -> (String, JoinHandle<Vec<(String, Value)>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let statuses = statuses.to_vec();
+
+        let task = tokio::spawn(async move {
+            let mut received = Vec::new();
+
+            for status in statuses {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut line = String::new();
+
+                reader.read_line(&mut line).await.unwrap();
+                let path = line.split_whitespace().nth(1).unwrap().to_string();
+                let mut length = 0;
+
+                loop {
+                    line.clear();
+                    reader.read_line(&mut line).await.unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).await.unwrap();
+                received.push((path, serde_json::from_slice(&body).unwrap()));
+
+                let response = r#"{"ok":true,"result":[]}"#;
+                let reply = format!(
+                    "HTTP/1.1 {status} Status\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                    response.len()
+                );
+                reader.write_all(reply.as_bytes()).await.unwrap();
+            }
+
+            received
+        });
+
+        (url, task)
+    }
+
+    fn notification(severity: Severity) -> Notification {
+        Notification {
+            node: 1,
+            kind: NotificationKind::Custom,
+            severity,
+            title: "Door open".into(),
+            body: "The door is open".into(),
+            message: "The door is open".into(),
+            url: None,
+        }
+    }
+
+    async fn database() -> DatabaseClient {
+        let config = Config {
+            database: DatabaseConfig::Memory {
+                devices: vec!["AA:BB:CC:DD:EE:FF".into()],
+            },
+            ..Default::default()
+        };
+
+        DatabaseClient::new(&config).await.unwrap()
+    }
+
+    async fn muted_until(db: &DatabaseClient) -> Option<DateTime<Utc>> {
+        let rows = db.read_rows(TransferTable::Settings, 0, 1).await.unwrap();
+        let Some(TransferRow::Settings(row)) = rows.into_iter().next() else {
+            panic!("Node has no settings");
+        };
+
+        row.muted_until
+    }
+
+    async fn muted(db: &DatabaseClient) -> bool {
+        db.get_settings(1)
+            .await
+            .unwrap()
+            .unwrap()
+            .mute_notifications
+    }
+
+    #[tokio::test]
+    async fn sends_notification_to_every_chat() {
+        let (url, api) = bot_api(&[200, 200]).await;
+        let client = TelegramClient::new(TOKEN, &[10, 20], Some(&url), false).unwrap();
+
+        client
+            .send_notification(&notification(Severity::Info))
+            .await
+            .unwrap();
+
+        let requests = api.await.unwrap();
+        assert_eq!(requests.len(), 2);
+
+        for ((path, body), chat_id) in requests.iter().zip([10, 20]) {
+            assert_eq!(path, &format!("/bot{TOKEN}/sendMessage"));
+            assert_eq!(body["chat_id"], chat_id);
+            assert_eq!(body["text"], "Door open\nThe door is open");
+            assert_eq!(body["disable_notification"], true);
+        }
+    }
+
+    #[tokio::test]
+    async fn errors_do_not_contain_token() {
+        let (url, api) = bot_api(&[401]).await;
+        let client = TelegramClient::new(TOKEN, &[10], Some(&url), false).unwrap();
+
+        let why = client
+            .send_notification(&notification(Severity::Critical))
+            .await
+            .unwrap_err();
+
+        assert!(!why.to_string().contains(TOKEN));
+        assert!(!format!("{why:?}").contains(TOKEN));
+        api.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn failing_chat_does_not_stop_others() {
+        let (url, api) = bot_api(&[403, 200]).await;
+        let client = TelegramClient::new(TOKEN, &[10, 20], Some(&url), false).unwrap();
+
+        client
+            .send_notification(&notification(Severity::Info))
+            .await
+            .unwrap();
+
+        let chats: Vec<_> = api
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(_, body)| body["chat_id"].clone())
+            .collect();
+        assert_eq!(chats, [10, 20]);
+    }
+
+    #[tokio::test]
+    async fn fails_if_every_chat_fails() {
+        let (url, api) = bot_api(&[403, 500]).await;
+        let client = TelegramClient::new(TOKEN, &[10, 20], Some(&url), false).unwrap();
+
+        assert!(
+            client
+                .send_notification(&notification(Severity::Info))
+                .await
+                .is_err()
+        );
+        api.await.unwrap();
+    }
+
+    #[test]
+    fn debug_does_not_contain_token() {
+        let client = TelegramClient::new(TOKEN, &[10], None, false).unwrap();
+
+        assert!(!format!("{client:?}").contains(TOKEN));
+    }
+
+    #[tokio::test]
+    async fn timed_mute_is_stored() {
+        let db = database().await;
+        let config = Config::default();
+
+        let reply = handle_command("/mute 1 2h", &db, &config).await.unwrap();
+        assert_eq!(reply, "Node #1 muted for 2h");
+        assert!(muted(&db).await);
+
+        let until = muted_until(&db).await.unwrap();
+        assert!(until > Utc::now() + TimeDelta::minutes(119));
+
+        // A later mute without a duration replaces the timed one
+        handle_command("/mute 1", &db, &config).await.unwrap();
+        assert!(muted(&db).await);
+        assert_eq!(muted_until(&db).await, None);
+    }
+
+    #[tokio::test]
+    async fn expired_mute_is_ignored() {
+        let db = database().await;
+
+        db.set_notifications_muted(1, true, Some(Utc::now() - TimeDelta::minutes(1)))
+            .await
+            .unwrap();
+        assert!(!muted(&db).await);
+
+        handle_command("/unmute 1", &db, &Config::default())
+            .await
+            .unwrap();
+        assert!(!muted(&db).await);
+        assert_eq!(muted_until(&db).await, None);
+    }
+}
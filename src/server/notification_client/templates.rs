@@ -0,0 +1,232 @@
+use super::{Notification, NotificationKind, Severity};
+use crate::{
+    error::Error,
+    server::{
+        config::{AlertRuleConfig, NotificationTemplatesConfig, UnitSystem},
+        db::{DeviceEntry, MeasurementEntry},
+        meteo::DerivedMetrics,
+    },
+};
+use minijinja::{Environment, Value, context};
+
+/// Name of the template that wraps every notification body.
+const MESSAGE_TEMPLATE: &str = "message";
+
+/// Every kind of notification, used to register their templates.
+const KINDS: [NotificationKind; 14] = [
+    NotificationKind::Custom,
+    NotificationKind::MeasurementsPosted,
+    NotificationKind::UpdateDiscovered,
+    NotificationKind::UpdateSuccess,
+    NotificationKind::UpdateFailed,
+    NotificationKind::AlertTriggered,
+    NotificationKind::AlertResolved,
+    NotificationKind::NodeOffline,
+    NotificationKind::NodeOnline,
+    NotificationKind::BatteryLow,
+    NotificationKind::BatteryCharging,
+    NotificationKind::WifiWeak,
+    NotificationKind::WifiUnexpectedSsid,
+    NotificationKind::SensorFault,
+];
+
+/// Renders notifications from the configured templates.
+#[derive(Debug)]
+pub struct NotificationTemplates {
+    env: Environment<'static>,
+    units: UnitSystem,
+}
+
+impl NotificationTemplates {
+    /// Compile all templates, so that syntax errors are reported on startup.
+    pub fn new(config: &NotificationTemplatesConfig) -> Result<Self, Error> {
+        let mut env = Environment::new();
+        env.add_template_owned(MESSAGE_TEMPLATE, config.message.to_string())?;
+
+        for kind in KINDS {
+            let template = config.event(kind);
+            env.add_template_owned(title_template(kind), template.title.to_string())?;
+            env.add_template_owned(body_template(kind), template.body.to_string())?;
+        }
+
+        Ok(Self {
+            env,
+            units: config.units,
+        })
+    }
+
+    /// Build a notification for `device` from the templates of `kind`.
+    ///
+    /// Every template has access to `node` (`id`, `mac` and `note`), `event`, `severity` and `units`, in addition to `values`.
+    pub fn render(
+        &self,
+        device: &DeviceEntry,
+        kind: NotificationKind,
+        values: Value,
+    ) -> Result<Notification, Error> {
+        self.render_as(device, kind, kind.severity(), values)
+    }
+
+    /// Like [`render()`](Self::render), but with a different severity than the default of `kind`.
+    pub fn render_as(
+        &self,
+        device: &DeviceEntry,
+        kind: NotificationKind,
+        severity: Severity,
+        values: Value,
+    ) -> Result<Notification, Error> {
+        let ctx = self.context(device, kind, severity, values);
+
+        let title = self.env.get_template(&title_template(kind))?.render(&ctx)?;
+        let body = self.env.get_template(&body_template(kind))?.render(&ctx)?;
+
+        self.wrap(device, kind, severity, title, body, ctx)
+    }
+
+    /// Rebuild a notification from its stored title and body, eg. to send it again.
+    pub fn restore(
+        &self,
+        device: &DeviceEntry,
+        kind: NotificationKind,
+        severity: Severity,
+        title: &str,
+        body: &str,
+    ) -> Result<Notification, Error> {
+        let ctx = self.context(device, kind, severity, context! {});
+        self.wrap(device, kind, severity, title, body, ctx)
+    }
+
+    /// Wrap a rendered body in the `message` template.
+    fn wrap<T: Into<Box<str>>>(
+        &self,
+        device: &DeviceEntry,
+        kind: NotificationKind,
+        severity: Severity,
+        title: T,
+        body: T,
+        ctx: Value,
+    ) -> Result<Notification, Error> {
+        let title = title.into();
+        let body = body.into();
+        let message = self
+            .env
+            .get_template(MESSAGE_TEMPLATE)?
+            .render(context! { title, body, ..ctx })?;
+
+        Ok(Notification {
+            node: device.id,
+            kind,
+            severity,
+            title,
+            body,
+            message: message.into(),
+            url: None,
+        })
+    }
+
+    fn context(
+        &self,
+        device: &DeviceEntry,
+        kind: NotificationKind,
+        severity: Severity,
+        values: Value,
+    ) -> Value {
+        context! {
+            node => context! {
+                id => device.id,
+                mac => device.mac,
+                note => device.note,
+                altitude => device.altitude,
+            },
+            event => kind.as_str(),
+            severity => severity.as_str(),
+            units => context! {
+                temperature => self.units.temperature_unit(),
+                air_pressure => self.units.air_pressure_unit(),
+            },
+            ..values
+        }
+    }
+
+    /// Values available to the `measurements_posted` template, converted to the configured unit system.
+    ///
+    /// `altitude` is the node's altitude, needed for the sea-level pressure.
+    pub fn measurements(&self, measurement: &MeasurementEntry, altitude: Option<f32>) -> Value {
+        let metrics = DerivedMetrics::new(
+            measurement.temperature,
+            measurement.humidity,
+            measurement.air_pressure,
+            altitude,
+        );
+        let air_pressure = measurement.air_pressure.map(|hpa| match self.units {
+            // Keep whole hectopascals as integers, so they're not printed with a decimal point
+            UnitSystem::Metric => Value::from(hpa),
+            UnitSystem::Imperial => Value::from(self.units.air_pressure(f64::from(hpa))),
+        });
+
+        context! {
+            temperature => self.units.temperature(f64::from(measurement.temperature)),
+            humidity => measurement.humidity,
+            air_pressure,
+            dew_point => metrics.dew_point.map(|celsius| self.units.temperature(celsius)),
+            dew_point_category => metrics.dew_point_category,
+            heat_index => self.units.temperature(metrics.heat_index),
+            humidex => metrics.humidex,
+            absolute_humidity => metrics.absolute_humidity,
+            sea_level_pressure => metrics.sea_level_pressure.map(|hpa| self.units.air_pressure(hpa)),
+        }
+    }
+
+    /// Values available to the `alert_triggered` and `alert_resolved` templates, converted to the configured unit system.
+    pub fn alert(&self, rule: &AlertRuleConfig, value: f32) -> Value {
+        context! {
+            rule => rule.name,
+            metric => rule.metric.as_str(),
+            condition => rule.condition.as_str(),
+            value => self.units.metric(rule.metric, f64::from(value)),
+            threshold => self.units.metric(rule.metric, f64::from(rule.threshold)),
+            unit => self.units.metric_unit(rule.metric),
+        }
+    }
+}
+
+fn title_template(kind: NotificationKind) -> String {
+    format!("{}.title", kind.as_str())
+}
+
+fn body_template(kind: NotificationKind) -> String {
+    format!("{}.body", kind.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(note: Option<&str>) -> DeviceEntry {
+        DeviceEntry {
+            id: 3,
+            mac: "AA:BB:CC:DD:EE:FF".to_string(),
+            note: note.map(str::to_string),
+            altitude: None,
+        }
+    }
+
+    #[test]
+    fn message_names_node_by_note() {
+        let templates =
+            NotificationTemplates::new(&NotificationTemplatesConfig::default()).unwrap();
+        let render = |note| {
+            templates
+                .render(
+                    &device(note),
+                    NotificationKind::Custom,
+                    context! { message => "Door open" },
+                )
+                .unwrap()
+                .message
+        };
+
+        assert_eq!(&*render(Some("Garden")), "[Garden] Door open");
+        assert_eq!(&*render(None), "[Node #3] Door open");
+    }
+}
@@ -0,0 +1,64 @@
+use super::{
+    NotifySender,
+    db::{DatabaseBackend, DatabaseClient, NodeId},
+    notification_client::{NotificationKind, NotificationTemplates, Severity},
+};
+use crate::error::Error;
+use minijinja::Value;
+use tracing::{debug, error};
+
+/// Render a notification, store it, and push it unless the node is muted.
+pub async fn notify_send(
+    notify: &NotifySender,
+    templates: &NotificationTemplates,
+    node_id: NodeId,
+    db_client: &DatabaseClient,
+    kind: NotificationKind,
+    values: Value,
+) -> Result<(), Error> {
+    notify_send_as(
+        notify,
+        templates,
+        node_id,
+        db_client,
+        kind,
+        kind.severity(),
+        values,
+    )
+    .await
+}
+
+/// Like [`notify_send()`], but with a different severity than the default of `kind`.
+pub async fn notify_send_as(
+    notify: &NotifySender,
+    templates: &NotificationTemplates,
+    node_id: NodeId,
+    db_client: &DatabaseClient,
+    kind: NotificationKind,
+    severity: Severity,
+    values: Value,
+) -> Result<(), Error> {
+    let Some(device) = db_client.get_device(node_id).await? else {
+        error!("Node #{node_id} does not exist, cannot create notification");
+        return Ok(());
+    };
+
+    let notification = templates.render_as(&device, kind, severity, values)?;
+
+    // The database already links messages to nodes, so the ID is not part of the stored body
+    db_client.create_notification(&notification).await?;
+
+    // Muted nodes still have their notifications stored, but they're not pushed
+    if db_client
+        .get_settings(node_id)
+        .await?
+        .is_some_and(|settings| settings.mute_notifications)
+    {
+        debug!("Node #{node_id} is muted, not pushing notification");
+        return Ok(());
+    }
+
+    notify
+        .try_send(notification)
+        .map_err(|_| Error::MpscTrySend)
+}
@@ -0,0 +1,92 @@
+use super::{
+    config::{Config, RetentionConfig},
+    db::{DatabaseBackend, DatabaseClient, PrunableTable},
+};
+use crate::error::Error;
+use tokio::time::interval;
+use tracing::{debug, error, info};
+
+/// Periodically prune rows older than their retention.
+pub async fn retention_loop(config: &Config, db: &DatabaseClient) {
+    let retention = &config.retention;
+
+    if !retention.is_enabled() {
+        return;
+    }
+
+    info!(
+        "Pruning old data every {}",
+        humantime::format_duration(retention.interval)
+    );
+    let mut timer = interval(retention.interval);
+
+    loop {
+        timer.tick().await;
+
+        match prune(retention, db).await {
+            Ok(pruned) => {
+                for (table, rows) in pruned {
+                    info!("Pruned {rows} row(s) from {}", table.as_str());
+                }
+            }
+            Err(why) => error!("Failed to prune old data: {why}"),
+        }
+    }
+}
+
+/// Delete rows older than their retention, in batches. Returns the number of deleted rows of every table that had any.
+pub async fn prune(
+    config: &RetentionConfig,
+    db: &DatabaseClient,
+) -> Result<Vec<(PrunableTable, u64)>, Error> {
+    // A batch size of zero would never delete anything
+    let batch_size = config.batch_size.max(1);
+    let mut pruned = Vec::new();
+
+    for table in PrunableTable::ALL {
+        let Some(older_than) = config.get(table) else {
+            continue;
+        };
+
+        let mut rows = 0;
+
+        // Every batch is a separate statement, so other queries get through in between
+        loop {
+            let deleted = db.prune(table, older_than, batch_size).await?;
+            rows += deleted;
+
+            if deleted < u64::from(batch_size) {
+                break;
+            }
+
+            tokio::task::yield_now().await;
+        }
+
+        if rows > 0 {
+            pruned.push((table, rows));
+        }
+    }
+
+    if config.vacuum && !pruned.is_empty() {
+        debug!("Reclaiming disk space");
+        db.compact(false).await?;
+    }
+
+    Ok(pruned)
+}
+
+/// Count the rows that [`prune()`] would delete, for every table with a retention.
+pub async fn count(
+    config: &RetentionConfig,
+    db: &DatabaseClient,
+) -> Result<Vec<(PrunableTable, u64)>, Error> {
+    let mut counts = Vec::new();
+
+    for table in PrunableTable::ALL {
+        if let Some(older_than) = config.get(table) {
+            counts.push((table, db.count_prunable(table, older_than).await?));
+        }
+    }
+
+    Ok(counts)
+}
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+
+/// Minimum number of samples needed to estimate a trend.
+const MIN_SAMPLES: usize = 3;
+
+/// Minimum time span of the samples needed to estimate a trend, in seconds.
+const MIN_SPAN: f64 = 6.0 * 3600.0;
+
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// Least squares slope of the values over time, in units per day.
+///
+/// Returns `None` if there are too few samples, or they cover too little time.
+#[allow(clippy::cast_precision_loss)] // timestamps relative to the first sample are small
+pub fn per_day(samples: &[(DateTime<Utc>, f64)]) -> Option<f64> {
+    if samples.len() < MIN_SAMPLES {
+        return None;
+    }
+
+    let start = samples.first()?.0;
+    let points: Vec<(f64, f64)> = samples
+        .iter()
+        .map(|(when, value)| ((*when - start).num_seconds() as f64, *value))
+        .collect();
+
+    let span = points.last()?.0;
+    if span < MIN_SPAN {
+        return None;
+    }
+
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+
+    let covariance: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+
+    Some(covariance / variance * SECONDS_PER_DAY)
+}
@@ -0,0 +1,95 @@
+use super::{
+    config::{PlausibleRange, ValidationConfig},
+    db::MeasurementEntry,
+};
+use std::fmt::Display;
+
+/// Maximum length of a Wi-Fi SSID in bytes, as defined by IEEE 802.11.
+const MAX_SSID_LENGTH: usize = 32;
+
+/// Check the readings of a measurement against the plausible ranges.
+///
+/// Returns the reasons why the measurement is invalid, or nothing if it's valid.
+pub fn validate(config: &ValidationConfig, measurement: &MeasurementEntry) -> Vec<String> {
+    let mut reasons = Vec::new();
+
+    check(
+        &mut reasons,
+        "temperature",
+        measurement.temperature,
+        config.temperature,
+        "°C",
+    );
+    check(
+        &mut reasons,
+        "humidity",
+        measurement.humidity,
+        config.humidity,
+        "%",
+    );
+    if let Some(air_pressure) = measurement.air_pressure {
+        check(
+            &mut reasons,
+            "air pressure",
+            air_pressure,
+            config.air_pressure,
+            "hPa",
+        );
+    }
+    check(
+        &mut reasons,
+        "CPU temperature",
+        measurement.cpu_temp,
+        config.cpu_temperature,
+        "°C",
+    );
+    check(
+        &mut reasons,
+        "battery voltage",
+        measurement.battery,
+        config.battery,
+        "V",
+    );
+
+    if measurement.wifi_ssid.len() > MAX_SSID_LENGTH {
+        reasons.push(format!(
+            "Wi-Fi SSID is {} bytes long, at most {MAX_SSID_LENGTH} are allowed",
+            measurement.wifi_ssid.len()
+        ));
+    }
+
+    reasons
+}
+
+fn check<T: PartialOrd + Copy + Display>(
+    reasons: &mut Vec<String>,
+    name: &str,
+    value: T,
+    range: PlausibleRange<T>,
+    unit: &str,
+) {
+    if !range.contains(value) {
+        reasons.push(format!(
+            "{name} {value}{unit} is outside of {}{unit} to {}{unit}",
+            range.min, range.max
+        ));
+    }
+}
+
+/// Clamp the readings of a measurement to the plausible ranges, and truncate the SSID.
+///
+/// Readings that are not a number can't be clamped, so the measurement should be validated again.
+pub fn clamp(config: &ValidationConfig, measurement: &mut MeasurementEntry) {
+    measurement.temperature = config.temperature.clamp(measurement.temperature);
+    measurement.humidity = config.humidity.clamp(measurement.humidity);
+    measurement.air_pressure = measurement
+        .air_pressure
+        .map(|air_pressure| config.air_pressure.clamp(air_pressure));
+    measurement.cpu_temp = config.cpu_temperature.clamp(measurement.cpu_temp);
+    measurement.battery = config.battery.clamp(measurement.battery);
+
+    if measurement.wifi_ssid.len() > MAX_SSID_LENGTH {
+        let end = measurement.wifi_ssid.floor_char_boundary(MAX_SSID_LENGTH);
+        measurement.wifi_ssid.truncate(end);
+    }
+}
@@ -0,0 +1,223 @@
+use super::{
+    NotifySender,
+    config::{Config, WatchdogConfig},
+    db::{DatabaseBackend, DatabaseClient, DeviceEntry},
+    notification_client::{NotificationKind, NotificationTemplates},
+    notify::notify_send,
+};
+use crate::error::Error;
+use chrono::Utc;
+use minijinja::context;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{debug, error, info};
+
+/// The watchdog keeps its state along with alert rules, under a name that rules don't use.
+const WATCHDOG_RULE: &str = "@offline";
+
+/// Periodically check whether nodes post measurements as often as their sleep time suggests.
+pub async fn watchdog_loop(
+    config: &Config,
+    db: &DatabaseClient,
+    notify: &NotifySender,
+    templates: &NotificationTemplates,
+) {
+    let Some(watchdog) = &config.notification.watchdog else {
+        return;
+    };
+
+    info!(
+        "Watching nodes every {}",
+        humantime::format_duration(watchdog.check_interval)
+    );
+    let mut timer = interval(watchdog.check_interval);
+
+    loop {
+        timer.tick().await;
+
+        if let Err(why) = check_nodes(watchdog, db, notify, templates).await {
+            error!("Failed to check for offline nodes: {why}");
+        }
+    }
+}
+
+async fn check_nodes(
+    watchdog: &WatchdogConfig,
+    db: &DatabaseClient,
+    notify: &NotifySender,
+    templates: &NotificationTemplates,
+) -> Result<(), Error> {
+    for device in db.get_devices().await? {
+        // A node that can't be checked or notified about doesn't keep the others from being checked
+        if let Err(why) = check_node(watchdog, db, notify, templates, &device).await {
+            error!(
+                "Failed to check whether node #{} is offline: {why}",
+                device.id
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn check_node(
+    watchdog: &WatchdogConfig,
+    db: &DatabaseClient,
+    notify: &NotifySender,
+    templates: &NotificationTemplates,
+    device: &DeviceEntry,
+) -> Result<(), Error> {
+    let overrides = watchdog.nodes.get(&device.id);
+
+    if overrides.is_some_and(|node| !node.enabled) {
+        return Ok(());
+    }
+
+    // Nodes without settings or measurements have never been set up
+    let Some(settings) = db.get_settings(device.id).await? else {
+        return Ok(());
+    };
+    let Some(last) = db.get_last_measurement(device.id).await? else {
+        return Ok(());
+    };
+
+    let missed_cycles = overrides
+        .and_then(|node| node.missed_cycles)
+        .unwrap_or(watchdog.missed_cycles);
+    let grace_factor = overrides
+        .and_then(|node| node.grace_factor)
+        .unwrap_or(watchdog.grace_factor);
+
+    let allowed = Duration::try_from_secs_f64(
+        f64::from(settings.sleep_time) * f64::from(missed_cycles) * f64::from(grace_factor),
+    )
+    .unwrap_or(Duration::MAX);
+    let silent_for = (Utc::now() - last.when).to_std().unwrap_or_default();
+    let offline = silent_for > allowed;
+
+    let was_offline = db
+        .get_alert_states(device.id)
+        .await?
+        .iter()
+        .any(|state| state.rule == WATCHDOG_RULE && state.active);
+
+    if offline == was_offline {
+        return Ok(());
+    }
+
+    debug!(
+        "Node #{}: silent for {silent_for:?}, allowed {allowed:?}",
+        device.id
+    );
+
+    let kind = if offline {
+        info!("Node #{} went offline", device.id);
+        NotificationKind::NodeOffline
+    } else {
+        info!("Node #{} is back online", device.id);
+        NotificationKind::NodeOnline
+    };
+
+    // The state is only saved once the notification is queued, so a notification that couldn't be queued is sent on the next check
+    notify_send(
+        notify,
+        templates,
+        device.id,
+        db,
+        kind,
+        context! {
+            last_seen => last.when.to_string(),
+            silent_for => humantime::format_duration(Duration::from_secs(silent_for.as_secs())).to_string(),
+            sleep_time => settings.sleep_time,
+            missed_cycles,
+        },
+    )
+    .await?;
+
+    db.set_alert_state(device.id, WATCHDOG_RULE, offline, 0)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{
+        config::{DatabaseConfig, NotificationTemplatesConfig},
+        db::{MeasurementEntry, NodeId, RawReadings},
+        diagnostics::SensorFlags,
+    };
+    use chrono::TimeDelta;
+    use std::collections::HashMap;
+    use tokio::sync::mpsc;
+
+    fn measurement(age: TimeDelta) -> MeasurementEntry {
+        MeasurementEntry {
+            when: Utc::now() - age,
+            temperature: 20.0,
+            humidity: 50,
+            air_pressure: None,
+            cpu_temp: 30.0,
+            battery: 3.9,
+            wifi_ssid: "Home".to_string(),
+            wifi_rssi: -60,
+            flags: SensorFlags::default(),
+            raw: RawReadings {
+                temperature: 20.0,
+                humidity: 50,
+                air_pressure: None,
+                battery: 3.9,
+            },
+        }
+    }
+
+    async fn offline(db: &DatabaseClient, node: NodeId) -> bool {
+        db.get_alert_states(node)
+            .await
+            .unwrap()
+            .iter()
+            .any(|state| state.rule == WATCHDOG_RULE && state.active)
+    }
+
+    #[tokio::test]
+    async fn retries_notifications_that_were_not_queued() {
+        let config = Config {
+            database: DatabaseConfig::Memory {
+                devices: vec!["AA:BB:CC:DD:EE:01".into(), "AA:BB:CC:DD:EE:02".into()],
+            },
+            ..Default::default()
+        };
+        let db = DatabaseClient::new(&config).await.unwrap();
+        let templates =
+            NotificationTemplates::new(&NotificationTemplatesConfig::default()).unwrap();
+        let watchdog = WatchdogConfig {
+            check_interval: Duration::from_mins(1),
+            missed_cycles: 3,
+            grace_factor: 1.5,
+            nodes: HashMap::new(),
+        };
+
+        for node in [1, 2] {
+            db.import_measurements(node, &[measurement(TimeDelta::hours(1))], false, false)
+                .await
+                .unwrap();
+        }
+
+        // Only one of the two notifications fits
+        let (notify, mut receiver) = mpsc::channel(1);
+        check_nodes(&watchdog, &db, &notify, &templates)
+            .await
+            .unwrap();
+
+        assert_eq!(receiver.try_recv().unwrap().node, 1);
+        assert!(offline(&db, 1).await);
+        assert!(!offline(&db, 2).await);
+
+        check_nodes(&watchdog, &db, &notify, &templates)
+            .await
+            .unwrap();
+
+        assert_eq!(receiver.try_recv().unwrap().node, 2);
+        assert!(receiver.try_recv().is_err());
+        assert!(offline(&db, 2).await);
+    }
+}
@@ -0,0 +1,174 @@
+use super::{
+    NotifySender,
+    config::WifiConfig,
+    db::{DatabaseBackend, DatabaseClient, MeasurementEntry, NodeId, WifiSample},
+    notification_client::{NotificationKind, NotificationTemplates},
+    notify::notify_send,
+    trend,
+};
+use crate::error::Error;
+use chrono::{DateTime, Utc};
+use minijinja::context;
+use pwmp_client::pwmp_msg::aliases::Rssi;
+use tracing::{debug, info};
+
+/// Wi-Fi alerts keep their state along with alert rules, under names that rules don't use.
+const WEAK_SIGNAL_RULE: &str = "@wifi_weak";
+const UNEXPECTED_SSID_RULE: &str = "@wifi_ssid";
+
+#[derive(Debug, Clone)]
+pub struct WifiReport {
+    /// Number of measurements the report is based on.
+    pub samples: usize,
+    /// Latest network.
+    pub ssid: String,
+    /// Latest signal strength.
+    pub rssi: Rssi,
+    pub rssi_min: Rssi,
+    pub rssi_max: Rssi,
+    pub rssi_avg: f64,
+    /// Estimated change of the signal strength in dBm per day.
+    pub rssi_trend: Option<f64>,
+    /// Every switch to a different network, oldest first.
+    pub roams: Vec<Roam>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Roam {
+    pub when: DateTime<Utc>,
+    pub from: String,
+    pub to: String,
+}
+
+/// Summarize the Wi-Fi history of a node, oldest sample first.
+pub fn analyze(samples: &[WifiSample]) -> Option<WifiReport> {
+    let latest = samples.last()?;
+
+    let rssi_min = samples.iter().map(|sample| sample.rssi).min()?;
+    let rssi_max = samples.iter().map(|sample| sample.rssi).max()?;
+    let rssi_sum: f64 = samples.iter().map(|sample| f64::from(sample.rssi)).sum();
+
+    let rssi: Vec<_> = samples
+        .iter()
+        .map(|sample| (sample.when, f64::from(sample.rssi)))
+        .collect();
+
+    let roams = samples
+        .windows(2)
+        .filter(|pair| pair[0].ssid != pair[1].ssid)
+        .map(|pair| Roam {
+            when: pair[1].when,
+            from: pair[0].ssid.clone(),
+            to: pair[1].ssid.clone(),
+        })
+        .collect();
+
+    Some(WifiReport {
+        samples: samples.len(),
+        ssid: latest.ssid.clone(),
+        rssi: latest.rssi,
+        rssi_min,
+        rssi_max,
+        #[allow(clippy::cast_precision_loss)] // there are never that many samples
+        rssi_avg: rssi_sum / samples.len() as f64,
+        rssi_trend: trend::per_day(&rssi),
+        roams,
+    })
+}
+
+/// Check the Wi-Fi connection of a node after it posted measurements, and notify about weak signals and unexpected networks.
+pub async fn check(
+    config: &WifiConfig,
+    notify: &NotifySender,
+    templates: &NotificationTemplates,
+    node_id: NodeId,
+    db: &DatabaseClient,
+    measurement: &MeasurementEntry,
+) -> Result<(), Error> {
+    if config.alert_rssi.is_none() && config.expected_ssids.is_empty() {
+        return Ok(());
+    }
+
+    let states = db.get_alert_states(node_id).await?;
+    let state = |rule: &str| {
+        states
+            .iter()
+            .find(|state| *state.rule == *rule)
+            .map_or((false, 0), |state| (state.active, state.streak))
+    };
+
+    let values = context! {
+        ssid => measurement.wifi_ssid,
+        rssi => measurement.wifi_rssi,
+        threshold => config.alert_rssi,
+        expected_ssids => config.expected_ssids,
+    };
+
+    if let Some(threshold) = config.alert_rssi {
+        let (active, streak) = state(WEAK_SIGNAL_RULE);
+
+        if measurement.wifi_rssi < threshold && !active {
+            let streak = streak + 1;
+            let triggered = streak >= config.consecutive;
+
+            db.set_alert_state(
+                node_id,
+                WEAK_SIGNAL_RULE,
+                triggered,
+                if triggered { 0 } else { streak },
+            )
+            .await?;
+
+            if triggered {
+                info!(
+                    "Node #{node_id}: weak Wi-Fi signal ({}dBm)",
+                    measurement.wifi_rssi
+                );
+                notify_send(
+                    notify,
+                    templates,
+                    node_id,
+                    db,
+                    NotificationKind::WifiWeak,
+                    values.clone(),
+                )
+                .await?;
+            }
+        } else if measurement.wifi_rssi >= threshold && (active || streak > 0) {
+            debug!("Node #{node_id}: Wi-Fi signal recovered");
+            db.set_alert_state(node_id, WEAK_SIGNAL_RULE, false, 0)
+                .await?;
+        }
+    }
+
+    if !config.expected_ssids.is_empty() {
+        let (active, _) = state(UNEXPECTED_SSID_RULE);
+        let unexpected = !config
+            .expected_ssids
+            .iter()
+            .any(|ssid| **ssid == *measurement.wifi_ssid);
+
+        if unexpected != active {
+            db.set_alert_state(node_id, UNEXPECTED_SSID_RULE, unexpected, 0)
+                .await?;
+        }
+
+        if unexpected && !active {
+            info!(
+                "Node #{node_id}: connected to unexpected network '{}'",
+                measurement.wifi_ssid
+            );
+            notify_send(
+                notify,
+                templates,
+                node_id,
+                db,
+                NotificationKind::WifiUnexpectedSsid,
+                values,
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}